@@ -0,0 +1,136 @@
+//! Rendimiento de los bucles calientes del render: transformación de vértices, rasterización de
+//! triángulos de distintos tamaños y cada shader de planeta. `cargo bench` guarda los resultados
+//! en `target/criterion` y en la siguiente ejecución informa de la diferencia.
+
+use std::f32::consts::PI;
+use computer_graphics_v3::depth::DepthMode;
+use computer_graphics_v3::fragment::Fragment;
+use computer_graphics_v3::light::Light;
+use computer_graphics_v3::pipeline::{
+    create_model_matrix, create_normal_matrix, create_projection_matrix, create_view_matrix, create_viewport_matrix, transform_indexed,
+    transform_triangles, Culling, Uniforms,
+};
+use computer_graphics_v3::shaders::{fragment_shader, vertex_shader, PlanetShaderType, ShaderParams, SurfaceMaps};
+use computer_graphics_v3::triangle::triangle;
+use computer_graphics_v3::vertex::{IndexedMesh, Vertex};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nalgebra_glm::Vec3;
+use raylib::math::{Vector2, Vector3};
+
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 600.0;
+
+fn uniforms() -> Uniforms {
+    let eye = Vec3::new(0.0, 0.0, 3.0);
+    let model_matrix = create_model_matrix(Vec3::zeros(), 1.0, Vec3::new(0.3, 0.5, 0.0));
+    Uniforms {
+        model_matrix,
+        normal_matrix: create_normal_matrix(&model_matrix),
+        inverse_model_matrix: model_matrix.try_inverse(),
+        view_matrix: create_view_matrix(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0)),
+        projection_matrix: create_projection_matrix(PI / 3.0, WIDTH / HEIGHT, 0.1, 100.0),
+        viewport_matrix: create_viewport_matrix(0.0, 0.0, WIDTH, HEIGHT),
+        time: 1.5,
+        camera_position: eye,
+        light_position: Vec3::new(5.0, 3.0, 4.0),
+        depth_mode: DepthMode::Perspective,
+        culling: Culling::default(),
+    }
+}
+
+/// Vértices de una esfera UV como las de los planetas (`rings × segments` cuadriláteros)
+fn sphere_vertices(rings: usize, segments: usize) -> Vec<Vertex> {
+    let point = |ring: usize, segment: usize| {
+        let theta = ring as f32 / rings as f32 * PI;
+        let phi = segment as f32 / segments as f32 * 2.0 * PI;
+        let p = Vector3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+        Vertex::new(p, p, Vector2::new(segment as f32 / segments as f32, ring as f32 / rings as f32))
+    };
+    let mut vertices = Vec::with_capacity(rings * segments * 6);
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let (a, b) = (point(ring, segment), point(ring + 1, segment));
+            let (c, d) = (point(ring, segment + 1), point(ring + 1, segment + 1));
+            vertices.extend([a, b.clone(), c.clone(), c, b, d]);
+        }
+    }
+    vertices
+}
+
+/// Triángulo ya en pantalla, con catetos de `size` píxeles
+fn screen_triangle(size: f32) -> [Vertex; 3] {
+    let corner = |x: f32, y: f32| {
+        let mut vertex = Vertex::new(Vector3::new(x, y, 0.0), Vector3::new(0.0, 0.0, 1.0), Vector2::new(x, y));
+        vertex.set_transformed(Vector3::new(10.0 + x, 10.0 + y, 0.5), Vector3::new(0.0, 0.0, 1.0));
+        vertex
+    };
+    [corner(0.0, 0.0), corner(0.0, size), corner(size, 0.0)]
+}
+
+fn vertex_transform(c: &mut Criterion) {
+    let uniforms = uniforms();
+    let vertices = sphere_vertices(32, 64);
+    let mut group = c.benchmark_group("vertex_transform");
+    group.throughput(Throughput::Elements(vertices.len() as u64));
+    group.bench_function("sphere_32x64", |b| {
+        b.iter(|| vertices.iter().map(|vertex| vertex_shader(black_box(vertex), &uniforms)).collect::<Vec<_>>())
+    });
+    // Con recorte: la lista de triángulos transforma cada vértice compartido varias veces
+    let mesh = IndexedMesh::from_triangles(&vertices);
+    group.bench_function("sphere_32x64_triangles", |b| b.iter(|| transform_triangles(&uniforms, black_box(&vertices))));
+    group.bench_function("sphere_32x64_indexed", |b| b.iter(|| transform_indexed(&uniforms, black_box(&mesh))));
+    group.finish();
+}
+
+fn triangle_rasterization(c: &mut Criterion) {
+    let light = Light::new(Vector3::new(5.0, 3.0, 4.0));
+    let mut group = c.benchmark_group("triangle_rasterization");
+    for size in [4.0, 32.0, 128.0, 512.0] {
+        let [a, b, d] = screen_triangle(size);
+        group.throughput(Throughput::Elements((size * size / 2.0) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size as u32), &size, |bench, _| {
+            bench.iter(|| triangle(black_box(&a), black_box(&b), black_box(&d), &light))
+        });
+    }
+    group.finish();
+}
+
+fn fragment_shaders(c: &mut Criterion) {
+    let uniforms = uniforms();
+    let fragments: Vec<Fragment> = (0..1024)
+        .map(|index| {
+            let (u, v) = ((index % 32) as f32 / 32.0, (index / 32) as f32 / 32.0);
+            let (theta, phi) = (v * PI, u * 2.0 * PI);
+            let p = Vector3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+            let mut fragment = Fragment::new_with_world_pos(0.0, 0.0, Vector3::new(1.0, 1.0, 1.0), 0.5, p);
+            fragment.normal = p;
+            fragment.tex_coords = Vector2::new(u, v);
+            fragment
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("fragment_shader");
+    group.throughput(Throughput::Elements(fragments.len() as u64));
+    for shader in [
+        PlanetShaderType::Terra,
+        PlanetShaderType::Vulcan,
+        PlanetShaderType::Solarius,
+        PlanetShaderType::Nepturion,
+        PlanetShaderType::Mossar,
+        PlanetShaderType::Mesh,
+    ] {
+        let params = ShaderParams::for_type(shader);
+        group.bench_function(format!("{:?}", shader), |b| {
+            b.iter(|| {
+                fragments
+                    .iter()
+                    .map(|fragment| fragment_shader(black_box(fragment), &uniforms, shader, &params, SurfaceMaps::default()))
+                    .collect::<Vec<_>>()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, vertex_transform, triangle_rasterization, fragment_shaders);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+/*
+ * Interfaz C del render de computer-graphics-v3 (ver src/ffi.rs).
+ * Se compila con `cargo build --release`: libcomputer_graphics_v3.so / .dylib /
+ * computer_graphics_v3.dll en target/release.
+ */
+#ifndef COMPUTER_GRAPHICS_V3_H
+#define COMPUTER_GRAPHICS_V3_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Escena opaca */
+typedef struct Scene Scene;
+
+/* Shaders de cg_scene_add_body */
+enum {
+    CG_SHADER_TERRA = 0,
+    CG_SHADER_VULCAN = 1,
+    CG_SHADER_SOLARIUS = 2,
+    CG_SHADER_NEPTURION = 3,
+    CG_SHADER_MOSSAR = 4,
+};
+
+/* NULL si width o height son 0 */
+Scene *cg_scene_create(uint32_t width, uint32_t height);
+void cg_scene_destroy(Scene *scene);
+
+/* Índice del cuerpo, o -1 si el shader no existe. orbit_radius 0 = en el centro */
+int32_t cg_scene_add_body(Scene *scene, uint32_t shader, uint32_t seed, float orbit_radius, float orbit_speed, float scale);
+void cg_scene_set_camera(Scene *scene, float eye_x, float eye_y, float eye_z, float target_x, float target_y, float target_z);
+void cg_scene_step(Scene *scene, float delta_time);
+
+/* pixels: width * height * 4 bytes RGBA. 0 si se ha dibujado, -1 si no */
+int32_t cg_scene_render(Scene *scene, uint8_t *pixels, size_t length);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
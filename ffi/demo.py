@@ -0,0 +1,65 @@
+"""Ejemplo de la interfaz C desde Python, solo con la biblioteca estándar.
+
+Compila primero la biblioteca (`cargo build --release`) y ejecuta desde la raíz del repositorio:
+
+    python3 ffi/demo.py
+
+Dibuja unos segundos de un sistema pequeño y guarda cada frame en `ffi_demo/frame_NN.ppm`.
+"""
+
+import ctypes
+import os
+import sys
+
+WIDTH, HEIGHT = 320, 240
+FRAMES = 8
+TERRA, VULCAN, SOLARIUS, NEPTURION, MOSSAR = range(5)
+
+
+def load_library():
+    names = {"win32": "computer_graphics_v3.dll", "darwin": "libcomputer_graphics_v3.dylib"}
+    path = os.path.join("target", "release", names.get(sys.platform, "libcomputer_graphics_v3.so"))
+    lib = ctypes.CDLL(path)
+    lib.cg_scene_create.argtypes = [ctypes.c_uint32, ctypes.c_uint32]
+    lib.cg_scene_create.restype = ctypes.c_void_p
+    lib.cg_scene_destroy.argtypes = [ctypes.c_void_p]
+    lib.cg_scene_add_body.argtypes = [ctypes.c_void_p, ctypes.c_uint32, ctypes.c_uint32] + [ctypes.c_float] * 3
+    lib.cg_scene_add_body.restype = ctypes.c_int32
+    lib.cg_scene_set_camera.argtypes = [ctypes.c_void_p] + [ctypes.c_float] * 6
+    lib.cg_scene_step.argtypes = [ctypes.c_void_p, ctypes.c_float]
+    lib.cg_scene_render.argtypes = [ctypes.c_void_p, ctypes.c_char_p, ctypes.c_size_t]
+    lib.cg_scene_render.restype = ctypes.c_int32
+    return lib
+
+
+def main():
+    lib = load_library()
+    scene = lib.cg_scene_create(WIDTH, HEIGHT)
+    # shader, semilla, radio de la órbita, velocidad orbital, tamaño
+    lib.cg_scene_add_body(scene, SOLARIUS, 0, 0.0, 0.0, 40.0)
+    lib.cg_scene_add_body(scene, VULCAN, 1, 80.0, 0.8, 8.0)
+    lib.cg_scene_add_body(scene, TERRA, 2, 140.0, 0.5, 12.0)
+    lib.cg_scene_add_body(scene, NEPTURION, 3, 220.0, 0.3, 18.0)
+    lib.cg_scene_set_camera(scene, 0.0, 180.0, 320.0, 0.0, 0.0, 0.0)
+
+    pixels = ctypes.create_string_buffer(WIDTH * HEIGHT * 4)
+    os.makedirs("ffi_demo", exist_ok=True)
+    try:
+        for frame in range(FRAMES):
+            if lib.cg_scene_render(scene, pixels, len(pixels)) != 0:
+                sys.exit("cg_scene_render ha fallado")
+            # PPM no tiene canal alfa: se descarta
+            rgb = bytearray(pixels.raw)
+            del rgb[3::4]
+            path = os.path.join("ffi_demo", "frame_%02d.ppm" % frame)
+            with open(path, "wb") as output:
+                output.write(b"P6 %d %d 255\n" % (WIDTH, HEIGHT))
+                output.write(rgb)
+            print("Guardado", path)
+            lib.cg_scene_step(scene, 0.5)
+    finally:
+        lib.cg_scene_destroy(scene)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,43 @@
+"""Ejemplo del módulo de Python (`maturin develop --release` desde la raíz del repositorio).
+
+Genera una miniatura de cada shader con varias semillas y una animación corta de un sistema, como
+arrays de NumPy que se guardan en `planets/` en formato PPM (sin más dependencias que NumPy).
+"""
+
+import os
+
+import numpy as np
+
+import computer_graphics_v3 as cg
+
+SHADERS = ["Terra", "Vulcan", "Solarius", "Nepturion", "Mossar"]
+
+
+def save_ppm(path, rgba):
+    height, width, _ = rgba.shape
+    with open(path, "wb") as output:
+        output.write(b"P6 %d %d 255\n" % (width, height))
+        output.write(np.ascontiguousarray(rgba[:, :, :3]).tobytes())
+
+
+def main():
+    os.makedirs("planets", exist_ok=True)
+
+    for shader in SHADERS:
+        for seed in range(3):
+            image = cg.render_planet(shader, seed=seed, size=192)
+            save_ppm(os.path.join("planets", "%s_%d.ppm" % (shader.lower(), seed)), image)
+
+    scene = cg.Scene(320, 240)
+    scene.add_body(cg.CelestialBody("Solarius", scale=30.0))
+    scene.add_body(cg.CelestialBody("Terra", seed=4, orbit_radius=90.0, orbit_speed=0.6, scale=9.0))
+    scene.add_body(cg.CelestialBody("Nepturion", seed=5, orbit_radius=160.0, orbit_speed=0.3, scale=15.0))
+    scene.set_camera((0.0, 160.0, 260.0))
+    for frame in range(24):
+        save_ppm(os.path.join("planets", "orbit_%02d.ppm" % frame), scene.render())
+        scene.step(0.25)
+    print("Imágenes en planets/")
+
+
+if __name__ == "__main__":
+    main()
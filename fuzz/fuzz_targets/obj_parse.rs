@@ -0,0 +1,23 @@
+//! Lector de OBJ con entradas arbitrarias: cualquier archivo debe cargarse (con avisos) o
+//! devolver un error, nunca provocar un pánico. Se ejecuta con `cargo fuzz run obj_parse`
+//! (necesita el compilador nightly); conviene sembrar el corpus con los modelos de `assets/models`.
+
+#![no_main]
+
+use std::path::Path;
+use computer_graphics_v3::obj::Obj;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Carpeta inexistente: un `mtllib` del fuzzer no debe leer archivos reales
+    if let Ok((model, _)) = Obj::parse(data, Path::new("/nonexistent")) {
+        for &index in &model.indices {
+            assert!((index as usize) < model.vertices.len());
+        }
+        for vertex in &model.vertices {
+            let p = vertex.position;
+            assert!(p.x.is_finite() && p.y.is_finite() && p.z.is_finite());
+        }
+        model.get_vertex_array();
+    }
+});
@@ -0,0 +1,134 @@
+use nalgebra_glm::Vec3;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::framebuffer::Framebuffer;
+use crate::memory::vec_bytes;
+use crate::scene::BeltConfig;
+use crate::{project_to_screen, Uniforms};
+
+/// Distancia a la cámara por debajo de la cual una roca se dibuja con su malla real
+pub const MESH_DISTANCE: f32 = 60.0;
+
+/// Constante de las órbitas del cinturón: velocidad angular = ORBIT_CONSTANT / r^1.5
+const ORBIT_CONSTANT: f32 = 1100.0;
+
+struct Asteroid {
+    orbit_radius: f32,
+    orbit_angle: f32,
+    orbit_speed: f32,
+    height: f32,
+    size: f32,
+    shade: f32,
+    spin: Vec3,
+}
+
+/// Roca que debe dibujarse con malla este frame
+pub struct NearAsteroid {
+    pub position: Vec3,
+    pub size: f32,
+    pub rotation: Vec3,
+}
+
+/// Cinturón de asteroides. Las órbitas se evalúan en función del tiempo de simulación,
+/// sin estado por frame, y cada roca lejana se dibuja como un punto sombreado (impostor).
+pub struct AsteroidBelt {
+    asteroids: Vec<Asteroid>,
+}
+
+impl AsteroidBelt {
+    pub fn new(config: &BeltConfig) -> Self {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let asteroids = (0..config.count)
+            .map(|_| {
+                let orbit_radius = rng.random_range(config.inner_radius..config.outer_radius);
+                Asteroid {
+                    orbit_radius,
+                    orbit_angle: rng.random_range(0.0..std::f32::consts::TAU),
+                    orbit_speed: ORBIT_CONSTANT / orbit_radius.powf(1.5),
+                    height: rng.random_range(-0.5..0.5) * config.thickness,
+                    size: rng.random_range(0.6..2.2),
+                    shade: rng.random_range(0.45..0.75),
+                    spin: Vec3::new(rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0), 0.0),
+                }
+            })
+            .collect();
+
+        AsteroidBelt { asteroids }
+    }
+
+    fn position(asteroid: &Asteroid, time: f32) -> Vec3 {
+        let angle = asteroid.orbit_angle + asteroid.orbit_speed * time;
+        Vec3::new(
+            asteroid.orbit_radius * angle.cos(),
+            asteroid.height,
+            asteroid.orbit_radius * angle.sin(),
+        )
+    }
+
+    pub fn count(&self) -> usize {
+        self.asteroids.len()
+    }
+
+    /// Posición y radio de cada roca en `time`, para las consultas espaciales (choques y selección)
+    pub fn colliders(&self, time: f32) -> impl Iterator<Item = (Vec3, f32)> + '_ {
+        self.asteroids.iter().map(move |asteroid| (Self::position(asteroid, time), asteroid.size))
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.asteroids)
+    }
+
+    /// Dibuja como impostores las rocas lejanas y devuelve las cercanas para dibujarlas con malla.
+    /// `uniforms` debe tener la matriz de modelo identidad.
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, time: f32) -> Vec<NearAsteroid> {
+        let eye = uniforms.camera_position;
+        let light = uniforms.light_position;
+        // Escala de proyección: píxeles por unidad de mundo a distancia 1
+        let focal = uniforms.viewport_matrix[(1, 1)].abs() * uniforms.projection_matrix[(1, 1)];
+        let mut near = Vec::new();
+
+        for asteroid in &self.asteroids {
+            let position = Self::position(asteroid, time);
+            let to_eye = eye - position;
+            let distance = to_eye.norm();
+
+            if distance < MESH_DISTANCE {
+                near.push(NearAsteroid {
+                    position,
+                    size: asteroid.size,
+                    rotation: asteroid.spin * time,
+                });
+                continue;
+            }
+
+            let Some(screen) = project_to_screen(uniforms, position) else {
+                continue;
+            };
+            if screen.x < 0.0 || screen.y < 0.0 {
+                continue;
+            }
+
+            // Fracción iluminada vista desde la cámara, como en las fases de una esfera
+            let to_light = (light - position).try_normalize(1e-6).unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+            let phase = to_light.dot(&(to_eye / distance));
+            let lit = 0.15 + 0.85 * (1.0 + phase) * 0.5;
+            let value = (asteroid.shade * lit * 255.0) as u32;
+            let color = (value << 16) | ((value * 9 / 10) << 8) | (value * 8 / 10);
+            framebuffer.set_current_color(color);
+
+            // Punto de 2 a 4 píxeles según el tamaño aparente
+            let radius_px = asteroid.size * focal / distance;
+            let pixels: &[(usize, usize)] = if radius_px > 1.5 {
+                &[(0, 0), (1, 0), (0, 1), (1, 1)]
+            } else {
+                &[(0, 0), (1, 0)]
+            };
+            let (x, y) = (screen.x as usize, screen.y as usize);
+            for &(dx, dy) in pixels {
+                framebuffer.point(x + dx, y + dy, screen.z);
+            }
+        }
+
+        near
+    }
+}
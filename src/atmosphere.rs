@@ -0,0 +1,162 @@
+use nalgebra_glm::{self as glm, Mat4, Vec3};
+use crate::framebuffer::Framebuffer;
+
+// Dispersión atmosférica simple (un solo rebote) para las vistas cercanas: por cada píxel se
+// recorre el tramo del rayo de vista dentro de la capa de aire y se suma la luz del sol que el
+// aire desvía hacia la cámara. Rayleigh (azul) domina con el sol alto; cuando la luz atraviesa
+// mucho aire, cerca del horizonte o del terminador, solo llega el rojo y el naranja.
+
+/// Espesor de la capa de aire en radios del planeta (exagerado, como el resto de la escena)
+const SHELL_THICKNESS: f32 = 0.25;
+/// Alturas de escala de Rayleigh y Mie en fracciones del espesor de la capa
+const RAYLEIGH_SCALE_HEIGHT: f32 = 0.25;
+const MIE_SCALE_HEIGHT: f32 = 0.1;
+/// Coeficientes de dispersión por espesor de capa (proporciones de la atmósfera terrestre)
+const RAYLEIGH: Vec3 = Vec3::new(0.21, 0.49, 1.19);
+const MIE: f32 = 0.25;
+/// Asimetría de Mie: el halo alrededor del sol
+const MIE_ASYMMETRY: f32 = 0.76;
+const SUN_INTENSITY: f32 = 20.0;
+const VIEW_SAMPLES: usize = 8;
+const LIGHT_SAMPLES: usize = 3;
+/// Cada muestra cubre un bloque de BLOCK × BLOCK píxeles
+const BLOCK: usize = 2;
+
+/// Capa de aire de un cuerpo, en coordenadas relativas al ojo (la cámara está en el origen)
+pub struct Atmosphere {
+    pub center: Vec3,
+    pub planet_radius: f32,
+}
+
+impl Atmosphere {
+    fn shell_radius(&self) -> f32 {
+        self.planet_radius * (1.0 + SHELL_THICKNESS)
+    }
+
+    /// Densidades relativas de Rayleigh y Mie en el punto `p`
+    fn density(&self, p: Vec3) -> (f32, f32) {
+        let thickness = self.planet_radius * SHELL_THICKNESS;
+        let height = ((p - self.center).norm() - self.planet_radius).max(0.0) / thickness;
+        ((-height / RAYLEIGH_SCALE_HEIGHT).exp(), (-height / MIE_SCALE_HEIGHT).exp())
+    }
+
+    /// Profundidad óptica (Rayleigh, Mie) desde `p` hasta salir de la capa hacia el sol, o `None`
+    /// si el planeta tapa el sol
+    fn light_depth(&self, p: Vec3, to_sun: Vec3) -> Option<(f32, f32)> {
+        if ray_sphere(p, to_sun, self.center, self.planet_radius).is_some_and(|(near, _)| near > 0.0) {
+            return None;
+        }
+        let (_, exit) = ray_sphere(p, to_sun, self.center, self.shell_radius())?;
+        let step = exit.max(0.0) / LIGHT_SAMPLES as f32;
+        let mut depth = (0.0, 0.0);
+        for i in 0..LIGHT_SAMPLES {
+            let (rayleigh, mie) = self.density(p + to_sun * (step * (i as f32 + 0.5)));
+            depth.0 += rayleigh * step;
+            depth.1 += mie * step;
+        }
+        Some(depth)
+    }
+
+    /// Luz dispersada hacia la cámara y transmitancia del tramo de aire que cruza el rayo
+    /// `direction` (normalizada) desde el ojo; `None` si el rayo no toca la capa
+    fn scatter(&self, direction: Vec3, sun: Vec3) -> Option<(Vec3, Vec3)> {
+        let (near, mut far) = ray_sphere(Vec3::zeros(), direction, self.center, self.shell_radius())?;
+        if far <= 0.0 {
+            return None;
+        }
+        if let Some((ground, _)) = ray_sphere(Vec3::zeros(), direction, self.center, self.planet_radius) {
+            if ground > 0.0 {
+                far = far.min(ground);
+            }
+        }
+        let near = near.max(0.0);
+
+        let thickness = self.planet_radius * SHELL_THICKNESS;
+        let rayleigh_coefficient = RAYLEIGH / thickness;
+        let mie_coefficient = MIE / thickness;
+        let extinction = |depth: (f32, f32)| {
+            (rayleigh_coefficient * depth.0 + Vec3::repeat(mie_coefficient * 1.1 * depth.1)).map(|x| (-x).exp())
+        };
+
+        let step = (far - near) / VIEW_SAMPLES as f32;
+        let mut view_depth = (0.0, 0.0);
+        let (mut rayleigh_sum, mut mie_sum) = (Vec3::zeros(), Vec3::zeros());
+        let mut mu = 0.0;
+        for i in 0..VIEW_SAMPLES {
+            let p = direction * (near + step * (i as f32 + 0.5));
+            let (rayleigh, mie) = self.density(p);
+            view_depth.0 += rayleigh * step;
+            view_depth.1 += mie * step;
+
+            let to_sun = (sun - p).normalize();
+            mu = direction.dot(&to_sun);
+            let Some(light_depth) = self.light_depth(p, to_sun) else {
+                continue;
+            };
+            let transmittance = extinction((view_depth.0 + light_depth.0, view_depth.1 + light_depth.1));
+            rayleigh_sum += transmittance * rayleigh * step;
+            mie_sum += transmittance * mie * step;
+        }
+
+        // Funciones de fase: Rayleigh simétrica, Mie (Henyey-Greenstein) concentrada hacia el sol
+        let rayleigh_phase = 3.0 / (16.0 * std::f32::consts::PI) * (1.0 + mu * mu);
+        let g = MIE_ASYMMETRY;
+        let mie_phase = (1.0 - g * g) / (4.0 * std::f32::consts::PI * (1.0 + g * g - 2.0 * g * mu).powf(1.5));
+        let light = (rayleigh_sum.component_mul(&rayleigh_coefficient) * rayleigh_phase
+            + mie_sum * mie_coefficient * mie_phase)
+            * SUN_INTENSITY;
+        Some((light, extinction(view_depth)))
+    }
+
+    /// Aplica la atmósfera sobre lo ya dibujado. `inverse_view_projection` es la inversa de
+    /// viewport · proyección · vista (relativa al ojo); `sun` es la posición del sol relativa al ojo.
+    pub fn render(&self, framebuffer: &mut Framebuffer, inverse_view_projection: &Mat4, sun: Vec3) {
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        for block_y in (0..height).step_by(BLOCK) {
+            for block_x in (0..width).step_by(BLOCK) {
+                let center = (block_x as f32 + BLOCK as f32 * 0.5, block_y as f32 + BLOCK as f32 * 0.5);
+                let point = inverse_view_projection * glm::vec4(center.0, center.1, 0.5, 1.0);
+                let Some(direction) = (point.xyz() / point.w).try_normalize(1e-6) else {
+                    continue;
+                };
+                let Some((light, transmittance)) = self.scatter(direction, sun) else {
+                    continue;
+                };
+
+                for y in block_y..(block_y + BLOCK).min(height) {
+                    for x in block_x..(block_x + BLOCK).min(width) {
+                        let index = y * width + x;
+                        let color = unpack(framebuffer.buffer[index]).component_mul(&transmittance) + light;
+                        framebuffer.buffer[index] = pack(color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Distancias de entrada y salida del rayo `origin + t · direction` en la esfera
+fn ray_sphere(origin: Vec3, direction: Vec3, center: Vec3, radius: f32) -> Option<(f32, f32)> {
+    let offset = origin - center;
+    let b = offset.dot(&direction);
+    let c = offset.norm_squared() - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    Some((-b - root, -b + root))
+}
+
+fn unpack(color: u32) -> Vec3 {
+    Vec3::new(
+        ((color >> 16) & 0xFF) as f32 / 255.0,
+        ((color >> 8) & 0xFF) as f32 / 255.0,
+        (color & 0xFF) as f32 / 255.0,
+    )
+}
+
+fn pack(color: Vec3) -> u32 {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0) as u32;
+    (channel(color.x) << 16) | (channel(color.y) << 8) | channel(color.z)
+}
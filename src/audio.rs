@@ -0,0 +1,54 @@
+use rand::Rng;
+use raylib::prelude::{AudioStream, RaylibAudio};
+
+const SAMPLE_RATE: u32 = 44_100;
+const BUFFER_FRAMES: usize = 4096;
+
+/// Rugido de reentrada sintetizado: ruido marrón más un retumbo grave,
+/// con volumen y tono controlados por la intensidad del efecto.
+pub struct EntryRoar<'a> {
+    stream: AudioStream<'a>,
+    /// Volumen general (0 a 1), del menú de pausa
+    pub volume: f32,
+    buffer: Vec<i16>,
+    brown: f32,
+    rumble_phase: f32,
+}
+
+impl<'a> EntryRoar<'a> {
+    pub fn new(audio: &'a RaylibAudio) -> Self {
+        audio.set_audio_stream_buffer_size_default(BUFFER_FRAMES as i32);
+        let stream = audio.new_audio_stream(SAMPLE_RATE, 16, 1);
+        stream.set_volume(0.0);
+        stream.play();
+
+        EntryRoar {
+            stream,
+            volume: 1.0,
+            buffer: vec![0; BUFFER_FRAMES],
+            brown: 0.0,
+            rumble_phase: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, intensity: f32) {
+        let intensity = intensity.clamp(0.0, 1.0);
+        self.stream.set_volume(intensity * 0.8 * self.volume);
+        self.stream.set_pitch(0.8 + intensity * 0.5);
+
+        if !self.stream.is_processed() {
+            return;
+        }
+
+        let mut rng = rand::rng();
+        let rumble_step = 45.0 * std::f32::consts::TAU / SAMPLE_RATE as f32;
+        for sample in self.buffer.iter_mut() {
+            let white: f32 = rng.random_range(-1.0..1.0);
+            self.brown = (self.brown + white * 0.02) / 1.02;
+            self.rumble_phase = (self.rumble_phase + rumble_step) % std::f32::consts::TAU;
+            let value = self.brown * 3.5 + self.rumble_phase.sin() * 0.25;
+            *sample = (value.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        }
+        self.stream.update(&self.buffer);
+    }
+}
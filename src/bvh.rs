@@ -0,0 +1,157 @@
+use nalgebra_glm::DVec3;
+
+// Jerarquía de esferas envolventes para las consultas espaciales de la escena (selección con el
+// ratón y choques de la nave). Se reconstruye cada vez que se usa, porque todo se mueve, pero
+// construirla cuesta O(n log n) y cada consulta recorre solo las ramas que pueden acertar, así
+// siguen siendo rápidas con miles de objetos (las rocas del cinturón, por ejemplo).
+
+/// Objetos por hoja
+const LEAF_SIZE: usize = 4;
+
+struct Item<T> {
+    center: DVec3,
+    radius: f64,
+    id: T,
+}
+
+enum Children {
+    /// Rango de `items`
+    Leaf(usize, usize),
+    /// Índices en `nodes`
+    Branch(usize, usize),
+}
+
+struct Node {
+    center: DVec3,
+    radius: f64,
+    children: Children,
+}
+
+pub struct Bvh<T> {
+    nodes: Vec<Node>,
+    items: Vec<Item<T>>,
+}
+
+impl<T: Copy> Bvh<T> {
+    /// `items` son esferas (centro, radio) con el identificador que devuelven las consultas
+    pub fn build<I: IntoIterator<Item = (DVec3, f64, T)>>(items: I) -> Self {
+        let items: Vec<Item<T>> = items.into_iter().map(|(center, radius, id)| Item { center, radius, id }).collect();
+        let mut bvh = Bvh { nodes: Vec::with_capacity(items.len() * 2 / LEAF_SIZE + 1), items };
+        if !bvh.items.is_empty() {
+            bvh.build_node(0, bvh.items.len());
+        }
+        bvh
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Crea el nodo de `items[start..end]` y devuelve su índice. Se parte por la mediana del eje
+    /// en el que más se reparten los centros.
+    fn build_node(&mut self, start: usize, end: usize) -> usize {
+        let items = &mut self.items[start..end];
+        let (mut min, mut max) = (DVec3::repeat(f64::INFINITY), DVec3::repeat(f64::NEG_INFINITY));
+        for item in items.iter() {
+            min = min.inf(&(item.center - DVec3::repeat(item.radius)));
+            max = max.sup(&(item.center + DVec3::repeat(item.radius)));
+        }
+        let center = (min + max) * 0.5;
+        let radius = items.iter().map(|item| (item.center - center).norm() + item.radius).fold(0.0, f64::max);
+
+        let index = self.nodes.len();
+        if items.len() <= LEAF_SIZE {
+            self.nodes.push(Node { center, radius, children: Children::Leaf(start, end) });
+            return index;
+        }
+
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let middle = items.len() / 2;
+        items.select_nth_unstable_by(middle, |a, b| a.center[axis].total_cmp(&b.center[axis]));
+
+        self.nodes.push(Node { center, radius, children: Children::Leaf(0, 0) });
+        let left = self.build_node(start, start + middle);
+        let right = self.build_node(start + middle, end);
+        self.nodes[index].children = Children::Branch(left, right);
+        index
+    }
+
+    /// Esfera más cercana que corta el rayo (`direction` normalizada), con la distancia desde el
+    /// origen a su superficie. Como al seleccionar con el ratón, solo cuentan las esferas cuyo
+    /// centro está por delante del origen. `accept` descarta objetos (los ocultos, por ejemplo).
+    pub fn ray_cast<F: Fn(T) -> bool>(&self, origin: DVec3, direction: DVec3, accept: F) -> Option<(T, f64)> {
+        // Distancias de entrada en la esfera y del centro a lo largo del rayo, si el rayo la corta
+        let hit = |center: DVec3, radius: f64| {
+            let to_center = center - origin;
+            let along = to_center.dot(&direction);
+            let closest_sq = to_center.norm_squared() - along * along;
+            let radius_sq = radius * radius;
+            (closest_sq <= radius_sq).then(|| (along - (radius_sq - closest_sq).sqrt(), along))
+        };
+
+        let mut best: Option<(T, f64)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let Some((entry, along)) = hit(node.center, node.radius) else {
+                continue;
+            };
+            if along + node.radius < 0.0 || best.is_some_and(|(_, distance)| entry > distance) {
+                continue;
+            }
+            match node.children {
+                Children::Branch(left, right) => stack.extend([left, right]),
+                Children::Leaf(start, end) => {
+                    for item in &self.items[start..end] {
+                        let Some((distance, along)) = hit(item.center, item.radius) else {
+                            continue;
+                        };
+                        if along >= 0.0 && best.is_none_or(|(_, best)| distance < best) && accept(item.id) {
+                            best = Some((item.id, distance));
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Objetos a menos de `margin` de la superficie de su esfera desde `point`
+    pub fn overlapping(&self, point: DVec3, margin: f64) -> Vec<T> {
+        let mut found = Vec::new();
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if (point - node.center).norm() >= node.radius + margin {
+                continue;
+            }
+            match node.children {
+                Children::Branch(left, right) => stack.extend([left, right]),
+                Children::Leaf(start, end) => found.extend(
+                    self.items[start..end]
+                        .iter()
+                        .filter(|item| (point - item.center).norm() < item.radius + margin)
+                        .map(|item| item.id),
+                ),
+            }
+        }
+        found
+    }
+}
@@ -0,0 +1,184 @@
+use std::f64::consts::PI;
+use std::fs;
+use nalgebra_glm::DVec3;
+use serde::{Deserialize, Serialize};
+
+fn default_fps() -> u32 {
+    30
+}
+
+fn default_time_scale() -> f64 {
+    1.0
+}
+
+/// Hacia dónde mira la cámara en un punto de paso: un punto fijo o el nombre de un cuerpo, que
+/// se sigue a lo largo de su órbita
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum LookAt {
+    Point([f64; 3]),
+    Body(String),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Keyframe {
+    /// Segundo del vídeo en el que la cámara pasa por aquí
+    pub time: f32,
+    pub position: [f64; 3],
+    pub look_at: LookAt,
+}
+
+/// Recorrido de cámara de `--render-video`, en TOML (`--camera-path`): la posición pasa por los
+/// puntos de paso con una curva suave (Catmull-Rom) y el punto al que mira se interpola en línea
+/// recta. El vídeo dura hasta el último punto de paso.
+#[derive(Serialize, Deserialize)]
+pub struct CameraPath {
+    #[serde(default = "default_fps")]
+    pub fps: u32,
+    /// Segundos de simulación por segundo de vídeo
+    #[serde(default = "default_time_scale")]
+    pub time_scale: f64,
+    /// Instante de la simulación en el que empieza el vídeo; sin él, el de la escena (o el de la
+    /// sesión con `--resume`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<f64>,
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|err| format!("no se pudo leer {}: {}", path, err))?;
+        let camera_path: CameraPath = toml::from_str(&contents).map_err(|err| format!("recorrido inválido: {}", err))?;
+        if camera_path.fps == 0 {
+            return Err("fps debe ser mayor que 0".to_string());
+        }
+        if camera_path.keyframes.len() < 2 {
+            return Err("el recorrido necesita al menos dos puntos de paso".to_string());
+        }
+        if camera_path.keyframes.windows(2).any(|pair| pair[1].time <= pair[0].time) {
+            return Err("los tiempos de los puntos de paso deben ir en aumento".to_string());
+        }
+        Ok(camera_path)
+    }
+
+    /// Recorrido por defecto: media vuelta de 12 s alrededor del sistema de radio `radius`,
+    /// bajando poco a poco hacia el plano de las órbitas y mirando siempre a la estrella
+    pub fn overview(radius: f32) -> Self {
+        const STEPS: usize = 6;
+        let radius = radius as f64;
+        let keyframes = (0..=STEPS)
+            .map(|step| {
+                let progress = step as f64 / STEPS as f64;
+                let angle = progress * PI;
+                let distance = radius * 1.4;
+                let height = radius * (0.5 - 0.3 * progress);
+                Keyframe {
+                    time: step as f32 * 2.0,
+                    position: [distance * angle.sin(), height, distance * angle.cos()],
+                    look_at: LookAt::Point([0.0; 3]),
+                }
+            })
+            .collect();
+        CameraPath { fps: default_fps(), time_scale: default_time_scale(), start_time: None, keyframes }
+    }
+
+    /// Guarda el recorrido en TOML, con el mismo formato que lee `load`
+    pub fn save(&self, path: &str, header: &str) -> Result<(), String> {
+        let contents = toml::to_string(self).map_err(|err| format!("no se pudo escribir el recorrido: {}", err))?;
+        fs::write(path, format!("{}\n{}", header, contents)).map_err(|err| format!("no se pudo guardar {}: {}", path, err))
+    }
+
+    pub fn frame_count(&self) -> usize {
+        let duration = self.keyframes.last().map_or(0.0, |keyframe| keyframe.time);
+        (duration * self.fps as f32).ceil() as usize + 1
+    }
+
+    /// Posición de la cámara y punto al que mira en el segundo `time`; `resolve` da la posición
+    /// de cada `LookAt` en ese instante
+    pub fn sample<F: Fn(&LookAt) -> DVec3>(&self, time: f32, resolve: F) -> (DVec3, DVec3) {
+        let keyframes = &self.keyframes;
+        let last = keyframes.len() - 1;
+        let segment = keyframes[1..].iter().position(|keyframe| time < keyframe.time).unwrap_or(last - 1);
+        let (from, to) = (&keyframes[segment], &keyframes[segment + 1]);
+        let t = ((time - from.time) / (to.time - from.time)).clamp(0.0, 1.0) as f64;
+
+        let point = |index: usize| DVec3::from(keyframes[index].position);
+        let (p0, p1) = (point(segment.saturating_sub(1)), point(segment));
+        let (p2, p3) = (point(segment + 1), point((segment + 2).min(last)));
+        let position = (p1 * 2.0
+            + (p2 - p0) * t
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (t * t)
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (t * t * t))
+            * 0.5;
+        let target = resolve(&from.look_at).lerp(&resolve(&to.look_at), t);
+        (position, target)
+    }
+}
+
+/// Segundos de vuelo entre dos puntos de paso de una grabación: con la curva entre ellos basta
+/// para seguir los giros sin que el archivo crezca demasiado
+const RECORD_INTERVAL: f32 = 0.25;
+/// Distancia delante de la cámara del punto al que mira cada punto de paso grabado. Lejos, para
+/// que al interpolarlo en línea recta el giro entre dos puntos de paso sea suave y la curva de
+/// la posición no lo adelante.
+const RECORD_LOOK_DISTANCE: f64 = 1000.0;
+
+/// Graba el vuelo en directo como un `CameraPath`: un punto de paso cada `RECORD_INTERVAL`
+/// segundos de tiempo real, mirando hacia donde miraba la cámara. Al reproducirlo con
+/// `--render-video` la simulación empieza en el mismo instante y avanza al ritmo medio de la
+/// grabación; el alabeo de la cámara no se guarda (el recorrido siempre tiene la y arriba).
+/// Como el vídeo solo avanza la simulación, los puntos de paso en los que no avanzó (en pausa o
+/// tras volver atrás en la línea de tiempo) se descartan, y los frames en pausa no cuentan.
+#[derive(Default)]
+pub struct PathRecorder {
+    /// Segundos reales grabados, sin los frames en los que la simulación estaba en pausa
+    elapsed: f32,
+    /// Instante de la simulación del último frame, para reconocer los que están en pausa
+    last_sim_time: Option<f64>,
+    keyframes: Vec<Keyframe>,
+    /// Instante de la simulación de cada punto de paso
+    sim_times: Vec<f64>,
+}
+
+impl PathRecorder {
+    /// Cuenta los `delta` segundos reales del frame, si la simulación avanzó hasta `sim_time`, y
+    /// toma un punto de paso si ya toca
+    pub fn record(&mut self, delta: f32, sim_time: f64, eye: DVec3, forward: DVec3) {
+        if self.last_sim_time.replace(sim_time) != Some(sim_time) {
+            self.elapsed += delta;
+        }
+        let due = self.keyframes.last().is_none_or(|last| self.elapsed - last.time >= RECORD_INTERVAL);
+        if due && self.sim_times.last().is_none_or(|&last| sim_time > last) {
+            let target = eye + forward * RECORD_LOOK_DISTANCE;
+            self.keyframes.push(Keyframe { time: self.elapsed, position: eye.into(), look_at: LookAt::Point(target.into()) });
+            self.sim_times.push(sim_time);
+        }
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Termina la grabación; falla si no quedaron dos puntos de paso en los que avanzara la
+    /// simulación
+    pub fn finish(mut self) -> Result<CameraPath, String> {
+        let (Some(&first), Some(&last)) = (self.sim_times.first(), self.sim_times.last()) else {
+            return Err("la grabación está vacía".to_string());
+        };
+        if self.keyframes.len() < 2 {
+            return Err("la grabación es demasiado corta o la simulación no avanzó".to_string());
+        }
+        // El recorrido empieza en su primer punto de paso
+        let offset = self.keyframes[0].time;
+        for keyframe in &mut self.keyframes {
+            keyframe.time -= offset;
+        }
+        let real = self.keyframes[self.keyframes.len() - 1].time as f64;
+        Ok(CameraPath {
+            fps: default_fps(),
+            time_scale: (last - first) / real,
+            start_time: Some(first),
+            keyframes: self.keyframes,
+        })
+    }
+}
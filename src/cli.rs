@@ -0,0 +1,253 @@
+use crate::hud::MAX_UI_SCALE;
+use crate::pacing::FrameCap;
+use crate::pipeline::Culling;
+use crate::scene::ScaleMode;
+use crate::telemetry::TelemetryFormat;
+use crate::upscale::ScaleFilter;
+
+/// Opciones de línea de comandos
+#[derive(Debug)]
+pub struct CliOptions {
+    pub telemetry: Option<String>,
+    pub telemetry_format: Option<TelemetryFormat>,
+    pub remote: Option<String>,
+    /// Manda el estado de la simulación en cada frame a los observadores que se conecten aquí
+    pub serve: Option<String>,
+    /// Sigue la simulación de un servidor (`--serve`) con la cámara libre
+    pub observe: Option<String>,
+    pub resolution: (usize, usize),
+    pub window_size: (usize, usize),
+    pub filter: ScaleFilter,
+    pub integer_scale: bool,
+    /// Exporta el esquema del sistema a este archivo y termina
+    pub orrery: Option<String>,
+    pub to_scale: bool,
+    /// Dibuja un solo frame a `resolution` en este PNG, sin ventana, y termina
+    pub render_still: Option<String>,
+    /// Exporta un vídeo del recorrido de `camera_path` a este archivo, sin ventana, y termina
+    pub render_video: Option<String>,
+    /// Recorrido de cámara (TOML) de `render_video`; sin él, una vuelta por el sistema
+    pub camera_path: Option<String>,
+    /// Muestras por lado de cada píxel de `render_still` y `render_video`
+    pub supersample: usize,
+    /// Z-buffer logarítmico desde el inicio (también con la tecla Z)
+    pub log_depth: bool,
+    /// Culling de todas las mallas; sin él, cada una usa el que se detectó al cargarla (ver
+    /// `MeshInfo::culling`)
+    pub culling: Option<Culling>,
+    /// Escenas entre las que se puede cambiar (la primera es la inicial); vacío = todas las de
+    /// `assets/scenes`
+    pub scenes: Vec<String>,
+    /// Semilla del modo galaxia: otros sistemas estelares a los que saltar (mapa con Tab)
+    pub galaxy: Option<u64>,
+    /// Sustituye el `scale_mode` del archivo de escena
+    pub scale_mode: Option<ScaleMode>,
+    /// Modelo de la nave (OBJ, STL o PLY) en lugar del Y-wing
+    pub ship_model: Option<String>,
+    /// Presupuesto de memoria para texturas, en MB
+    pub texture_budget: usize,
+    /// Memoria total a partir de la que se avisa, en MB
+    pub memory_budget: usize,
+    /// Tamaño de la interfaz (1 a `MAX_UI_SCALE`)
+    pub ui_scale: usize,
+    pub high_contrast: bool,
+    pub frame_cap: FrameCap,
+    /// Espera activa al final de cada frame para clavar el límite de FPS
+    pub busy_wait: bool,
+    /// Continúa desde el estado guardado al salir la última vez
+    pub resume: bool,
+    /// Escribe los cambios de estado importantes en stdout como líneas JSON
+    pub status_output: bool,
+    /// Escenario guiado (TOML) con el que empezar
+    pub scenario: Option<String>,
+    /// Hilos del planificador de tareas, contando el principal (por defecto, uno por núcleo)
+    pub threads: Option<usize>,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        CliOptions {
+            telemetry: None,
+            telemetry_format: None,
+            remote: None,
+            serve: None,
+            observe: None,
+            resolution: (800, 600),
+            window_size: (1200, 800),
+            filter: ScaleFilter::Bilinear,
+            integer_scale: false,
+            orrery: None,
+            to_scale: false,
+            render_still: None,
+            render_video: None,
+            camera_path: None,
+            supersample: 2,
+            log_depth: false,
+            culling: None,
+            scenes: Vec::new(),
+            galaxy: None,
+            scale_mode: None,
+            ship_model: None,
+            texture_budget: 64,
+            memory_budget: 256,
+            ui_scale: 1,
+            high_contrast: false,
+            frame_cap: FrameCap::Fps(60),
+            busy_wait: false,
+            resume: false,
+            status_output: false,
+            scenario: None,
+            threads: None,
+        }
+    }
+}
+
+/// Más muestras por lado no mejoran la imagen y disparan la memoria (n² veces la resolución)
+const MAX_SUPERSAMPLE: usize = 4;
+
+/// Interpreta un tamaño con formato ANCHOxALTO, p. ej. 800x600
+fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("tamaño inválido: {} (usa ANCHOxALTO)", value);
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+    let width: usize = width.parse().map_err(|_| invalid())?;
+    let height: usize = height.parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width, height))
+}
+
+pub const USAGE: &str = "\
+Uso: computer-graphics-v3 [opciones]
+
+Opciones:
+  --telemetry <destino>         Exporta telemetría por frame a un archivo o a tcp://host:puerto
+  --telemetry-format <formato>  jsonl (por defecto) o csv; se deduce de la extensión .csv
+  --remote <host:puerto>        Acepta comandos JSON de control remoto (requiere la feature \"remote\")
+                                sin autenticación: usa 127.0.0.1 salvo en una red de confianza
+  --serve <host:puerto>         Reparte el reloj, los cuerpos y la nave a los observadores
+  --observe <host:puerto>       Sigue la simulación de otra instancia con --serve, con cámara libre
+                                (las dos con la misma escena)
+  --resolution <AxH>            Resolución interna de render (por defecto 800x600)
+  --window <AxH>                Tamaño de la ventana (por defecto 1200x800)
+  --filter <filtro>             Escalado a la ventana: bilinear (por defecto) o nearest
+  --integer-scale               Escala solo por factores enteros
+  --orrery <archivo>            Exporta una vista cenital del sistema (.svg o .png) y termina
+  --to-scale                    Distancias y tamaños a escala en los esquemas (tecla E y --orrery)
+  --render-still <archivo.png>  Dibuja un frame a --resolution con el máximo detalle y sin límite
+                                de tiempo, lo guarda y termina (con --resume, desde la sesión)
+  --render-video <archivo>      Exporta un vídeo del recorrido de cámara a paso fijo y termina: .mp4
+                                (u otro formato) con ffmpeg, o .y4m sin comprimir y sin ffmpeg
+  --camera-path <archivo>       Recorrido de --render-video (.toml; por defecto, vuelta al sistema);
+                                la tecla Y graba uno volando
+  --supersample <n>             Muestras por lado de cada píxel de --render-still y --render-video
+                                (por defecto 2)
+  --log-depth                   Z-buffer logarítmico: más precisión para los cuerpos lejanos
+  --cull <sentido>              Caras que se dibujan: cw, ccw u off (las dos); por defecto, las
+                                delanteras según el sentido detectado en cada malla
+  --scene <archivo>             Escena (.toml); se puede repetir y cambiar entre ellas en el menú
+                                (por defecto, todas las de assets/scenes)
+  --galaxy <semilla>            Modo galaxia: sistemas generados con la semilla; Tab abre el mapa
+  --scale <modo>                stylized (compacto) o semi-realistic (distancias comprimidas con log.)
+  --ship <modelo>               Modelo de la nave: .obj, .stl o .ply
+  --texture-budget <MB>         Memoria para texturas (por defecto 64); el inspector la compara con la usada
+  --memory-budget <MB>          Avisa si la memoria total pasa de este valor (por defecto 256)
+  --ui-scale <1-3>              Tamaño del texto y la interfaz (por defecto 1)
+  --high-contrast               Interfaz de alto contraste: colores vivos, contorno y fondos negros
+  --fps <límite>                30, 60 (por defecto), 120 o uncapped
+  --busy-wait                   Apura cada frame con espera activa: ritmo más exacto, más CPU
+  --resume                      Continúa donde se dejó la última sesión (autosave.json)
+  --status-output               Cambios de estado (selección, warp, choques...) en stdout como
+                                líneas \"@estado {json}\" para lectores de pantalla y overlays
+  --scenario <archivo>          Empieza un escenario guiado (.toml), p. ej.
+                                assets/scenarios/honda_gravitatoria.toml
+  --threads <n>                 Hilos para el render (por defecto, uno por núcleo; 1 = sin hilos)
+  -h, --help                    Muestra esta ayuda";
+
+impl CliOptions {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = CliOptions::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next().ok_or_else(|| format!("falta el valor de {}", name))
+            };
+            match arg.as_str() {
+                "--telemetry" => options.telemetry = Some(value("--telemetry")?),
+                "--telemetry-format" => {
+                    let format = value("--telemetry-format")?;
+                    options.telemetry_format = Some(TelemetryFormat::parse(&format)?);
+                }
+                "--remote" => options.remote = Some(value("--remote")?),
+                "--serve" => options.serve = Some(value("--serve")?),
+                "--observe" => options.observe = Some(value("--observe")?),
+                "--resolution" => options.resolution = parse_size(&value("--resolution")?)?,
+                "--window" => options.window_size = parse_size(&value("--window")?)?,
+                "--filter" => options.filter = ScaleFilter::parse(&value("--filter")?)?,
+                "--integer-scale" => options.integer_scale = true,
+                "--orrery" => options.orrery = Some(value("--orrery")?),
+                "--to-scale" => options.to_scale = true,
+                "--render-still" => options.render_still = Some(value("--render-still")?),
+                "--render-video" => options.render_video = Some(value("--render-video")?),
+                "--camera-path" => options.camera_path = Some(value("--camera-path")?),
+                "--supersample" => {
+                    let factor = value("--supersample")?;
+                    options.supersample = factor
+                        .parse()
+                        .ok()
+                        .filter(|&factor| (1..=MAX_SUPERSAMPLE).contains(&factor))
+                        .ok_or_else(|| format!("supersampling inválido: {} (de 1 a {})", factor, MAX_SUPERSAMPLE))?;
+                }
+                "--log-depth" => options.log_depth = true,
+                "--cull" => options.culling = Some(Culling::parse(&value("--cull")?)?),
+                "--scene" => options.scenes.push(value("--scene")?),
+                "--galaxy" => {
+                    let seed = value("--galaxy")?;
+                    options.galaxy = Some(seed.parse().map_err(|_| format!("semilla inválida: {}", seed))?);
+                }
+                "--scale" => options.scale_mode = Some(ScaleMode::parse(&value("--scale")?)?),
+                "--ship" => options.ship_model = Some(value("--ship")?),
+                "--texture-budget" => {
+                    let budget = value("--texture-budget")?;
+                    options.texture_budget = budget.parse().map_err(|_| format!("presupuesto inválido: {} (en MB)", budget))?;
+                }
+                "--memory-budget" => {
+                    let budget = value("--memory-budget")?;
+                    options.memory_budget = budget.parse().map_err(|_| format!("presupuesto inválido: {} (en MB)", budget))?;
+                }
+                "--ui-scale" => {
+                    let scale = value("--ui-scale")?;
+                    options.ui_scale = scale
+                        .parse()
+                        .ok()
+                        .filter(|scale| (1..=MAX_UI_SCALE).contains(scale))
+                        .ok_or_else(|| format!("escala de interfaz inválida: {} (de 1 a {})", scale, MAX_UI_SCALE))?;
+                }
+                "--high-contrast" => options.high_contrast = true,
+                "--fps" => options.frame_cap = FrameCap::parse(&value("--fps")?)?,
+                "--busy-wait" => options.busy_wait = true,
+                "--resume" => options.resume = true,
+                "--status-output" => options.status_output = true,
+                "--scenario" => options.scenario = Some(value("--scenario")?),
+                "--threads" => {
+                    let threads = value("--threads")?;
+                    options.threads = Some(
+                        threads
+                            .parse()
+                            .ok()
+                            .filter(|&threads| threads > 0)
+                            .ok_or_else(|| format!("número de hilos inválido: {}", threads))?,
+                    );
+                }
+                "-h" | "--help" => return Err(String::new()),
+                other => return Err(format!("opción desconocida: {}", other)),
+            }
+        }
+
+        if options.serve.is_some() && options.observe.is_some() {
+            return Err("--serve y --observe no se pueden usar a la vez".to_string());
+        }
+        Ok(options)
+    }
+}
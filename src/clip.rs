@@ -0,0 +1,103 @@
+use nalgebra_glm::{Mat4, Vec4};
+use raylib::math::Vector4;
+use crate::vertex::Vertex;
+
+/// Planos del frustum en espacio de recorte, con la normal hacia dentro: un punto (x, y, z, w)
+/// está dentro si `plano · punto >= 0`. El cercano va primero: es el que evita que un vértice
+/// detrás de la cámara (w <= 0) llegue a la división de perspectiva.
+const PLANES: [Vec4; 6] = [
+    Vec4::new(0.0, 0.0, 1.0, 1.0),
+    Vec4::new(0.0, 0.0, -1.0, 1.0),
+    Vec4::new(1.0, 0.0, 0.0, 1.0),
+    Vec4::new(-1.0, 0.0, 0.0, 1.0),
+    Vec4::new(0.0, 1.0, 0.0, 1.0),
+    Vec4::new(0.0, -1.0, 0.0, 1.0),
+];
+
+/// Resultado de recortar un triángulo contra el frustum
+#[derive(Debug)]
+pub enum Clipped {
+    /// Entero dentro: se dibuja tal cual
+    Inside,
+    /// Entero fuera de algún plano: no se dibuja
+    Outside,
+    /// Cruza algún plano: triángulos nuevos (los vértices de 3 en 3, en abanico y con el mismo
+    /// sentido de giro) con los atributos interpolados. Contra el plano cercano solo salen uno o
+    /// dos; contra los seis, hasta siete.
+    Split(Vec<Vertex>),
+}
+
+/// Recorta el triángulo (vértices del modelo) contra el frustum de `clip_matrix` (proyección ·
+/// vista · modelo) antes de la división de perspectiva. En espacio de recorte la interpolación
+/// es lineal, así que los vértices nuevos se pueden pasar por `vertex_shader` como los demás.
+pub fn clip_triangle(triangle: &[Vertex], clip_matrix: &Mat4) -> Clipped {
+    let corners = [&triangle[0], &triangle[1], &triangle[2]];
+    clip_corners(corners, corners.map(|vertex| clip_position(vertex, clip_matrix)))
+}
+
+/// Posición del vértice en espacio de recorte
+pub fn clip_position(vertex: &Vertex, clip_matrix: &Mat4) -> Vec4 {
+    let p = vertex.position;
+    clip_matrix * Vec4::new(p.x, p.y, p.z, 1.0)
+}
+
+/// Como `clip_triangle`, con las posiciones en espacio de recorte ya calculadas: las mallas
+/// indexadas las calculan una vez por vértice y no por cada triángulo que lo usa
+pub fn clip_corners(corners: [&Vertex; 3], positions: [Vec4; 3]) -> Clipped {
+    let mut crossing = false;
+    for plane in &PLANES {
+        let inside = positions.iter().filter(|position| plane.dot(position) >= 0.0).count();
+        if inside == 0 {
+            return Clipped::Outside;
+        }
+        crossing |= inside < positions.len();
+    }
+    if !crossing {
+        return Clipped::Inside;
+    }
+
+    // Sutherland-Hodgman: el polígono pierde lo que queda fuera de cada plano
+    let mut polygon: Vec<(Vertex, Vec4)> = corners.into_iter().cloned().zip(positions).collect();
+    for plane in &PLANES {
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (index, (vertex, position)) in polygon.iter().enumerate() {
+            let (next_vertex, next_position) = &polygon[(index + 1) % polygon.len()];
+            let (distance, next_distance) = (plane.dot(position), plane.dot(next_position));
+            if distance >= 0.0 {
+                clipped.push((vertex.clone(), *position));
+            }
+            if (distance >= 0.0) != (next_distance >= 0.0) {
+                let t = distance / (distance - next_distance);
+                clipped.push((lerp_vertex(vertex, next_vertex, t), position.lerp(next_position, t)));
+            }
+        }
+        polygon = clipped;
+        if polygon.len() < 3 {
+            return Clipped::Outside;
+        }
+    }
+
+    let mut vertices = Vec::with_capacity((polygon.len() - 2) * 3);
+    for index in 1..polygon.len() - 1 {
+        vertices.extend([polygon[0].0.clone(), polygon[index].0.clone(), polygon[index + 1].0.clone()]);
+    }
+    Clipped::Split(vertices)
+}
+
+/// Vértice a la fracción `t` de `a` a `b`. La normal y la tangente no se normalizan: el
+/// rasterizador ya lo hace al interpolarlas.
+fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    let position = a.position.lerp(b.position, t);
+    let normal = a.normal.lerp(b.normal, t);
+    let (ta, tb) = (a.tangent, b.tangent);
+    let tangent = Vector4::new(ta.x + (tb.x - ta.x) * t, ta.y + (tb.y - ta.y) * t, ta.z + (tb.z - ta.z) * t, ta.w);
+    Vertex {
+        position,
+        normal,
+        tex_coords: a.tex_coords.lerp(b.tex_coords, t),
+        tangent,
+        color: a.color.lerp(b.color, t),
+        transformed_position: position,
+        transformed_normal: normal,
+    }
+}
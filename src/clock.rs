@@ -0,0 +1,60 @@
+/// Reloj de simulación independiente del reloj real: se puede pausar y acelerar.
+/// Todo lo que pertenece al sistema (órbitas, rotaciones, animación de shaders) avanza con él;
+/// la nave y la interfaz siguen usando el tiempo real.
+pub struct SimulationClock {
+    /// En doble precisión: con f32 el tiempo avanzaría a saltos visibles tras unas horas
+    pub time: f64,
+    pub time_scale: f32,
+    pub paused: bool,
+}
+
+/// Segundos simulados por día: se toma la órbita de Terra (0.3 rad/s) como un año de 365 días
+pub const SECONDS_PER_DAY: f32 = std::f32::consts::TAU / 0.3 / 365.0;
+
+pub const MIN_TIME_SCALE: f32 = 0.125;
+pub const MAX_TIME_SCALE: f32 = 1024.0;
+
+impl SimulationClock {
+    pub fn new() -> Self {
+        SimulationClock {
+            time: 0.0,
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+
+    /// Avanza el reloj con el delta real del frame y devuelve el delta simulado
+    pub fn advance(&mut self, real_delta: f32) -> f32 {
+        if self.paused {
+            return 0.0;
+        }
+        let delta = real_delta * self.time_scale;
+        self.time += delta as f64;
+        delta
+    }
+
+    pub fn days(&self) -> f32 {
+        (self.time / SECONDS_PER_DAY as f64) as f32
+    }
+
+    /// Salta `days` días hacia delante o hacia atrás, sin bajar de t = 0
+    pub fn jump_days(&mut self, days: f32) {
+        self.time = (self.time + (days * SECONDS_PER_DAY) as f64).max(0.0);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn speed_up(&mut self) {
+        self.set_time_scale(self.time_scale * 2.0);
+    }
+
+    pub fn slow_down(&mut self) {
+        self.set_time_scale(self.time_scale / 2.0);
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    }
+}
@@ -0,0 +1,75 @@
+use nalgebra_glm as glm;
+use crate::framebuffer::Framebuffer;
+use crate::shaders::ShaderParams;
+use crate::Uniforms;
+
+// Corona: el halo de gas tenue que rodea a la estrella. Al lado del disco no se ve, así que solo
+// se dibuja cuando algo lo tapa casi entero, como en un eclipse total. Es un billboard aditivo
+// con la profundidad del centro de la estrella: el cuerpo que la tapa queda por delante y la
+// corona asoma alrededor de su silueta.
+
+/// Alcance de la corona en radios de la estrella
+const CORONA_EXTENT: f32 = 3.5;
+/// Cobertura del disco a partir de la que empieza a verse
+const VISIBLE_FROM: f32 = 0.85;
+const STREAMERS: u32 = 7;
+
+/// Dibuja la corona de la estrella (`uniforms` con su modelo) según `coverage`, la fracción
+/// de su disco que tapan otros cuerpos vistos desde la cámara
+pub fn render_corona(framebuffer: &mut Framebuffer, uniforms: &Uniforms, params: &ShaderParams, coverage: f32) {
+    let visibility = ((coverage - VISIBLE_FROM) / (1.0 - VISIBLE_FROM)).clamp(0.0, 1.0);
+    if visibility <= 0.0 {
+        return;
+    }
+    let mvp = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+    let clip = mvp * glm::vec4(0.0, 0.0, 0.0, 1.0);
+    if clip.w <= 0.0 {
+        return;
+    }
+    let center = uniforms.viewport_matrix * glm::vec4(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    let depth = uniforms.depth_mode.encode(clip.z, clip.w);
+    let pixel_scale = uniforms.viewport_matrix[(1, 1)].abs() * uniforms.projection_matrix[(1, 1)];
+    let model_scale = (uniforms.model_matrix * glm::vec4(1.0, 0.0, 0.0, 0.0)).xyz().norm();
+    let radius = model_scale * pixel_scale / clip.w;
+    if radius < 1.0 {
+        return;
+    }
+
+    // Rayos largos y finos en direcciones fijas de cada estrella que ondulan despacio
+    let streamers: Vec<(f32, f32)> = (0..STREAMERS)
+        .map(|i| (params.hash01(700 + i) * std::f32::consts::TAU, 0.5 + params.hash01(710 + i)))
+        .collect();
+    let extent = radius * CORONA_EXTENT;
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let (x0, x1) = ((center.x - extent).max(0.0) as usize, (center.x + extent).min(width - 1.0).max(0.0) as usize);
+    let (y0, y1) = ((center.y - extent).max(0.0) as usize, (center.y + extent).min(height - 1.0).max(0.0) as usize);
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let (dx, dy) = (x as f32 + 0.5 - center.x, y as f32 + 0.5 - center.y);
+            let distance = (dx * dx + dy * dy).sqrt() / radius;
+            if !(1.0..CORONA_EXTENT).contains(&distance) {
+                continue;
+            }
+            let angle = dy.atan2(dx);
+            let rays: f32 = streamers
+                .iter()
+                .map(|&(direction, length)| {
+                    let offset = (angle - direction + (uniforms.time * 0.05 + direction).sin() * 0.05).sin().abs();
+                    (1.0 - offset * 6.0).max(0.0) * length
+                })
+                .sum();
+            // Anillo interior brillante que cae deprisa, y los rayos que llegan más lejos
+            let falloff = (distance - 1.0).max(0.0);
+            let glow = (-falloff * 4.0).exp() + rays * 0.35 * (-falloff * 1.2).exp();
+            let fade = 1.0 - ((distance - 1.0) / (CORONA_EXTENT - 1.0)).powi(2);
+            let weight = (glow * fade * visibility).min(1.0);
+            if weight <= 0.01 {
+                continue;
+            }
+            let red = (235.0 * weight) as u32;
+            let green = (238.0 * weight) as u32;
+            let blue = (255.0 * weight) as u32;
+            framebuffer.add_point(x, y, (red << 16) | (green << 8) | blue, depth);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use nalgebra_glm::Mat4;
+
+/// Cómo se guarda la profundidad en el z-buffer. En los dos modos el valor va de -1 (cámara)
+/// a 1 (plano lejano) y menor es más cerca, así la prueba de profundidad y el borrado no cambian.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DepthMode {
+    /// z en NDC de la proyección: casi toda la precisión se concentra junto al plano cercano
+    Perspective,
+    /// log2(1 + w) / log2(1 + far): la misma precisión relativa a cualquier distancia, para que
+    /// anillos, órbitas y planetas lejanos no se peleen por el mismo valor
+    Logarithmic { far: f32 },
+}
+
+impl DepthMode {
+    /// Valor que el vértice guarda para interpolar por el triángulo. En modo logarítmico es 1/w,
+    /// que sí es lineal en pantalla; el logaritmo se toma por fragmento en `resolve`.
+    pub fn interpolant(self, clip_z: f32, clip_w: f32) -> f32 {
+        match self {
+            DepthMode::Perspective => clip_z / clip_w,
+            DepthMode::Logarithmic { .. } => 1.0 / clip_w,
+        }
+    }
+
+    /// Profundidad del z-buffer a partir del valor interpolado en el fragmento
+    pub fn resolve(self, interpolant: f32) -> f32 {
+        match self {
+            DepthMode::Perspective => interpolant,
+            DepthMode::Logarithmic { far } => {
+                let w = if interpolant > 0.0 { 1.0 / interpolant } else { 0.0 };
+                (1.0 + w).log2() / (1.0 + far).log2() * 2.0 - 1.0
+            }
+        }
+    }
+
+    /// Profundidad de un punto suelto (líneas, partículas, destellos)
+    pub fn encode(self, clip_z: f32, clip_w: f32) -> f32 {
+        self.resolve(self.interpolant(clip_z, clip_w))
+    }
+
+    /// Distancia a la cámara a lo largo del eje de vista para un valor del z-buffer
+    pub fn linear(self, projection: &Mat4, depth: f32) -> f32 {
+        match self {
+            DepthMode::Perspective => projection[(2, 3)] / (depth + projection[(2, 2)]),
+            DepthMode::Logarithmic { far } => (1.0 + far).powf((depth + 1.0) * 0.5) - 1.0,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::line::line;
+use crate::vertex::Vertex;
+use crate::{project_segment, Uniforms};
+
+// Cuadrícula de referencia sobre el plano de la eclíptica (y = 0), el de las órbitas: da una
+// idea de la altura y la distancia al volar entre ellas. Se suma al color de lo que hay detrás
+// (translúcida) con prueba de profundidad, y se apaga con la distancia a la cámara.
+
+/// Celdas a cada lado de la celda bajo la cámara
+const GRID_CELLS: i64 = 24;
+const GRID_COLOR: u32 = 0x1A3355;
+/// Cada cuántas líneas va una más marcada
+const MAJOR_EVERY: i64 = 5;
+const MAJOR_COLOR: u32 = 0x2E5A8C;
+
+/// Dibuja la cuadrícula de celdas de lado `spacing` alrededor de la vertical de la cámara. Va
+/// después de la geometría: las líneas que quedan detrás de un cuerpo no se ven.
+pub fn render_ecliptic_grid(framebuffer: &mut Framebuffer, uniforms: &Uniforms, spacing: f32) {
+    let camera = uniforms.camera_position;
+    let reach = GRID_CELLS as f32 * spacing;
+    // Muy por encima o por debajo del plano ya no se vería ninguna línea
+    if camera.y.abs() >= reach {
+        return;
+    }
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    // Centrada en una línea de la cuadrícula para que no se deslice al moverse la cámara. Con
+    // celdas minúsculas los índices saturan en vez de desbordarse (la escena no deja que sean 0).
+    let center = ((camera.x / spacing).round() as i64, (camera.z / spacing).round() as i64);
+
+    for index in -GRID_CELLS..=GRID_CELLS {
+        for along_x in [false, true] {
+            let line_index = index.saturating_add(if along_x { center.1 } else { center.0 });
+            let color = if line_index % MAJOR_EVERY == 0 { MAJOR_COLOR } else { GRID_COLOR };
+            // Tramos de una celda, cada uno con su atenuación
+            for step in -GRID_CELLS..GRID_CELLS {
+                let point = |cell: i64| {
+                    let (fixed, moving) = (line_index as f32 * spacing, cell as f32 * spacing);
+                    if along_x {
+                        Vec3::new(moving + center.0 as f32 * spacing, 0.0, fixed)
+                    } else {
+                        Vec3::new(fixed, 0.0, moving + center.1 as f32 * spacing)
+                    }
+                };
+                let (from, to) = (point(step), point(step + 1));
+                let fade = 1.0 - ((from + to) * 0.5 - camera).norm() / reach;
+                if fade <= 0.0 {
+                    continue;
+                }
+                let Some((start, end)) = project_segment(uniforms, width, height, from, to) else {
+                    continue;
+                };
+                let faded = scale_color(color, fade * fade);
+                let mut a = Vertex::default();
+                let mut b = Vertex::default();
+                a.transformed_position = start;
+                b.transformed_position = end;
+                for fragment in line(&a, &b) {
+                    framebuffer.add_point(fragment.position.x as usize, fragment.position.y as usize, faded, fragment.depth);
+                }
+            }
+        }
+    }
+}
+
+fn scale_color(color: u32, factor: f32) -> u32 {
+    [16, 8, 0].iter().fold(0, |packed, &shift| packed | ((((color >> shift) & 0xFF) as f32 * factor) as u32) << shift)
+}
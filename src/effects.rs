@@ -0,0 +1,229 @@
+use nalgebra_glm::Vec3;
+use rand::Rng;
+use raylib::math::Vector3;
+use crate::framebuffer::Framebuffer;
+use crate::line::{line, clip_to_screen};
+use crate::particles::{Particle, ParticleSystem};
+use crate::vertex::Vertex;
+use crate::{project_segment, Uniforms};
+
+/// Efecto de reentrada atmosférica: estelas de plasma en los bordes, vibración de cámara
+/// e intensidad para el sonido. Depende de la velocidad de la nave y de su cercanía al planeta.
+pub struct EntryHeating {
+    pub intensity: f32,
+}
+
+impl EntryHeating {
+    pub fn new() -> Self {
+        EntryHeating { intensity: 0.0 }
+    }
+
+    /// `bodies` son pares (centro, radio). La atmósfera se extiende `radius * 2` sobre la superficie.
+    pub fn update<I>(&mut self, position: Vec3, velocity: Vec3, max_speed: f32, bodies: I, delta_time: f32)
+    where
+        I: IntoIterator<Item = (Vec3, f32)>,
+    {
+        let speed = velocity.norm();
+        let mut target: f32 = 0.0;
+
+        if speed > 0.0 && max_speed > 0.0 {
+            for (center, radius) in bodies {
+                let to_body = center - position;
+                let distance = to_body.norm();
+                let atmosphere = radius * 2.0;
+                let altitude = distance - radius;
+                if altitude > atmosphere || distance == 0.0 {
+                    continue;
+                }
+
+                let proximity = (1.0 - altitude / atmosphere).clamp(0.0, 1.0);
+                // Picar hacia el planeta calienta más que pasar de lado
+                let diving = (velocity.dot(&to_body) / (speed * distance)).max(0.0);
+                let heat = proximity * (speed / max_speed).min(1.0) * (0.3 + 0.7 * diving);
+                target = target.max(heat);
+            }
+        }
+
+        // Suavizado para que el efecto no parpadee al cambiar de dirección
+        let rate = if target > self.intensity { 3.0 } else { 1.5 };
+        self.intensity += (target - self.intensity) * (rate * delta_time).min(1.0);
+    }
+
+    /// Desplazamiento aleatorio de la cámara proporcional a la intensidad
+    pub fn shake_offset(&self, right: Vec3, up: Vec3) -> Vec3 {
+        if self.intensity < 0.01 {
+            return Vec3::zeros();
+        }
+        let mut rng = rand::rng();
+        let amplitude = self.intensity * self.intensity * 0.8;
+        right * rng.random_range(-amplitude..amplitude) + up * rng.random_range(-amplitude..amplitude)
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer) {
+        if self.intensity < 0.01 {
+            return;
+        }
+
+        let width = framebuffer.width as f32;
+        let height = framebuffer.height as f32;
+        let (cx, cy) = (width / 2.0, height / 2.0);
+        let half_diagonal = (cx * cx + cy * cy).sqrt();
+
+        // Resplandor anaranjado que crece desde los bordes
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let dx = (x as f32 - cx) / cx;
+                let dy = (y as f32 - cy) / cy;
+                let edge = ((dx * dx + dy * dy).sqrt() - 0.55).max(0.0) / 0.9;
+                let weight = (edge * edge * self.intensity).min(1.0);
+                if weight > 0.01 {
+                    let color = ((255.0 * weight) as u32) << 16
+                        | ((110.0 * weight) as u32) << 8
+                        | (30.0 * weight) as u32;
+                    framebuffer.add_point(x, y, color, f32::NEG_INFINITY);
+                }
+            }
+        }
+
+        // Estelas de plasma radiales
+        let mut rng = rand::rng();
+        let streaks = (self.intensity * 48.0) as usize;
+        for _ in 0..streaks {
+            let angle = rng.random_range(0.0..std::f32::consts::TAU);
+            let start = rng.random_range(0.55..0.85) * half_diagonal;
+            let length = rng.random_range(0.08..0.25) * half_diagonal * (0.5 + self.intensity);
+            let (sin, cos) = angle.sin_cos();
+
+            let from = Vector3::new(cx + cos * start, cy + sin * start, 0.0);
+            let to = Vector3::new(cx + cos * (start + length), cy + sin * (start + length), 0.0);
+            let Some((from, to)) = clip_to_screen(from, to, width, height) else {
+                continue;
+            };
+
+            let mut a = Vertex::default();
+            let mut b = Vertex::default();
+            a.transformed_position = from;
+            b.transformed_position = to;
+
+            let brightness = rng.random_range(0.4..1.0) * self.intensity;
+            let color = ((255.0 * brightness) as u32) << 16
+                | ((160.0 * brightness) as u32) << 8
+                | (60.0 * brightness) as u32;
+            for fragment in line(&a, &b) {
+                framebuffer.add_point(
+                    fragment.position.x as usize,
+                    fragment.position.y as usize,
+                    color,
+                    f32::NEG_INFINITY,
+                );
+            }
+        }
+    }
+}
+
+/// Radio de la nube de polvo alrededor de la nave
+const WIND_RADIUS: f32 = 40.0;
+const WIND_PARTICLES: usize = 160;
+/// Velocidad del viento solar, hacia fuera desde el sol
+const WIND_SPEED: f32 = 4.0;
+/// Altitud sobre el cuerpo más cercano a partir de la cual el polvo se ve del todo
+const WIND_FADE_ALTITUDE: f32 = 150.0;
+/// Duración del movimiento que representa cada trazo
+const STREAK_SECONDS: f32 = 0.08;
+
+/// Polvo y viento solar alrededor de la nave: trazos cortos que pasan en sentido contrario a su
+/// velocidad, para dar idea de la rapidez y la dirección cuando no hay ningún planeta cerca.
+/// Las partículas son relativas a la nave y se desvanecen al acercarse a un cuerpo.
+pub struct SolarWind {
+    particles: ParticleSystem,
+    visibility: f32,
+    /// Velocidad de la nave en el último `update`, para la longitud de los trazos
+    ship_velocity: Vec3,
+}
+
+impl SolarWind {
+    pub fn new() -> Self {
+        SolarWind {
+            particles: ParticleSystem::new(WIND_PARTICLES),
+            visibility: 0.0,
+            ship_velocity: Vec3::zeros(),
+        }
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.particles.memory_bytes()
+    }
+
+    /// `displacement` es lo que se movió la nave desde el frame anterior, `away_from_sun` la
+    /// dirección del viento en la posición de la nave y `altitude` su distancia a la superficie
+    /// más cercana
+    pub fn update(&mut self, displacement: Vec3, away_from_sun: Vec3, altitude: f32, delta_time: f32) {
+        if delta_time <= 0.0 {
+            return;
+        }
+        self.ship_velocity = displacement / delta_time;
+        self.visibility = (altitude / WIND_FADE_ALTITUDE).clamp(0.0, 1.0);
+
+        // La nave avanza: el polvo, quieto salvo por el viento, queda atrás
+        self.particles.shift(-displacement);
+        self.particles.update(delta_time);
+        self.particles.retain_within(WIND_RADIUS);
+
+        let mut rng = rand::rng();
+        let target = (WIND_PARTICLES as f32 * self.visibility) as usize;
+        let heading = self.ship_velocity.try_normalize(1e-3);
+        while self.particles.len() < target {
+            let direction = Vec3::new(
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+            );
+            let Some(mut direction) = direction.try_normalize(1e-3) else {
+                continue;
+            };
+            // Las nuevas aparecen por delante, que es de donde vendrán
+            if heading.is_some_and(|heading| direction.dot(&heading) < 0.0) {
+                direction = -direction;
+            }
+            let brightness = rng.random_range(0.15..0.35);
+            self.particles.emit(Particle {
+                position: direction * WIND_RADIUS * rng.random_range(0.2f32..1.0).cbrt(),
+                velocity: away_from_sun * WIND_SPEED,
+                age: 0.0,
+                lifetime: rng.random_range(1.5..3.0),
+                color_start: Vec3::new(brightness * 0.9, brightness * 0.95, brightness),
+                color_end: Vec3::new(brightness * 0.9, brightness * 0.95, brightness),
+            });
+        }
+    }
+
+    /// `origin` es la posición de la nave en el espacio de `uniforms`
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, origin: Vec3) {
+        if self.visibility < 0.01 {
+            return;
+        }
+        let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+
+        for particle in self.particles.iter() {
+            // El trazo une la posición actual con la de hace `STREAK_SECONDS` respecto a la nave
+            let head = origin + particle.position;
+            let tail = head + (self.ship_velocity - particle.velocity) * STREAK_SECONDS;
+            let Some((from, to)) = project_segment(uniforms, width, height, head, tail) else {
+                continue;
+            };
+
+            // Aparece y desaparece con suavidad a lo largo de su vida
+            let life = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+            let color = particle.color_start * (life * std::f32::consts::PI).sin() * self.visibility;
+            let packed = ((255.0 * color.x) as u32) << 16 | ((255.0 * color.y) as u32) << 8 | (255.0 * color.z) as u32;
+
+            let mut a = Vertex::default();
+            let mut b = Vertex::default();
+            a.transformed_position = from;
+            b.transformed_position = to;
+            for fragment in line(&a, &b) {
+                framebuffer.add_point(fragment.position.x as usize, fragment.position.y as usize, packed, fragment.depth);
+            }
+        }
+    }
+}
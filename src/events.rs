@@ -0,0 +1,222 @@
+use std::collections::HashSet;
+use nalgebra_glm::DVec3;
+use crate::CelestialBody;
+
+/// Desde dónde se mide la separación angular de una alineación
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Vantage {
+    Sun,
+    Ship,
+}
+
+/// Dos cuerpos casi en la misma dirección vistos desde `vantage`
+#[derive(Debug, Clone, Copy)]
+pub struct Conjunction {
+    pub first: usize,
+    pub second: usize,
+    pub vantage: Vantage,
+    /// Separación angular en radianes
+    pub separation: f32,
+}
+
+/// Detecta conjunciones entre los cuerpos visibles que orbitan. Cada par avisa una sola vez
+/// por acercamiento: vuelve a armarse cuando se separa más del doble del umbral.
+pub struct ConjunctionDetector {
+    threshold: f32,
+    active: HashSet<(usize, usize, Vantage)>,
+}
+
+fn separation(vantage: DVec3, a: DVec3, b: DVec3) -> Option<f32> {
+    let a = (a - vantage).try_normalize(1e-9)?;
+    let b = (b - vantage).try_normalize(1e-9)?;
+    Some(a.dot(&b).clamp(-1.0, 1.0).acos() as f32)
+}
+
+impl ConjunctionDetector {
+    pub fn new(threshold_degrees: f32) -> Self {
+        ConjunctionDetector {
+            threshold: threshold_degrees.to_radians(),
+            active: HashSet::new(),
+        }
+    }
+
+    /// Olvida las conjunciones en curso (los índices dejan de valer al recargar la escena)
+    pub fn reset(&mut self) {
+        self.active.clear();
+    }
+
+    /// Conjunciones que empiezan en este frame
+    pub fn update(&mut self, planets: &[CelestialBody], ship_position: DVec3) -> Vec<Conjunction> {
+        let sun_position = planets
+            .iter()
+            .find(|planet| planet.orbit_radius == 0.0)
+            .map_or(DVec3::zeros(), |sun| sun.position);
+        let candidates: Vec<usize> = (0..planets.len())
+            .filter(|&index| planets[index].orbit_radius > 0.0 && !planets[index].hidden)
+            .collect();
+
+        let mut started = Vec::new();
+        for (slot, &first) in candidates.iter().enumerate() {
+            for &second in &candidates[slot + 1..] {
+                for (vantage, origin) in [(Vantage::Sun, sun_position), (Vantage::Ship, ship_position)] {
+                    let Some(angle) = separation(origin, planets[first].position, planets[second].position) else {
+                        continue;
+                    };
+                    let key = (first, second, vantage);
+                    if angle < self.threshold {
+                        if self.active.insert(key) {
+                            started.push(Conjunction { first, second, vantage, separation: angle });
+                        }
+                    } else if angle > self.threshold * 2.0 {
+                        self.active.remove(&key);
+                    }
+                }
+            }
+        }
+        started
+    }
+}
+
+/// Eclipse total de la estrella visto desde `observer`: `occluder` la tapa entera entre los
+/// instantes de `totality`, y al menos en parte entre los de `partial`
+#[derive(Debug, Clone, Copy)]
+pub struct Eclipse {
+    pub observer: usize,
+    pub occluder: usize,
+    pub partial: (f64, f64),
+    pub totality: (f64, f64),
+}
+
+/// Paso de la búsqueda de eclipses en segundos simulados: una totalidad dura unas décimas
+const ECLIPSE_STEP: f64 = 0.02;
+
+/// Fracción del disco de la estrella (radio angular `star`) que tapa un disco de radio
+/// `occluder` cuyo centro está a `separation`, todo en radianes
+pub fn disc_coverage(separation: f32, star: f32, occluder: f32) -> f32 {
+    if separation >= star + occluder {
+        return 0.0;
+    }
+    if separation <= (occluder - star).abs() {
+        return (occluder.min(star) / star).powi(2);
+    }
+    // Área de la lente que forman los dos círculos
+    let (d, r1, r2) = (separation, star, occluder);
+    let a1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).clamp(-1.0, 1.0).acos();
+    let a2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).clamp(-1.0, 1.0).acos();
+    let kite = ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).max(0.0).sqrt();
+    (r1 * r1 * a1 + r2 * r2 * a2 - 0.5 * kite) / (std::f32::consts::PI * r1 * r1)
+}
+
+fn angular_radius(eye: DVec3, center: DVec3, radius: f32) -> f32 {
+    (radius as f64 / (center - eye).norm()).min(1.0).asin() as f32
+}
+
+/// Cuánto de la estrella tapan los demás cuerpos vistos desde `eye` (0 nada, 1 entera). El
+/// cuerpo sobre cuya superficie está el ojo no cuenta: ese es el horizonte, no un eclipse.
+pub fn star_coverage(eye: DVec3, planets: &[CelestialBody], star: usize) -> f32 {
+    let (star_position, star_radius) = (planets[star].position, angular_radius(eye, planets[star].position, planets[star].scale));
+    let star_distance = (star_position - eye).norm();
+    planets
+        .iter()
+        .enumerate()
+        .filter(|&(index, planet)| {
+            let distance = (planet.position - eye).norm();
+            index != star && !planet.hidden && distance < star_distance && distance > planet.scale as f64 * 1.1
+        })
+        .filter_map(|(_, planet)| {
+            let angle = separation(eye, star_position, planet.position)?;
+            Some(disc_coverage(angle, star_radius, angular_radius(eye, planet.position, planet.scale)))
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Margen del eclipse visto desde `eye`: positivo mientras el cuerpo tapa la estrella entera
+/// (`total`) o al menos una parte
+fn eclipse_margin(eye: DVec3, star: (DVec3, f32), occluder: (DVec3, f32), total: bool) -> f32 {
+    let star_radius = angular_radius(eye, star.0, star.1);
+    let occluder_radius = angular_radius(eye, occluder.0, occluder.1);
+    let angle = separation(eye, star.0, occluder.0).unwrap_or(std::f32::consts::PI);
+    if total {
+        occluder_radius - star_radius - angle
+    } else {
+        occluder_radius + star_radius - angle
+    }
+}
+
+/// Primer eclipse total en `(from, from + horizon]` según `margin(time, total)`; uno que ya
+/// esté en curso en `from` no cuenta
+fn find_eclipse<F: Fn(f64, bool) -> f32>(margin: F, from: f64, horizon: f64) -> Option<((f64, f64), (f64, f64))> {
+    // Instante en que el margen cruza el cero entre `outside` (negativo) e `inside` (positivo)
+    let crossing = |mut outside: f64, mut inside: f64, total: bool| {
+        for _ in 0..40 {
+            let middle = (outside + inside) * 0.5;
+            if margin(middle, total) >= 0.0 {
+                inside = middle;
+            } else {
+                outside = middle;
+            }
+        }
+        inside
+    };
+    let samples = (horizon / ECLIPSE_STEP) as usize;
+    let time = |sample: usize| from + sample as f64 * ECLIPSE_STEP;
+    let sample = (1..=samples).find(|&sample| margin(time(sample), true) >= 0.0 && margin(time(sample - 1), true) < 0.0)?;
+    let totality_start = crossing(time(sample - 1), time(sample), true);
+    let mut end = time(sample);
+    while margin(end, true) >= 0.0 {
+        end += ECLIPSE_STEP;
+    }
+    let totality_end = crossing(end, end - ECLIPSE_STEP, true);
+
+    let mut start = totality_start;
+    while margin(start, false) >= 0.0 {
+        start -= ECLIPSE_STEP;
+    }
+    let mut end = totality_end;
+    while margin(end, false) >= 0.0 {
+        end += ECLIPSE_STEP;
+    }
+    let partial = (crossing(start, start + ECLIPSE_STEP, false), crossing(end, end - ECLIPSE_STEP, false));
+    Some((partial, (totality_start, totality_end)))
+}
+
+/// Siguiente eclipse total de la estrella visto desde el centro de algún cuerpo, entre `from` y
+/// `from + horizon`, según las efemérides (no la simulación en curso). Si desde `preferred`
+/// hay alguno en ese plazo, se elige ese aunque haya otros antes.
+pub fn next_eclipse(planets: &[CelestialBody], star: usize, preferred: Option<usize>, from: f64, horizon: f64) -> Option<Eclipse> {
+    let ephemerides: Vec<_> = planets.iter().map(CelestialBody::ephemeris).collect();
+    let sphere = |index: usize, time: f64| (ephemerides[index].state_at(time).position, planets[index].scale);
+
+    let mut found: Vec<Eclipse> = Vec::new();
+    for observer in (0..planets.len()).filter(|&index| index != star && !planets[index].hidden) {
+        // Solo puede pasar por delante de la estrella un cuerpo de una órbita interior
+        let occluders = (0..planets.len()).filter(|&index| {
+            index != star && index != observer && !planets[index].hidden && planets[index].orbit_radius < planets[observer].orbit_radius
+        });
+        for occluder in occluders {
+            let margin = |time: f64, total: bool| eclipse_margin(sphere(observer, time).0, sphere(star, time), sphere(occluder, time), total);
+            if let Some((partial, totality)) = find_eclipse(margin, from, horizon) {
+                found.push(Eclipse { observer, occluder, partial, totality });
+            }
+        }
+    }
+    found.into_iter().min_by(|a, b| {
+        let key = |eclipse: &Eclipse| (Some(eclipse.observer) != preferred, eclipse.totality.0);
+        key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal)
+    })
+}
+
+/// Vuelve a calcular los instantes de `eclipse` para un ojo que no está en el centro del
+/// observador (`eye` da su posición en cada instante, como un punto de la superficie que gira):
+/// el paralaje adelanta o retrasa el eclipse y puede hacer que deje de ser total.
+pub fn eclipse_seen_from<F: Fn(f64) -> DVec3>(eclipse: &Eclipse, planets: &[CelestialBody], star: usize, eye: F) -> Option<Eclipse> {
+    let (star_ephemeris, occluder_ephemeris) = (planets[star].ephemeris(), planets[eclipse.occluder].ephemeris());
+    let margin = |time: f64, total: bool| {
+        let star_sphere = (star_ephemeris.state_at(time).position, planets[star].scale);
+        let occluder_sphere = (occluder_ephemeris.state_at(time).position, planets[eclipse.occluder].scale);
+        eclipse_margin(eye(time), star_sphere, occluder_sphere, total)
+    };
+    let lead = eclipse.partial.1 - eclipse.partial.0;
+    let (partial, totality) = find_eclipse(margin, eclipse.partial.0 - lead, lead * 3.0)?;
+    Some(Eclipse { partial, totality, ..*eclipse })
+}
@@ -0,0 +1,232 @@
+//! Interfaz C del render para incrustarlo en otros programas o usarlo desde otros lenguajes (ver
+//! `ffi/computer_graphics_v3.h` y el ejemplo `ffi/demo.py`). Es una escena mínima: cuerpos en
+//! órbitas circulares alrededor del origen, donde está la luz, vistos desde una cámara fija, que
+//! se dibujan a un búfer RGBA del llamador. Todas las funciones aceptan un puntero nulo y no hacen
+//! nada (o devuelven error) en ese caso.
+
+use std::f32::consts::PI;
+use std::ops::ControlFlow;
+use nalgebra_glm::Vec3;
+use raylib::math::{Vector2, Vector3};
+use crate::depth::DepthMode;
+use crate::light::Light;
+use crate::pipeline::{create_model_matrix, create_normal_matrix, create_projection_matrix, create_view_matrix, create_viewport_matrix, rasterize_each, Culling, Uniforms};
+use crate::shaders::{fragment_shader, PlanetShaderType, ShaderParams, SurfaceMaps};
+use crate::vertex::Vertex;
+
+/// Resolución de la esfera de los cuerpos: 480 triángulos
+const SPHERE_RINGS: usize = 12;
+const SPHERE_SEGMENTS: usize = 20;
+/// Velocidad de giro de los cuerpos sobre sí mismos, en radianes por segundo
+const SPIN_SPEED: f32 = 0.3;
+
+struct Body {
+    shader: PlanetShaderType,
+    params: ShaderParams,
+    orbit_radius: f32,
+    orbit_speed: f32,
+    scale: f32,
+}
+
+/// Escena opaca para C: se crea con `cg_scene_create` y se libera con `cg_scene_destroy`. El
+/// módulo de Python (`python.rs`, con la característica `python`) la envuelve tal cual.
+pub struct Scene {
+    width: usize,
+    height: usize,
+    time: f32,
+    eye: Vec3,
+    target: Vec3,
+    bodies: Vec<Body>,
+    sphere: Vec<Vertex>,
+    depth: Vec<f32>,
+}
+
+impl Scene {
+    pub fn new(width: usize, height: usize) -> Self {
+        Scene {
+            width,
+            height,
+            time: 0.0,
+            eye: Vec3::new(0.0, 150.0, 400.0),
+            target: Vec3::zeros(),
+            bodies: Vec::new(),
+            sphere: sphere_vertices(SPHERE_RINGS, SPHERE_SEGMENTS),
+            depth: vec![f32::INFINITY; width * height],
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    pub fn set_camera(&mut self, eye: Vec3, target: Vec3) {
+        self.eye = eye;
+        self.target = target;
+    }
+
+    /// Añade un cuerpo y devuelve su índice
+    pub fn add_body(&mut self, shader: PlanetShaderType, seed: u32, orbit_radius: f32, orbit_speed: f32, scale: f32) -> usize {
+        let mut params = ShaderParams::for_type(shader);
+        params.seed = seed;
+        self.bodies.push(Body { shader, params, orbit_radius, orbit_speed, scale });
+        self.bodies.len() - 1
+    }
+
+    /// Dibuja el instante actual en `pixels` (RGBA, 4 bytes por píxel, fila a fila), que debe
+    /// medir exactamente `ancho × alto × 4` bytes
+    pub fn render(&mut self, pixels: &mut [u8]) {
+        pixels.chunks_exact_mut(4).for_each(|pixel| pixel.copy_from_slice(&[0, 0, 0, 255]));
+        self.depth.fill(f32::INFINITY);
+
+        let (width, height) = (self.width as f32, self.height as f32);
+        let view_matrix = create_view_matrix(self.eye, self.target, Vec3::new(0.0, 1.0, 0.0));
+        let projection_matrix = create_projection_matrix(PI / 3.0, width / height, 0.1, 5000.0);
+        let viewport_matrix = create_viewport_matrix(0.0, 0.0, width, height);
+        let light = Light::new(Vector3::zero());
+
+        for body in &self.bodies {
+            let angle = body.orbit_speed * self.time;
+            let position = Vec3::new(body.orbit_radius * angle.cos(), 0.0, body.orbit_radius * angle.sin());
+            let model_matrix = create_model_matrix(position, body.scale, Vec3::new(0.0, SPIN_SPEED * self.time, 0.0));
+            let uniforms = Uniforms {
+                model_matrix,
+                normal_matrix: create_normal_matrix(&model_matrix),
+                inverse_model_matrix: model_matrix.try_inverse(),
+                view_matrix,
+                projection_matrix,
+                viewport_matrix,
+                time: self.time,
+                camera_position: self.eye,
+                light_position: Vec3::zeros(),
+                depth_mode: DepthMode::Perspective,
+                culling: Culling::default(),
+            };
+            rasterize_each(&uniforms, &self.sphere, &light, |fragment| {
+                let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+                if x < self.width && y < self.height {
+                    let index = y * self.width + x;
+                    if self.depth[index] > fragment.depth {
+                        self.depth[index] = fragment.depth;
+                        let color = fragment_shader(&fragment, &uniforms, body.shader, &body.params, SurfaceMaps::default());
+                        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0) as u8;
+                        pixels[index * 4..index * 4 + 3].copy_from_slice(&[channel(color.x), channel(color.y), channel(color.z)]);
+                    }
+                }
+                ControlFlow::Continue(())
+            });
+        }
+    }
+}
+
+/// Vértices de una esfera UV de radio 1 (`rings × segments` cuadriláteros)
+fn sphere_vertices(rings: usize, segments: usize) -> Vec<Vertex> {
+    let point = |ring: usize, segment: usize| {
+        let theta = ring as f32 / rings as f32 * PI;
+        let phi = segment as f32 / segments as f32 * 2.0 * PI;
+        let p = Vector3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+        Vertex::new(p, p, Vector2::new(segment as f32 / segments as f32, ring as f32 / rings as f32))
+    };
+    let mut vertices = Vec::with_capacity(rings * segments * 6);
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let (a, b) = (point(ring, segment), point(ring + 1, segment));
+            let (c, d) = (point(ring, segment + 1), point(ring + 1, segment + 1));
+            vertices.extend([a, b.clone(), c.clone(), c, b, d]);
+        }
+    }
+    vertices
+}
+
+/// Shader por número, en el orden de `PlanetShaderType` (sin `Mesh`, que necesita un modelo)
+fn shader_from_index(index: u32) -> Option<PlanetShaderType> {
+    match index {
+        0 => Some(PlanetShaderType::Terra),
+        1 => Some(PlanetShaderType::Vulcan),
+        2 => Some(PlanetShaderType::Solarius),
+        3 => Some(PlanetShaderType::Nepturion),
+        4 => Some(PlanetShaderType::Mossar),
+        _ => None,
+    }
+}
+
+/// Crea una escena vacía de `width × height` píxeles; nulo si alguna dimensión es 0
+#[no_mangle]
+pub extern "C" fn cg_scene_create(width: u32, height: u32) -> *mut Scene {
+    if width == 0 || height == 0 {
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(Scene::new(width as usize, height as usize)))
+}
+
+/// Libera una escena de `cg_scene_create`
+///
+/// # Safety
+/// `scene` debe ser nulo o un puntero de `cg_scene_create` que no se haya liberado ya.
+#[no_mangle]
+pub unsafe extern "C" fn cg_scene_destroy(scene: *mut Scene) {
+    if !scene.is_null() {
+        drop(Box::from_raw(scene));
+    }
+}
+
+/// Añade un cuerpo con el shader `shader` (0 Terra, 1 Vulcan, 2 Solarius, 3 Nepturion,
+/// 4 Mossar) y la semilla `seed` de sus parámetros procedurales. Con `orbit_radius` 0 queda en el
+/// centro, como la estrella. Devuelve su índice, o -1 si el shader no existe.
+///
+/// # Safety
+/// `scene` debe ser nulo o un puntero válido de `cg_scene_create`.
+#[no_mangle]
+pub unsafe extern "C" fn cg_scene_add_body(scene: *mut Scene, shader: u32, seed: u32, orbit_radius: f32, orbit_speed: f32, scale: f32) -> i32 {
+    let (Some(scene), Some(shader)) = (scene.as_mut(), shader_from_index(shader)) else {
+        return -1;
+    };
+    scene.add_body(shader, seed, orbit_radius, orbit_speed, scale) as i32
+}
+
+/// Coloca la cámara en `eye` mirando a `target`
+///
+/// # Safety
+/// `scene` debe ser nulo o un puntero válido de `cg_scene_create`.
+#[no_mangle]
+pub unsafe extern "C" fn cg_scene_set_camera(scene: *mut Scene, eye_x: f32, eye_y: f32, eye_z: f32, target_x: f32, target_y: f32, target_z: f32) {
+    if let Some(scene) = scene.as_mut() {
+        scene.set_camera(Vec3::new(eye_x, eye_y, eye_z), Vec3::new(target_x, target_y, target_z));
+    }
+}
+
+/// Avanza la simulación `delta_time` segundos
+///
+/// # Safety
+/// `scene` debe ser nulo o un puntero válido de `cg_scene_create`.
+#[no_mangle]
+pub unsafe extern "C" fn cg_scene_step(scene: *mut Scene, delta_time: f32) {
+    if let Some(scene) = scene.as_mut() {
+        scene.time += delta_time;
+    }
+}
+
+/// Dibuja la escena en `pixels`, que debe tener `length` bytes: exactamente `width × height × 4`
+/// (RGBA, fila a fila empezando por arriba). Devuelve 0, o -1 si la escena o el búfer no sirven.
+///
+/// # Safety
+/// `scene` debe ser nulo o un puntero válido de `cg_scene_create`, y `pixels` nulo o válido para
+/// escribir `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cg_scene_render(scene: *mut Scene, pixels: *mut u8, length: usize) -> i32 {
+    let Some(scene) = scene.as_mut() else {
+        return -1;
+    };
+    if pixels.is_null() || length != scene.width * scene.height * 4 {
+        return -1;
+    }
+    scene.render(std::slice::from_raw_parts_mut(pixels, length));
+    0
+}
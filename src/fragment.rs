@@ -1,12 +1,19 @@
 #![allow(dead_code)]
 
-use raylib::math::{Vector2, Vector3};
+use raylib::math::{Vector2, Vector3, Vector4};
 
 pub struct Fragment {
     pub position: Vector2,      // Screen-space position
     pub color: Vector3,          // Interpolated color
     pub depth: f32,              // Interpolated depth
     pub world_position: Vector3, // Interpolated world-space position
+    pub normal: Vector3,         // Interpolated object-space normal
+    pub tex_coords: Vector2,     // Interpolated texture coordinates
+    pub tangent: Vector4,        // Interpolated tangent, w = bitangent sign (see Vertex::tangent)
+    pub position_ddx: Vector3,   // Change of world_position per pixel along screen x
+    pub position_ddy: Vector3,   // Change of world_position per pixel along screen y
+    pub tex_coords_ddx: Vector2, // Change of tex_coords per pixel along screen x
+    pub tex_coords_ddy: Vector2, // Change of tex_coords per pixel along screen y
 }
 
 impl Fragment {
@@ -16,6 +23,13 @@ impl Fragment {
             color,
             depth,
             world_position: Vector3::zero(),
+            normal: Vector3::zero(),
+            tex_coords: Vector2::zero(),
+            tangent: Vector4::new(0.0, 0.0, 0.0, 0.0),
+            position_ddx: Vector3::zero(),
+            position_ddy: Vector3::zero(),
+            tex_coords_ddx: Vector2::zero(),
+            tex_coords_ddy: Vector2::zero(),
         }
     }
 
@@ -25,6 +39,44 @@ impl Fragment {
             color,
             depth,
             world_position: world_pos,
+            normal: Vector3::zero(),
+            tex_coords: Vector2::zero(),
+            tangent: Vector4::new(0.0, 0.0, 0.0, 0.0),
+            position_ddx: Vector3::zero(),
+            position_ddy: Vector3::zero(),
+            tex_coords_ddx: Vector2::zero(),
+            tex_coords_ddy: Vector2::zero(),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Valor de `ShadedFragments::color` para los píxeles que reutilizan el color del frame anterior
+pub const REUSE_PREVIOUS: u32 = u32::MAX;
+
+/// Fragmentos ya sombreados, en estructura de arrays: solo lo que hace falta para escribirlos en
+/// el framebuffer, 16 bytes por fragmento frente a los más de 100 de `Fragment`
+#[derive(Default)]
+pub struct ShadedFragments {
+    pub x: Vec<u32>,
+    pub y: Vec<u32>,
+    pub depth: Vec<f32>,
+    /// 0xRRGGBB, o `REUSE_PREVIOUS`
+    pub color: Vec<u32>,
+}
+
+impl ShadedFragments {
+    pub fn push(&mut self, x: u32, y: u32, depth: f32, color: u32) {
+        self.x.push(x);
+        self.y.push(y);
+        self.depth.push(depth);
+        self.color.push(color);
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+}
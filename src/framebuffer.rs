@@ -1,5 +1,10 @@
 // framebuffer.rs
 
+use std::fs::File;
+use std::io::{self, BufWriter};
+use crate::gbuffer::GBuffer;
+use crate::memory::vec_bytes;
+
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
@@ -7,6 +12,17 @@ pub struct Framebuffer {
     pub zbuffer: Vec<f32>,
     background_color: u32,
     current_color: u32,
+    scissor: Option<(usize, usize, usize, usize)>,
+    checkerboard: Option<usize>,
+    history: Vec<u32>,
+    /// Píxeles cuyo color en `history` sigue siendo válido tras reproyectar el frame anterior
+    reprojected: Vec<bool>,
+    /// Mientras está activo lo dibujado no reutiliza el frame anterior (objetos que se mueven con la cámara)
+    volatile: bool,
+    volatile_pixels: Vec<bool>,
+    previous_volatile: Vec<bool>,
+    /// Con G-buffer la geometría se sombrea en diferido (ver `render_scene`)
+    pub gbuffer: Option<GBuffer>,
 }
 
 impl Framebuffer {
@@ -18,6 +34,14 @@ impl Framebuffer {
             zbuffer: vec![f32::INFINITY; width * height],
             background_color: 0x000000,
             current_color: 0xFFFFFF,
+            scissor: None,
+            checkerboard: None,
+            history: vec![0; width * height],
+            reprojected: vec![false; width * height],
+            volatile: false,
+            volatile_pixels: vec![false; width * height],
+            previous_volatile: vec![false; width * height],
+            gbuffer: None,
         }
     }
 
@@ -28,18 +52,244 @@ impl Framebuffer {
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
         }
+        self.volatile_pixels.fill(false);
     }
 
-    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
-        if x < self.width && y < self.height {
+    /// Bytes de todos los búferes por píxel, incluido el G-buffer
+    pub fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.buffer)
+            + vec_bytes(&self.zbuffer)
+            + vec_bytes(&self.history)
+            + vec_bytes(&self.reprojected)
+            + vec_bytes(&self.volatile_pixels)
+            + vec_bytes(&self.previous_volatile)
+            + self.gbuffer.as_ref().map_or(0, GBuffer::memory_bytes)
+    }
+
+    /// Restringe el dibujo al rectángulo (x0, y0, x1, y1), extremos superiores exclusivos
+    pub fn set_scissor(&mut self, rect: Option<(usize, usize, usize, usize)>) {
+        self.scissor = rect;
+    }
+
+    /// Modo ajedrez temporal: solo se sombrean los píxeles con (x + y) % 2 == paridad;
+    /// el resto se reconstruye con el frame anterior
+    pub fn set_checkerboard(&mut self, parity: Option<usize>) {
+        self.checkerboard = parity;
+    }
+
+    #[inline]
+    pub fn skips_shading(&self, x: usize, y: usize) -> bool {
+        self.skips_shading_for(x, y, self.volatile)
+    }
+
+    /// Como `skips_shading`, para un dibujo volátil o no; sirve para decidirlo desde otro hilo
+    /// antes de dibujar
+    #[inline]
+    pub fn skips_shading_for(&self, x: usize, y: usize, volatile: bool) -> bool {
+        match self.checkerboard {
+            Some(parity) => (x + y) % 2 != parity,
+            None => !volatile && x < self.width && y < self.height && self.reprojected[y * self.width + x],
+        }
+    }
+
+    pub fn set_volatile(&mut self, volatile: bool) {
+        self.volatile = volatile;
+    }
+
+    /// Reubica el frame anterior: `source` da, para cada píxel nuevo, el píxel del frame anterior
+    /// que veía la misma dirección. Los píxeles sin origen, o cuyo origen era de un objeto volátil,
+    /// se sombrean de nuevo; el resto solo se rasteriza para la profundidad.
+    pub fn reproject<F: Fn(usize, usize) -> Option<(usize, usize)>>(&mut self, source: F) {
+        let previous = self.history.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y * self.width + x;
+                let origin = source(x, y)
+                    .map(|(sx, sy)| sy * self.width + sx)
+                    .filter(|&origin| !self.previous_volatile[origin]);
+                self.reprojected[index] = origin.is_some();
+                if let Some(origin) = origin {
+                    self.history[index] = previous[origin];
+                }
+            }
+        }
+    }
+
+    /// Desactiva la reutilización del frame anterior (salvo el modo ajedrez)
+    pub fn reset_reprojection(&mut self) {
+        self.reprojected.fill(false);
+    }
+
+    /// Escribe en (x, y) el color que tenía el píxel en el frame anterior, con prueba de profundidad
+    pub fn reuse_previous(&mut self, x: usize, y: usize, depth: f32) {
+        if self.writable(x, y) {
             let index = y * self.width + x;
             if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;
+                self.buffer[index] = self.history[index];
                 self.zbuffer[index] = depth;
+                self.volatile_pixels[index] = self.volatile;
             }
         }
     }
 
+    /// Guarda el frame terminado para reconstruir los píxeles omitidos en el siguiente
+    pub fn store_history(&mut self) {
+        self.history.copy_from_slice(&self.buffer);
+        self.previous_volatile.copy_from_slice(&self.volatile_pixels);
+    }
+
+    #[inline]
+    fn writable(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        match self.scissor {
+            Some((x0, y0, x1, y1)) => x >= x0 && x < x1 && y >= y0 && y < y1,
+            None => true,
+        }
+    }
+
+    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
+        self.point_with_depth(x, y, self.current_color, depth);
+    }
+
+    /// Prueba de profundidad y escritura de color y profundidad en un paso: el fragmento solo
+    /// queda si está más cerca que lo que ya hay en el píxel, dibuje quien dibuje antes. Devuelve
+    /// si se escribió.
+    pub fn point_with_depth(&mut self, x: usize, y: usize, color: u32, depth: f32) -> bool {
+        if !self.writable(x, y) {
+            return false;
+        }
+        let index = y * self.width + x;
+        if self.zbuffer[index] > depth {
+            self.buffer[index] = color;
+            self.zbuffer[index] = depth;
+            self.volatile_pixels[index] = self.volatile;
+            return true;
+        }
+        false
+    }
+
+    /// Prueba y escribe solo la profundidad; devuelve si el fragmento quedó visible
+    pub fn depth_write(&mut self, x: usize, y: usize, depth: f32) -> bool {
+        if !self.writable(x, y) {
+            return false;
+        }
+        let index = y * self.width + x;
+        if self.zbuffer[index] > depth {
+            self.zbuffer[index] = depth;
+            self.volatile_pixels[index] = self.volatile;
+            return true;
+        }
+        false
+    }
+
+    /// Escribe el color, sin tocar el z-buffer, si `visible` lo acepta a la vista de la
+    /// profundidad que ya hay en el píxel: para dibujar sobre una superficie a su misma
+    /// profundidad (las aristas del modo alambre), donde la prueba estricta fallaría a ratos
+    pub fn point_if<F: FnOnce(f32) -> bool>(&mut self, x: usize, y: usize, color: u32, visible: F) {
+        if self.writable(x, y) {
+            let index = y * self.width + x;
+            if visible(self.zbuffer[index]) {
+                self.buffer[index] = color;
+                self.volatile_pixels[index] = self.volatile;
+            }
+        }
+    }
+
+    /// Suma el color al píxel (mezcla aditiva) si pasa la prueba de profundidad, sin escribir el z-buffer
+    pub fn add_point(&mut self, x: usize, y: usize, color: u32, depth: f32) {
+        if self.writable(x, y) {
+            let index = y * self.width + x;
+            if self.zbuffer[index] > depth {
+                let current = self.buffer[index];
+                let channel = |shift: u32| {
+                    (((current >> shift) & 0xFF) + ((color >> shift) & 0xFF)).min(0xFF) << shift
+                };
+                self.buffer[index] = channel(16) | channel(8) | channel(0);
+                // El brillo se vuelve a sumar cada frame: el píxel no puede reutilizarse
+                self.volatile_pixels[index] = true;
+            }
+        }
+    }
+
+    /// Oscurece el píxel en `amount` (0 nada, 1 negro) si lo que hay dibujado en él está entre
+    /// las profundidades `near` y `far`, sin escribir el z-buffer: sombras sobre una superficie
+    pub fn darken_point(&mut self, x: usize, y: usize, amount: f32, near: f32, far: f32) {
+        if self.writable(x, y) {
+            let index = y * self.width + x;
+            let depth = self.zbuffer[index];
+            if depth > near && depth <= far {
+                let current = self.buffer[index];
+                let keep = 1.0 - amount.clamp(0.0, 1.0);
+                let channel = |shift: u32| ((((current >> shift) & 0xFF) as f32 * keep) as u32) << shift;
+                self.buffer[index] = channel(16) | channel(8) | channel(0);
+                // La sombra sigue a la nave: el píxel no puede reutilizarse
+                self.volatile_pixels[index] = true;
+            }
+        }
+    }
+
+    /// Escribe un píxel de la interfaz: sin prueba de profundidad, ignorando coordenadas fuera de pantalla
+    pub fn overlay_pixel(&mut self, x: i32, y: i32, color: u32) {
+        if x >= 0 && y >= 0 && self.writable(x as usize, y as usize) {
+            self.buffer[y as usize * self.width + x as usize] = color;
+        }
+    }
+
+    /// Rellena un rectángulo de la interfaz (sin prueba de profundidad)
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: usize, height: usize, color: u32) {
+        for py in y..y + height as i32 {
+            for px in x..x + width as i32 {
+                self.overlay_pixel(px, py, color);
+            }
+        }
+    }
+
+    /// Líneas divisorias de una cuadrícula de celdas `cell_width` x `cell_height`
+    pub fn draw_grid_lines(&mut self, cell_width: usize, cell_height: usize, color: u32) {
+        for x in (cell_width..self.width).step_by(cell_width.max(1)) {
+            for y in 0..self.height {
+                self.buffer[y * self.width + x] = color;
+            }
+        }
+        for y in (cell_height..self.height).step_by(cell_height.max(1)) {
+            for x in 0..self.width {
+                self.buffer[y * self.width + x] = color;
+            }
+        }
+    }
+
+    /// Combina el buffer actual (ojo derecho) con el del ojo izquierdo en un anaglifo rojo/cian.
+    /// El rojo toma la luminancia del ojo izquierdo (anaglifo de medio color) para que los cuerpos
+    /// muy saturados, como el sol, no aparezcan en un solo ojo.
+    pub fn compose_anaglyph(&mut self, left: &[u32]) {
+        for (pixel, left_pixel) in self.buffer.iter_mut().zip(left) {
+            let r = (left_pixel >> 16) & 0xFF;
+            let g = (left_pixel >> 8) & 0xFF;
+            let b = left_pixel & 0xFF;
+            let luma = (r * 299 + g * 587 + b * 114) / 1000;
+            *pixel = (luma << 16) | (*pixel & 0x00FFFF);
+        }
+    }
+
+    /// Guarda el contenido actual como PNG RGB de 8 bits
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut data = Vec::with_capacity(self.buffer.len() * 3);
+        for pixel in &self.buffer {
+            data.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8]);
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        Ok(())
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
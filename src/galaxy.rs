@@ -0,0 +1,351 @@
+use std::f32::consts::{PI, TAU};
+use minifb::{Key, KeyRepeat, Window};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::framebuffer::Framebuffer;
+use crate::hud::{draw_line_2d, Theme};
+use crate::scene::{BeltConfig, BodyConfig, DisplacementConfig, GridConfig, ScaleMode, SceneFile, ShaderParamsConfig, StarsConfig};
+use crate::shaders::PlanetShaderType;
+
+// Modo galaxia (--galaxy <semilla>): varios sistemas estelares repartidos por un disco de años
+// luz. El de origen es la escena del archivo; el resto se generan con la semilla de la galaxia y
+// solo se construyen al viajar a ellos, así en memoria nunca hay más de un sistema. El mapa (Tab)
+// elige el destino y el salto hiperespacial cambia de sistema a mitad de la animación.
+
+/// Sistemas de la galaxia, contando el de origen
+pub const GALAXY_SYSTEMS: usize = 12;
+/// Radio del disco de la galaxia y separación mínima entre sistemas, en años luz
+const GALAXY_RADIUS: f32 = 60.0;
+const MIN_SEPARATION: f32 = 10.0;
+
+const SYLLABLES: [&str; 20] = [
+    "al", "be", "cor", "dra", "el", "fen", "gal", "hy", "ka", "lum",
+    "mir", "nor", "or", "pha", "rho", "sar", "tau", "ve", "xi", "zer",
+];
+const ENDINGS: [&str; 8] = ["a", "e", "is", "on", "us", "ar", "ix", "ea"];
+const NUMERALS: [&str; 6] = ["I", "II", "III", "IV", "V", "VI"];
+
+/// Tipo de estrella de un sistema generado: fija su color, su tamaño y su masa
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StarClass {
+    RedDwarf,
+    Yellow,
+    BlueGiant,
+}
+
+impl StarClass {
+    pub fn name(self) -> &'static str {
+        match self {
+            StarClass::RedDwarf => "enana roja",
+            StarClass::Yellow => "estrella amarilla",
+            StarClass::BlueGiant => "gigante azul",
+        }
+    }
+
+    /// Paleta del shader `Solarius`
+    fn palette(self) -> [[f32; 3]; 3] {
+        match self {
+            StarClass::RedDwarf => [[1.0, 0.55, 0.25], [0.9, 0.25, 0.05], [0.6, 0.05, 0.0]],
+            StarClass::Yellow => [[1.0, 0.9, 0.3], [1.0, 0.5, 0.0], [1.0, 0.15, 0.0]],
+            StarClass::BlueGiant => [[0.85, 0.95, 1.0], [0.45, 0.65, 1.0], [0.2, 0.35, 0.9]],
+        }
+    }
+
+    /// Color en el mapa
+    fn map_color(self) -> u32 {
+        match self {
+            StarClass::RedDwarf => 0xFF7744,
+            StarClass::Yellow => 0xFFDD66,
+            StarClass::BlueGiant => 0x99BBFF,
+        }
+    }
+
+    /// Tamaño y masa de la estrella
+    fn size(self) -> (f32, f32) {
+        match self {
+            StarClass::RedDwarf => (16.0, 400.0),
+            StarClass::Yellow => (25.0, 1000.0),
+            StarClass::BlueGiant => (38.0, 3000.0),
+        }
+    }
+}
+
+pub struct StarSystem {
+    pub name: String,
+    /// Posición en el plano de la galaxia, en años luz
+    pub position: (f32, f32),
+    pub star: StarClass,
+    seed: u64,
+}
+
+pub struct Galaxy {
+    pub seed: u64,
+    /// El primero es el sistema de origen (el de la escena del archivo), en el centro
+    pub systems: Vec<StarSystem>,
+}
+
+impl Galaxy {
+    /// `home` es el nombre del sistema de origen (el de su estrella)
+    pub fn generate(seed: u64, count: usize, home: &str) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut systems = vec![StarSystem { name: home.to_string(), position: (0.0, 0.0), star: StarClass::Yellow, seed }];
+
+        let mut attempts = 0;
+        while systems.len() < count && attempts < count * 100 {
+            attempts += 1;
+            // Raíz cuadrada del radio para repartir los sistemas por igual en el disco
+            let radius = GALAXY_RADIUS * rng.random::<f32>().sqrt();
+            let angle = rng.random_range(0.0..TAU);
+            let position = (radius * angle.cos(), radius * angle.sin());
+            let too_close = systems.iter().any(|other| distance(other.position, position) < MIN_SEPARATION);
+            let name = star_name(&mut rng);
+            if too_close || systems.iter().any(|other| other.name == name) {
+                continue;
+            }
+            let star = match rng.random_range(0..10) {
+                0..=4 => StarClass::RedDwarf,
+                5..=8 => StarClass::Yellow,
+                _ => StarClass::BlueGiant,
+            };
+            systems.push(StarSystem { name, position, star, seed: rng.random() });
+        }
+
+        Galaxy { seed, systems }
+    }
+
+    /// Años luz entre dos sistemas
+    pub fn distance(&self, from: usize, to: usize) -> f32 {
+        distance(self.systems[from].position, self.systems[to].position)
+    }
+
+    /// Escena de un sistema generado, con la disposición estilizada. El de origen no se genera:
+    /// se carga de su archivo.
+    pub fn scene(&self, index: usize) -> Option<SceneFile> {
+        if index == 0 {
+            return None;
+        }
+        let system = &self.systems[index];
+        let mut rng = StdRng::seed_from_u64(system.seed);
+        let (star_scale, star_mass) = system.star.size();
+        let mut bodies = vec![BodyConfig {
+            name: system.name.clone(),
+            orbit_radius: 0.0,
+            orbit_speed: 0.0,
+            scale: star_scale,
+            mass: star_mass,
+            rotation_speed: [0.0, 0.08, 0.0],
+            shader: PlanetShaderType::Solarius,
+            params: ShaderParamsConfig { seed: Some(rng.random()), palette: Some(system.star.palette()), ..Default::default() },
+            displacement: None,
+            facts: vec![format!(
+                "{} es una {} a {:.1} años luz del sistema de origen.",
+                system.name,
+                system.star.name(),
+                self.distance(0, index)
+            )],
+        }];
+
+        let mut orbit_radius = star_scale * 3.0;
+        for numeral in NUMERALS.iter().take(rng.random_range(2..=NUMERALS.len())) {
+            orbit_radius += rng.random_range(50.0..110.0);
+            let shader = [PlanetShaderType::Terra, PlanetShaderType::Vulcan, PlanetShaderType::Nepturion, PlanetShaderType::Mossar]
+                [rng.random_range(0..4)];
+            let scale = match shader {
+                PlanetShaderType::Nepturion => rng.random_range(14.0..24.0),
+                _ => rng.random_range(6.0..15.0),
+            };
+            // Los rocosos con algo de relieve; los gaseosos lisos
+            let displacement = (shader != PlanetShaderType::Nepturion)
+                .then(|| DisplacementConfig { amplitude: rng.random_range(0.02..0.06), frequency: 3.0, octaves: 4 });
+            bodies.push(BodyConfig {
+                name: format!("{} {}", system.name, numeral),
+                orbit_radius,
+                // Como en la escena por defecto: más lentos cuanto más lejos
+                orbit_speed: 0.3 * (150.0 / orbit_radius).powf(0.85),
+                scale,
+                mass: scale * scale / 200.0,
+                rotation_speed: [0.0, rng.random_range(0.2..0.6), 0.0],
+                shader,
+                params: ShaderParamsConfig { seed: Some(rng.random()), ..Default::default() },
+                displacement,
+                facts: Vec::new(),
+            });
+        }
+
+        let belt = rng.random_bool(0.4).then(|| BeltConfig {
+            inner_radius: orbit_radius + 40.0,
+            outer_radius: orbit_radius + 90.0,
+            count: 1500,
+            thickness: 10.0,
+            seed: rng.random(),
+            displacement: Some(DisplacementConfig { amplitude: 0.35, frequency: 1.5, octaves: 3 }),
+        });
+
+        Some(SceneFile {
+            bodies,
+            belt,
+            stars: StarsConfig { seed: system.seed, ..StarsConfig::default() },
+            scale_mode: ScaleMode::Stylized,
+            grid: GridConfig::default(),
+        })
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Nombre de dos sílabas y una terminación, con mayúscula inicial
+fn star_name(rng: &mut StdRng) -> String {
+    let name = format!(
+        "{}{}{}",
+        SYLLABLES[rng.random_range(0..SYLLABLES.len())],
+        SYLLABLES[rng.random_range(0..SYLLABLES.len())],
+        ENDINGS[rng.random_range(0..ENDINGS.len())]
+    );
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Mapa de la galaxia (Tab): mientras está abierto la nave se detiene. Las flechas marcan un
+/// destino, Enter salta a él y Tab cierra.
+pub struct GalaxyMap {
+    pub open: bool,
+    cursor: usize,
+}
+
+impl GalaxyMap {
+    pub fn new() -> Self {
+        GalaxyMap { open: false, cursor: 0 }
+    }
+
+    /// Lee el teclado; devuelve el destino elegido. `current` es el sistema en el que está la nave.
+    pub fn handle_input(&mut self, window: &Window, galaxy: &Galaxy, current: usize) -> Option<usize> {
+        if window.is_key_pressed(Key::Tab, KeyRepeat::No) {
+            self.open = !self.open;
+            self.cursor = current;
+            return None;
+        }
+        if !self.open {
+            return None;
+        }
+
+        let count = galaxy.systems.len();
+        if window.is_key_pressed(Key::Right, KeyRepeat::Yes) || window.is_key_pressed(Key::Down, KeyRepeat::Yes) {
+            self.cursor = (self.cursor + 1) % count;
+        }
+        if window.is_key_pressed(Key::Left, KeyRepeat::Yes) || window.is_key_pressed(Key::Up, KeyRepeat::Yes) {
+            self.cursor = (self.cursor + count - 1) % count;
+        }
+        if window.is_key_pressed(Key::Enter, KeyRepeat::No) && self.cursor != current {
+            self.open = false;
+            return Some(self.cursor);
+        }
+        None
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, theme: &Theme, galaxy: &Galaxy, current: usize) {
+        for pixel in framebuffer.buffer.iter_mut() {
+            *pixel = (*pixel >> 2) & 0x3F3F3F;
+        }
+
+        let (width, height) = (framebuffer.width as i32, framebuffer.height as i32);
+        let (cx, cy) = (width / 2, height / 2);
+        let scale = (width.min(height) / 2 - theme.px(40)) as f32 / GALAXY_RADIUS;
+        let to_screen = |system: &StarSystem| (cx + (system.position.0 * scale) as i32, cy + (system.position.1 * scale) as i32);
+
+        let (from, to) = (to_screen(&galaxy.systems[current]), to_screen(&galaxy.systems[self.cursor]));
+        if self.cursor != current {
+            theme.line(framebuffer, from, to, 0x446688);
+        }
+        for (index, system) in galaxy.systems.iter().enumerate() {
+            let (x, y) = to_screen(system);
+            let size = theme.px(if index == self.cursor { 5 } else { 3 });
+            framebuffer.fill_rect(x - size / 2, y - size / 2, size as usize, size as usize, theme.color(system.star.map_color()));
+            if index == current {
+                draw_ring(framebuffer, (x, y), theme.px(7), theme.color(0x66FF99));
+            }
+            let color = if index == self.cursor { 0xFFFFFF } else { 0x8899AA };
+            theme.text(framebuffer, x + theme.px(6), y - theme.px(3), &system.name, color);
+        }
+
+        let target = &galaxy.systems[self.cursor];
+        let info = if self.cursor == current {
+            format!("{} ({}): sistema actual", target.name, target.star.name())
+        } else {
+            format!("{} ({}): {:.1} años luz", target.name, target.star.name(), galaxy.distance(current, self.cursor))
+        };
+        let text = format!("Galaxia {}\n{}\nFlechas = destino   Enter = saltar   Tab = cerrar", galaxy.seed, info);
+        theme.text_panel(framebuffer, (theme.px(8), theme.px(8)), &text, (0xDDE6EE, 0x0A1018, Some(0x445566)), 4);
+    }
+}
+
+fn draw_ring(framebuffer: &mut Framebuffer, center: (i32, i32), radius: i32, color: u32) {
+    let steps = (radius * 8).max(16);
+    for step in 0..steps {
+        let angle = step as f32 / steps as f32 * TAU;
+        let x = center.0 + (angle.cos() * radius as f32).round() as i32;
+        let y = center.1 + (angle.sin() * radius as f32).round() as i32;
+        framebuffer.overlay_pixel(x, y, color);
+    }
+}
+
+/// Duración del salto hiperespacial, en segundos de tiempo real
+const HYPERSPACE_SECONDS: f32 = 2.0;
+/// Estelas del efecto
+const HYPERSPACE_STREAKS: u32 = 240;
+
+/// Salto hiperespacial: las estrellas se estiran en estelas desde el centro, la pantalla se
+/// funde a blanco y a mitad del salto se cambia de sistema
+pub struct Hyperspace {
+    pub destination: usize,
+    elapsed: f32,
+}
+
+impl Hyperspace {
+    pub fn new(destination: usize) -> Self {
+        Hyperspace { destination, elapsed: 0.0 }
+    }
+
+    /// Avanza la animación; devuelve `true` el frame en que hay que cambiar de sistema
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        let half = HYPERSPACE_SECONDS / 2.0;
+        let before = self.elapsed;
+        self.elapsed += delta_time;
+        before < half && self.elapsed >= half
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= HYPERSPACE_SECONDS
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer) {
+        let progress = (self.elapsed / HYPERSPACE_SECONDS).clamp(0.0, 1.0);
+        // 0 → 1 → 0 a lo largo del salto
+        let intensity = (progress * PI).sin();
+
+        let (cx, cy) = (framebuffer.width as f32 / 2.0, framebuffer.height as f32 / 2.0);
+        let reach = (cx * cx + cy * cy).sqrt();
+        for streak in 0..HYPERSPACE_STREAKS {
+            // Dirección y distancia fijas por estela (hash entero), para que no parpadeen
+            let hash = streak.wrapping_mul(0x9E37_79B9) ^ (streak >> 3).wrapping_mul(0x85EB_CA6B);
+            let angle = (hash & 0xFFFF) as f32 / 65536.0 * TAU;
+            let start = 0.05 + ((hash >> 16) & 0xFFFF) as f32 / 65536.0 * 0.9;
+            let inner = reach * start * (1.0 + progress);
+            let outer = inner * (1.0 + 1.5 * intensity);
+            let (dx, dy) = (angle.cos(), angle.sin());
+            let from = ((cx + dx * inner) as i32, (cy + dy * inner) as i32);
+            let to = ((cx + dx * outer) as i32, (cy + dy * outer) as i32);
+            draw_line_2d(framebuffer, from, to, 0xCCDDFF);
+        }
+
+        // Destello blanco alrededor del cambio de sistema
+        let flash = (1.0 - (progress - 0.5).abs() / 0.15).clamp(0.0, 1.0);
+        if flash > 0.0 {
+            for pixel in framebuffer.buffer.iter_mut() {
+                let blend = |channel: u32| channel + ((255 - channel) as f32 * flash) as u32;
+                *pixel = (blend((*pixel >> 16) & 0xFF) << 16) | (blend((*pixel >> 8) & 0xFF) << 8) | blend(*pixel & 0xFF);
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::memory::vec_bytes;
+
+/// Fragmento visible de un píxel y la pasada de dibujo que lo produjo
+pub struct GSample {
+    pub draw: usize,
+    pub fragment: Fragment,
+}
+
+/// G-buffer del modo diferido: por cada píxel guarda el fragmento más cercano (posición,
+/// normal, UV, profundidad y color base). El sombreado procedural se hace después, una sola
+/// vez por píxel, en lugar de una vez por fragmento rasterizado.
+pub struct GBuffer {
+    /// Aplica oclusión ambiental en espacio de pantalla al resolver (ver `ssao`)
+    pub ambient_occlusion: bool,
+    width: usize,
+    samples: Vec<Option<GSample>>,
+}
+
+impl GBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        GBuffer {
+            ambient_occlusion: false,
+            width,
+            samples: (0..width * height).map(|_| None).collect(),
+        }
+    }
+
+    /// Guarda el fragmento si pasa la prueba de profundidad; actualiza el z-buffer del framebuffer
+    pub fn write(&mut self, framebuffer: &mut Framebuffer, draw: usize, fragment: Fragment) {
+        let x = fragment.position.x as usize;
+        let y = fragment.position.y as usize;
+
+        if framebuffer.skips_shading(x, y) {
+            framebuffer.reuse_previous(x, y, fragment.depth);
+            return;
+        }
+
+        if framebuffer.depth_write(x, y, fragment.depth) {
+            self.samples[y * self.width + x] = Some(GSample { draw, fragment });
+        }
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.samples)
+    }
+
+    pub fn sample(&self, index: usize) -> Option<&GSample> {
+        self.samples.get(index)?.as_ref()
+    }
+
+    /// Pasada de sombreado a pantalla completa. Solo se escriben los píxeles cuyo fragmento sigue
+    /// siendo el visible (algo dibujado después, como un asteroide lejano, puede haberlo tapado).
+    pub fn resolve<F: FnMut(&GSample) -> u32>(&self, framebuffer: &mut Framebuffer, mut shade: F) {
+        for (index, slot) in self.samples.iter().enumerate() {
+            let Some(sample) = slot else {
+                continue;
+            };
+            if framebuffer.zbuffer[index] == sample.fragment.depth {
+                framebuffer.buffer[index] = shade(sample);
+            }
+        }
+    }
+
+    /// Vacía el G-buffer para la siguiente pasada
+    pub fn clear(&mut self) {
+        self.samples.iter_mut().for_each(|slot| *slot = None);
+    }
+}
@@ -0,0 +1,107 @@
+use nalgebra_glm::Vec3;
+
+// Parche de relieve bajo la nave para aterrizar: una rejilla de radios de la superficie sobre el
+// plano tangente a un punto del cuerpo. Se muestrea con la misma función que la icosfera
+// adaptativa (ver `icosphere`), así el suelo contra el que choca la nave es el que se ve, y
+// consultarlo luego es una interpolación en la rejilla en lugar de volver a evaluar el ruido.
+//
+// Las muestras se reparten en el plano tangente y se proyectan desde el centro del cuerpo (una
+// proyección gnomónica): la dirección de cualquier punto cae en la rejilla con un producto
+// escalar, sin trigonometría.
+
+/// Radios de la superficie en una rejilla alrededor de una dirección, en el espacio del modelo
+/// con la esfera de radio 1 en el origen
+#[derive(Debug, Clone)]
+pub struct Heightfield {
+    /// Dirección del centro del parche
+    pub center: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    /// Mitad del lado del parche sobre el plano tangente
+    pub extent: f32,
+    resolution: usize,
+    /// `resolution` × `resolution` radios, fila a fila
+    heights: Vec<f32>,
+}
+
+impl Heightfield {
+    /// Muestrea `radius` (radio de la superficie en cada dirección) en `resolution` ×
+    /// `resolution` puntos alrededor de `center`, hasta `extent` a cada lado
+    pub fn sample<F: Fn(Vec3) -> f32>(center: Vec3, extent: f32, resolution: usize, radius: F) -> Self {
+        let center = center.normalize();
+        let helper = if center.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+        let tangent = center.cross(&helper).normalize();
+        let bitangent = center.cross(&tangent);
+        let resolution = resolution.max(2);
+        let mut field = Heightfield { center, tangent, bitangent, extent, resolution, heights: Vec::with_capacity(resolution * resolution) };
+        for row in 0..resolution {
+            for column in 0..resolution {
+                let (u, v) = (field.coordinate(column), field.coordinate(row));
+                field.heights.push(radius((center + tangent * u + bitangent * v).normalize()));
+            }
+        }
+        field
+    }
+
+    /// Radio de la superficie en `direction`, o `None` fuera del parche
+    pub fn radius_at(&self, direction: Vec3) -> Option<f32> {
+        let (u, v) = self.project(direction)?;
+        let (column, row) = (self.cell(u)?, self.cell(v)?);
+        let (x0, y0) = (column.floor().min(self.resolution as f32 - 2.0), row.floor().min(self.resolution as f32 - 2.0));
+        let (tx, ty) = (column - x0, row - y0);
+        let (x0, y0) = (x0 as usize, y0 as usize);
+        let at = |x: usize, y: usize| self.heights[y * self.resolution + x];
+        let top = at(x0, y0) + (at(x0 + 1, y0) - at(x0, y0)) * tx;
+        let bottom = at(x0, y0 + 1) + (at(x0 + 1, y0 + 1) - at(x0, y0 + 1)) * tx;
+        Some(top + (bottom - top) * ty)
+    }
+
+    /// El radio más alto bajo un círculo de radio `footprint` (sobre el plano tangente) centrado
+    /// en `direction`: lo que tiene que salvar algo de ese tamaño. `None` si el centro cae fuera
+    pub fn highest_within(&self, direction: Vec3, footprint: f32) -> Option<f32> {
+        let (u, v) = self.project(direction)?;
+        let mut highest = self.radius_at(direction)?;
+        let reach = (footprint / self.spacing()).ceil() as isize;
+        let (column, row) = (self.cell(u)?.round() as isize, self.cell(v)?.round() as isize);
+        let last = self.resolution as isize - 1;
+        for y in (row - reach).max(0)..=(row + reach).min(last) {
+            for x in (column - reach).max(0)..=(column + reach).min(last) {
+                let (du, dv) = (self.coordinate(x as usize) - u, self.coordinate(y as usize) - v);
+                if du * du + dv * dv <= footprint * footprint {
+                    highest = highest.max(self.heights[y as usize * self.resolution + x as usize]);
+                }
+            }
+        }
+        Some(highest)
+    }
+
+    /// Si `direction` queda dentro del parche con al menos `margin` hasta el borde
+    pub fn contains(&self, direction: Vec3, margin: f32) -> bool {
+        self.project(direction).is_some_and(|(u, v)| u.abs().max(v.abs()) <= self.extent - margin)
+    }
+
+    /// Separación entre muestras sobre el plano tangente
+    pub fn spacing(&self) -> f32 {
+        2.0 * self.extent / (self.resolution - 1) as f32
+    }
+
+    fn coordinate(&self, index: usize) -> f32 {
+        -self.extent + index as f32 * self.spacing()
+    }
+
+    /// Posición en la rejilla (en muestras) de la coordenada `u`, si cae dentro
+    fn cell(&self, u: f32) -> Option<f32> {
+        let cell = (u + self.extent) / self.spacing();
+        (0.0..=(self.resolution - 1) as f32).contains(&cell).then_some(cell)
+    }
+
+    /// Punto del plano tangente en la dirección `direction`
+    fn project(&self, direction: Vec3) -> Option<(f32, f32)> {
+        let along = direction.dot(&self.center);
+        if along <= 1e-6 {
+            return None;
+        }
+        let point = direction / along;
+        Some((point.dot(&self.tangent), point.dot(&self.bitangent)))
+    }
+}
@@ -0,0 +1,309 @@
+use nalgebra_glm::{Mat4, Vec3};
+use crate::clock::SECONDS_PER_DAY;
+use crate::framebuffer::Framebuffer;
+use crate::orbital::OrbitalElements;
+use crate::text;
+use crate::Uniforms;
+
+/// Línea 2D de interfaz (Bresenham), sin prueba de profundidad
+pub fn draw_line_2d(framebuffer: &mut Framebuffer, from: (i32, i32), to: (i32, i32), color: u32) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let sx = if x < to.0 { 1 } else { -1 };
+    let sy = if y < to.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        framebuffer.overlay_pixel(x, y, color);
+        if (x, y) == to {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+/// Aspecto de la interfaz: tamaño (1x–3x, para pantallas de alta densidad y proyectores) y tema
+/// de alto contraste. Las posiciones y tamaños de la interfaz se escriben para 1x y pasan por
+/// `px`; el texto, las líneas y los paneles se dibujan a través del tema.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub scale: usize,
+    pub high_contrast: bool,
+}
+
+pub const MAX_UI_SCALE: usize = 3;
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme { scale: 1, high_contrast: false }
+    }
+}
+
+impl Theme {
+    /// Medida de la interfaz a 1x llevada a la escala actual
+    pub fn px(&self, value: i32) -> i32 {
+        value * self.scale as i32
+    }
+
+    /// En alto contraste cada color se lleva al brillo máximo conservando el tono (los grises
+    /// pasan a blanco)
+    pub fn color(&self, color: u32) -> u32 {
+        if !self.high_contrast {
+            return color;
+        }
+        let channels = [(color >> 16) & 0xFF, (color >> 8) & 0xFF, color & 0xFF];
+        let max = channels.iter().copied().max().unwrap_or(0);
+        if max == 0 {
+            return 0xFFFFFF;
+        }
+        channels.iter().fold(0, |packed, &channel| (packed << 8) | (channel * 255 / max))
+    }
+
+    pub fn text_width(&self, text: &str) -> usize {
+        text::text_width(text, self.scale)
+    }
+
+    pub fn text_height(&self, text: &str) -> usize {
+        text::text_height(text, self.scale)
+    }
+
+    /// Texto a la escala del tema; en alto contraste, con un contorno negro para que se lea
+    /// también sobre los cuerpos más brillantes
+    pub fn text(&self, framebuffer: &mut Framebuffer, x: i32, y: i32, text: &str, color: u32) {
+        if self.high_contrast {
+            let offset = self.scale as i32;
+            for (dx, dy) in [(-offset, 0), (offset, 0), (0, -offset), (0, offset)] {
+                text::draw_text(framebuffer, x + dx, y + dy, text, 0x000000, self.scale);
+            }
+        }
+        text::draw_text(framebuffer, x, y, text, self.color(color), self.scale);
+    }
+
+    /// Línea de interfaz con el grosor de la escala
+    pub fn line(&self, framebuffer: &mut Framebuffer, from: (i32, i32), to: (i32, i32), color: u32) {
+        let color = self.color(color);
+        let horizontal = (to.0 - from.0).abs() >= (to.1 - from.1).abs();
+        for offset in 0..self.scale as i32 {
+            let (dx, dy) = if horizontal { (0, offset) } else { (offset, 0) };
+            draw_line_2d(framebuffer, (from.0 + dx, from.1 + dy), (to.0 + dx, to.1 + dy), color);
+        }
+    }
+
+    /// Fondo de un panel con borde opcional; en alto contraste, negro con borde blanco
+    pub fn panel(&self, framebuffer: &mut Framebuffer, x: i32, y: i32, size: (usize, usize), background: u32, border: Option<u32>) {
+        let (width, height) = size;
+        let (background, border) = match self.high_contrast {
+            true => (0x000000, Some(0xFFFFFF)),
+            false => (background, border),
+        };
+        match border {
+            Some(border) => {
+                let thickness = self.scale;
+                framebuffer.fill_rect(x, y, width, height, border);
+                framebuffer.fill_rect(
+                    x + thickness as i32,
+                    y + thickness as i32,
+                    width.saturating_sub(thickness * 2),
+                    height.saturating_sub(thickness * 2),
+                    background,
+                );
+            }
+            None => framebuffer.fill_rect(x, y, width, height, background),
+        }
+    }
+
+    /// Panel ajustado a `text` con el margen dado (a 1x) y el texto dentro
+    pub fn text_panel(&self, framebuffer: &mut Framebuffer, origin: (i32, i32), text: &str, colors: (u32, u32, Option<u32>), padding: i32) {
+        let (foreground, background, border) = colors;
+        let padding = self.px(padding);
+        let size = self.text_panel_size(text, padding);
+        self.panel(framebuffer, origin.0, origin.1, size, background, border);
+        self.text(framebuffer, origin.0 + padding, origin.1 + padding, text, foreground);
+    }
+
+    /// Tamaño del panel de `text_panel`, con `padding` ya escalado
+    pub fn text_panel_size(&self, text: &str, padding: i32) -> (usize, usize) {
+        let padding = padding as usize * 2;
+        (self.text_width(text) + padding, self.text_height(text) + padding)
+    }
+}
+
+/// Ejes X/Y/Z del mundo orientados según la vista, centrados en (x, y)
+pub fn render_axes_gizmo(framebuffer: &mut Framebuffer, theme: &Theme, view_matrix: &Mat4, center: (i32, i32), size: f32) {
+    let axes = [
+        (Vec3::new(1.0, 0.0, 0.0), 0xFF4444, "X"),
+        (Vec3::new(0.0, 1.0, 0.0), 0x44FF44, "Y"),
+        (Vec3::new(0.0, 0.0, 1.0), 0x4488FF, "Z"),
+    ];
+
+    // Solo la rotación de la vista; los ejes que se alejan se dibujan primero
+    let mut projected: Vec<_> = axes
+        .iter()
+        .map(|&(axis, color, label)| {
+            let rotated = (view_matrix * nalgebra_glm::vec4(axis.x, axis.y, axis.z, 0.0)).xyz();
+            (rotated, color, label)
+        })
+        .collect();
+    projected.sort_by(|a, b| a.0.z.total_cmp(&b.0.z));
+
+    for (direction, color, label) in projected {
+        let end = (
+            center.0 + (direction.x * size) as i32,
+            center.1 - (direction.y * size) as i32,
+        );
+        theme.line(framebuffer, center, end, color);
+        let label_distance = size + theme.px(8) as f32;
+        let label_x = center.0 + (direction.x * label_distance) as i32 - theme.px(2);
+        let label_y = center.1 - (direction.y * label_distance) as i32 - theme.px(3);
+        theme.text(framebuffer, label_x, label_y, label, color);
+    }
+}
+
+/// Longitud "redonda" (1, 2 o 5 por potencia de diez) que ocupa como mucho `max_pixels`
+fn nice_length(pixels_per_unit: f32, max_pixels: f32) -> f32 {
+    let max_units = max_pixels / pixels_per_unit;
+    let magnitude = 10f32.powf(max_units.log10().floor());
+    [5.0, 2.0, 1.0]
+        .iter()
+        .map(|factor| factor * magnitude)
+        .find(|length| *length <= max_units)
+        .unwrap_or(magnitude)
+}
+
+/// Barra de escala en (x, y) para objetos a `depth` unidades de la cámara
+pub fn render_scale_bar(framebuffer: &mut Framebuffer, theme: &Theme, uniforms: &Uniforms, depth: f32, origin: (i32, i32)) {
+    if depth <= 0.0 {
+        return;
+    }
+    // Píxeles por unidad de mundo a esa distancia, según la proyección en perspectiva
+    let focal = uniforms.viewport_matrix[(0, 0)].abs() * uniforms.projection_matrix[(0, 0)];
+    let pixels_per_unit = focal / depth;
+    let length = nice_length(pixels_per_unit, theme.px(150) as f32);
+    let pixels = (length * pixels_per_unit) as i32;
+
+    let (x, y) = origin;
+    let color = 0xCCCCCC;
+    let tick = theme.px(4);
+    theme.line(framebuffer, (x, y), (x + pixels, y), color);
+    theme.line(framebuffer, (x, y - tick), (x, y + tick), color);
+    theme.line(framebuffer, (x + pixels, y - tick), (x + pixels, y + tick), color);
+
+    let label = format!("{} unidades a {:.0}", length, depth);
+    theme.text(framebuffer, x, y - theme.px(14), &label, color);
+}
+
+/// Línea de tiempo de un año centrada en el día actual, con marcas cada 30 días
+pub fn render_timeline(framebuffer: &mut Framebuffer, theme: &Theme, days: f32, origin: (i32, i32), width: i32) {
+    const SPAN_DAYS: f32 = 365.0;
+    let (x, y) = origin;
+    let color = 0x8899AA;
+    let pixels_per_day = width as f32 / SPAN_DAYS;
+    let start = days - SPAN_DAYS / 2.0;
+
+    theme.line(framebuffer, (x, y), (x + width, y), color);
+    let first_tick = (start / 30.0).ceil() as i32;
+    let last_tick = ((start + SPAN_DAYS) / 30.0).floor() as i32;
+    for tick in first_tick.max(0)..=last_tick {
+        let tick_x = x + ((tick as f32 * 30.0 - start) * pixels_per_day) as i32;
+        let height = theme.px(if tick % 12 == 0 { 5 } else { 2 });
+        theme.line(framebuffer, (tick_x, y - height), (tick_x, y + height), color);
+    }
+
+    // El tiempo no baja de 0: se marca el inicio si queda a la vista
+    if start < 0.0 {
+        let zero_x = x + (-start * pixels_per_day) as i32;
+        theme.line(framebuffer, (zero_x, y - theme.px(6)), (zero_x, y + theme.px(6)), 0xCC6666);
+    }
+
+    let center = x + width / 2;
+    theme.line(framebuffer, (center, y - theme.px(7)), (center, y + theme.px(7)), 0xFFDD88);
+    let mut label = format!("Día {:.1}  (,/. = -/+30 días, con Ctrl 1 año)", days);
+    // A escalas grandes la ayuda de las teclas no cabe
+    if theme.text_width(&label) > framebuffer.width {
+        label = format!("Día {:.1}", days);
+    }
+    let label_x = center - theme.text_width(&label) as i32 / 2;
+    theme.text(framebuffer, label_x, y - theme.px(18), &label, 0xCCCCCC);
+}
+
+/// Lecturas de la órbita de la nave alrededor de `body`, en (x, y)
+pub fn render_orbital_readout(framebuffer: &mut Framebuffer, theme: &Theme, body: &str, elements: &OrbitalElements, origin: (i32, i32)) {
+    let apoapsis = match elements.apoapsis {
+        Some(apoapsis) => format!("{:.0} u", apoapsis),
+        None => "escape".to_string(),
+    };
+    let text = format!(
+        "Órbita de {}\nAltitud: {:.0} u\nVelocidad: {:.1} u/s\nPeriapsis: {:.0} u\nApoapsis: {}",
+        body, elements.altitude, elements.speed, elements.periapsis, apoapsis
+    );
+    theme.text(framebuffer, origin.0, origin.1, &text, 0x88DDAA);
+}
+
+/// Ficha del cuerpo seleccionado: duración del día (una rotación) y del año (una órbita) en
+/// segundos simulados y en días, con el año de Terra como referencia (ver `SECONDS_PER_DAY`).
+/// `day_tour` es el avance del recorrido "un día en Terra", si está en marcha.
+pub fn render_body_info(
+    framebuffer: &mut Framebuffer,
+    theme: &Theme,
+    name: &str,
+    rotation_period: Option<f32>,
+    orbital_period: Option<f32>,
+    day_tour: Option<f32>,
+    origin: (i32, i32),
+) {
+    let mut text = name.to_string();
+    match rotation_period {
+        Some(period) => text.push_str(&format!("\nDía: {:.1} s ({:.0} días)", period, period / SECONDS_PER_DAY)),
+        None => text.push_str("\nDía: no gira"),
+    }
+    match orbital_period {
+        Some(period) => {
+            let days = period / SECONDS_PER_DAY;
+            text.push_str(&format!("\nAño: {:.1} s ({:.0} días, {:.2} años)", period, days, days / 365.0));
+        }
+        None => text.push_str("\nAño: centro del sistema"),
+    }
+    if let Some(progress) = day_tour {
+        text.push_str(&format!("\nUn día en {}: {:.0}% (T para salir)", name, progress * 100.0));
+    }
+    theme.text(framebuffer, origin.0, origin.1, &text, 0xBBCCDD);
+}
+
+/// Columnas de texto de las fichas de datos (menos si no caben a la escala actual)
+const FACT_COLUMNS: usize = 44;
+
+/// Ficha de datos curiosos de un cuerpo, con fondo oscuro: el dato `page` de `facts` y la
+/// indicación para pasar de página. `origin` es su esquina superior izquierda.
+pub fn render_fact_card(framebuffer: &mut Framebuffer, theme: &Theme, name: &str, facts: &[String], page: usize, origin: (i32, i32)) {
+    let columns = (framebuffer.width / theme.text_width("M").max(1)).saturating_sub(4).clamp(16, FACT_COLUMNS);
+    // Tras recargar la escena puede haber menos datos que antes
+    let page = page.min(facts.len().saturating_sub(1));
+    let text = match facts.get(page) {
+        Some(fact) => format!(
+            "{} - dato {} de {}\n\n{}\n\n{}",
+            name,
+            page + 1,
+            facts.len(),
+            text::wrap(fact, columns),
+            text::wrap("(RePág/AvPág para pasar, N para ocultar)", columns)
+        ),
+        None => format!("{}\n\n{}", name, text::wrap("La escena no tiene datos de este cuerpo", columns)),
+    };
+    theme.text_panel(framebuffer, origin, &text, (0xDDE6EE, 0x0A1018, None), 4);
+}
+
+/// Panel de depuración con fondo oscuro; `top_right` es su esquina superior derecha
+pub fn render_debug_panel(framebuffer: &mut Framebuffer, theme: &Theme, text: &str, top_right: (i32, i32)) {
+    let padding = theme.px(3);
+    let (width, _) = theme.text_panel_size(text, padding);
+    theme.text_panel(framebuffer, (top_right.0 - width as i32, top_right.1), text, (0xAABBCC, 0x0A1018, None), 3);
+}
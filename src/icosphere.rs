@@ -0,0 +1,163 @@
+use nalgebra_glm::Vec3;
+use raylib::prelude::{Vector2, Vector3};
+use crate::vertex::Vertex;
+
+// Icosfera adaptativa para ver un cuerpo de cerca: cada cara del icosaedro es la raíz de un
+// árbol cuaternario que solo se subdivide cerca de la cámara y de cara a ella, así el terreno
+// cercano tiene geometría densa y el hemisferio de detrás se queda en unas pocas caras.
+//
+// Lo que se decide es si se parte cada arista, y la decisión depende solo de la arista, así los
+// dos triángulos que la comparten siempre están de acuerdo. Un triángulo se divide en cuatro si
+// se parte alguna de sus aristas; el punto medio de las que no se parten queda sobre la cuerda,
+// alineado con el borde del vecino, y sus mitades ya no se parten nunca. Así los niveles
+// distintos se unen sin grietas.
+//
+// La malla sigue las convenciones de `sphere1.obj` tal como la carga `Obj::load`: posiciones con
+// la y invertida (lo que deja los triángulos en sentido horario vistos desde fuera) y normales
+// en el espacio del archivo.
+
+/// Nivel de la malla lejana, el de `sphere1.obj` (80 caras)
+pub const BASE_LEVEL: u32 = 1;
+/// Paso de las diferencias finitas para las normales, en radios
+const NORMAL_STEP: f32 = 1e-3;
+
+/// Cuánto se subdivide la icosfera y hacia dónde
+#[derive(Debug, Clone, Copy)]
+pub struct Tessellation {
+    /// Cámara en el espacio del modelo, con la esfera de radio 1 en el origen
+    pub eye: Vec3,
+    /// Una arista se parte si su longitud supera esta fracción de su distancia a la cámara
+    pub detail: f32,
+    /// Subdivisiones como mucho, contando desde el icosaedro
+    pub max_level: u32,
+}
+
+#[derive(Clone, Copy)]
+struct Point {
+    /// Dirección desde el centro, que es lo que decide las subdivisiones
+    direction: Vec3,
+    position: Vec3,
+    normal: Vec3,
+}
+
+/// Triángulos de la icosfera subdividida según `tessellation`. `radius` da el radio de la
+/// superficie en cada dirección (1 para una esfera lisa; el relieve, si lo hay).
+pub fn adaptive_icosphere<F: Fn(Vec3) -> f32>(tessellation: &Tessellation, radius: F) -> Vec<Vertex> {
+    let mut builder = Builder { tessellation, radius, vertices: Vec::new() };
+    let corners: Vec<Point> = icosahedron_directions().iter().map(|&direction| builder.surface_point(direction)).collect();
+    for [a, b, c] in ICOSAHEDRON_FACES {
+        builder.triangle([corners[a], corners[b], corners[c]], [true; 3], 0);
+    }
+    builder.vertices
+}
+
+struct Builder<'a, F> {
+    tessellation: &'a Tessellation,
+    radius: F,
+    vertices: Vec<Vertex>,
+}
+
+impl<F: Fn(Vec3) -> f32> Builder<'_, F> {
+    fn surface_point(&self, direction: Vec3) -> Point {
+        let position = direction * (self.radius)(direction);
+        let helper = if direction.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+        let tangent = direction.cross(&helper).normalize();
+        let bitangent = direction.cross(&tangent);
+        let offset = |step: Vec3| {
+            let neighbour = (direction + step * NORMAL_STEP).normalize();
+            neighbour * (self.radius)(neighbour) - position
+        };
+        let normal = offset(tangent).cross(&offset(bitangent)).try_normalize(1e-12).unwrap_or(direction);
+        Point { direction, position, normal }
+    }
+
+    /// Punto medio de la arista: sobre la superficie si se parte, sobre la cuerda si no
+    fn midpoint(&self, a: &Point, b: &Point, split: bool) -> Point {
+        let direction = (a.direction + b.direction).normalize();
+        if split {
+            self.surface_point(direction)
+        } else {
+            Point { direction, position: (a.position + b.position) * 0.5, normal: (a.normal + b.normal).normalize() }
+        }
+    }
+
+    /// Si se parte la arista (a, b) de un triángulo de nivel `level`
+    fn splits(&self, a: &Point, b: &Point, level: u32) -> bool {
+        if level >= self.tessellation.max_level {
+            return false;
+        }
+        if level < BASE_LEVEL {
+            return true;
+        }
+        let middle = (a.direction + b.direction).normalize();
+        let length = (a.direction - b.direction).norm();
+        let to_eye = self.tessellation.eye - middle;
+        let distance = to_eye.norm();
+        // De espaldas a la cámara no; con un margen del tamaño de la arista para el horizonte
+        if middle.dot(&to_eye) < -length * distance {
+            return false;
+        }
+        length > self.tessellation.detail * distance
+    }
+
+    /// `splittable[i]` dice si la arista i (ab, bc, ca) puede partirse: no si es la mitad de
+    /// una arista que no se partió
+    fn triangle(&mut self, [a, b, c]: [Point; 3], splittable: [bool; 3], level: u32) {
+        let split = [(&a, &b, 0), (&b, &c, 1), (&c, &a, 2)].map(|(from, to, edge)| splittable[edge] && self.splits(from, to, level));
+        if split == [false; 3] {
+            self.emit(&a, &b, &c);
+            return;
+        }
+        let ab = self.midpoint(&a, &b, split[0]);
+        let bc = self.midpoint(&b, &c, split[1]);
+        let ca = self.midpoint(&c, &a, split[2]);
+        let level = level + 1;
+        self.triangle([a, ab, ca], [split[0], true, split[2]], level);
+        self.triangle([ab, b, bc], [split[0], split[1], true], level);
+        self.triangle([ca, bc, c], [true, split[1], split[2]], level);
+        self.triangle([ab, bc, ca], [true; 3], level);
+    }
+
+    /// Los triángulos se construyen en sentido antihorario desde fuera; se emiten al revés para
+    /// seguir el de la malla cargada
+    fn emit(&mut self, a: &Point, b: &Point, c: &Point) {
+        for point in [a, c, b] {
+            let (direction, position, normal) = (point.direction, point.position, point.normal);
+            let u = 0.5 + direction.z.atan2(direction.x) / std::f32::consts::TAU;
+            let v = (-direction.y).clamp(-1.0, 1.0).acos() / std::f32::consts::PI;
+            self.vertices.push(Vertex::new(
+                Vector3::new(position.x, position.y, position.z),
+                Vector3::new(normal.x, -normal.y, normal.z),
+                Vector2::new(u, v),
+            ));
+        }
+    }
+}
+
+/// Vértices del icosaedro unidad
+fn icosahedron_directions() -> [Vec3; 12] {
+    let phi = (1.0 + 5.0f32.sqrt()) / 2.0;
+    [
+        Vec3::new(-1.0, phi, 0.0),
+        Vec3::new(1.0, phi, 0.0),
+        Vec3::new(-1.0, -phi, 0.0),
+        Vec3::new(1.0, -phi, 0.0),
+        Vec3::new(0.0, -1.0, phi),
+        Vec3::new(0.0, 1.0, phi),
+        Vec3::new(0.0, -1.0, -phi),
+        Vec3::new(0.0, 1.0, -phi),
+        Vec3::new(phi, 0.0, -1.0),
+        Vec3::new(phi, 0.0, 1.0),
+        Vec3::new(-phi, 0.0, -1.0),
+        Vec3::new(-phi, 0.0, 1.0),
+    ]
+    .map(|direction| direction.normalize())
+}
+
+/// Caras del icosaedro, en sentido antihorario vistas desde fuera
+const ICOSAHEDRON_FACES: [[usize; 3]; 20] = [
+    [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+    [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+    [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+    [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+];
@@ -0,0 +1,100 @@
+use nalgebra_glm::DVec3;
+
+/// Triángulos rasterizados en un frame por cada entidad, para el inspector
+#[derive(Default)]
+pub struct RenderStats {
+    /// Por cuerpo, en el orden de `planets`; los ocultos se quedan a 0
+    pub body_triangles: Vec<usize>,
+    /// Por cuerpo, si se descartó entero por quedar su esfera envolvente fuera del frustum (en
+    /// todas las vistas del frame); los ocultos no cuentan
+    pub body_culled: Vec<bool>,
+    /// Rocas del cinturón dibujadas con malla (el resto son impostores)
+    pub rock_meshes: usize,
+    pub rock_triangles: usize,
+    pub ship_triangles: usize,
+}
+
+impl RenderStats {
+    /// Suma las de otra vista del mismo frame (observatorio, anaglifo)
+    pub fn merge(&mut self, other: RenderStats) {
+        if self.body_triangles.len() < other.body_triangles.len() {
+            self.body_triangles.resize(other.body_triangles.len(), 0);
+        }
+        for (total, triangles) in self.body_triangles.iter_mut().zip(other.body_triangles) {
+            *total += triangles;
+        }
+        // Solo sigue descartado lo que ninguna vista ha dibujado
+        if self.body_culled.is_empty() {
+            self.body_culled = other.body_culled;
+        } else {
+            for (culled, other) in self.body_culled.iter_mut().zip(other.body_culled) {
+                *culled &= other;
+            }
+        }
+        self.rock_meshes += other.rock_meshes;
+        self.rock_triangles += other.rock_triangles;
+        self.ship_triangles += other.ship_triangles;
+    }
+
+    /// Línea del panel de depuración con lo que ha ahorrado el descarte por frustum
+    pub fn culling_summary(&self) -> String {
+        let culled = self.body_culled.iter().filter(|&&culled| culled).count();
+        format!("Fuera de vista: {} de {} cuerpos sin dibujar", culled, self.body_culled.len())
+    }
+}
+
+/// Una línea del inspector
+pub struct EntityRow {
+    pub name: String,
+    /// Posición en el mundo; el cinturón no tiene una sola
+    pub position: Option<DVec3>,
+    /// Ángulo orbital en grados, para lo que orbita
+    pub orbit_angle: Option<f32>,
+    /// Nivel de detalle en uso (ver `MeshInfo`)
+    pub lod: String,
+    pub triangles: usize,
+}
+
+/// Inspector de entidades del panel de depuración (F3): una fila por entidad con sus valores del
+/// último frame. Las primeras `selectable` filas (los cuerpos) se recorren con RePág/AvPág, e
+/// Intro lleva la cámara en órbita a la marcada.
+pub struct Inspector {
+    pub open: bool,
+    cursor: usize,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Inspector { open: false, cursor: 0 }
+    }
+
+    /// Fila marcada, si hay alguna que se pueda seleccionar
+    pub fn cursor(&self, selectable: usize) -> Option<usize> {
+        (selectable > 0).then(|| self.cursor.min(selectable - 1))
+    }
+
+    /// Mueve la marca una fila, dando la vuelta en los extremos
+    pub fn step(&mut self, selectable: usize, forward: bool) {
+        let Some(cursor) = self.cursor(selectable) else {
+            return;
+        };
+        self.cursor = if forward { (cursor + 1) % selectable } else { (cursor + selectable - 1) % selectable };
+    }
+
+    pub fn text(&self, rows: &[EntityRow], selectable: usize) -> String {
+        let cursor = self.cursor(selectable);
+        let mut lines = vec!["Entidades (RePág/AvPág, Intro = orbitar)".to_string()];
+        for (index, row) in rows.iter().enumerate() {
+            let mut line = format!("{} {}", if cursor == Some(index) { ">" } else { " " }, row.name);
+            if let Some(position) = row.position {
+                line.push_str(&format!("  ({:.0}, {:.0}, {:.0})", position.x, position.y, position.z));
+            }
+            if let Some(angle) = row.orbit_angle {
+                line.push_str(&format!("  {:.0}°", angle));
+            }
+            line.push_str(&format!("  {}  {} tri.", row.lod, row.triangles));
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+}
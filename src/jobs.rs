@@ -0,0 +1,233 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+// Planificador de tareas del frame. El trabajo se describe como un grafo de tareas con
+// dependencias (`FrameGraph`) y `JobSystem::run` lo reparte entre hilos que viven tanto como el
+// planificador: cada tarea empieza en cuanto terminan las que necesita. `run` no vuelve hasta que
+// todas han terminado, así las tareas pueden tomar prestados las mallas, los uniforms o el
+// framebuffer (solo para leer) sin copiarlos. Lo que escribe en el framebuffer se hace en el hilo
+// principal después del grafo.
+//
+// Solo reparte trabajo que empieza y acaba dentro de un frame. La simulación, la predicción, la
+// sincronización en red y el control remoto siguen en sus propios hilos: pasan la sesión entera
+// esperando en un canal o un socket y ocuparían un hilo del planificador para siempre.
+
+/// Índice de una tarea dentro de su grafo
+pub type TaskId = usize;
+
+type Job<'a> = Box<dyn FnOnce() + Send + 'a>;
+
+/// Tareas de un frame y sus dependencias
+pub struct FrameGraph<'a> {
+    jobs: Vec<Job<'a>>,
+    dependencies: Vec<Vec<TaskId>>,
+}
+
+impl<'a> FrameGraph<'a> {
+    pub fn new() -> Self {
+        FrameGraph { jobs: Vec::new(), dependencies: Vec::new() }
+    }
+
+    /// Añade una tarea que empieza cuando terminan todas las de `dependencies`. Solo se puede
+    /// depender de tareas ya añadidas, así el grafo nunca tiene ciclos.
+    pub fn add<F: FnOnce() + Send + 'a>(&mut self, dependencies: &[TaskId], job: F) -> TaskId {
+        let id = self.jobs.len();
+        assert!(dependencies.iter().all(|&dependency| dependency < id), "dependencia de una tarea posterior");
+        self.jobs.push(Box::new(job));
+        self.dependencies.push(dependencies.to_vec());
+        id
+    }
+}
+
+/// Grafo en ejecución. Sus tareas se guardan como `'static` aunque tomen prestado lo que vive
+/// en la llamada a `run`, que no vuelve hasta que se han ejecutado o descartado todas.
+struct Schedule {
+    jobs: Vec<Option<Job<'static>>>,
+    /// Dependencias sin terminar de cada tarea
+    waiting_on: Vec<usize>,
+    dependents: Vec<Vec<TaskId>>,
+    ready: VecDeque<TaskId>,
+    unfinished: usize,
+    /// Tareas que algún hilo está ejecutando ahora mismo
+    running: usize,
+    /// Pánico de una tarea: no se empieza ninguna más y `run` lo propaga al terminar
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl Schedule {
+    fn take_ready(&mut self) -> Option<(TaskId, Job<'static>)> {
+        if self.panic.is_some() {
+            return None;
+        }
+        let id = self.ready.pop_front()?;
+        self.running += 1;
+        Some((id, self.jobs[id].take().expect("tarea ejecutada dos veces")))
+    }
+
+    fn finish(&mut self, id: TaskId, result: thread::Result<()>) {
+        self.running -= 1;
+        self.unfinished -= 1;
+        if let Err(payload) = result {
+            self.panic.get_or_insert(payload);
+        }
+        for index in 0..self.dependents[id].len() {
+            let dependent = self.dependents[id][index];
+            self.waiting_on[dependent] -= 1;
+            if self.waiting_on[dependent] == 0 {
+                self.ready.push_back(dependent);
+            }
+        }
+    }
+
+    /// Ya no queda nada que esperar: todo ha terminado, o ha habido un pánico y las tareas que
+    /// estaban en marcha han acabado
+    fn settled(&self) -> bool {
+        self.unfinished == 0 || (self.panic.is_some() && self.running == 0)
+    }
+}
+
+/// Lo que comparten el hilo que llama a `run` y los trabajadores
+struct Pool {
+    state: Mutex<PoolState>,
+    /// Avisa de un grafo nuevo, de una tarea terminada o del cierre
+    wake: Condvar,
+}
+
+struct PoolState {
+    schedule: Option<Schedule>,
+    shutdown: bool,
+}
+
+impl Pool {
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        // Las tareas se ejecutan fuera del cerrojo, así que nunca queda envenenado a medias
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Ejecuta una tarea fuera del cerrojo y apunta que ha terminado
+    fn execute<'s>(&'s self, state: MutexGuard<'s, PoolState>, (id, job): (TaskId, Job<'static>)) -> MutexGuard<'s, PoolState> {
+        drop(state);
+        let result = panic::catch_unwind(AssertUnwindSafe(job));
+        let mut state = self.lock();
+        if let Some(schedule) = state.schedule.as_mut() {
+            schedule.finish(id, result);
+        }
+        self.wake.notify_all();
+        state
+    }
+}
+
+/// Grupo de hilos con el que se ejecutan todos los grafos del programa. Los hilos se crean una
+/// vez y duermen entre grafo y grafo.
+pub struct JobSystem {
+    workers: usize,
+    pool: Arc<Pool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl JobSystem {
+    /// `threads` incluye el hilo principal; sin valor se usan todos los núcleos
+    pub fn new(threads: Option<usize>) -> Self {
+        let workers = threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |count| count.get())).max(1);
+        let pool = Arc::new(Pool { state: Mutex::new(PoolState { schedule: None, shutdown: false }), wake: Condvar::new() });
+        let threads = (1..workers)
+            .map(|index| {
+                let pool = Arc::clone(&pool);
+                thread::Builder::new()
+                    .name(format!("tareas-{}", index))
+                    .spawn(move || work(&pool))
+                    .expect("no se pudo crear un hilo de tareas")
+            })
+            .collect();
+        JobSystem { workers, pool, threads }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Ejecuta el grafo y vuelve cuando han terminado todas sus tareas. El hilo que llama también
+    /// trabaja; con un solo hilo las tareas se ejecutan en el orden en que se añadieron. Si una
+    /// tarea entra en pánico, las que no habían empezado se descartan y el pánico sigue desde
+    /// aquí. No se puede llamar desde una tarea.
+    pub fn run(&self, graph: FrameGraph) {
+        let count = graph.jobs.len();
+        if count == 0 {
+            return;
+        }
+        let mut dependents = vec![Vec::new(); count];
+        for (id, dependencies) in graph.dependencies.iter().enumerate() {
+            for &dependency in dependencies {
+                dependents[dependency].push(id);
+            }
+        }
+        let waiting_on: Vec<usize> = graph.dependencies.iter().map(Vec::len).collect();
+        let schedule = Schedule {
+            ready: (0..count).filter(|&id| waiting_on[id] == 0).collect(),
+            // Las tareas pasan por `'static` para que las vean los trabajadores. Es correcto
+            // porque de aquí no se sale (ni con un pánico, que se captura) hasta que cada una se
+            // ha ejecutado o se ha descartado con el grafo, antes de que caduque lo que toman
+            // prestado.
+            jobs: graph
+                .jobs
+                .into_iter()
+                .map(|job| Some(unsafe { std::mem::transmute::<Job<'_>, Job<'static>>(job) }))
+                .collect(),
+            waiting_on,
+            dependents,
+            unfinished: count,
+            running: 0,
+            panic: None,
+        };
+
+        let mut state = self.pool.lock();
+        if state.schedule.is_some() {
+            drop(state);
+            panic!("JobSystem::run llamado mientras se ejecutaba otro grafo");
+        }
+        state.schedule = Some(schedule);
+        self.pool.wake.notify_all();
+        loop {
+            let schedule = state.schedule.as_mut().expect("el grafo sigue instalado hasta el final");
+            if schedule.settled() {
+                break;
+            }
+            state = match schedule.take_ready() {
+                Some(task) => self.pool.execute(state, task),
+                None => self.pool.wake.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner()),
+            };
+        }
+        let schedule = state.schedule.take().expect("el grafo sigue instalado hasta el final");
+        drop(state);
+
+        // Al soltar el grafo se descartan las tareas que no llegaron a empezar
+        let Schedule { panic, .. } = schedule;
+        if let Some(payload) = panic {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+impl Drop for JobSystem {
+    fn drop(&mut self) {
+        self.pool.lock().shutdown = true;
+        self.pool.wake.notify_all();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Bucle de cada trabajador: toma tareas listas del grafo en curso y duerme cuando no hay
+fn work(pool: &Pool) {
+    let mut state = pool.lock();
+    while !state.shutdown {
+        state = match state.schedule.as_mut().and_then(Schedule::take_ready) {
+            Some(task) => pool.execute(state, task),
+            None => pool.wake.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner()),
+        };
+    }
+}
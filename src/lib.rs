@@ -0,0 +1,28 @@
+//! Parte del render sin ventana ni estado global: vértices y fragmentos, transformaciones,
+//! recorte, rasterización (también por tiles), shaders de los planetas, el lector de OBJ, la
+//! icosfera adaptativa de las vistas cercanas, el relieve para aterrizar, la jerarquía de
+//! esferas de las consultas espaciales y los recorridos de cámara de los vídeos. El programa
+//! (`main.rs`) la usa como cualquier otro módulo; las pruebas de `tests/` y el fuzzer de
+//! `fuzz/` la usan directamente, y `ffi` la expone como biblioteca de C para otros programas y
+//! lenguajes (y `python`, con esa característica, como módulo de Python).
+
+pub mod bvh;
+pub mod camera_path;
+pub mod clip;
+pub mod depth;
+pub mod ffi;
+pub mod fragment;
+pub mod heightfield;
+pub mod icosphere;
+pub mod light;
+pub mod line;
+pub mod matrix;
+pub mod obj;
+pub mod pipeline;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod shaders;
+pub mod texture;
+pub mod tiles;
+pub mod triangle;
+pub mod vertex;
@@ -23,8 +23,12 @@ pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
 
     let mut err = if dx > dy { dx / 2 } else { -dy / 2 };
 
+    // Interpolate depth along the major axis so vertical lines don't divide by zero
+    let steps = dx.max(dy).max(1) as f32;
+    let mut step = 0;
+
     loop {
-        let z = start.z + (end.z - start.z) * (x0 - start.x as i32) as f32 / (end.x - start.x) as f32;
+        let z = start.z + (end.z - start.z) * (step as f32 / steps);
         // For now, we'll just use white for the line color.
         // A more advanced implementation would interpolate the vertex colors.
         fragments.push(Fragment::new(x0 as f32, y0 as f32, Vector3::new(1.0, 1.0, 1.0), z));
@@ -40,7 +44,51 @@ pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
             err += dx;
             y0 += sy;
         }
+        step += 1;
     }
 
     fragments
-}
\ No newline at end of file
+}
+
+/// Clips a screen-space segment to the rectangle [0, width) x [0, height) (Liang-Barsky).
+/// Returns the clipped endpoints with their depth re-interpolated, or None if fully outside.
+pub fn clip_to_screen(start: Vector3, end: Vector3, width: f32, height: f32) -> Option<(Vector3, Vector3)> {
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+
+    let checks = [
+        (-dx, start.x),
+        (dx, width - 1.0 - start.x),
+        (-dy, start.y),
+        (dy, height - 1.0 - start.y),
+    ];
+
+    for (p, q) in checks {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let r = q / p;
+            if p < 0.0 {
+                t0 = t0.max(r);
+            } else {
+                t1 = t1.min(r);
+            }
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+
+    let lerp = |t: f32| Vector3::new(
+        start.x + dx * t,
+        start.y + dy * t,
+        start.z + (end.z - start.z) * t,
+    );
+
+    Some((lerp(t0), lerp(t1)))
+}
@@ -1,145 +1,462 @@
-use nalgebra_glm::{Vec3, Mat4, perspective, look_at};
+use nalgebra_glm::{DVec3, Vec3, Mat4};
 use minifb::{Key, Window, WindowOptions};
-use std::time::{Duration, Instant};
+use std::time::Instant;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::OnceLock;
 use std::f32::consts::PI;
+use computer_graphics_v3::bvh::Bvh;
+use computer_graphics_v3::camera_path::{CameraPath, LookAt, PathRecorder};
+use computer_graphics_v3::{depth, fragment, light, line, matrix, obj, pipeline, shaders, texture, vertex};
+use computer_graphics_v3::pipeline::{pack_color, project_segment, project_to_screen, rasterize, transform_indexed, Culling, Uniforms};
+use computer_graphics_v3::tiles::{rasterize_tile, TileGrid, TileTriangle};
+use computer_graphics_v3::triangle::{triangle_depths_in, PixelRect};
+use computer_graphics_v3::heightfield::Heightfield;
+use computer_graphics_v3::icosphere::{adaptive_icosphere, Tessellation};
+use computer_graphics_v3::pipeline::{create_model_matrix, create_normal_matrix, create_projection_matrix, create_view_matrix, create_viewport_matrix};
 
 mod framebuffer;
-mod triangle;
-mod line;
-mod vertex;
-mod fragment;
-mod shaders;
-mod obj;
-mod matrix;
 mod camera;
-mod light;
+mod scene;
+mod particles;
+mod prominence;
+mod corona;
+mod ship_shadow;
+mod rings;
+mod atmosphere;
+mod effects;
+mod audio;
+mod clock;
+mod cli;
+mod telemetry;
+mod simulation;
+mod asteroids;
+mod upscale;
+mod pacing;
+mod jobs;
+mod menu;
+mod session;
+mod status;
+mod text;
+mod hud;
+mod texture_cache;
+mod memory;
+mod inspector;
+mod shader_panel;
+mod ecliptic;
+mod galaxy;
+mod prediction;
+mod measure;
+mod panorama;
+mod still;
+mod toon;
+mod video;
+mod gbuffer;
+mod ssao;
+mod starfield;
+mod orrery;
+mod events;
+mod orbital;
+mod mesh;
+mod noise;
+mod stl;
+mod ply;
+mod netsync;
+mod scenario;
+#[cfg(feature = "remote")]
+mod remote;
 
 use framebuffer::Framebuffer;
-use vertex::Vertex;
+use fragment::{Fragment, ShadedFragments, REUSE_PREVIOUS};
+use vertex::{IndexedMesh, Vertex};
+use line::{clip_to_screen, line};
+use shaders::{fragment_shader, PlanetShaderType, ShaderParams, SurfaceMaps};
+use shaders::{ERUPTION_HOTSPOTS, hotspot_direction, eruption_intensity};
+use particles::{Particle, ParticleSystem};
+use prominence::render_prominences;
+use corona::render_corona;
+use ship_shadow::render_ship_shadow;
+use rings::render_rings;
+use ecliptic::render_ecliptic_grid;
+use atmosphere::Atmosphere;
+use effects::{EntryHeating, SolarWind};
+use audio::EntryRoar;
+use clock::SimulationClock;
+use cli::CliOptions;
+use asteroids::AsteroidBelt;
+use upscale::Upscaler;
+use pacing::FramePacer;
+use jobs::{FrameGraph, JobSystem, TaskId};
+use menu::{Hotkeys, MenuCommand, MenuValues, PauseMenu, RESOLUTION_SCALES};
+use session::{Autosave, SessionStats, AUTOSAVE_PATH, VISIT_RADII};
+use status::{StatusEvent, StatusOutput};
+use texture_cache::TextureCache;
+use memory::{MemoryBudget, MemoryReport};
+use gbuffer::GBuffer;
+use starfield::StarField;
+use orrery::OrreryBody;
+use events::{Conjunction, ConjunctionDetector, Eclipse, Vantage};
+use depth::DepthMode;
+use orbital::OrbitalElements;
+use video::VideoSink;
+use mesh::MeshInfo;
+use inspector::{EntityRow, Inspector, RenderStats};
+use shader_panel::ShaderPanel;
+use galaxy::{Galaxy, GalaxyMap, Hyperspace, GALAXY_SYSTEMS};
+use prediction::{PredictionRequest, TrajectoryPredictor};
+use measure::{Endpoint, EndpointState, Measurement};
 use obj::Obj;
-use triangle::triangle;
-use shaders::{vertex_shader, fragment_shader, PlanetShaderType};
+use simulation::{SimulationWorker, SimulationStep, OrbitalState, Ephemeris, SHIP_CLEARANCE};
+use telemetry::{TelemetryWriter, TelemetryFormat, TelemetryFrame, BodySample};
+use netsync::{BodyState, NetObserver, NetServer, NetState, ShipState};
+use scenario::{BodyReading, Scenario, ScenarioRun};
+#[cfg(feature = "remote")]
+use remote::{capture_path, RemoteCommand, RemoteServer};
+use raylib::prelude::RaylibAudio;
 use light::Light;
+use scene::{SceneFile, SceneWatcher, BodyConfig, BeltConfig, DisplacementConfig, ScaleMode, StarsConfig};
 use raylib::prelude::Vector3;
 
-pub struct Uniforms {
-    pub model_matrix: Mat4,
-    pub view_matrix: Mat4,
-    pub projection_matrix: Mat4,
-    pub viewport_matrix: Mat4,
-    pub time: f32,
-}
-
-fn simplify_mesh(vertices: &[Vertex], target_triangles: usize) -> Vec<Vertex> {
-    if vertices.len() < 3 {
-        return vertices.to_vec();
-    }
-    
-    let current_triangles = vertices.len() / 3;
-    if current_triangles <= target_triangles {
-        return vertices.to_vec();
-    }
-    
-    let mut simplified = Vec::new();
-    let skip_factor = (current_triangles / target_triangles).max(1);
-    
-    for i in (0..current_triangles).step_by(skip_factor) {
-        let idx = i * 3;
-        if idx + 2 < vertices.len() {
-            let v0 = &vertices[idx];
-            let v1 = &vertices[idx + 1];
-            let v2 = &vertices[idx + 2];
-            
-            let edge1 = Vec3::new(
-                v1.position.x - v0.position.x,
-                v1.position.y - v0.position.y,
-                v1.position.z - v0.position.z,
-            );
-            let edge2 = Vec3::new(
-                v2.position.x - v0.position.x,
-                v2.position.y - v0.position.y,
-                v2.position.z - v0.position.z,
-            );
-            
-            let cross = edge1.cross(&edge2);
-            let area = cross.norm();
-            
-            if area > 0.0001 {
-                simplified.push(v0.clone());
-                simplified.push(v1.clone());
-                simplified.push(v2.clone());
-            }
-        }
-    }
-    
-    simplified
-}
-
 struct CelestialBody {
     name: String,
-    position: Vec3,
+    /// En doble precisión; se pasa a f32 relativo a la cámara justo antes de dibujar
+    position: DVec3,
     scale: f32,
     rotation: Vec3,
     rotation_speed: Vec3,
     orbit_radius: f32,
     orbit_speed: f32,
     orbit_angle: f32,
+    /// Ángulo orbital y rotación en t = 0 (ver `ephemeris`)
+    orbit_phase: f32,
+    rotation_phase: Vec3,
+    mass: f32,
     shader_type: PlanetShaderType,
-    vertex_array: Vec<Vertex>,
+    shader_params: ShaderParams,
+    mesh: IndexedMesh,
+    /// Distancia del centro al vértice más lejano de `mesh`, antes de escalar
+    mesh_radius: f32,
+    /// Relieve de la superficie, para rehacerla en la icosfera adaptativa
+    displacement: Option<DisplacementConfig>,
+    /// Malla que sustituye a `mesh` mientras se ve el cuerpo de cerca
+    close_up: Option<CloseUpMesh>,
+    plumes: ParticleSystem,
+    /// Oculto por el usuario (o por el modo solo); sigue en la simulación
+    hidden: bool,
+    /// Datos curiosos de la escena para la ficha educativa
+    facts: Vec<String>,
+}
+
+/// Icosfera adaptativa de un cuerpo visto de cerca y la cámara para la que se hizo
+struct CloseUpMesh {
+    /// En el espacio del modelo, sin escala ni giro
+    eye: Vec3,
+    mesh: IndexedMesh,
 }
 
+/// Fracción de la distancia a la cámara por encima de la que se parte una arista de cerca
+const CLOSE_UP_DETAIL: f32 = 0.08;
+/// Subdivisiones como mucho de la icosfera adaptativa
+const CLOSE_UP_MAX_LEVEL: u32 = 8;
+/// Movimiento de la cámara, en altitudes, a partir del que se rehace la malla cercana
+const CLOSE_UP_REBUILD: f32 = 0.05;
+/// Altitud mínima (en radios) para ese umbral, para no rehacerla a cada frame a ras de suelo
+const CLOSE_UP_MIN_ALTITUDE: f32 = 0.01;
+
 impl CelestialBody {
-    fn new(
-        name: &str,
-        orbit_radius: f32,
-        orbit_speed: f32,
-        scale: f32,
-        rotation_speed: Vec3,
-        shader_type: PlanetShaderType,
-        vertex_array: Vec<Vertex>,
-    ) -> Self {
+    fn from_config(config: &BodyConfig, mesh: IndexedMesh) -> Self {
+        let [rx, ry, rz] = config.rotation_speed;
+        let mut plumes = ParticleSystem::new(600);
+        plumes.attraction = config.scale * 0.6;
+        plumes.drag = 0.3;
+
         CelestialBody {
-            name: name.to_string(),
-            position: Vec3::new(orbit_radius, 0.0, 0.0),
-            scale,
+            name: config.name.clone(),
+            position: DVec3::new(config.orbit_radius as f64, 0.0, 0.0),
+            scale: config.scale,
             rotation: Vec3::zeros(),
-            rotation_speed,
-            orbit_radius,
-            orbit_speed,
+            rotation_speed: Vec3::new(rx, ry, rz),
+            orbit_radius: config.orbit_radius,
+            orbit_speed: config.orbit_speed,
             orbit_angle: 0.0,
-            shader_type,
-            vertex_array,
+            orbit_phase: 0.0,
+            rotation_phase: Vec3::zeros(),
+            mass: config.mass,
+            shader_type: config.shader,
+            shader_params: config.shader_params(),
+            mesh_radius: mesh_radius(&mesh.vertices),
+            mesh,
+            displacement: config.displacement,
+            close_up: None,
+            plumes,
+            hidden: false,
+            facts: config.facts.clone(),
+        }
+    }
+
+    /// Actualiza los parámetros desde una escena recargada, conservando el ángulo orbital,
+    /// la rotación y las partículas en vuelo. `time` es el instante actual: las fases se
+    /// recalculan para que el cuerpo siga donde estaba con las velocidades nuevas.
+    fn apply_config(&mut self, config: &BodyConfig, time: f64) {
+        let [rx, ry, rz] = config.rotation_speed;
+        self.scale = config.scale;
+        self.rotation_speed = Vec3::new(rx, ry, rz);
+        self.orbit_radius = config.orbit_radius;
+        self.orbit_speed = config.orbit_speed;
+        self.orbit_phase = (self.orbit_angle as f64 - self.orbit_speed as f64 * time).rem_euclid(std::f64::consts::TAU) as f32;
+        self.rotation_phase = (widen(self.rotation) - widen(self.rotation_speed) * time)
+            .map(|angle| angle.rem_euclid(std::f64::consts::TAU))
+            .cast();
+        self.mass = config.mass;
+        self.shader_type = config.shader;
+        self.shader_params = config.shader_params();
+        self.displacement = config.displacement;
+        self.close_up = None;
+        self.plumes.attraction = config.scale * 0.6;
+        self.facts = config.facts.clone();
+        let state = self.ephemeris().state_at(time);
+        self.apply_orbital_state(&state);
+    }
+
+    fn ephemeris(&self) -> Ephemeris {
+        Ephemeris {
+            orbit_radius: self.orbit_radius,
+            orbit_speed: self.orbit_speed,
+            orbit_phase: self.orbit_phase,
+            rotation_speed: self.rotation_speed,
+            rotation_phase: self.rotation_phase,
+            scale: self.scale,
+        }
+    }
+
+    /// Velocidad orbital actual: tangente a la órbita circular
+    fn velocity(&self) -> Vec3 {
+        let (sin, cos) = self.orbit_angle.sin_cos();
+        Vec3::new(-sin, 0.0, cos) * self.orbit_radius * self.orbit_speed
+    }
+
+    /// Segundos simulados que tarda en dar una vuelta sobre sí mismo (`None` si no gira)
+    fn rotation_period(&self) -> Option<f32> {
+        let rate = self.rotation_speed.norm();
+        (rate > 0.0).then(|| std::f32::consts::TAU / rate)
+    }
+
+    /// Segundos simulados que tarda en dar una vuelta al sol (`None` para la estrella central)
+    fn orbital_period(&self) -> Option<f32> {
+        (self.orbit_radius > 0.0 && self.orbit_speed != 0.0).then(|| std::f32::consts::TAU / self.orbit_speed.abs())
+    }
+
+    fn apply_orbital_state(&mut self, state: &OrbitalState) {
+        self.position = state.position;
+        self.rotation = state.rotation;
+        self.orbit_angle = state.orbit_angle;
+    }
+
+    /// Radio de la esfera que envuelve la malla ya escalada, para descartar el cuerpo entero
+    /// cuando queda fuera de la vista
+    fn bounding_radius(&self) -> f32 {
+        self.mesh_radius * self.scale
+    }
+
+    /// Radio de la superficie (con la esfera de radio 1) en cada dirección del modelo: el
+    /// relieve de `displacement`, siempre hacia fuera del centro como en `mesh::displace`
+    fn surface_radius(&self) -> impl Fn(Vec3) -> f32 {
+        let (displacement, seed) = (self.displacement, self.shader_params.seed);
+        move |direction| 1.0 + displacement.map_or(0.0, |relief| noise::fbm(direction * relief.frequency, relief.octaves, seed) * relief.amplitude)
+    }
+
+    /// Vector del mundo (relativo al centro) en el espacio del cuerpo, sin su giro
+    fn to_local(&self, offset: Vec3) -> Vec3 {
+        // La orientación es una rotación: la inversa es la traspuesta
+        (create_model_matrix(Vec3::zeros(), 1.0, self.rotation).transpose() * nalgebra_glm::vec4(offset.x, offset.y, offset.z, 0.0)).xyz()
+    }
+
+    fn to_world(&self, local: Vec3) -> Vec3 {
+        (create_model_matrix(Vec3::zeros(), 1.0, self.rotation) * nalgebra_glm::vec4(local.x, local.y, local.z, 0.0)).xyz()
+    }
+
+    /// Malla que se dibuja: la icosfera adaptativa si se ve de cerca
+    fn visible_mesh(&self) -> &IndexedMesh {
+        self.close_up.as_ref().map_or(&self.mesh, |close_up| &close_up.mesh)
+    }
+
+    /// Rehace la icosfera adaptativa para una cámara en `eye`, o la descarta con `None`. Solo
+    /// se rehace cuando la cámara se ha movido (o el cuerpo ha girado) una fracción de la
+    /// altitud, que es lo que cambia qué caras se subdividen.
+    fn update_close_up(&mut self, eye: Option<DVec3>) {
+        let Some(eye) = eye else {
+            self.close_up = None;
+            return;
+        };
+        let Some(inverse) = create_model_matrix(Vec3::zeros(), self.scale, self.rotation).try_inverse() else {
+            return;
+        };
+        let relative = narrow(eye - self.position);
+        let local = (inverse * nalgebra_glm::vec4(relative.x, relative.y, relative.z, 0.0)).xyz();
+        let altitude = (local.norm() - 1.0).max(CLOSE_UP_MIN_ALTITUDE);
+        if self.close_up.as_ref().is_some_and(|close_up| (close_up.eye - local).norm() < altitude * CLOSE_UP_REBUILD) {
+            return;
+        }
+        let tessellation = Tessellation { eye: local, detail: CLOSE_UP_DETAIL, max_level: CLOSE_UP_MAX_LEVEL };
+        let vertices = adaptive_icosphere(&tessellation, self.surface_radius());
+        self.close_up = Some(CloseUpMesh { eye: local, mesh: IndexedMesh::from_triangles(&vertices) });
+    }
+
+    /// Emite penachos de partículas desde los puntos calientes activos (solo planetas Vulcan)
+    fn update_eruptions(&mut self, time: f32, delta_time: f32) {
+        self.plumes.update(delta_time);
+        if self.shader_type != PlanetShaderType::Vulcan {
+            return;
+        }
+
+        use rand::Rng;
+        let mut rng = rand::rng();
+        let orientation = create_model_matrix(Vec3::zeros(), 1.0, self.rotation);
+
+        for index in 0..ERUPTION_HOTSPOTS {
+            let intensity = eruption_intensity(index, time, &self.shader_params);
+            if intensity <= 0.0 {
+                continue;
+            }
+
+            let local = hotspot_direction(index, &self.shader_params);
+            let normal = (orientation * nalgebra_glm::vec4(local.x, local.y, local.z, 0.0)).xyz();
+
+            // Número esperado de partículas este frame, con la parte fraccionaria al azar
+            let expected = intensity * 60.0 * delta_time;
+            let mut count = expected.floor() as usize;
+            if rng.random::<f32>() < expected.fract() {
+                count += 1;
+            }
+
+            for _ in 0..count {
+                let jitter = Vec3::new(
+                    rng.random_range(-0.3..0.3),
+                    rng.random_range(-0.3..0.3),
+                    rng.random_range(-0.3..0.3),
+                );
+                let speed = self.scale * rng.random_range(0.6..1.1) * (0.5 + intensity);
+                self.plumes.emit(Particle {
+                    position: normal * self.scale,
+                    velocity: (normal + jitter).normalize() * speed,
+                    age: 0.0,
+                    lifetime: rng.random_range(1.2..2.4),
+                    color_start: Vec3::new(1.0, 0.85, 0.4),
+                    color_end: Vec3::new(0.6, 0.1, 0.02),
+                });
+            }
         }
     }
+}
+
+/// Constante gravitacional en unidades de la escena, usada para la velocidad de las órbitas de la nave
+const GRAVITATIONAL_CONSTANT: f32 = 1000.0;
+
+/// Órbita circular cinemática de la nave alrededor de un cuerpo
+struct ShipOrbit {
+    parent: usize,
+    radius: f32,
+    angle: f32,
+    angular_speed: f32,
+    radial: Vec3,
+    tangent: Vec3,
+}
+
+impl ShipOrbit {
+    /// Velocidad de la nave respecto al cuerpo, derivada de la posición en el círculo
+    fn relative_velocity(&self) -> Vec3 {
+        let (sin, cos) = self.angle.sin_cos();
+        (self.tangent * cos - self.radial * sin) * self.radius * self.angular_speed
+    }
+}
+
+/// Velocidad automática de la nave: unidades por segundo por cada unidad de altitud sobre el
+/// cuerpo más cercano, con un mínimo para no quedarse parada en la superficie
+const SPEED_PER_ALTITUDE: f32 = 0.8;
+const MIN_SHIP_SPEED: f32 = 5.0;
+/// Tope de la velocidad automática en múltiplos de la velocidad de crucero
+const MAX_SPEED_FACTOR: f32 = 8.0;
+/// Empuje de los motores en vuelo balístico, en unidades por segundo²
+const THRUST_ACCELERATION: f32 = 20.0;
+/// Segundos de vuelo hacia delante en los que el asistente busca cuerpos en la trayectoria
+const AVOID_LOOKAHEAD: f32 = 2.0;
+/// Distancia que el asistente deja hasta la esfera de un cuerpo, en múltiplos de `SHIP_CLEARANCE`
+const AVOID_MARGIN: f32 = 3.0;
+/// Giro máximo del rumbo al esquivar, en radianes por segundo
+const AVOID_TURN_RATE: f32 = 2.5;
+
+/// Rodeo en curso del asistente de esquiva: el cuerpo y el rumbo que lleva la nave
+struct Avoidance {
+    body: usize,
+    heading: Vec3,
+}
+
+/// Altitud sobre la esfera de un cuerpo, en múltiplos de `SHIP_CLEARANCE`, por debajo de la que
+/// la nave pasa a modo aterrizaje (con velocidad manual y sin vuelo balístico)
+const LANDING_RANGE: f32 = 2.0;
+/// Distancia mínima de la nave al suelo en modo aterrizaje
+const LANDING_CLEARANCE: f32 = 1.0;
+/// Mitad del lado del parche de relieve bajo la nave, en radios del cuerpo, y muestras por lado
+const TERRAIN_EXTENT: f32 = 0.15;
+const TERRAIN_RESOLUTION: usize = 33;
+
+/// Modo aterrizaje: la nave se mueve con el cuerpo que tiene debajo y choca con el relieve del
+/// parche (ver `Heightfield`) en lugar de con su esfera
+struct Landing {
+    body: usize,
+    terrain: Heightfield,
+    /// Posición de la nave respecto al centro del cuerpo, sin su giro
+    local: Vec3,
+    /// Dónde quedó la nave en el mundo: si algo la mueve fuera de `update` (un viaje, una
+    /// órbita), deja de seguir al cuerpo
+    anchor: DVec3,
+}
 
-    fn update(&mut self, delta_time: f32) {
-        self.orbit_angle += self.orbit_speed * delta_time;
-        self.position.x = self.orbit_radius * self.orbit_angle.cos();
-        self.position.z = self.orbit_radius * self.orbit_angle.sin();
-        self.rotation.x += self.rotation_speed.x * delta_time;
-        self.rotation.y += self.rotation_speed.y * delta_time;
-        self.rotation.z += self.rotation_speed.z * delta_time;
+impl Landing {
+    /// Radio del suelo bajo `local` en unidades de la escena: lo más alto bajo la nave
+    fn ground(&self, planet: &CelestialBody, local: Vec3) -> Option<f32> {
+        let direction = local.try_normalize(1e-6)?;
+        let footprint = LANDING_CLEARANCE / planet.scale;
+        let radius = self.terrain.highest_within(direction, footprint).unwrap_or_else(|| planet.surface_radius()(direction));
+        Some(radius * planet.scale)
     }
 }
 
 struct SpaceshipCamera {
-    position: Vec3,
+    position: DVec3,
     yaw: f32,
     pitch: f32,
     velocity: Vec3,
+    /// Velocidad actual: la automática o la fijada a mano
     speed: f32,
+    /// Velocidad de referencia del sistema (ver `ship_speed`)
+    cruise_speed: f32,
+    manual_speed: Option<f32>,
     turn_speed: f32,
+    orbit: Option<ShipOrbit>,
+    /// Vuelo balístico (escenarios): los mandos empujan en lugar de mover, y la gravedad de los
+    /// cuerpos curva la trayectoria como en la predicción de la tecla X
+    physics: bool,
+    /// Rodeo del asistente de esquiva, si está rodeando un cuerpo (ver `avoid`)
+    avoidance: Option<Avoidance>,
+    landing: Option<Landing>,
 }
 
 impl SpaceshipCamera {
-    fn new(position: Vec3) -> Self {
+    fn new(position: DVec3) -> Self {
         SpaceshipCamera {
             position,
             yaw: 0.0,
             pitch: 0.0,
             velocity: Vec3::zeros(),
-            speed: 50.0,
+            speed: BASE_SHIP_SPEED,
+            cruise_speed: BASE_SHIP_SPEED,
+            manual_speed: None,
             turn_speed: 1.5,
+            orbit: None,
+            physics: false,
+            avoidance: None,
+            landing: None,
         }
     }
 
@@ -163,7 +480,25 @@ impl SpaceshipCamera {
         self.get_right().cross(&self.get_forward())
     }
 
-    fn update(&mut self, window: &Window, delta_time: f32, planets: &[CelestialBody]) {
+    /// Proporcional a la altitud sobre el cuerpo más cercano: lenta para inspeccionar una
+    /// superficie de cerca y rápida para cruzar entre órbitas
+    fn auto_speed(&self, planets: &[CelestialBody]) -> f32 {
+        let altitude = planets
+            .iter()
+            .map(|planet| ((self.position - planet.position).norm() as f32 - planet.scale).max(0.0))
+            .fold(f32::INFINITY, f32::min);
+        (altitude * SPEED_PER_ALTITUDE).clamp(MIN_SHIP_SPEED, self.cruise_speed * MAX_SPEED_FACTOR)
+    }
+
+    /// Devuelve el objeto contra el que choca la nave, si el movimiento se ha detenido
+    fn update(
+        &mut self,
+        window: &Window,
+        delta_time: f32,
+        planets: &[CelestialBody],
+        obstacles: &Bvh<SceneObject>,
+    ) -> Option<SceneObject> {
+        self.speed = self.manual_speed.unwrap_or_else(|| self.auto_speed(planets));
         let mut movement = Vec3::zeros();
 
         if window.is_key_down(Key::W) {
@@ -203,423 +538,3356 @@ impl SpaceshipCamera {
         let movement_length = movement.norm();
         if movement_length > 0.0 {
             movement = movement.normalize();
+            if self.orbit.take().is_some() {
+                println!("Órbita abandonada");
+            }
         }
 
-        let new_position = self.position + movement * self.speed * delta_time;
-
-        let mut collision = false;
-        for planet in planets {
-            let distance = (new_position - planet.position).norm();
-            let min_distance = planet.scale + 15.0;
-            
-            if distance < min_distance {
-                collision = true;
-                break;
-            }
+        // Con velocidad automática o por encima de la de crucero, la nave rodea los cuerpos que
+        // tiene delante en lugar de pararse contra ellos
+        let assisted = self.manual_speed.is_none() || self.speed > self.cruise_speed;
+        if movement_length > 0.0 && !self.physics && assisted {
+            movement = self.avoid(movement, planets, delta_time);
+        } else {
+            self.avoidance = None;
         }
+        self.update_landing(planets, assisted);
+
+        let previous_position = self.position;
+        let new_position = if self.physics {
+            // Euler semiimplícito, como `prediction::predict`
+            self.velocity += (movement * THRUST_ACCELERATION + gravity_at(self.position, planets)) * delta_time;
+            self.position + widen(self.velocity * delta_time)
+        } else {
+            self.position + widen(movement * self.speed * delta_time)
+        };
 
-        if !collision {
+        // Si toca varios, el de menor índice (los cuerpos antes que las rocas). Del cuerpo en el
+        // que se aterriza cuenta el relieve, no la esfera
+        let landing_body = self.landing.as_ref().map(|landing| SceneObject::Body(landing.body));
+        let collision = obstacles
+            .overlapping(new_position, SHIP_CLEARANCE as f64)
+            .into_iter()
+            .filter(|&object| Some(object) != landing_body)
+            .min()
+            .or_else(|| self.touches_ground(new_position, planets));
+
+        if collision.is_none() {
             self.position = new_position;
+        } else if self.physics {
+            self.velocity = Vec3::zeros();
+        }
+
+        if delta_time > 0.0 && !self.physics {
+            self.velocity = narrow(self.position - previous_position) / delta_time;
+        }
+        if let Some(landing) = self.landing.as_mut() {
+            let planet = &planets[landing.body];
+            landing.local = planet.to_local(narrow(self.position - planet.position));
+            landing.anchor = self.position;
         }
+        collision
     }
 
-    fn warp_to(&mut self, target: Vec3, offset: f32) {
-        let direction = (target - self.position).normalize();
-        self.position = target - direction * offset;
+    /// Entra o sale del modo aterrizaje sobre el cuerpo más cercano a menos de `LANDING_RANGE`
+    /// y rehace el parche de relieve cuando la nave se acerca a su borde. En modo aterrizaje la
+    /// nave se mueve con el cuerpo, que se ha desplazado y girado desde el frame anterior.
+    fn update_landing(&mut self, planets: &[CelestialBody], assisted: bool) {
+        if self.landing.as_ref().is_some_and(|landing| landing.anchor != self.position) {
+            self.landing = None;
+        }
+        if let Some((landing, planet)) = self.landing.as_ref().and_then(|landing| Some((landing, planets.get(landing.body)?))) {
+            self.position = planet.position + widen(planet.to_world(landing.local));
+        }
+        let body = if assisted || self.physics || self.orbit.is_some() {
+            None
+        } else {
+            planets
+                .iter()
+                .enumerate()
+                .filter(|(_, planet)| planet.shader_type != PlanetShaderType::Solarius)
+                .map(|(index, planet)| (index, (planet.position - self.position).norm() as f32 - planet.scale))
+                .filter(|&(_, altitude)| altitude < SHIP_CLEARANCE * LANDING_RANGE)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(index, _)| index)
+        };
+        let Some(body) = body else {
+            if let Some(landing) = self.landing.take() {
+                println!("Fin del aterrizaje en {}", planets.get(landing.body).map_or("", |planet| planet.name.as_str()));
+            }
+            return;
+        };
+
+        let planet = &planets[body];
+        let local = planet.to_local(narrow(self.position - planet.position));
+        let Some(direction) = local.try_normalize(1e-6) else {
+            return;
+        };
+        let same_body = self.landing.as_ref().is_some_and(|landing| landing.body == body);
+        if same_body && self.landing.as_ref().is_some_and(|landing| landing.terrain.contains(direction, TERRAIN_EXTENT * 0.5)) {
+            return;
+        }
+        if !same_body {
+            println!("Modo aterrizaje sobre {}", planet.name);
+        }
+        let terrain = Heightfield::sample(direction, TERRAIN_EXTENT, TERRAIN_RESOLUTION, planet.surface_radius());
+        self.landing = Some(Landing { body, terrain, local, anchor: self.position });
     }
-}
 
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
-    let (sin_x, cos_x) = rotation.x.sin_cos();
-    let (sin_y, cos_y) = rotation.y.sin_cos();
-    let (sin_z, cos_z) = rotation.z.sin_cos();
+    /// El cuerpo en el que aterriza la nave, si en `position` queda a menos de
+    /// `LANDING_CLEARANCE` de su suelo
+    fn touches_ground(&self, position: DVec3, planets: &[CelestialBody]) -> Option<SceneObject> {
+        let landing = self.landing.as_ref()?;
+        let planet = planets.get(landing.body)?;
+        let local = planet.to_local(narrow(position - planet.position));
+        let ground = landing.ground(planet, local)?;
+        (local.norm() < ground + LANDING_CLEARANCE).then_some(SceneObject::Body(landing.body))
+    }
 
-    let rotation_matrix_x = Mat4::new(
-        1.0,  0.0,    0.0,   0.0,
-        0.0,  cos_x, -sin_x, 0.0,
-        0.0,  sin_x,  cos_x, 0.0,
-        0.0,  0.0,    0.0,   1.0,
-    );
+    /// Cuerpo en el que aterriza la nave y su altura sobre el suelo
+    fn landing_altitude(&self, planets: &[CelestialBody]) -> Option<(usize, f32)> {
+        let landing = self.landing.as_ref()?;
+        let planet = planets.get(landing.body)?;
+        let ground = landing.ground(planet, landing.local)?;
+        Some((landing.body, landing.local.norm() - ground))
+    }
 
-    let rotation_matrix_y = Mat4::new(
-        cos_y,  0.0,  sin_y, 0.0,
-        0.0,    1.0,  0.0,   0.0,
-        -sin_y, 0.0,  cos_y, 0.0,
-        0.0,    0.0,  0.0,   1.0,
-    );
+    /// Asistente de esquiva: si seguir en `direction` lleva a la esfera de un cuerpo (más un
+    /// margen) en los próximos `AVOID_LOOKAHEAD` segundos, devuelve un rumbo que gira hacia la
+    /// tangente a esa esfera. Como la tangente cambia al avanzar, la nave describe un arco
+    /// alrededor del cuerpo y recupera la dirección pedida cuando lo deja atrás.
+    fn avoid(&mut self, direction: Vec3, planets: &[CelestialBody], delta_time: f32) -> Vec3 {
+        let reach = self.speed * AVOID_LOOKAHEAD;
+        let safe_radius = |planet: &CelestialBody| planet.bounding_radius().max(planet.scale) + SHIP_CLEARANCE * AVOID_MARGIN;
+        // El cuerpo más cercano de los que corta la trayectoria (los ocultos también chocan)
+        let threat = planets
+            .iter()
+            .enumerate()
+            .filter_map(|(index, planet)| {
+                let offset = narrow(planet.position - self.position);
+                let along = offset.dot(&direction);
+                let radius = safe_radius(planet);
+                let miss = (offset - direction * along).norm();
+                (along > 0.0 && along < reach + radius && miss < radius).then_some((index, along))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((body, _)) = threat else {
+            self.avoidance = None;
+            return direction;
+        };
 
-    let rotation_matrix_z = Mat4::new(
-        cos_z, -sin_z, 0.0, 0.0,
-        sin_z,  cos_z, 0.0, 0.0,
-        0.0,    0.0,  1.0, 0.0,
-        0.0,    0.0,  0.0, 1.0,
-    );
+        let planet = &planets[body];
+        let offset = narrow(planet.position - self.position);
+        let distance = offset.norm();
+        let to_center = offset / distance;
+        // Se rodea por el lado hacia el que ya se iba; de frente, por la derecha
+        let lateral = (direction - to_center * direction.dot(&to_center))
+            .try_normalize(1e-3)
+            .or_else(|| to_center.cross(&self.get_up()).try_normalize(1e-6))
+            .unwrap_or(self.get_right());
+        let radius = safe_radius(planet);
+        let target = if distance > radius {
+            let grazing = (radius / distance).asin();
+            to_center * grazing.cos() + lateral * grazing.sin()
+        } else {
+            // Dentro del margen: de lado y alejándose
+            (lateral - to_center * 0.5).normalize()
+        };
 
-    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+        let heading = match self.avoidance.as_ref() {
+            Some(avoidance) => avoidance.heading,
+            None => {
+                println!("Asistente de esquiva: rodeando {}", planet.name);
+                direction
+            }
+        };
+        let angle = heading.dot(&target).clamp(-1.0, 1.0).acos();
+        let max_turn = AVOID_TURN_RATE * delta_time;
+        let heading = if angle <= max_turn { target } else { nalgebra_glm::slerp(&heading, &target, max_turn / angle) };
+        self.avoidance = Some(Avoidance { body, heading });
+        heading
+    }
 
-    let transform_matrix = Mat4::new(
-        scale, 0.0,   0.0,   translation.x,
-        0.0,   scale, 0.0,   translation.y,
-        0.0,   0.0,   scale, translation.z,
-        0.0,   0.0,   0.0,   1.0,
-    );
+    /// Entra en órbita circular alrededor de `planets[parent]` a la altitud actual, en el plano
+    /// que forman la dirección al cuerpo y la dirección en la que mira la nave
+    fn enter_orbit(&mut self, parent: usize, planets: &[CelestialBody]) {
+        let planet = &planets[parent];
+        let offset = narrow(self.position - planet.position);
+        let radius = offset.norm().max(planet.scale + SHIP_CLEARANCE);
+        let radial = offset.try_normalize(1e-6).unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+
+        let forward = self.get_forward();
+        let tangent = (forward - radial * forward.dot(&radial))
+            .try_normalize(1e-3)
+            .unwrap_or_else(|| radial.cross(&Vec3::new(0.0, 1.0, 0.0)).try_normalize(1e-6).unwrap_or(Vec3::new(0.0, 0.0, 1.0)));
+
+        // Velocidad angular de una órbita circular: w = sqrt(G·M / r³)
+        let angular_speed = (GRAVITATIONAL_CONSTANT * planet.mass / radius.powi(3)).sqrt();
+
+        self.orbit = Some(ShipOrbit { parent, radius, angle: 0.0, angular_speed, radial, tangent });
+        self.position = planet.position + widen(radial * radius);
+        self.look_at(planet.position);
+    }
+
+    /// Avanza la órbita con el tiempo de simulación y mantiene la vista sobre el cuerpo
+    fn follow_orbit(&mut self, planets: &[CelestialBody], delta_time: f32) {
+        let Some(orbit) = self.orbit.as_mut() else {
+            return;
+        };
+        orbit.angle += orbit.angular_speed * delta_time;
+        let center = planets[orbit.parent].position;
+        let (sin, cos) = orbit.angle.sin_cos();
+        self.position = center + widen((orbit.radial * cos + orbit.tangent * sin) * orbit.radius);
+        self.look_at(center);
+    }
 
-    transform_matrix * rotation_matrix
+    fn look_at(&mut self, target: DVec3) {
+        if let Some(direction) = narrow(target - self.position).try_normalize(1e-6) {
+            self.yaw = direction.z.atan2(direction.x);
+            self.pitch = direction.y.asin().clamp(-PI / 2.5, PI / 2.5);
+        }
+    }
+
+    fn warp_to(&mut self, target: DVec3, offset: f32) {
+        self.orbit = None;
+        let direction = (target - self.position).normalize();
+        self.position = target - direction * offset as f64;
+    }
 }
 
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
+/// Pone el reloj y la nave (en vuelo balístico, mirando al cuerpo de referencia) como al
+/// principio del escenario. Devuelve el cuerpo de referencia.
+fn start_scenario(scenario: &Scenario, planets: &[CelestialBody], clock: &mut SimulationClock, camera: &mut SpaceshipCamera) -> Option<usize> {
+    let body = planets.iter().position(|planet| planet.name == scenario.ship.body)?;
+    clock.time = scenario.time;
+    clock.paused = false;
+    clock.set_time_scale(scenario.time_scale);
+    let center = planets[body].ephemeris().state_at(scenario.time).position;
+    camera.orbit = None;
+    camera.physics = true;
+    camera.position = center + DVec3::from(scenario.ship.offset);
+    camera.velocity = Vec3::from(scenario.ship.velocity);
+    camera.look_at(center);
+    Some(body)
 }
 
-fn create_projection_matrix(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
-    perspective(fov_y, aspect, near, far)
+/// Aceleración de la gravedad de todos los cuerpos en `position`
+fn gravity_at(position: DVec3, planets: &[CelestialBody]) -> Vec3 {
+    let acceleration: DVec3 = planets
+        .iter()
+        .map(|planet| {
+            let offset = planet.position - position;
+            let distance = offset.norm().max(planet.scale as f64);
+            offset * (GRAVITATIONAL_CONSTANT * planet.mass) as f64 / distance.powi(3)
+        })
+        .sum();
+    narrow(acceleration)
 }
 
-fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
-    Mat4::new(
-        width / 2.0, 0.0, 0.0, width / 2.0,
-        0.0, -height / 2.0, 0.0, height / 2.0,
-        0.0, 0.0, 1.0, 0.0,
-        0.0, 0.0, 0.0, 1.0,
-    )
+/// Cámara fija a un punto de la superficie de un cuerpo, que gira con él
+struct SurfaceCamera {
+    body: usize,
+    latitude: f32,
+    longitude: f32,
+    /// Cuerpo al que se mira en vez de al este (el eclipse sigue a la estrella)
+    target: Option<usize>,
 }
 
-fn render_orbit(
-    framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    radius: f32,
-    segments: usize,
-) {
-    let color = 0x444444;
-    framebuffer.set_current_color(color);
+impl SurfaceCamera {
+    fn new(body: usize) -> Self {
+        SurfaceCamera { body, latitude: 0.3, longitude: 0.0, target: None }
+    }
 
-    for i in 0..segments {
-        let angle1 = (i as f32 / segments as f32) * 2.0 * PI;
-        let angle2 = ((i + 1) as f32 / segments as f32) * 2.0 * PI;
+    /// Punto de la superficie en el plano de las órbitas desde el que `target` se ve a
+    /// `elevation` sobre el horizonte en el instante `time` de las efemérides, por el lado en que
+    /// va subiendo. Fuera de ese plano el paralaje aparta la estrella del cuerpo que la tapa.
+    fn facing(body: usize, target: usize, elevation: f32, planets: &[CelestialBody], time: f64) -> Self {
+        let state = planets[body].ephemeris().state_at(time);
+        let toward = narrow(planets[target].ephemeris().state_at(time).position - state.position).normalize();
+        let mut side = toward.cross(&Vec3::new(0.0, 1.0, 0.0)).try_normalize(1e-6).unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+        // Con el giro del cuerpo el punto debe acercarse a la dirección del objetivo
+        if planets[body].rotation_speed.cross(&side).dot(&toward) < 0.0 {
+            side = -side;
+        }
+        let zenith_angle = PI / 2.0 - elevation;
+        let normal = toward * zenith_angle.cos() + side * zenith_angle.sin();
+        // La orientación es una rotación: su traspuesta lleva la normal al espacio del cuerpo
+        let orientation = create_model_matrix(Vec3::zeros(), 1.0, state.rotation);
+        let local = (orientation.transpose() * nalgebra_glm::vec4(normal.x, normal.y, normal.z, 0.0)).xyz();
+        SurfaceCamera {
+            body,
+            latitude: local.y.clamp(-1.0, 1.0).asin().clamp(-PI / 2.0 + 0.05, PI / 2.0 - 0.05),
+            longitude: local.z.atan2(local.x),
+            target: Some(target),
+        }
+    }
 
-        let p1 = nalgebra_glm::vec4(radius * angle1.cos(), 0.0, radius * angle1.sin(), 1.0);
-        let p2 = nalgebra_glm::vec4(radius * angle2.cos(), 0.0, radius * angle2.sin(), 1.0);
+    /// Las flechas mueven el punto de observación por la superficie
+    fn update(&mut self, window: &Window, delta_time: f32) {
+        let step = 0.8 * delta_time;
+        if window.is_key_down(Key::Left) {
+            self.longitude -= step;
+        }
+        if window.is_key_down(Key::Right) {
+            self.longitude += step;
+        }
+        if window.is_key_down(Key::Up) {
+            self.latitude = (self.latitude + step).min(PI / 2.0 - 0.05);
+        }
+        if window.is_key_down(Key::Down) {
+            self.latitude = (self.latitude - step).max(-PI / 2.0 + 0.05);
+        }
+    }
 
-        let vp_matrix = uniforms.viewport_matrix 
-            * uniforms.projection_matrix 
-            * uniforms.view_matrix;
+    /// Ojo, dirección de vista y vector "arriba": se mira hacia el este, un poco por encima del
+    /// horizonte, o hacia `target` si lo hay
+    fn view(&self, planets: &[CelestialBody]) -> (DVec3, Vec3, Vec3) {
+        let planet = &planets[self.body];
+        let (eye, normal, east) = self.frame(planet.position, planet.rotation, planet.scale);
+        let forward = self
+            .target
+            .and_then(|target| narrow(planets[target].position - eye).try_normalize(1e-6))
+            .unwrap_or_else(|| (east + normal * 0.35).normalize());
+        (eye, forward, normal)
+    }
 
-        let screen1 = vp_matrix * p1;
-        let screen2 = vp_matrix * p2;
+    /// Ojo en el instante `time` de las efemérides, sin mover los cuerpos
+    fn eye_at(&self, planets: &[CelestialBody], time: f64) -> DVec3 {
+        let state = planets[self.body].ephemeris().state_at(time);
+        self.frame(state.position, state.rotation, state.scale).0
+    }
 
-        if screen1.w > 0.0 && screen2.w > 0.0 {
-            let x1 = (screen1.x / screen1.w) as usize;
-            let y1 = (screen1.y / screen1.w) as usize;
-            let x2 = (screen2.x / screen2.w) as usize;
-            let y2 = (screen2.y / screen2.w) as usize;
+    /// Ojo, normal y dirección este del punto de observación para el cuerpo en esa posición y giro
+    fn frame(&self, position: DVec3, rotation: Vec3, scale: f32) -> (DVec3, Vec3, Vec3) {
+        let orientation = create_model_matrix(Vec3::zeros(), 1.0, rotation);
+        let rotate = |v: Vec3| (orientation * nalgebra_glm::vec4(v.x, v.y, v.z, 0.0)).xyz();
 
-            if x1 < framebuffer.width && y1 < framebuffer.height {
-                framebuffer.point(x1, y1, 0.0);
-            }
-            if x2 < framebuffer.width && y2 < framebuffer.height {
-                framebuffer.point(x2, y2, 0.0);
-            }
-        }
+        let (sin_lat, cos_lat) = self.latitude.sin_cos();
+        let (sin_lon, cos_lon) = self.longitude.sin_cos();
+        let normal = rotate(Vec3::new(cos_lat * cos_lon, sin_lat, cos_lat * sin_lon));
+        let east = rotate(Vec3::new(-sin_lon, 0.0, cos_lon));
+        (position + widen(normal * scale * 1.03), normal, east)
     }
 }
 
-struct Skybox {
-    stars: Vec<(usize, usize, u32, bool)>,
+/// Duración en segundos reales del recorrido "un día en Terra"
+const DAY_TOUR_SECONDS: f32 = 20.0;
+
+/// Recorrido "un día en Terra" (T): cámara en el ecuador durante una rotación completa, con el
+/// reloj ajustado para que dure `DAY_TOUR_SECONDS`. Al terminar se recupera la escala de tiempo.
+struct DayTour {
+    body: usize,
+    start: f64,
+    end: f64,
+    previous_time_scale: f32,
 }
 
-impl Skybox {
-    fn new(width: usize, height: usize, star_count: usize) -> Self {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        let mut stars = Vec::with_capacity(star_count);
-        
-        for _ in 0..star_count {
-            let x = rng.gen_range(0..width);
-            let y = rng.gen_range(0..height);
-            
-            let star_type = rng.gen_range(0..100);
-            let color = if star_type < 70 {
-                let brightness = rng.gen_range(180..255) as u32;
-                (brightness << 16) | (brightness << 8) | brightness
-            } else if star_type < 85 {
-                let b = rng.gen_range(200..255) as u32;
-                let g = rng.gen_range(180..220) as u32;
-                let r = rng.gen_range(150..200) as u32;
-                (r << 16) | (g << 8) | b
-            } else if star_type < 95 {
-                let r = rng.gen_range(220..255) as u32;
-                let g = rng.gen_range(200..240) as u32;
-                let b = rng.gen_range(100..150) as u32;
-                (r << 16) | (g << 8) | b
-            } else {
-                let r = rng.gen_range(230..255) as u32;
-                let g = rng.gen_range(100..150) as u32;
-                let b = rng.gen_range(80..120) as u32;
-                (r << 16) | (g << 8) | b
-            };
-            
-            let is_bright = rng.gen_range(0..100) < 10 && color > 0xCCCCCC;
-            stars.push((x, y, color, is_bright));
-        }
-        
-        Skybox { stars }
+impl DayTour {
+    /// Empieza en Terra, o en el cuerpo seleccionado si la escena no tiene ninguno con ese nombre
+    fn start(planets: &[CelestialBody], selected: Option<usize>, clock: &mut SimulationClock) -> Option<Self> {
+        let body = planets.iter().position(|planet| planet.name == "Terra").or(selected)?;
+        let period = planets[body].rotation_period()?;
+        let tour = DayTour {
+            body,
+            start: clock.time,
+            end: clock.time + period as f64,
+            previous_time_scale: clock.time_scale,
+        };
+        clock.paused = false;
+        clock.set_time_scale(period / DAY_TOUR_SECONDS);
+        Some(tour)
     }
-    
-    fn render(&self, framebuffer: &mut Framebuffer) {
-        for &(x, y, color, is_bright) in &self.stars {
-            if x < framebuffer.width && y < framebuffer.height {
-                framebuffer.set_current_color(color);
-                framebuffer.point(x, y, f32::INFINITY);
-                
-                if is_bright {
-                    if x > 0 {
-                        framebuffer.point(x - 1, y, f32::INFINITY);
-                    }
-                    if x < framebuffer.width - 1 {
-                        framebuffer.point(x + 1, y, f32::INFINITY);
-                    }
-                    if y > 0 {
-                        framebuffer.point(x, y - 1, f32::INFINITY);
-                    }
-                    if y < framebuffer.height - 1 {
-                        framebuffer.point(x, y + 1, f32::INFINITY);
-                    }
-                }
-            }
-        }
+
+    fn progress(&self, time: f64) -> f32 {
+        ((time - self.start) / (self.end - self.start)).clamp(0.0, 1.0) as f32
+    }
+
+    fn finish(self, clock: &mut SimulationClock) {
+        clock.set_time_scale(self.previous_time_scale);
     }
 }
 
-fn render(
-    framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    light: &Light,
-    planet_type: PlanetShaderType,
-) {
-    let start_time = Instant::now();
-    
-    let max_vertices = 1500;
-    let vertices_to_process = if vertex_array.len() > max_vertices {
-        &vertex_array[..max_vertices]
-    } else {
-        vertex_array
-    };
+/// Segundos reales que duran las fases parciales del eclipse, antes y después de la totalidad
+const ECLIPSE_PARTIAL_SECONDS: f32 = 6.0;
+/// Segundos reales que se intenta que dure la totalidad (la escala de tiempo tiene un mínimo)
+const ECLIPSE_TOTALITY_SECONDS: f32 = 8.0;
+/// Parte de cada fase parcial que se muestra, la más cercana a la totalidad: enteras, con el giro
+/// del observador la estrella saldría por debajo del horizonte o pasaría por el cénit
+const ECLIPSE_PARTIAL_SHOWN: f64 = 0.5;
+/// Segundos simulados hacia delante en los que se busca el eclipse
+const ECLIPSE_HORIZON: f64 = 600.0;
+/// Altura de la estrella sobre el horizonte en mitad de la totalidad
+const ECLIPSE_ELEVATION: f32 = 0.75;
+
+/// Eclipse de sol (F5): lleva el reloj al siguiente eclipse total de la estrella, visto desde
+/// Terra si la escena lo permite o si no desde el primer cuerpo que tenga uno, y deja la cámara en
+/// su superficie mirando a la estrella. El tiempo se frena en la totalidad, cuando se ve la
+/// corona, y al terminar el eclipse se recupera la escala de tiempo.
+struct EclipseTour {
+    eclipse: Eclipse,
+    /// Tramo del eclipse que se muestra (ver `ECLIPSE_PARTIAL_SHOWN`)
+    shown: (f64, f64),
+    previous_time_scale: f32,
+}
 
-    let mut transformed_vertices = Vec::with_capacity(vertices_to_process.len());
-    for vertex in vertices_to_process {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
+impl EclipseTour {
+    fn start(planets: &[CelestialBody], clock: &mut SimulationClock) -> Result<(Self, SurfaceCamera), &'static str> {
+        let star = planets.iter().position(|planet| planet.orbit_radius == 0.0).ok_or("La escena no tiene estrella")?;
+        let terra = planets.iter().position(|planet| planet.name == "Terra");
+        let mut eclipse = events::next_eclipse(planets, star, terra, clock.time, ECLIPSE_HORIZON)
+            .ok_or("Ningún cuerpo llega a tapar la estrella entera en los próximos años")?;
+        // El ojo está en la superficie y no en el centro: el paralaje mueve el eclipse, así que se
+        // recoloca el punto de observación en mitad de la totalidad recalculada unas cuantas veces
+        let mut surface = SurfaceCamera::new(eclipse.observer);
+        for _ in 0..3 {
+            let middle = (eclipse.totality.0 + eclipse.totality.1) * 0.5;
+            surface = SurfaceCamera::facing(eclipse.observer, star, ECLIPSE_ELEVATION, planets, middle);
+            eclipse = events::eclipse_seen_from(&eclipse, planets, star, |time| surface.eye_at(planets, time))
+                .ok_or("Desde la superficie el eclipse no llega a ser total")?;
+        }
+        let (partial, totality) = (eclipse.partial, eclipse.totality);
+        let shown = (
+            totality.0 - (totality.0 - partial.0) * ECLIPSE_PARTIAL_SHOWN,
+            totality.1 + (partial.1 - totality.1) * ECLIPSE_PARTIAL_SHOWN,
+        );
+        let tour = EclipseTour { eclipse, shown, previous_time_scale: clock.time_scale };
+        clock.time = shown.0;
+        clock.paused = false;
+        tour.pace(clock);
+        Ok((tour, surface))
     }
 
-    let mut triangles_vec = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles_vec.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
+    /// Ajusta la escala de tiempo a la fase en la que está el reloj
+    fn pace(&self, clock: &mut SimulationClock) {
+        let totality = self.eclipse.totality;
+        let scale = if (totality.0..totality.1).contains(&clock.time) {
+            (totality.1 - totality.0) as f32 / ECLIPSE_TOTALITY_SECONDS
+        } else {
+            (totality.0 - self.shown.0).max(self.shown.1 - totality.1) as f32 / ECLIPSE_PARTIAL_SECONDS
+        };
+        clock.set_time_scale(scale);
     }
 
-    let mut visible_triangles = Vec::new();
-    for tri in triangles_vec {
-        let avg_z = (tri[0].transformed_position.z + 
-                     tri[1].transformed_position.z + 
-                     tri[2].transformed_position.z) / 3.0;
-        
-        if avg_z > -2000.0 && avg_z < 2000.0 {
-            visible_triangles.push(tri);
-        }
+    fn finished(&self, time: f64) -> bool {
+        time >= self.shown.1
     }
 
-    let max_triangles = 500;
-    let triangles_to_process = visible_triangles.len().min(max_triangles);
+    fn finish(self, clock: &mut SimulationClock) {
+        clock.set_time_scale(self.previous_time_scale);
+    }
+}
 
-    let mut fragments = Vec::new();
-    let max_fragments = 15000;
-    
-    for tri in &visible_triangles[..triangles_to_process] {
-        if fragments.len() >= max_fragments {
-            break;
-        }
-        
-        let tri_fragments = triangle(&tri[0], &tri[1], &tri[2], light);
-        
-        let space_left = max_fragments - fragments.len();
-        if tri_fragments.len() <= space_left {
-            fragments.extend(tri_fragments);
-        } else {
-            fragments.extend(tri_fragments.into_iter().take(space_left));
-            break;
+/// Posiciones de mundo (f64) a f32. Solo debe aplicarse a diferencias pequeñas, como
+/// la posición de un cuerpo relativa a la cámara, para no perder la precisión del f64.
+fn narrow(v: DVec3) -> Vec3 {
+    v.cast()
+}
+
+fn widen(v: Vec3) -> DVec3 {
+    v.cast()
+}
+
+/// Cámara + región de pantalla para una pasada de render de la escena.
+///
+/// Las mallas se dibujan relativas a la cámara: `origin` (el ojo, en f64) se resta antes de
+/// pasar a f32, así los vértices cerca de la cámara conservan toda la precisión aunque el
+/// cuerpo esté muy lejos del sol. Las líneas y marcas (órbitas, baricentro, ruta) siguen en
+/// coordenadas de mundo con `view_matrix`: a un píxel de grosor el error no se aprecia.
+struct RenderView {
+    origin: DVec3,
+    view_matrix: Mat4,
+    relative_view_matrix: Mat4,
+    projection_matrix: Mat4,
+    viewport_matrix: Mat4,
+    camera_position: Vec3,
+    time: f32,
+    light_position: DVec3,
+    depth_mode: DepthMode,
+    /// Estilo cómic: luz en bandas, contornos y órbitas de colores planos (ver `toon`)
+    toon: bool,
+    /// Modo alambre (F1): solo las aristas de las mallas
+    wireframe: bool,
+    /// Lado de las celdas de la cuadrícula de la eclíptica (F2), si se dibuja
+    ecliptic_grid: Option<f32>,
+}
+
+impl RenderView {
+    /// `rect` es (x, y, ancho, alto) en píxeles del framebuffer
+    fn new(eye: DVec3, target: DVec3, up: Vec3, far: f32, rect: (f32, f32, f32, f32), time: f32, light_position: DVec3) -> Self {
+        let (x, y, width, height) = rect;
+        RenderView {
+            origin: eye,
+            view_matrix: create_view_matrix(narrow(eye), narrow(target), up),
+            relative_view_matrix: create_view_matrix(Vec3::zeros(), narrow(target - eye), up),
+            projection_matrix: create_projection_matrix(PI / 3.0, width / height, 0.1, far),
+            viewport_matrix: create_viewport_matrix(x, y, width, height),
+            camera_position: narrow(eye),
+            time,
+            light_position,
+            depth_mode: DepthMode::Perspective,
+            toon: false,
+            wireframe: false,
+            ecliptic_grid: None,
         }
     }
 
-    const BATCH_SIZE: usize = 1000;
-    for batch_start in (0..fragments.len()).step_by(BATCH_SIZE) {
-        let batch_end = (batch_start + BATCH_SIZE).min(fragments.len());
-        
-        for fragment in &mut fragments[batch_start..batch_end] {
-            fragment.color = fragment_shader(fragment, uniforms, planet_type);
-            
-            let x = fragment.position.x as usize;
-            let y = fragment.position.y as usize;
-            
-            if x < framebuffer.width && y < framebuffer.height {
-                let r = (fragment.color.x.clamp(0.0, 1.0) * 255.0) as u32;
-                let g = (fragment.color.y.clamp(0.0, 1.0) * 255.0) as u32;
-                let b = (fragment.color.z.clamp(0.0, 1.0) * 255.0) as u32;
-                let color = (r << 16) | (g << 8) | b;
-                framebuffer.set_current_color(color);
-                framebuffer.point(x, y, fragment.depth);
-            }
-        }
-        
-        if start_time.elapsed().as_millis() > 50 {
-            break;
+    /// Activa el z-buffer logarítmico hasta el plano lejano de la vista
+    fn with_logarithmic_depth(mut self, enabled: bool) -> Self {
+        if enabled {
+            let far = self.projection_matrix[(2, 3)] / (self.projection_matrix[(2, 2)] + 1.0);
+            self.depth_mode = DepthMode::Logarithmic { far };
         }
+        self
     }
-}
 
-fn main() {
-    println!("=== Sistema Solar Ultra-Optimizado v3 ===");
-    
-    let window_width = 1200;
-    let window_height = 800;
-    let framebuffer_width = 800;
-    let framebuffer_height = 600;
-    let frame_delay = Duration::from_millis(16);
+    fn with_toon(mut self, enabled: bool) -> Self {
+        self.toon = enabled;
+        self
+    }
 
-    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-    
-    let mut window = Window::new(
-        "Sistema Solar - WASD Space/Shift Flechas, F=warp, ESC=salir",
-        window_width,
-        window_height,
-        WindowOptions::default(),
-    ).unwrap();
+    fn with_wireframe(mut self, enabled: bool) -> Self {
+        self.wireframe = enabled;
+        self
+    }
 
-    window.set_position(100, 100);
-    window.limit_update_rate(Some(Duration::from_micros(16600)));
-    framebuffer.set_background_color(0x000011);
+    fn with_ecliptic_grid(mut self, spacing: Option<f32>) -> Self {
+        self.ecliptic_grid = spacing;
+        self
+    }
 
-    let sphere_obj = Obj::load("assets/models/sphere1.obj").unwrap();
-    let sphere_vertices = sphere_obj.get_vertex_array();
-
-    let ywing_obj = Obj::load("assets/models/Y-wing.obj").unwrap();
-    let ywing_vertices = simplify_mesh(&ywing_obj.get_vertex_array(), 80);
-
-    let mut planets = vec![
-        CelestialBody::new("Sol", 0.0, 0.0, 25.0, Vec3::new(0.0, 0.1, 0.0), 
-            PlanetShaderType::Solarius, sphere_vertices.clone()),
-        CelestialBody::new("Terra", 150.0, 0.3, 15.0, Vec3::new(0.0, 0.5, 0.0), 
-            PlanetShaderType::Terra, sphere_vertices.clone()),
-        CelestialBody::new("Vulcan", 250.0, 0.2, 14.0, Vec3::new(0.0, 0.4, 0.0), 
-            PlanetShaderType::Vulcan, sphere_vertices.clone()),
-        CelestialBody::new("Nepturion", 400.0, 0.15, 22.0, Vec3::new(0.1, 0.3, 0.0), 
-            PlanetShaderType::Nepturion, sphere_vertices.clone()),
-        CelestialBody::new("Mossar", 550.0, 0.1, 18.0, Vec3::new(0.0, 0.35, 0.1), 
-            PlanetShaderType::Mossar, sphere_vertices.clone()),
-    ];
-
-    let mut camera = SpaceshipCamera::new(Vec3::new(0.0, 100.0, 300.0));
-    let mut light = Light::new(Vector3::new(0.0, 0.0, 0.0));
-    let skybox = Skybox::new(framebuffer_width, framebuffer_height, 200);
+    /// Cambia el campo de visión vertical (el de `new` es de 60°), con el mismo aspecto y planos
+    fn with_field_of_view(mut self, fov_y: f32) -> Self {
+        let aspect = self.projection_matrix[(1, 1)] / self.projection_matrix[(0, 0)];
+        let far = self.projection_matrix[(2, 3)] / (self.projection_matrix[(2, 2)] + 1.0);
+        self.projection_matrix = create_projection_matrix(fov_y, aspect, 0.1, far);
+        self
+    }
 
-    let aspect_ratio = framebuffer_width as f32 / framebuffer_height as f32;
-    let start_time = Instant::now();
-    let mut last_frame = Instant::now();
-    let mut warp_planet_index = 0;
-    let mut frame_count = 0;
-    let mut fps_timer = Instant::now();
-    let mut fps_counter = 0;
+    /// Posición de mundo relativa al ojo, ya en f32
+    fn relative(&self, position: DVec3) -> Vec3 {
+        narrow(position - self.origin)
+    }
 
-    println!("=== Iniciando renderizado ===\n");
+    /// Rayo de mundo (origen, dirección) que pasa por el píxel (x, y) del framebuffer
+    fn picking_ray(&self, x: f32, y: f32) -> Option<(DVec3, Vec3)> {
+        let inverse = (self.viewport_matrix * self.projection_matrix * self.view_matrix).try_inverse()?;
+        let unproject = |z: f32| {
+            let point = inverse * nalgebra_glm::vec4(x, y, z, 1.0);
+            point.xyz() / point.w
+        };
+        let direction = (unproject(1.0) - unproject(-1.0)).try_normalize(1e-6)?;
+        Some((self.origin, direction))
+    }
 
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        let current_time = Instant::now();
-        let delta_time = (current_time - last_frame).as_secs_f32();
-        last_frame = current_time;
-        let elapsed = start_time.elapsed().as_secs_f32();
+    /// Uniforms en coordenadas de mundo, para las líneas y marcas de la escena
+    fn uniforms(&self, model_matrix: Mat4) -> Uniforms {
+        Uniforms {
+            model_matrix,
+            normal_matrix: create_normal_matrix(&model_matrix),
+            inverse_model_matrix: model_matrix.try_inverse(),
+            view_matrix: self.view_matrix,
+            projection_matrix: self.projection_matrix,
+            viewport_matrix: self.viewport_matrix,
+            time: self.time,
+            camera_position: self.camera_position,
+            light_position: narrow(self.light_position),
+            depth_mode: self.depth_mode,
+            culling: Culling::default(),
+        }
+    }
 
-        fps_counter += 1;
-        if fps_timer.elapsed().as_secs() >= 1 {
-            println!("FPS: {}", fps_counter);
-            fps_counter = 0;
-            fps_timer = Instant::now();
+    /// Uniforms de una malla en `position`, con la cámara en el origen
+    fn relative_uniforms(&self, position: DVec3, scale: f32, rotation: Vec3) -> Uniforms {
+        let model_matrix = create_model_matrix(self.relative(position), scale, rotation);
+        Uniforms {
+            model_matrix,
+            normal_matrix: create_normal_matrix(&model_matrix),
+            inverse_model_matrix: model_matrix.try_inverse(),
+            view_matrix: self.relative_view_matrix,
+            projection_matrix: self.projection_matrix,
+            viewport_matrix: self.viewport_matrix,
+            time: self.time,
+            camera_position: Vec3::zeros(),
+            light_position: self.relative(self.light_position),
+            depth_mode: self.depth_mode,
+            culling: Culling::default(),
         }
+    }
+}
 
-        camera.update(&window, delta_time, &planets);
+/// Longitud máxima en pantalla de un tramo de órbita antes de subdividirlo
+const MAX_ORBIT_SEGMENT_PIXELS: f32 = 24.0;
+const MAX_ORBIT_SUBDIVISION: u32 = 4;
+
+/// Comprueba si una esfera toca el frustum de la vista (planos extraídos de proyección * vista)
+fn sphere_in_frustum(uniforms: &Uniforms, center: Vec3, radius: f32) -> bool {
+    let m = uniforms.projection_matrix * uniforms.view_matrix;
+    let row = |i: usize| nalgebra_glm::vec4(m[(i, 0)], m[(i, 1)], m[(i, 2)], m[(i, 3)]);
+    let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+    let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2];
+
+    planes.iter().all(|plane| {
+        let normal = plane.xyz();
+        let length = normal.norm();
+        length <= 0.0 || (normal.dot(&center) + plane.w) / length >= -radius
+    })
+}
 
-        if window.is_key_pressed(Key::F, minifb::KeyRepeat::No) {
-            warp_planet_index = (warp_planet_index + 1) % planets.len();
-            camera.warp_to(planets[warp_planet_index].position, 100.0);
-        }
+fn draw_screen_line(framebuffer: &mut Framebuffer, start: Vector3, end: Vector3) {
+    let mut a = Vertex::default();
+    let mut b = Vertex::default();
+    a.transformed_position = start;
+    b.transformed_position = end;
 
-        for planet in &mut planets {
-            planet.update(delta_time);
-        }
+    for fragment in line(&a, &b) {
+        framebuffer.point(fragment.position.x as usize, fragment.position.y as usize, fragment.depth);
+    }
+}
 
-        light.position = Vector3::new(
-            planets[0].position.x,
-            planets[0].position.y,
-            planets[0].position.z,
-        );
+/// Color de las órbitas fuera del estilo cómic
+const ORBIT_COLOR: u32 = 0x444444;
 
-        framebuffer.clear();
-        skybox.render(&mut framebuffer);
+fn render_orbit(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    radius: f32,
+    segments: usize,
+    color: u32,
+) {
+    if !sphere_in_frustum(uniforms, Vec3::zeros(), radius) {
+        return;
+    }
 
-        let camera_target = camera.position + camera.get_forward() * 10.0;
-        let view_matrix = create_view_matrix(camera.position, camera_target, camera.get_up());
-        let projection_matrix = create_projection_matrix(PI / 3.0, aspect_ratio, 0.1, 2000.0);
-        let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
+    framebuffer.set_current_color(color);
 
-        for planet in &planets {
-            if planet.orbit_radius > 0.0 {
-                let orbit_uniforms = Uniforms {
-                    model_matrix: Mat4::identity(),
-                    view_matrix,
-                    projection_matrix,
-                    viewport_matrix,
-                    time: elapsed,
-                };
-                render_orbit(&mut framebuffer, &orbit_uniforms, planet.orbit_radius, 32);
-            }
-        }
+    for i in 0..segments {
+        let angle1 = (i as f32 / segments as f32) * 2.0 * PI;
+        let angle2 = ((i + 1) as f32 / segments as f32) * 2.0 * PI;
+        render_orbit_arc(framebuffer, uniforms, radius, angle1, angle2, 0);
+    }
+}
 
-        for planet in planets.iter() {
-            let model_matrix = create_model_matrix(planet.position, planet.scale, planet.rotation);
-            let uniforms = Uniforms {
-                model_matrix,
-                view_matrix,
-                projection_matrix,
-                viewport_matrix,
-                time: elapsed,
-            };
-            render(&mut framebuffer, &uniforms, &planet.vertex_array, &light, planet.shader_type);
-        }
+/// Dibuja el arco entre dos ángulos; solo se subdivide si es visible y largo en pantalla
+fn render_orbit_arc(framebuffer: &mut Framebuffer, uniforms: &Uniforms, radius: f32, from: f32, to: f32, depth: u32) {
+    let point = |angle: f32| Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin());
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let Some((start, end)) = project_segment(uniforms, width, height, point(from), point(to)) else {
+        return;
+    };
 
-        let ship_offset = camera.get_forward() * 15.0 + camera.get_right() * -3.0 + camera.get_up() * -2.0;
-        let ship_position = camera.position + ship_offset;
-        let ship_rotation = Vec3::new(-camera.pitch, camera.yaw + PI, 0.0);
-        let ship_model = create_model_matrix(ship_position, 2.5, ship_rotation);
-        
-        let ship_uniforms = Uniforms {
-            model_matrix: ship_model,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time: elapsed,
-        };
-        
-        render(&mut framebuffer, &ship_uniforms, &ywing_vertices, &light, PlanetShaderType::Terra);
+    let length = ((end.x - start.x).powi(2) + (end.y - start.y).powi(2)).sqrt();
+    if length > MAX_ORBIT_SEGMENT_PIXELS && depth < MAX_ORBIT_SUBDIVISION {
+        let middle = (from + to) * 0.5;
+        render_orbit_arc(framebuffer, uniforms, radius, from, middle, depth + 1);
+        render_orbit_arc(framebuffer, uniforms, radius, middle, to, depth + 1);
+        return;
+    }
 
-        window.update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height).ok();
+    draw_screen_line(framebuffer, start, end);
+}
 
-        std::thread::sleep(frame_delay);
-        frame_count += 1;
+fn compute_barycenter(bodies: &[CelestialBody]) -> DVec3 {
+    let total_mass: f64 = bodies.iter().map(|body| body.mass as f64).sum();
+    if total_mass <= 0.0 {
+        return DVec3::zeros();
+    }
+
+    let weighted = bodies
+        .iter()
+        .fold(DVec3::zeros(), |acc, body| acc + body.position * body.mass as f64);
+    weighted / total_mass
+}
+
+fn render_line_3d(framebuffer: &mut Framebuffer, uniforms: &Uniforms, from: Vec3, to: Vec3, color: u32) {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let Some((start, end)) = project_segment(uniforms, width, height, from, to) else {
+        return;
+    };
+
+    framebuffer.set_current_color(color);
+    draw_screen_line(framebuffer, start, end);
+}
+
+/// Segundos de tiempo real que abarcan las trayectorias previstas (X)
+const PREDICTION_SECONDS: f32 = 20.0;
+
+/// Trayectoria prevista que se desvanece hacia el final
+fn render_predicted_path(framebuffer: &mut Framebuffer, uniforms: &Uniforms, path: &[DVec3], color: u32) {
+    let segments = path.len().saturating_sub(1).max(1) as f32;
+    for (index, pair) in path.windows(2).enumerate() {
+        let fade = 1.0 - index as f32 / segments;
+        let faded = [16, 8, 0].iter().fold(0, |packed, &shift| {
+            packed | ((((color >> shift) & 0xFF) as f32 * fade) as u32) << shift
+        });
+        render_line_3d(framebuffer, uniforms, narrow(pair[0]), narrow(pair[1]), faded);
+    }
+}
+
+fn render_barycenter(framebuffer: &mut Framebuffer, uniforms: &Uniforms, bodies: &[CelestialBody]) {
+    let barycenter = narrow(compute_barycenter(bodies));
+
+    for body in bodies {
+        render_line_3d(framebuffer, uniforms, narrow(body.position), barycenter, 0x665500);
+    }
+
+    if let Some(center) = project_to_screen(uniforms, barycenter) {
+        let cx = center.x as i32;
+        let cy = center.y as i32;
+        framebuffer.set_current_color(0xFFAA00);
+        for offset in -4..=4 {
+            for (x, y) in [(cx + offset, cy), (cx, cy + offset)] {
+                if x >= 0 && y >= 0 {
+                    framebuffer.point(x as usize, y as usize, -1.0);
+                }
+            }
+        }
+    }
+}
+
+/// Marca el cuerpo seleccionado con cuatro esquinas alrededor de su silueta
+fn render_selection(framebuffer: &mut Framebuffer, uniforms: &Uniforms, body: &CelestialBody) {
+    let position = narrow(body.position);
+    let Some(center) = project_to_screen(uniforms, position) else {
+        return;
+    };
+    // La primera fila de la matriz de vista es el vector "derecha" de la cámara
+    let view = &uniforms.view_matrix;
+    let right = Vec3::new(view[(0, 0)], view[(0, 1)], view[(0, 2)]);
+    let Some(edge) = project_to_screen(uniforms, position + right * body.scale) else {
+        return;
+    };
+
+    let radius = ((edge.x - center.x).abs() * 1.3).max(6.0) as i32;
+    let arm = (radius / 3).max(3);
+    let (cx, cy) = (center.x as i32, center.y as i32);
+
+    framebuffer.set_current_color(0x00FFCC);
+    for (sx, sy) in [(-1, -1), (1, -1), (-1, 1), (1, 1)] {
+        let (corner_x, corner_y) = (cx + sx * radius, cy + sy * radius);
+        for step in 0..arm {
+            for (x, y) in [(corner_x - sx * step, corner_y), (corner_x, corner_y - sy * step)] {
+                if x >= 0 && y >= 0 {
+                    framebuffer.point(x as usize, y as usize, -1.0);
+                }
+            }
+        }
+    }
+}
+
+/// Aplica una escena recargada: los cuerpos que conservan el nombre se actualizan en su sitio,
+/// los nuevos se crean y los que ya no están se eliminan
+/// La esfera, con relieve si se pide (ver `mesh::displace`)
+fn displaced_sphere(sphere: &Obj, displacement: Option<&DisplacementConfig>, seed: u32) -> Obj {
+    let mut model = sphere.clone();
+    if let Some(displacement) = displacement {
+        mesh::displace(&mut model, displacement, seed);
+    }
+    model
+}
+
+fn mesh_radius(vertices: &[Vertex]) -> f32 {
+    vertices.iter().map(|vertex| vertex.position.length()).fold(0.0, f32::max)
+}
+
+fn body_mesh(config: &BodyConfig, sphere: &Obj) -> IndexedMesh {
+    displaced_sphere(sphere, config.displacement.as_ref(), config.shader_params().seed).get_indexed_mesh()
+}
+
+/// Triángulos de la malla simplificada de las rocas cercanas del cinturón
+const ROCK_TRIANGLES: usize = 40;
+
+/// Esfera con el relieve de las rocas del cinturón, antes de simplificarla a `ROCK_TRIANGLES`
+fn belt_rock_mesh(belt: Option<&BeltConfig>, sphere: &Obj) -> Vec<Vertex> {
+    let displacement = belt.and_then(|belt| belt.displacement.as_ref());
+    let seed = belt.map_or(0, |belt| belt.seed as u32);
+    displaced_sphere(sphere, displacement, seed).get_vertex_array()
+}
+
+fn reload_bodies(planets: &mut Vec<CelestialBody>, scene: &SceneFile, sphere: &Obj, time: f64) {
+    let mut previous = std::mem::take(planets);
+    for config in &scene.bodies {
+        let body = match previous.iter().position(|planet| planet.name == config.name) {
+            Some(index) => {
+                let mut body = previous.swap_remove(index);
+                body.apply_config(config, time);
+                body.mesh = body_mesh(config, sphere);
+                body.mesh_radius = mesh_radius(&body.mesh.vertices);
+                body
+            }
+            None => {
+                let mut body = CelestialBody::from_config(config, body_mesh(config, sphere));
+                let state = body.ephemeris().state_at(time);
+                body.apply_orbital_state(&state);
+                body
+            }
+        };
+        planets.push(body);
+    }
+}
+
+/// Plano lejano y velocidad de la nave para un sistema de `REFERENCE_SYSTEM_RADIUS`; los
+/// sistemas más grandes (p. ej. en escala semirrealista) los aumentan en proporción
+const BASE_FAR_PLANE: f32 = 2000.0;
+const BASE_SHIP_SPEED: f32 = 50.0;
+const REFERENCE_SYSTEM_RADIUS: f32 = 600.0;
+
+/// Órbita más lejana más el tamaño de su cuerpo
+fn system_radius(planets: &[CelestialBody]) -> f32 {
+    planets.iter().map(|p| p.orbit_radius + p.scale).fold(0.0, f32::max)
+}
+
+fn far_plane(planets: &[CelestialBody]) -> f32 {
+    (system_radius(planets) * 3.0).max(BASE_FAR_PLANE)
+}
+
+fn ship_speed(planets: &[CelestialBody]) -> f32 {
+    BASE_SHIP_SPEED * (system_radius(planets) / REFERENCE_SYSTEM_RADIUS).max(1.0)
+}
+
+/// Unidad astronómica de la escena: el radio de la órbita de Terra, si la hay
+fn astronomical_unit(planets: &[CelestialBody]) -> Option<f32> {
+    planets.iter().find(|planet| planet.name == "Terra").map(|planet| planet.orbit_radius)
+}
+
+/// Recupera el reloj, la nave, la selección y los cuerpos ocultos de un autoguardado. Los
+/// cuerpos que ya no están en la escena se ignoran.
+fn restore_autosave(
+    save: &Autosave,
+    clock: &mut SimulationClock,
+    camera: &mut SpaceshipCamera,
+    planets: &mut [CelestialBody],
+    selected_body: &mut Option<usize>,
+) {
+    clock.time = save.time.max(0.0);
+    clock.set_time_scale(save.time_scale);
+    clock.paused = save.paused;
+    for planet in planets.iter_mut() {
+        let state = planet.ephemeris().state_at(clock.time);
+        planet.apply_orbital_state(&state);
+        planet.hidden = save.hidden_bodies.contains(&planet.name);
+    }
+    camera.position = DVec3::from(save.ship_position);
+    camera.yaw = save.ship_yaw;
+    camera.pitch = save.ship_pitch;
+    *selected_body = save.selected_body.as_ref().and_then(|name| planets.iter().position(|planet| &planet.name == name));
+}
+
+/// Escena del archivo con la escala pedida por línea de comandos, o la del propio archivo
+fn load_scene(path: &str, scale_override: Option<ScaleMode>) -> Result<SceneFile, scene::SceneError> {
+    let mut scene = SceneFile::load(path)?;
+    scene.apply_scale_mode(scale_override.unwrap_or(scene.scale_mode));
+    Ok(scene)
+}
+
+/// Carga una escena para cambiar a ella; una escena sin cuerpos no sirve
+fn open_scene(path: &str, scale_override: Option<ScaleMode>) -> Result<SceneFile, String> {
+    let scene = load_scene(path, scale_override).map_err(|err| format!("{}: {}", path, err))?;
+    if scene.bodies.is_empty() {
+        return Err(format!("{} no tiene cuerpos", path));
+    }
+    Ok(scene)
+}
+
+/// Distancia al destino a la que llega el warp
+const WARP_ARRIVAL_DISTANCE: f32 = 100.0;
+
+/// Ruta prevista desde `start` hasta el punto de llegada del warp junto a `target`: un arco
+/// alrededor del sol que interpola radio y ángulo en el plano orbital, como un gran círculo
+fn route_preview(start: Vec3, target: Vec3, segments: usize) -> Vec<Vec3> {
+    let arrival = target - (target - start).try_normalize(1e-6).unwrap_or(Vec3::zeros()) * WARP_ARRIVAL_DISTANCE;
+    let polar = |p: Vec3| (p.x.hypot(p.z), p.z.atan2(p.x));
+    let (start_radius, start_angle) = polar(start);
+    let (end_radius, end_angle) = polar(arrival);
+
+    // Sentido más corto alrededor del sol
+    let mut sweep = end_angle - start_angle;
+    if sweep > PI {
+        sweep -= 2.0 * PI;
+    } else if sweep < -PI {
+        sweep += 2.0 * PI;
+    }
+
+    (0..=segments)
+        .map(|i| {
+            let t = i as f32 / segments as f32;
+            let radius = start_radius + (end_radius - start_radius) * t;
+            let angle = start_angle + sweep * t;
+            let height = start.y + (arrival.y - start.y) * t;
+            Vec3::new(radius * angle.cos(), height, radius * angle.sin())
+        })
+        .collect()
+}
+
+fn route_length(points: &[Vec3]) -> f32 {
+    points.windows(2).map(|pair| (pair[1] - pair[0]).norm()).sum()
+}
+
+/// Línea de estado con los cuerpos ocultos o el cuerpo en modo solo
+fn visibility_status(planets: &[CelestialBody], solo: bool) -> Option<String> {
+    if solo {
+        let body = planets.iter().find(|planet| !planet.hidden)?;
+        return Some(format!("Solo: {}", body.name));
+    }
+    let hidden: Vec<&str> = planets
+        .iter()
+        .filter(|planet| planet.hidden)
+        .map(|planet| planet.name.as_str())
+        .collect();
+    (!hidden.is_empty()).then(|| format!("Ocultos: {}", hidden.join(", ")))
+}
+
+/// Objeto visible más cercano (cuerpo o roca) cuya esfera corta el rayo, con la distancia desde el
+/// origen a su superficie
+fn pick_object(
+    objects: &Bvh<SceneObject>,
+    planets: &[CelestialBody],
+    origin: DVec3,
+    direction: Vec3,
+) -> Option<(SceneObject, f32)> {
+    let visible = |object| !matches!(object, SceneObject::Body(index) if planets[index].hidden);
+    objects.ray_cast(origin, widen(direction), visible).map(|(object, distance)| (object, distance as f32))
+}
+
+/// Filas del inspector (F3): primero los cuerpos, que son los que se pueden seleccionar, y
+/// después el cinturón y la nave
+fn entity_rows(planets: &[CelestialBody], belt: Option<&AsteroidBelt>, ship_position: DVec3, stats: &RenderStats) -> Vec<EntityRow> {
+    let mut rows: Vec<EntityRow> = planets
+        .iter()
+        .enumerate()
+        .map(|(index, planet)| EntityRow {
+            name: if planet.hidden { format!("{} (oculto)", planet.name) } else { planet.name.clone() },
+            position: Some(planet.position),
+            orbit_angle: (planet.orbit_radius > 0.0).then(|| planet.orbit_angle.to_degrees().rem_euclid(360.0)),
+            lod: if stats.body_culled.get(index).copied().unwrap_or(false) { "fuera de vista" } else { "LOD 0" }.to_string(),
+            triangles: stats.body_triangles.get(index).copied().unwrap_or(0),
+        })
+        .collect();
+    if let Some(belt) = belt {
+        rows.push(EntityRow {
+            name: format!("Cinturón ({} rocas)", belt.count()),
+            position: None,
+            orbit_angle: None,
+            lod: format!("LOD 1 en {}, el resto impostores", stats.rock_meshes),
+            triangles: stats.rock_triangles,
+        });
+    }
+    rows.push(EntityRow {
+        name: "Nave".to_string(),
+        position: Some(ship_position),
+        orbit_angle: None,
+        lod: "LOD 1".to_string(),
+        triangles: stats.ship_triangles,
+    });
+    rows
+}
+
+/// Lo que pueden devolver las consultas espaciales de la escena
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum SceneObject {
+    /// Índice en `planets`
+    Body(usize),
+    /// Índice de la roca en el cinturón
+    Asteroid(usize),
+}
+
+/// Jerarquía de esferas con los cuerpos y las rocas del cinturón en `time`, para la selección
+/// con el ratón y los choques de la nave
+fn scene_bvh(planets: &[CelestialBody], belt: Option<&AsteroidBelt>, time: f32) -> Bvh<SceneObject> {
+    let bodies = planets
+        .iter()
+        .enumerate()
+        .map(|(index, planet)| (planet.position, planet.scale as f64, SceneObject::Body(index)));
+    let rocks = belt
+        .into_iter()
+        .flat_map(|belt| belt.colliders(time))
+        .enumerate()
+        .map(|(index, (position, size))| (widen(position), size as f64, SceneObject::Asteroid(index)));
+    Bvh::build(bodies.chain(rocks))
+}
+
+/// Cuerpo cuya gravedad domina en `position`: el de mayor M / r²
+fn dominant_body(planets: &[CelestialBody], position: DVec3) -> Option<usize> {
+    let pull = |planet: &CelestialBody| planet.mass as f64 / (planet.position - position).norm_squared().max(1e-6);
+    (0..planets.len()).max_by(|&a, &b| pull(&planets[a]).total_cmp(&pull(&planets[b])))
+}
+
+/// Elementos orbitales de la nave respecto a `planets[index]`. En órbita la velocidad es la
+/// del círculo que sigue la nave; en vuelo libre, su velocidad menos la del cuerpo, que avanza a
+/// `time_scale` segundos simulados por segundo real.
+fn ship_orbital_elements(camera: &SpaceshipCamera, planets: &[CelestialBody], index: usize, time_scale: f32) -> Option<OrbitalElements> {
+    let body = &planets[index];
+    let velocity = match camera.orbit.as_ref() {
+        Some(orbit) if orbit.parent == index => orbit.relative_velocity(),
+        _ => camera.velocity - body.velocity() * time_scale,
+    };
+    let position = narrow(camera.position - body.position);
+    OrbitalElements::from_state(position, velocity, GRAVITATIONAL_CONSTANT * body.mass, body.scale)
+}
+
+/// Recuadro de texto junto al cursor, desplazado para no salirse de la pantalla
+fn render_tooltip(framebuffer: &mut Framebuffer, theme: &hud::Theme, cursor: (f32, f32), text: &str) {
+    let (width, height) = theme.text_panel_size(text, theme.px(3));
+    let offset = theme.px(12);
+
+    let mut x = cursor.0 as i32 + offset;
+    let mut y = cursor.1 as i32 + offset;
+    if x + width as i32 > framebuffer.width as i32 {
+        x = cursor.0 as i32 - offset - width as i32;
+    }
+    if y + height as i32 > framebuffer.height as i32 {
+        y = cursor.1 as i32 - offset - height as i32;
+    }
+
+    theme.text_panel(framebuffer, (x, y), text, (0xDDEEFF, 0x0A1018, Some(0x223344)), 3);
+}
+
+/// Segundos que permanece en pantalla el aviso de una conjunción
+const NOTIFICATION_SECONDS: f32 = 8.0;
+/// Separación angular por debajo de la cual dos cuerpos se consideran alineados
+const CONJUNCTION_DEGREES: f32 = 2.0;
+
+fn conjunction_text(conjunction: &Conjunction, planets: &[CelestialBody]) -> String {
+    let vantage = match conjunction.vantage {
+        Vantage::Sun => "desde el sol",
+        Vantage::Ship => "desde la nave",
+    };
+    format!(
+        "Conjunción: {} y {} a {:.1} grados {} (J=ver)",
+        planets[conjunction.first].name,
+        planets[conjunction.second].name,
+        conjunction.separation.to_degrees(),
+        vantage
+    )
+}
+
+/// Coloca la cámara para ver la alineación: junto al sol mirando a lo largo de la línea
+/// de los dos cuerpos, o desde la nave girando hacia ellos
+fn view_conjunction(camera: &mut SpaceshipCamera, planets: &[CelestialBody], conjunction: &Conjunction) {
+    let (first, second) = (&planets[conjunction.first], &planets[conjunction.second]);
+    let target = (first.position + second.position) * 0.5;
+    if conjunction.vantage == Vantage::Sun {
+        let sun = planets.iter().find(|planet| planet.orbit_radius == 0.0);
+        let (sun_position, sun_scale) = sun.map_or((DVec3::zeros(), 0.0), |sun| (sun.position, sun.scale));
+        let direction = narrow(target - sun_position).try_normalize(1e-6).unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+        camera.orbit = None;
+        camera.velocity = Vec3::zeros();
+        camera.position = sun_position + widen(direction * (sun_scale + SHIP_CLEARANCE * 2.0) + Vec3::new(0.0, sun_scale * 0.5, 0.0));
+    }
+    camera.look_at(target);
+}
+
+/// Aviso centrado en la parte superior de la pantalla
+fn render_notification(framebuffer: &mut Framebuffer, theme: &hud::Theme, text: &str) {
+    let (width, _) = theme.text_panel_size(text, theme.px(4));
+    let x = (framebuffer.width as i32 - width as i32) / 2;
+    theme.text_panel(framebuffer, (x, theme.px(34)), text, (0xFFDD88, 0x1A140A, Some(0x665522)), 4);
+}
+
+/// Indicaciones del paso actual del escenario, abajo y centradas
+fn render_scenario_prompt(framebuffer: &mut Framebuffer, theme: &hud::Theme, text: &str) {
+    let (width, height) = theme.text_panel_size(text, theme.px(6));
+    let x = (framebuffer.width as i32 - width as i32) / 2;
+    let y = framebuffer.height as i32 - height as i32 - theme.px(16);
+    theme.text_panel(framebuffer, (x, y), text, (0xE8F4FF, 0x0A1420, Some(0x3A6A8A)), 6);
+}
+
+struct ShipModel<'a> {
+    mesh: &'a IndexedMesh,
+    /// Radio de la malla sin escalar (ver `mesh_radius`)
+    radius: f32,
+    position: DVec3,
+    scale: f32,
+    rotation: Vec3,
+    shader_params: &'a ShaderParams,
+    maps: SurfaceMaps<'a>,
+    /// Caras que se dibujan, según el sentido detectado al cargar el modelo
+    culling: Culling,
+}
+
+/// Mallas de la escena que no son cuerpos celestes
+struct SceneModels<'a> {
+    ship: ShipModel<'a>,
+    belt: Option<BeltModel<'a>>,
+    /// Culling de los cuerpos, el de la esfera de la que salen sus mallas
+    body_culling: Culling,
+    /// Culling de todas las mallas (`--cull`) en lugar del de cada una
+    culling: Option<Culling>,
+}
+
+/// Cinturón de asteroides con la malla y los parámetros usados para las rocas cercanas
+struct BeltModel<'a> {
+    belt: &'a AsteroidBelt,
+    mesh: &'a IndexedMesh,
+    shader_params: &'a ShaderParams,
+    culling: Culling,
+}
+
+/// Shader, parámetros y texturas con los que se sombrea una malla
+#[derive(Clone, Copy)]
+struct Material<'a> {
+    shader_type: PlanetShaderType,
+    params: &'a ShaderParams,
+    maps: SurfaceMaps<'a>,
+}
+
+/// Malla pendiente de la pasada de geometría
+struct Draw<'a> {
+    uniforms: Uniforms,
+    mesh: &'a IndexedMesh,
+    material: Material<'a>,
+    /// Objetos que se mueven con la cámara (ver `Framebuffer::set_volatile`)
+    volatile: bool,
+}
+
+/// Resultado de rasterizar un tile: en modo directo, fragmentos ya sombreados y el dibujo de
+/// cada uno; en diferido, los fragmentos completos para el G-buffer
+enum TileOutput {
+    Shaded(ShadedFragments, Vec<u32>),
+    Deferred(Vec<(usize, Fragment)>),
+}
+
+/// Rasteriza un tile y, en modo directo, sombrea el fragmento visible de cada píxel, quedándose
+/// solo con posición, profundidad y color. Se ejecuta en los hilos del planificador, así que el
+/// framebuffer solo se lee; la profundidad de partida es la que ya tiene (órbitas, puntos).
+fn shade_tile(framebuffer: &Framebuffer, draws: &[Draw], rect: PixelRect, triangles: &[TileTriangle], light: &Light, deferred: bool, toon: bool) -> TileOutput {
+    let (x0, y0, x1, y1) = rect;
+    let mut depth = Vec::with_capacity(((x1 - x0) * (y1 - y0)) as usize);
+    for y in y0..y1 {
+        let row = y as usize * framebuffer.width;
+        depth.extend_from_slice(&framebuffer.zbuffer[row + x0 as usize..row + x1 as usize]);
+    }
+
+    if deferred {
+        let mut fragments = Vec::new();
+        rasterize_tile(rect, triangles, &mut depth, light, |draw, mut fragment| {
+            if toon {
+                fragment.color = toon::quantize_light(fragment.color);
+            }
+            fragments.push((draw, fragment));
+        });
+        return TileOutput::Deferred(fragments);
+    }
+
+    let mut shaded = ShadedFragments::default();
+    let mut shaded_draws = Vec::new();
+    rasterize_tile(rect, triangles, &mut depth, light, |index, mut fragment| {
+        let draw = &draws[index];
+        let material = &draw.material;
+        let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+        if toon {
+            fragment.color = toon::quantize_light(fragment.color);
+        }
+        let color = if framebuffer.skips_shading_for(x, y, draw.volatile) {
+            REUSE_PREVIOUS
+        } else {
+            pack_color(fragment_shader(&fragment, &draw.uniforms, material.shader_type, material.params, material.maps))
+        };
+        shaded.push(x as u32, y as u32, fragment.depth, color);
+        shaded_draws.push(index as u32);
+    });
+    TileOutput::Shaded(shaded, shaded_draws)
+}
+
+/// Pasada de geometría de `render_scene`: reúne las mallas y al terminar las transforma (una
+/// tarea por malla) y las rasteriza por tiles (una tarea por tile). En modo directo cada tile se
+/// sombrea en su tarea; en modo diferido se rasteriza al G-buffer y se guardan uniforms y
+/// material de cada dibujo para sombrear después.
+struct GeometryPass<'a> {
+    gbuffer: Option<GBuffer>,
+    draws: Vec<Draw<'a>>,
+    toon: bool,
+    wireframe: bool,
+}
+
+impl<'a> GeometryPass<'a> {
+    fn draw(&mut self, uniforms: Uniforms, mesh: &'a IndexedMesh, material: Material<'a>, volatile: bool) {
+        self.draws.push(Draw { uniforms, mesh, material, volatile });
+    }
+
+    /// Rasteriza los dibujos, vuelca al framebuffer el fragmento visible de cada píxel y sombrea
+    /// el G-buffer si lo hay. Los empates de profundidad los gana el dibujo añadido antes. Devuelve
+    /// los triángulos de cada dibujo que han llegado a la pantalla. En modo alambre solo se
+    /// trazan sus aristas.
+    fn finish(self, framebuffer: &mut Framebuffer, jobs: &JobSystem, light: &Light) -> Vec<usize> {
+        let GeometryPass { gbuffer, draws, toon, wireframe } = self;
+        let deferred = gbuffer.is_some();
+
+        // Un solo grafo: cada malla se transforma en su tarea, el reparto por tiles espera a todas
+        // y cada tile se rasteriza en cuanto está el reparto
+        let grid = TileGrid::new(framebuffer.width, framebuffer.height);
+        let screen: Vec<OnceLock<Vec<Vertex>>> = draws.iter().map(|_| OnceLock::new()).collect();
+        let bins: OnceLock<Vec<Vec<TileTriangle>>> = OnceLock::new();
+        let outputs: Vec<OnceLock<TileOutput>> = (0..grid.len()).map(|_| OnceLock::new()).collect();
+        {
+            let target: &Framebuffer = framebuffer;
+            let (draws, grid, screen, bins) = (&draws, &grid, &screen, &bins);
+            let mut graph = FrameGraph::new();
+            let transforms: Vec<TaskId> = draws
+                .iter()
+                .zip(screen)
+                .map(|(draw, vertices)| {
+                    graph.add(&[], move || {
+                        let _ = vertices.set(transform_indexed(&draw.uniforms, draw.mesh));
+                    })
+                })
+                .collect();
+            if !wireframe {
+                let bin = graph.add(&transforms, move || {
+                    let vertices = screen.iter().map(|vertices| vertices.get().map_or(&[][..], Vec::as_slice));
+                    let _ = bins.set(grid.bin(vertices.zip(draws).map(|(vertices, draw)| (vertices, draw.uniforms.depth_mode))));
+                });
+                for (tile, output) in outputs.iter().enumerate() {
+                    graph.add(&[bin], move || {
+                        let bin = &bins.get().expect("el reparto termina antes que los tiles")[tile];
+                        if !bin.is_empty() {
+                            let _ = output.set(shade_tile(target, draws, grid.rect(tile), bin, light, deferred, toon));
+                        }
+                    });
+                }
+            }
+            jobs.run(graph);
+        }
+        let triangles = screen.iter().map(|vertices| vertices.get().map_or(0, |vertices| vertices.len() / 3)).collect();
+        if wireframe {
+            let screen: Vec<Vec<Vertex>> = screen.into_iter().map(|vertices| vertices.into_inner().unwrap_or_default()).collect();
+            render_wireframe(framebuffer, &draws, &screen);
+            framebuffer.gbuffer = gbuffer;
+            return triangles;
+        }
+
+        let mut gbuffer = gbuffer;
+        for output in outputs.into_iter().filter_map(OnceLock::into_inner) {
+            match (output, gbuffer.as_mut()) {
+                (TileOutput::Deferred(fragments), Some(gbuffer)) => {
+                    for (draw, fragment) in fragments {
+                        framebuffer.set_volatile(draws[draw].volatile);
+                        gbuffer.write(framebuffer, draw, fragment);
+                    }
+                }
+                (TileOutput::Shaded(shaded, shaded_draws), _) => {
+                    for i in 0..shaded.len() {
+                        framebuffer.set_volatile(draws[shaded_draws[i] as usize].volatile);
+                        let (x, y, depth) = (shaded.x[i] as usize, shaded.y[i] as usize, shaded.depth[i]);
+                        match shaded.color[i] {
+                            REUSE_PREVIOUS => framebuffer.reuse_previous(x, y, depth),
+                            color => {
+                                framebuffer.point_with_depth(x, y, color, depth);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        framebuffer.set_volatile(false);
+
+        let Some(mut gbuffer) = gbuffer else {
+            return triangles;
+        };
+        gbuffer.resolve(framebuffer, |sample| {
+            let Draw { uniforms, material, .. } = &draws[sample.draw];
+            pack_color(fragment_shader(&sample.fragment, uniforms, material.shader_type, material.params, material.maps))
+        });
+
+        if gbuffer.ambient_occlusion {
+            if let Some(Draw { uniforms, .. }) = draws.first() {
+                ssao::apply(framebuffer, &gbuffer, &uniforms.projection_matrix, &uniforms.viewport_matrix, uniforms.depth_mode, |sample| {
+                    let uniforms = &draws[sample.draw].uniforms;
+                    let normal = sample.fragment.normal;
+                    let world_normal = uniforms.world_normal(Vec3::new(normal.x, normal.y, normal.z));
+                    let view_normal = uniforms.view_matrix * nalgebra_glm::vec4(world_normal.x, world_normal.y, world_normal.z, 0.0);
+                    view_normal.xyz().try_normalize(1e-6).unwrap_or(Vec3::new(0.0, 0.0, 1.0))
+                });
+            }
+        }
+
+        gbuffer.clear();
+        framebuffer.gbuffer = Some(gbuffer);
+        triangles
+    }
+}
+
+/// Color de las aristas en modo alambre
+const WIREFRAME_COLOR: u32 = 0x66DDAA;
+/// Holgura, en fracción de la distancia, con la que una arista cuenta como sobre la superficie
+/// de la que sale: la línea y el triángulo no dan la misma profundidad en cada píxel
+const WIREFRAME_DEPTH_MARGIN: f32 = 0.01;
+
+/// Aristas de los triángulos que han llegado a la pantalla, ya recortados contra el frustum y sin
+/// las caras que descarta el culling, trazadas con `line`: se ve lo que hace la simplificación de
+/// las mallas y dónde corta el recorte. Antes se rasterizan los triángulos solo en el z-buffer,
+/// para que las aristas de detrás de una superficie (las de la cara oculta de un cuerpo o las de
+/// otro cuerpo más lejos) no se vean. Las aristas compartidas se trazan dos veces, una por
+/// triángulo.
+fn render_wireframe(framebuffer: &mut Framebuffer, draws: &[Draw], screen: &[Vec<Vertex>]) {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let rect: PixelRect = (0, 0, framebuffer.width as i32, framebuffer.height as i32);
+    for (draw, vertices) in draws.iter().zip(screen) {
+        framebuffer.set_volatile(draw.volatile);
+        let depth_mode = draw.uniforms.depth_mode;
+        for triangle in vertices.chunks_exact(3) {
+            triangle_depths_in(&triangle[0], &triangle[1], &triangle[2], rect, |x, y, depth| {
+                framebuffer.depth_write(x as usize, y as usize, depth_mode.resolve(depth));
+            });
+        }
+    }
+
+    for (draw, vertices) in draws.iter().zip(screen) {
+        framebuffer.set_volatile(draw.volatile);
+        let (depth_mode, projection) = (draw.uniforms.depth_mode, &draw.uniforms.projection_matrix);
+        for triangle in vertices.chunks_exact(3) {
+            for (from, to) in [(0, 1), (1, 2), (2, 0)] {
+                let (start, end) = (triangle[from].transformed_position, triangle[to].transformed_position);
+                let Some((start, end)) = clip_to_screen(start, end, width, height) else {
+                    continue;
+                };
+                let mut a = Vertex::default();
+                let mut b = Vertex::default();
+                a.transformed_position = start;
+                b.transformed_position = end;
+                for fragment in line(&a, &b) {
+                    let depth = depth_mode.resolve(fragment.depth);
+                    let distance = depth_mode.linear(projection, depth);
+                    framebuffer.point_if(fragment.position.x as usize, fragment.position.y as usize, WIREFRAME_COLOR, |surface| {
+                        depth <= surface || distance <= depth_mode.linear(projection, surface) * (1.0 + WIREFRAME_DEPTH_MARGIN)
+                    });
+                }
+            }
+        }
+    }
+    framebuffer.set_volatile(false);
+}
+
+fn render_scene(
+    framebuffer: &mut Framebuffer,
+    jobs: &JobSystem,
+    view: &RenderView,
+    planets: &[CelestialBody],
+    light: &Light,
+    models: &SceneModels,
+    show_barycenter: bool,
+) -> RenderStats {
+    let world_uniforms = view.uniforms(Mat4::identity());
+
+    // En estilo cómic las órbitas van después de los contornos, que las ennegrecerían
+    if !view.toon {
+        for planet in planets.iter().filter(|planet| !planet.hidden && planet.orbit_radius > 0.0) {
+            render_orbit(framebuffer, &world_uniforms, planet.orbit_radius, 32, ORBIT_COLOR);
+        }
+    }
+
+    if show_barycenter {
+        render_barycenter(framebuffer, &world_uniforms, planets);
+    }
+
+    let mut geometry = GeometryPass { gbuffer: framebuffer.gbuffer.take(), draws: Vec::new(), toon: view.toon, wireframe: view.wireframe };
+    // Cuerpo dibujado en cada uno de los primeros dibujos; después van las rocas y la nave
+    let mut drawn_bodies = Vec::new();
+    let mut body_culled = vec![false; planets.len()];
+
+    for (index, planet) in planets.iter().enumerate().filter(|(_, planet)| !planet.hidden) {
+        let uniforms = Uniforms {
+            culling: models.culling.unwrap_or(models.body_culling),
+            ..view.relative_uniforms(planet.position, planet.scale, planet.rotation)
+        };
+        // Fuera del frustum no se transforma ni un vértice
+        if !sphere_in_frustum(&uniforms, view.relative(planet.position), planet.bounding_radius()) {
+            body_culled[index] = true;
+            continue;
+        }
+        let material = Material { shader_type: planet.shader_type, params: &planet.shader_params, maps: SurfaceMaps::default() };
+        geometry.draw(uniforms, planet.visible_mesh(), material, false);
+        drawn_bodies.push(index);
+    }
+
+    if let Some(belt) = &models.belt {
+        let material = Material { shader_type: PlanetShaderType::Terra, params: belt.shader_params, maps: SurfaceMaps::default() };
+        for rock in belt.belt.render(framebuffer, &world_uniforms, view.time) {
+            let uniforms = Uniforms {
+                culling: models.culling.unwrap_or(belt.culling),
+                ..view.relative_uniforms(widen(rock.position), rock.size, rock.rotation)
+            };
+            geometry.draw(uniforms, belt.mesh, material, false);
+        }
+    }
+
+    let ship = &models.ship;
+    let ship_material = Material { shader_type: PlanetShaderType::Mesh, params: ship.shader_params, maps: ship.maps };
+    let ship_uniforms = Uniforms {
+        culling: models.culling.unwrap_or(ship.culling),
+        ..view.relative_uniforms(ship.position, ship.scale, ship.rotation)
+    };
+    geometry.draw(ship_uniforms, ship.mesh, ship_material, true);
+
+    let depth_before = view.toon.then(|| framebuffer.zbuffer.clone());
+    let triangles = geometry.finish(framebuffer, jobs, light);
+    if let Some(depth_before) = depth_before {
+        toon::outline(framebuffer, &depth_before, &view.projection_matrix, view.depth_mode);
+        for planet in planets.iter().filter(|planet| !planet.hidden && planet.orbit_radius > 0.0) {
+            let color = toon::orbit_color(planet.shader_params.palette[0]);
+            render_orbit(framebuffer, &world_uniforms, planet.orbit_radius, 32, color);
+        }
+    }
+    if let Some(spacing) = view.ecliptic_grid {
+        render_ecliptic_grid(framebuffer, &world_uniforms, spacing);
+    }
+    let (body_triangles, rest) = triangles.split_at(drawn_bodies.len());
+    let (ship_triangles, rock_triangles) = rest.split_last().map_or((0, &[][..]), |(&ship, rocks)| (ship, rocks));
+    let mut stats = RenderStats {
+        body_triangles: vec![0; planets.len()],
+        body_culled,
+        rock_meshes: rock_triangles.len(),
+        rock_triangles: rock_triangles.iter().sum(),
+        ship_triangles,
+    };
+    for (&index, &count) in drawn_bodies.iter().zip(body_triangles) {
+        stats.body_triangles[index] = count;
+    }
+
+    // Sombra de contacto de la nave sobre el cuerpo más cercano, si vuela a poca altura (sin
+    // nave dibujada, como en los vídeos, tampoco hay sombra)
+    let below = planets
+        .iter()
+        .filter(|planet| !ship.mesh.is_empty() && !planet.hidden && planet.shader_type != PlanetShaderType::Solarius)
+        .map(|planet| (planet, (ship.position - planet.position).norm() as f32 - planet.scale))
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((planet, _)) = below {
+        let uniforms = view.relative_uniforms(planet.position, 1.0, Vec3::zeros());
+        let relief = planet.bounding_radius() - planet.scale;
+        render_ship_shadow(framebuffer, &uniforms, narrow(ship.position - planet.position), planet.scale, relief.abs(), ship.radius * ship.scale);
+    }
+
+    // Efectos aditivos: después de la geometría para que la prueba de profundidad la vea completa
+    for (index, planet) in planets.iter().enumerate().filter(|(_, planet)| !planet.hidden) {
+        let uniforms = view.relative_uniforms(planet.position, planet.scale, planet.rotation);
+        if planet.shader_type == PlanetShaderType::Solarius {
+            render_prominences(framebuffer, &uniforms, &planet.shader_params);
+            render_corona(framebuffer, &uniforms, &planet.shader_params, events::star_coverage(view.origin, planets, index));
+        }
+        if planet.shader_type == PlanetShaderType::Nepturion {
+            render_rings(framebuffer, &uniforms, light, &planet.shader_params);
+        }
+        if !planet.plumes.is_empty() {
+            planet.plumes.render(framebuffer, &uniforms, view.relative(planet.position));
+        }
+    }
+    stats
+}
+
+/// Atmósfera del cuerpo para la vista de superficie o en órbita; solo los planetas terrestres
+/// y los musgosos tienen aire
+fn close_up_atmosphere(view: &RenderView, planet: &CelestialBody) -> Option<Atmosphere> {
+    let has_air = matches!(planet.shader_type, PlanetShaderType::Terra | PlanetShaderType::Mossar);
+    (has_air && !planet.hidden).then(|| Atmosphere { center: view.relative(planet.position), planet_radius: planet.scale })
+}
+
+/// Polvo alrededor de la nave, en coordenadas relativas a la cámara como las mallas
+fn render_solar_wind(framebuffer: &mut Framebuffer, view: &RenderView, wind: &SolarWind, ship_position: DVec3) {
+    let uniforms = view.relative_uniforms(ship_position, 1.0, Vec3::zeros());
+    wind.render(framebuffer, &uniforms, view.relative(ship_position));
+}
+
+/// Frames seguidos que pueden reutilizar el anterior antes de forzar uno completo; cada
+/// reproyección redondea al píxel más cercano y el error se acumularía
+const MAX_REPROJECTED_FRAMES: u32 = 30;
+
+/// Lo necesario del frame anterior para reproyectarlo cuando la cámara solo gira
+struct ReprojectionState {
+    /// viewport * proyección * vista
+    transform: Mat4,
+    eye: DVec3,
+    time: f32,
+    hidden: Vec<bool>,
+    age: u32,
+}
+
+impl ReprojectionState {
+    fn new(view: &RenderView, planets: &[CelestialBody], age: u32) -> Self {
+        ReprojectionState {
+            transform: view.viewport_matrix * view.projection_matrix * view.view_matrix,
+            eye: view.origin,
+            time: view.time,
+            hidden: planets.iter().map(|planet| planet.hidden).collect(),
+            age,
+        }
+    }
+
+    /// El frame anterior sirve si la escena está quieta (pausa) y la cámara no se desplazó:
+    /// entonces cada píxel ve en la misma dirección lo mismo que antes, sin importar la profundidad
+    fn matches(&self, view: &RenderView, planets: &[CelestialBody]) -> bool {
+        self.age < MAX_REPROJECTED_FRAMES
+            && self.eye == view.origin
+            && self.time == view.time
+            && planets.iter().map(|planet| planet.hidden).eq(self.hidden.iter().copied())
+    }
+
+    /// Píxel del frame anterior que veía la misma dirección que (x, y) en `view`
+    fn source(&self, inverse: &Mat4, x: usize, y: usize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let point = inverse * nalgebra_glm::vec4(x as f32 + 0.5, y as f32 + 0.5, 0.0, 1.0);
+        let world = point.xyz() / point.w;
+        let previous = self.transform * nalgebra_glm::vec4(world.x, world.y, world.z, 1.0);
+        if previous.w <= 0.0 {
+            return None;
+        }
+        let (px, py) = (previous.x / previous.w, previous.y / previous.w);
+        if px < 0.0 || py < 0.0 || px >= width as f32 || py >= height as f32 {
+            return None;
+        }
+        Some((px as usize, py as usize))
+    }
+}
+
+/// Vistas del modo observatorio: nave, mapa cenital, primer plano del planeta más cercano y el sol,
+/// cada una con su cuadrante (columna, fila)
+fn observatory_views(
+    ship_view: (DVec3, DVec3, Vec3),
+    planets: &[CelestialBody],
+    width: f32,
+    height: f32,
+    far: f32,
+    time: f32,
+    light_position: DVec3,
+) -> Vec<((usize, usize), RenderView)> {
+    let (half_w, half_h) = (width / 2.0, height / 2.0);
+    let quadrant = |col: usize, row: usize| (col as f32 * half_w, row as f32 * half_h, half_w, half_h);
+    let (ship_eye, ship_target, ship_up) = ship_view;
+    let mut views = Vec::with_capacity(4);
+
+    views.push(((0, 0), RenderView::new(ship_eye, ship_target, ship_up, far, quadrant(0, 0), time, light_position)));
+
+    let map_height = system_radius(planets) * 1.9;
+    let map = RenderView::new(
+        DVec3::new(0.0, map_height as f64, 0.0),
+        DVec3::zeros(),
+        Vec3::new(0.0, 0.0, -1.0),
+        map_height * 1.5,
+        quadrant(1, 0),
+        time,
+        light_position,
+    );
+    views.push(((1, 0), map));
+
+    let closest = planets
+        .iter()
+        .filter(|planet| planet.shader_type != PlanetShaderType::Solarius)
+        .min_by(|a, b| {
+            let da = (a.position - ship_eye).norm();
+            let db = (b.position - ship_eye).norm();
+            da.partial_cmp(&db).unwrap()
+        });
+    if let Some(planet) = closest {
+        let toward_ship = narrow(ship_eye - planet.position).try_normalize(1e-6).unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+        let eye = planet.position + widen(toward_ship * planet.scale * 3.5 + Vec3::new(0.0, planet.scale, 0.0));
+        views.push(((0, 1), RenderView::new(eye, planet.position, Vec3::new(0.0, 1.0, 0.0), far, quadrant(0, 1), time, light_position)));
+    }
+
+    if let Some(sun) = planets.iter().find(|planet| planet.shader_type == PlanetShaderType::Solarius) {
+        let eye = sun.position + widen(Vec3::new(0.0, sun.scale * 1.2, sun.scale * 4.0));
+        views.push(((1, 1), RenderView::new(eye, sun.position, Vec3::new(0.0, 1.0, 0.0), far, quadrant(1, 1), time, light_position)));
+    }
+
+    views
+}
+
+fn find_scene(paths: &[String], name: &str) -> Result<usize, String> {
+    paths
+        .iter()
+        .position(|path| scene::scene_name(path).eq_ignore_ascii_case(name) || path == name)
+        .ok_or_else(|| format!("no existe la escena '{}'", name))
+}
+
+#[cfg(feature = "remote")]
+fn find_body(planets: &[CelestialBody], name: &str) -> Result<usize, String> {
+    planets
+        .iter()
+        .position(|planet| planet.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("no existe el cuerpo '{}'", name))
+}
+
+/// Aplica un comando remoto al estado de la simulación. Las capturas (después de renderizar el
+/// frame) y los cambios de escena se atienden aparte.
+#[cfg(feature = "remote")]
+fn apply_remote_command(
+    command: &RemoteCommand,
+    clock: &mut SimulationClock,
+    camera: &mut SpaceshipCamera,
+    planets: &[CelestialBody],
+    selected_body: &mut Option<usize>,
+) -> Result<String, String> {
+    match command {
+        RemoteCommand::SetTimescale { value } => {
+            if !value.is_finite() || *value <= 0.0 {
+                return Err(format!("escala de tiempo inválida: {}", value));
+            }
+            clock.set_time_scale(*value);
+            Ok(format!("escala de tiempo {}x", clock.time_scale))
+        }
+        RemoteCommand::Pause { paused } => {
+            clock.paused = paused.unwrap_or(!clock.paused);
+            Ok(if clock.paused { "en pausa" } else { "reanudada" }.to_string())
+        }
+        RemoteCommand::Warp { body } => {
+            let index = find_body(planets, body)?;
+            camera.warp_to(planets[index].position, WARP_ARRIVAL_DISTANCE);
+            *selected_body = Some(index);
+            Ok(format!("warp a {}", planets[index].name))
+        }
+        RemoteCommand::Select { body } => {
+            let index = find_body(planets, body)?;
+            *selected_body = Some(index);
+            Ok(format!("{} seleccionado", planets[index].name))
+        }
+        RemoteCommand::Screenshot { .. } => Err("la captura se atiende tras renderizar".to_string()),
+        RemoteCommand::LoadScene { .. } => Err("el cambio de escena se atiende aparte".to_string()),
+    }
+}
+
+/// Separación entre ojos y distancia de convergencia del modo anaglifo, en unidades de mundo
+const EYE_SEPARATION: f32 = 4.0;
+const CONVERGENCE_DISTANCE: f32 = 120.0;
+
+/// Vistas izquierda y derecha del modo anaglifo: ambas cámaras convergen en un punto
+/// delante de la nave, así lo que está más cerca parece salir de la pantalla
+fn stereo_views(
+    eye: DVec3,
+    forward: Vec3,
+    right: Vec3,
+    rect: (f32, f32, f32, f32),
+    far: f32,
+    time: f32,
+    light_position: DVec3,
+) -> (RenderView, RenderView) {
+    let focus = eye + widen(forward * CONVERGENCE_DISTANCE);
+    let offset = widen(right * (EYE_SEPARATION / 2.0));
+    let up = right.cross(&forward);
+    (
+        RenderView::new(eye - offset, focus, up, far, rect, time, light_position),
+        RenderView::new(eye + offset, focus, up, far, rect, time, light_position),
+    )
+}
+
+const SCENE_PATH: &str = "assets/scenes/sistema_solar.toml";
+const SCENE_DIRECTORY: &str = "assets/scenes";
+
+/// Origen de la escena a la que se cambia
+enum SceneSource {
+    /// Índice en la lista de archivos de escena
+    File(usize),
+    /// Sistema generado del modo galaxia
+    Galaxy,
+}
+
+/// Color de un cuerpo en el esquema: mezcla de los dos primeros colores de su paleta
+fn orrery_body(name: &str, orbit_radius: f32, orbit_angle: f32, scale: f32, params: &ShaderParams) -> OrreryBody {
+    OrreryBody {
+        name: name.to_string(),
+        orbit_radius,
+        orbit_angle,
+        scale,
+        color: pack_color((params.palette[0] + params.palette[1]) * 0.5),
+    }
+}
+
+fn export_orrery(path: &str, bodies: &[OrreryBody], to_scale: bool) {
+    match orrery::export(path, bodies, to_scale) {
+        Ok(()) => println!("Esquema guardado en {}", path),
+        Err(err) => println!("No se pudo guardar {}: {}", path, err),
+    }
+}
+
+const SHIP_PATH: &str = "assets/models/Y-wing.obj";
+
+/// Modelo de la nave; sin ella no se puede seguir
+fn load_ship(path: &str) -> Obj {
+    match mesh::load_model(path) {
+        Ok(model) => model,
+        Err(err) => {
+            eprintln!("No se pudo cargar la nave {}: {}", path, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Material de la nave: color y brillo del archivo del modelo, si los trae
+fn ship_shader_params(model: &Obj) -> ShaderParams {
+    let mut params = ShaderParams::for_type(PlanetShaderType::Mesh);
+    if let Some([r, g, b]) = model.diffuse_color {
+        params.palette[0] = Vector3::new(r, g, b);
+    }
+    if let Some(shininess) = model.shininess {
+        params.shininess = shininess.max(1.0);
+    }
+    params
+}
+
+/// Las rocas cercanas del cinturón: paleta gris sin océano ni nubes
+fn rock_shader_params() -> ShaderParams {
+    ShaderParams {
+        palette: [Vector3::new(0.3, 0.28, 0.25), Vector3::new(0.45, 0.42, 0.38), Vector3::new(0.6, 0.58, 0.55)],
+        cloud_coverage: 0.0,
+        ocean_level: 0.0,
+        ice_cap_size: 0.0,
+        ..ShaderParams::for_type(PlanetShaderType::Terra)
+    }
+}
+
+/// Escena de los renders sin ventana (`--render-still` y `--render-video`): las mallas completas
+/// (sin LOD). Con `--resume` parte de la
+/// sesión guardada; si no, del inicio.
+struct OfflineScene {
+    stars: StarsConfig,
+    planets: Vec<CelestialBody>,
+    camera: SpaceshipCamera,
+    start_time: f64,
+    time: f64,
+    ship_mesh: IndexedMesh,
+    ship_params: ShaderParams,
+    ship_texture: Option<Rc<texture::Texture>>,
+    ship_normal_map: Option<Rc<texture::Texture>>,
+    ship_culling: Culling,
+    /// Culling de los cuerpos y las rocas, todos salidos de la esfera
+    sphere_culling: Culling,
+    belt: Option<AsteroidBelt>,
+    rock_mesh: IndexedMesh,
+    rock_params: ShaderParams,
+}
+
+impl OfflineScene {
+    fn load(options: &CliOptions, scene_path: &str) -> Self {
+        let scene = load_scene(scene_path, options.scale_mode).unwrap();
+        let sphere_obj = mesh::load_model("assets/models/sphere1.obj").unwrap();
+        let mut planets: Vec<CelestialBody> = scene
+            .bodies
+            .iter()
+            .map(|config| CelestialBody::from_config(config, body_mesh(config, &sphere_obj)))
+            .collect();
+        let mut clock = SimulationClock::new();
+        let mut camera = SpaceshipCamera::new(DVec3::new(0.0, 100.0, 300.0));
+        if options.resume {
+            match Autosave::load(AUTOSAVE_PATH) {
+                Ok(save) => restore_autosave(&save, &mut clock, &mut camera, &mut planets, &mut None),
+                Err(err) => println!("No se pudo reanudar: {}", err),
+            }
+        }
+
+        let ship_path = options.ship_model.as_deref().unwrap_or(SHIP_PATH);
+        let ship_obj = load_ship(ship_path);
+        let mut texture_cache = TextureCache::new(options.texture_budget.saturating_mul(1024 * 1024));
+        let mut load_texture = |path: &PathBuf| match texture_cache.load(path) {
+            Ok(texture) => Some(texture),
+            Err(err) => {
+                println!("No se pudo cargar la textura {}: {}", path.display(), err);
+                None
+            }
+        };
+        let mut offline = OfflineScene {
+            stars: scene.stars,
+            planets,
+            camera,
+            start_time: clock.time,
+            time: clock.time,
+            ship_mesh: ship_obj.get_indexed_mesh(),
+            ship_params: ship_shader_params(&ship_obj),
+            ship_texture: ship_obj.diffuse_texture.as_ref().and_then(&mut load_texture),
+            ship_normal_map: ship_obj.normal_texture.as_ref().and_then(&mut load_texture),
+            ship_culling: MeshInfo::from_obj(ship_path, &ship_obj).culling(),
+            sphere_culling: MeshInfo::from_obj("sphere1.obj", &sphere_obj).culling(),
+            belt: scene.belt.as_ref().map(AsteroidBelt::new),
+            rock_mesh: IndexedMesh::from_triangles(&belt_rock_mesh(scene.belt.as_ref(), &sphere_obj)),
+            rock_params: rock_shader_params(),
+        };
+        offline.set_time(clock.time);
+        offline
+    }
+
+    /// Coloca los cuerpos en su órbita en el instante `time` de la simulación
+    fn set_time(&mut self, time: f64) {
+        self.time = time;
+        for planet in &mut self.planets {
+            let state = planet.ephemeris().state_at(time);
+            planet.apply_orbital_state(&state);
+        }
+    }
+
+    /// Dibuja a `--resolution` con `--supersample`² muestras por píxel, mirando desde `eye` hacia
+    /// `target`. La nave, si `with_ship`, va delante de la cámara de la sesión, como en el juego.
+    fn render(&self, jobs: &JobSystem, (eye, target, up): (DVec3, DVec3, Vec3), with_ship: bool, options: &CliOptions) -> Framebuffer {
+        let camera = &self.camera;
+        let ship_offset = camera.get_forward() * 15.0 + camera.get_right() * -3.0 + camera.get_up() * -2.0;
+        let no_ship = IndexedMesh::default();
+        let models = SceneModels {
+            ship: ShipModel {
+                mesh: if with_ship { &self.ship_mesh } else { &no_ship },
+                radius: mesh_radius(&self.ship_mesh.vertices),
+                position: camera.position + widen(ship_offset),
+                scale: 2.5,
+                rotation: Vec3::new(-camera.pitch, camera.yaw + PI, 0.0),
+                shader_params: &self.ship_params,
+                maps: SurfaceMaps { albedo: self.ship_texture.as_deref(), normal: self.ship_normal_map.as_deref() },
+                culling: self.ship_culling,
+            },
+            belt: self.belt.as_ref().map(|belt| BeltModel {
+                belt,
+                mesh: &self.rock_mesh,
+                shader_params: &self.rock_params,
+                culling: self.sphere_culling,
+            }),
+            body_culling: self.sphere_culling,
+            culling: options.culling,
+        };
+
+        let (width, height) = options.resolution;
+        let factor = options.supersample;
+        let light_position = self.planets[0].position;
+        let light = Light::new(Vector3::new(light_position.x as f32, light_position.y as f32, light_position.z as f32));
+        let rect = (0.0, 0.0, (width * factor) as f32, (height * factor) as f32);
+        let view = RenderView::new(eye, target, up, far_plane(&self.planets), rect, self.time as f32, light_position)
+            .with_logarithmic_depth(options.log_depth);
+
+        let mut samples = Framebuffer::new(width * factor, height * factor);
+        samples.set_background_color(0x000011);
+        samples.clear();
+        render_scene(&mut samples, jobs, &view, &self.planets, &light, &models, false);
+
+        let mut stars = Framebuffer::new(width, height);
+        stars.set_background_color(0x000011);
+        stars.clear();
+        StarField::random(width, height, &self.stars).render(&mut stars);
+        still::resolve(&samples, factor, &stars)
+    }
+}
+
+/// `--render-still`: un frame desde la cámara de la sesión (o la inicial), con supersampling
+fn render_still(options: &CliOptions, scene_path: &str, path: &str) {
+    let offline = OfflineScene::load(options, scene_path);
+    let camera = &offline.camera;
+    let target = camera.position + widen(camera.get_forward() * 10.0);
+
+    let start = Instant::now();
+    let jobs = JobSystem::new(options.threads);
+    let image = offline.render(&jobs, (camera.position, target, camera.get_up()), true, options);
+    let (width, height) = options.resolution;
+    let factor = options.supersample;
+    match image.save_png(path) {
+        Ok(()) => println!("{}x{} ({}x{} muestras) guardado en {} en {:.1} s", width, height, factor, factor, path, start.elapsed().as_secs_f32()),
+        Err(err) => {
+            eprintln!("No se pudo guardar {}: {}", path, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `--render-video`: el recorrido de `--camera-path` (o una vuelta por el sistema) a paso fijo,
+/// sin nave, hacia ffmpeg o un `.y4m`. La simulación avanza `time_scale` segundos por segundo de
+/// vídeo desde el inicio (o desde la sesión con `--resume`).
+fn render_video(options: &CliOptions, scene_path: &str, output: &str) {
+    let fail = |message: String| -> ! {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    };
+    let mut offline = OfflineScene::load(options, scene_path);
+    let camera_path = match options.camera_path.as_deref() {
+        Some(file) => CameraPath::load(file).unwrap_or_else(|err| fail(err)),
+        None => CameraPath::overview(system_radius(&offline.planets)),
+    };
+    if let Some(start_time) = camera_path.start_time {
+        offline.start_time = start_time;
+    }
+    for keyframe in &camera_path.keyframes {
+        if let LookAt::Body(name) = &keyframe.look_at {
+            if !offline.planets.iter().any(|planet| &planet.name == name) {
+                fail(format!("El recorrido mira a {}, que no está en la escena", name));
+            }
+        }
+    }
+
+    let (width, height) = options.resolution;
+    let mut sink = VideoSink::create(output, width, height, camera_path.fps)
+        .unwrap_or_else(|err| fail(format!("No se pudo crear {}: {}", output, err)));
+    let jobs = JobSystem::new(options.threads);
+    let frames = camera_path.frame_count();
+    let start = Instant::now();
+    for frame in 0..frames {
+        let seconds = frame as f32 / camera_path.fps as f32;
+        offline.set_time(offline.start_time + seconds as f64 * camera_path.time_scale);
+        let (eye, target) = camera_path.sample(seconds, |look_at| match look_at {
+            LookAt::Point(point) => DVec3::from(*point),
+            LookAt::Body(name) => offline.planets.iter().find(|planet| &planet.name == name).map_or(DVec3::zeros(), |planet| planet.position),
+        });
+        let image = offline.render(&jobs, (eye, target, Vec3::new(0.0, 1.0, 0.0)), false, options);
+        if let Err(err) = sink.write_frame(&image) {
+            fail(format!("No se pudo escribir el fotograma {}: {}", frame, err));
+        }
+        if (frame + 1) % camera_path.fps as usize == 0 {
+            println!("{}/{} fotogramas ({:.1} s)", frame + 1, frames, start.elapsed().as_secs_f32());
+        }
+    }
+    if let Err(err) = sink.finish() {
+        fail(format!("No se pudo terminar {}: {}", output, err));
+    }
+    println!("{} fotogramas de {}x{} guardados en {} en {:.1} s", frames, width, height, output, start.elapsed().as_secs_f32());
+}
+
+fn main() {
+    let options = match CliOptions::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("Error: {}\n", message);
+            }
+            eprintln!("{}", cli::USAGE);
+            std::process::exit(if message.is_empty() { 0 } else { 2 });
+        }
+    };
+
+    let mut scene_paths = if options.scenes.is_empty() {
+        scene::list_scenes(SCENE_DIRECTORY, SCENE_PATH)
+    } else {
+        options.scenes.clone()
+    };
+
+    // El escenario empieza en su escena, que pasa a ser la primera
+    let mut scenario = options.scenario.as_deref().and_then(|path| {
+        let loaded = Scenario::load(path).and_then(|scenario| Ok((find_scene(&scene_paths, &scenario.scene)?, scenario)));
+        match loaded {
+            Ok((index, scenario)) => {
+                let path = scene_paths.remove(index);
+                scene_paths.insert(0, path);
+                Some(ScenarioRun::new(scenario))
+            }
+            Err(err) => {
+                println!("No se pudo cargar el escenario {}: {}", path, err);
+                None
+            }
+        }
+    });
+
+    if let Some(path) = options.orrery.as_deref() {
+        let scene = load_scene(&scene_paths[0], options.scale_mode).unwrap();
+        let bodies: Vec<OrreryBody> = scene
+            .bodies
+            .iter()
+            .map(|config| orrery_body(&config.name, config.orbit_radius, 0.0, config.scale, &config.shader_params()))
+            .collect();
+        export_orrery(path, &bodies, options.to_scale);
+        return;
+    }
+
+    if let Some(path) = options.render_still.as_deref() {
+        render_still(&options, &scene_paths[0], path);
+        return;
+    }
+
+    if let Some(path) = options.render_video.as_deref() {
+        render_video(&options, &scene_paths[0], path);
+        return;
+    }
+
+    println!("=== Sistema Solar Ultra-Optimizado v3 ===");
+    
+    let (window_width, window_height) = options.window_size;
+    let (mut framebuffer_width, mut framebuffer_height) = options.resolution;
+
+    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+    let mut upscaler = Upscaler::new(window_width, window_height, options.filter, options.integer_scale);
+    
+    let mut window = Window::new(
+        "Sistema Solar - WASD Space/Shift Flechas, F=warp, I=orbitar, M=velocidad auto/manual, [/]=velocidad, V=superficie, F5=eclipse, B=baricentro, G=ejes, H=ocultar, K=solo, O=observatorio, P=pausa, ,/.=línea de tiempo, +/-=tiempo, 3=anaglifo, R=cómic, C=ajedrez, L=diferido, Q=oclusión, Z=profundidad log, E=esquema, J=ver conjunción, F1=alambre, F2=cuadrícula, F3=inspector, F4=shader, Y=grabar recorrido, X=trayectorias, U=medir, F11=panorama 360°, F12=captura, ESC=menú",
+        window_width,
+        window_height,
+        WindowOptions::default(),
+    ).unwrap();
+
+    window.set_position(100, 100);
+    // El ritmo lo lleva `FramePacer`
+    window.limit_update_rate(None);
+    framebuffer.set_background_color(0x000011);
+
+    let sphere_obj = mesh::load_model("assets/models/sphere1.obj").unwrap();
+    let mut sphere_info = MeshInfo::from_obj("sphere1.obj", &sphere_obj);
+    let sphere_culling = sphere_info.culling();
+
+    let ship_path = options.ship_model.as_deref().unwrap_or(SHIP_PATH);
+    let ywing_obj = load_ship(ship_path);
+    let mut ywing_info = MeshInfo::from_obj(ship_path, &ywing_obj);
+    let ywing_mesh = IndexedMesh::from_triangles(&ywing_info.add_lod(&ywing_obj.get_vertex_array(), 80));
+    let ywing_radius = mesh_radius(&ywing_mesh.vertices);
+    let ywing_culling = ywing_info.culling();
+
+    let mut scene = load_scene(&scene_paths[0], options.scale_mode).unwrap();
+    // Archivo de la escena actual (ninguno en un sistema generado) y su vigilante para recargarla
+    let mut current_scene = Some(0);
+    let mut scene_watcher = Some(SceneWatcher::new(&scene_paths[0]));
+    let galaxy = options.galaxy.map(|seed| Galaxy::generate(seed, GALAXY_SYSTEMS, &scene.bodies[0].name));
+    let mut galaxy_system = 0;
+    let mut galaxy_map = GalaxyMap::new();
+    let mut hyperspace: Option<Hyperspace> = None;
+    let mut planets: Vec<CelestialBody> = scene
+        .bodies
+        .iter()
+        .map(|config| CelestialBody::from_config(config, body_mesh(config, &sphere_obj)))
+        .collect();
+    // Las rocas cercanas usan la esfera simplificada (con su relieve, si lo hay) y una paleta
+    // gris sin océano ni nubes
+    let mut belt = scene.belt.as_ref().map(AsteroidBelt::new);
+    let mut rock_mesh = IndexedMesh::from_triangles(&sphere_info.add_lod(&belt_rock_mesh(scene.belt.as_ref(), &sphere_obj), ROCK_TRIANGLES));
+    let mesh_infos = [sphere_info, ywing_info];
+    for info in &mesh_infos {
+        println!("{}", info.summary());
+    }
+    let rock_shader_params = rock_shader_params();
+
+    let mut simulation = SimulationWorker::spawn(planets.iter().map(CelestialBody::ephemeris).collect());
+    let mut texture_cache = TextureCache::new(options.texture_budget.saturating_mul(1024 * 1024));
+    let mut load_texture = |path: &PathBuf| match texture_cache.load(path) {
+        Ok(texture) => Some(texture),
+        Err(err) => {
+            println!("No se pudo cargar la textura {}: {}", path.display(), err);
+            None
+        }
+    };
+    let ship_texture = ywing_obj.diffuse_texture.as_ref().and_then(&mut load_texture);
+    let ship_normal_map = ywing_obj.normal_texture.as_ref().and_then(&mut load_texture);
+    let ship_shader_params = ship_shader_params(&ywing_obj);
+
+    let mut camera = SpaceshipCamera::new(DVec3::new(0.0, 100.0, 300.0));
+    camera.cruise_speed = ship_speed(&planets);
+    let mut far = far_plane(&planets);
+    let mut light = Light::new(Vector3::new(0.0, 0.0, 0.0));
+    let mut skybox = StarField::random(framebuffer_width, framebuffer_height, &scene.stars);
+
+    let mut clock = SimulationClock::new();
+    let mut pacer = FramePacer::new(options.frame_cap, options.busy_wait);
+    let jobs = JobSystem::new(options.threads);
+    println!("Hilos de render: {}", jobs.workers());
+    let mut selected_body: Option<usize> = None;
+    let mut surface_camera: Option<SurfaceCamera> = None;
+    let mut day_tour: Option<DayTour> = None;
+    let mut eclipse_tour: Option<EclipseTour> = None;
+    // Ficha educativa (N): página actual y cuerpo al que corresponde
+    let mut facts_visible = false;
+    let mut fact_page = (None, 0);
+    // Visibilidad previa de cada cuerpo mientras el modo solo está activo
+    let mut solo_restore: Option<Vec<bool>> = None;
+    let mut conjunctions = ConjunctionDetector::new(CONJUNCTION_DEGREES);
+    let mut notification: Option<(Conjunction, f32)> = None;
+    let mut frame_count: u64 = 0;
+    let mut fps_timer = Instant::now();
+    let mut fps_counter = 0;
+    let mut show_barycenter = false;
+    let mut observatory_mode = false;
+    let mut anaglyph_mode = false;
+    let mut toon_mode = false;
+    let mut wireframe_mode = false;
+    let mut show_ecliptic_grid = false;
+    let mut grid_spacing = scene.grid.spacing;
+    let mut checkerboard_mode = false;
+    let mut ambient_occlusion_mode = false;
+    let mut reprojection: Option<ReprojectionState> = None;
+    let mut show_gizmo = false;
+    // Trayectorias previstas (X), calculadas en su propio hilo
+    let mut show_predictions = false;
+    let mut predictor = TrajectoryPredictor::spawn();
+    let mut measurement: Option<Measurement> = None;
+    let mut inspector = Inspector::new();
+    let mut shader_panel = ShaderPanel::new();
+    // Grabación del vuelo como recorrido de `--camera-path` (Y)
+    let mut path_recorder: Option<PathRecorder> = None;
+    let mut memory_budget = MemoryBudget::new(options.memory_budget * 1024 * 1024);
+    let mut memory_report = MemoryReport::new();
+    let mut logarithmic_depth = options.log_depth;
+    let theme = hud::Theme { scale: options.ui_scale, high_contrast: options.high_contrast };
+    let mut pause_menu = PauseMenu::new(scene_paths.iter().map(|path| scene::scene_name(path).to_string()).collect());
+    // Índice en `RESOLUTION_SCALES` aplicado sobre `--resolution`
+    let mut resolution_scale = RESOLUTION_SCALES.iter().position(|&scale| scale == 1.0).unwrap_or(0);
+    let mut volume = 1.0;
+    let mut quit = false;
+    let mut entry_heating = EntryHeating::new();
+    let mut solar_wind = SolarWind::new();
+    if options.resume {
+        match Autosave::load(AUTOSAVE_PATH) {
+            Ok(save) => {
+                restore_autosave(&save, &mut clock, &mut camera, &mut planets, &mut selected_body);
+                simulation.replace_bodies(planets.iter().map(CelestialBody::ephemeris).collect());
+                println!("Sesión reanudada en el día {:.1}", clock.days());
+            }
+            Err(err) => println!("No se pudo reanudar: {}", err),
+        }
+    }
+    let missing_body = scenario
+        .as_ref()
+        .and_then(|run| run.scenario.bodies().find(|name| !planets.iter().any(|planet| planet.name == *name)).map(str::to_string));
+    if let Some(missing) = missing_body {
+        println!("El escenario usa el cuerpo {}, que no está en la escena", missing);
+        scenario = None;
+    }
+    if let Some(run) = scenario.as_ref() {
+        selected_body = start_scenario(&run.scenario, &planets, &mut clock, &mut camera);
+        show_predictions = true;
+        println!("Escenario: {}", run.scenario.title);
+        if let Some(prompt) = run.prompt() {
+            println!("{}", prompt);
+        }
+    }
+    let mut session = SessionStats::new(clock.time);
+    let status = StatusOutput::new(options.status_output);
+    // Último estado comunicado, para avisar solo de los cambios
+    let mut reported_selection = None;
+    let mut reported_orbit = None;
+    let mut last_collision = None;
+    let mut previous_camera_position = camera.position;
+
+    let audio = match RaylibAudio::init_audio_device() {
+        Ok(audio) => Some(audio),
+        Err(err) => {
+            println!("Audio no disponible: {}", err);
+            None
+        }
+    };
+    let mut entry_roar = audio.as_ref().map(EntryRoar::new);
+
+    let mut telemetry = options.telemetry.as_deref().and_then(|destination| {
+        let format = options
+            .telemetry_format
+            .unwrap_or_else(|| TelemetryFormat::for_destination(destination));
+        match TelemetryWriter::open(destination, format) {
+            Ok(writer) => {
+                println!("Telemetría ({:?}) -> {}", format, destination);
+                Some(writer)
+            }
+            Err(err) => {
+                println!("No se pudo abrir la telemetría en {}: {}", destination, err);
+                None
+            }
+        }
+    });
+
+    #[cfg(feature = "remote")]
+    let remote = options.remote.as_deref().and_then(|address| match RemoteServer::start(address) {
+        Ok(server) => {
+            println!("Control remoto escuchando en {}", address);
+            Some(server)
+        }
+        Err(err) => {
+            println!("No se pudo iniciar el control remoto en {}: {}", address, err);
+            None
+        }
+    });
+    #[cfg(not(feature = "remote"))]
+    if options.remote.is_some() {
+        println!("Control remoto no disponible: compila con --features remote");
+    }
+
+    let net_server = options.serve.as_deref().and_then(|address| match NetServer::start(address) {
+        Ok(server) => {
+            println!("Sirviendo la simulación a observadores en {}", address);
+            Some(server)
+        }
+        Err(err) => {
+            println!("No se pudo servir la simulación en {}: {}", address, err);
+            None
+        }
+    });
+    let mut net_observer = options.observe.as_deref().and_then(|address| match NetObserver::connect(address) {
+        Ok(observer) => {
+            println!("Observando la simulación de {}", address);
+            Some(observer)
+        }
+        Err(err) => {
+            println!("No se pudo conectar con {}: {}", address, err);
+            None
+        }
+    });
+    // Nave del servidor en modo observador, desde el último estado recibido
+    let mut observed_ship: Option<ShipState> = None;
+    // Cuerpos y rocas en sus posiciones del frame: se rehace una vez por frame, tras moverlos, y
+    // la usan la selección con el ratón y, en el frame siguiente, los choques de la nave
+    let mut obstacles = scene_bvh(&planets, belt.as_ref(), clock.time as f32);
+
+    println!("=== Iniciando renderizado ===\n");
+
+    while window.is_open() && !quit {
+        let frame_delta = pacer.begin_frame();
+
+        let menu_values = MenuValues {
+            scene: current_scene,
+            resolution_scale,
+            resolution: (framebuffer_width, framebuffer_height),
+            deferred: framebuffer.gbuffer.is_some(),
+            ambient_occlusion: ambient_occlusion_mode,
+            checkerboard: checkerboard_mode,
+            log_depth: logarithmic_depth,
+            anaglyph: anaglyph_mode,
+            toon: toon_mode,
+            volume,
+        };
+        let menu_command = pause_menu.handle_input(&window, &menu_values);
+        // Escena a la que cambiar este frame, ya cargada
+        let mut next_scene: Option<(SceneSource, SceneFile)> = None;
+        match menu_command {
+            Some(MenuCommand::LoadScene(index)) => match open_scene(&scene_paths[index], options.scale_mode) {
+                Ok(loaded) => next_scene = Some((SceneSource::File(index), loaded)),
+                Err(err) => println!("No se pudo cargar la escena {}", err),
+            },
+            Some(MenuCommand::SetResolutionScale(index)) => {
+                resolution_scale = index;
+                let scale = RESOLUTION_SCALES[index];
+                framebuffer_width = ((options.resolution.0 as f32 * scale) as usize).max(1);
+                framebuffer_height = ((options.resolution.1 as f32 * scale) as usize).max(1);
+                let deferred = framebuffer.gbuffer.is_some();
+                framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+                framebuffer.set_background_color(0x000011);
+                if deferred {
+                    framebuffer.gbuffer = Some(GBuffer::new(framebuffer_width, framebuffer_height));
+                }
+                skybox = StarField::random(framebuffer_width, framebuffer_height, &scene.stars);
+                reprojection = None;
+                println!("Resolución interna: {}x{}", framebuffer_width, framebuffer_height);
+            }
+            Some(MenuCommand::SetVolume(level)) => volume = level,
+            Some(MenuCommand::Quit) => quit = true,
+            _ => {}
+        }
+        session.record_frame();
+        // Con el menú abierto el mundo y la nave se quedan quietos
+        if let Some(galaxy) = galaxy.as_ref().filter(|_| !pause_menu.open && hyperspace.is_none()) {
+            if let Some(destination) = galaxy_map.handle_input(&window, galaxy, galaxy_system) {
+                println!("Salto a {} ({:.1} años luz)", galaxy.systems[destination].name, galaxy.distance(galaxy_system, destination));
+                hyperspace = Some(Hyperspace::new(destination));
+            }
+        }
+        // Con el menú o el mapa de la galaxia abiertos el mundo y la nave se quedan quietos
+        let interface_open = pause_menu.open || galaxy_map.open;
+        let delta_time = if interface_open { 0.0 } else { frame_delta };
+        let keys = Hotkeys { window: &window, enabled: !interface_open };
+
+        fps_counter += 1;
+        if fps_timer.elapsed().as_secs() >= 1 {
+            match pacer.take_dropped() {
+                0 => println!("FPS: {}", fps_counter),
+                dropped => println!("FPS: {} ({} frames perdidos)", fps_counter, dropped),
+            }
+            fps_counter = 0;
+            fps_timer = Instant::now();
+
+            memory_report = MemoryReport::new();
+            memory_report.add("Mallas", sphere_obj.memory_bytes() + ywing_obj.memory_bytes());
+            let lod_bytes = planets
+                .iter()
+                .map(|planet| planet.mesh.memory_bytes() + planet.close_up.as_ref().map_or(0, |close_up| close_up.mesh.memory_bytes()))
+                .sum::<usize>()
+                + ywing_mesh.memory_bytes()
+                + rock_mesh.memory_bytes();
+            memory_report.add("LOD", lod_bytes);
+            memory_report.add("Texturas", texture_cache.memory_used());
+            let particle_bytes = planets.iter().map(|planet| planet.plumes.memory_bytes()).sum::<usize>() + solar_wind.memory_bytes();
+            memory_report.add("Partículas", particle_bytes);
+            memory_report.add("Framebuffers", framebuffer.memory_bytes() + upscaler.memory_bytes());
+            memory_report.add("Cinturón y estrellas", belt.as_ref().map_or(0, AsteroidBelt::memory_bytes) + skybox.memory_bytes());
+            memory_budget.check(&memory_report);
+        }
+
+        let collision = match surface_camera.as_mut() {
+            Some(surface) if !interface_open => {
+                surface.update(&window, delta_time);
+                None
+            }
+            None if !interface_open => camera.update(&window, delta_time, &planets, &obstacles),
+            _ => None,
+        };
+        // Solo el primer frame de cada choque
+        if let Some(object) = collision.filter(|_| collision != last_collision) {
+            let body = match object {
+                SceneObject::Body(index) => planets[index].name.as_str(),
+                SceneObject::Asteroid(_) => "asteroide",
+            };
+            status.emit(clock.days(), StatusEvent::Collision { body });
+        }
+        if let Some(run) = scenario.as_mut() {
+            if collision.is_some() && collision != last_collision {
+                println!("Choque: el escenario vuelve a empezar");
+                run.restart();
+                start_scenario(&run.scenario, &planets, &mut clock, &mut camera);
+                predictor.clear();
+            }
+            let ship_speed = camera.velocity.norm();
+            let reading = |name: &str| {
+                let planet = planets.iter().find(|planet| planet.name == name)?;
+                let distance = (planet.position - camera.position).norm();
+                let escape_speed = (2.0 * GRAVITATIONAL_CONSTANT * planet.mass / distance as f32).sqrt();
+                Some(BodyReading { distance, escape_speed })
+            };
+            if run.update(delta_time, ship_speed, reading).is_some() {
+                match run.prompt() {
+                    Some(prompt) => println!("{}", prompt),
+                    None => println!("Escenario completado: {}", run.scenario.title),
+                }
+            }
+            if run.finished() {
+                scenario = None;
+            }
+        }
+        last_collision = collision;
+        for planet in &planets {
+            if (planet.position - camera.position).norm() < (planet.scale * VISIT_RADII) as f64 {
+                session.record_visit(&planet.name);
+            }
+        }
+
+        if keys.pressed(Key::V, minifb::KeyRepeat::No) {
+            surface_camera = match (surface_camera.take(), selected_body) {
+                (None, Some(index)) => {
+                    println!("Cámara en la superficie de {}", planets[index].name);
+                    Some(SurfaceCamera::new(index))
+                }
+                (None, None) => {
+                    println!("Selecciona un cuerpo (F) para la cámara de superficie");
+                    None
+                }
+                (Some(_), _) => None,
+            };
+        }
+
+        if keys.pressed(Key::T, minifb::KeyRepeat::No) {
+            match day_tour.take() {
+                Some(tour) => {
+                    tour.finish(&mut clock);
+                    surface_camera = None;
+                }
+                None => {
+                    if let Some(eclipse) = eclipse_tour.take() {
+                        eclipse.finish(&mut clock);
+                    }
+                    match DayTour::start(&planets, selected_body, &mut clock) {
+                        Some(tour) => {
+                            println!("Un día en {}", planets[tour.body].name);
+                            let mut surface = SurfaceCamera::new(tour.body);
+                            surface.latitude = 0.0;
+                            surface_camera = Some(surface);
+                            selected_body = Some(tour.body);
+                            day_tour = Some(tour);
+                        }
+                        None => println!("No hay ningún cuerpo que gire para seguir su día"),
+                    }
+                }
+            }
+        }
+        // Termina al completar la vuelta o si se deja la cámara de superficie
+        let tour_over = day_tour.as_ref().is_some_and(|tour| {
+            clock.time >= tour.end || surface_camera.as_ref().is_none_or(|surface| surface.body != tour.body)
+        });
+        if tour_over {
+            if let Some(tour) = day_tour.take() {
+                println!("Fin del día en {}", planets[tour.body].name);
+                tour.finish(&mut clock);
+            }
+        }
+
+        if keys.pressed(Key::F5, minifb::KeyRepeat::No) {
+            match eclipse_tour.take() {
+                Some(tour) => {
+                    tour.finish(&mut clock);
+                    surface_camera = None;
+                }
+                None => {
+                    if let Some(day) = day_tour.take() {
+                        day.finish(&mut clock);
+                    }
+                    match EclipseTour::start(&planets, &mut clock) {
+                        Ok((tour, surface)) => {
+                            let Eclipse { observer, occluder, .. } = tour.eclipse;
+                            println!("Eclipse de sol desde {}: {} tapa la estrella", planets[observer].name, planets[occluder].name);
+                            for planet in &mut planets {
+                                let state = planet.ephemeris().state_at(clock.time);
+                                planet.apply_orbital_state(&state);
+                            }
+                            simulation.replace_bodies(planets.iter().map(CelestialBody::ephemeris).collect());
+                            surface_camera = Some(surface);
+                            selected_body = Some(observer);
+                            eclipse_tour = Some(tour);
+                        }
+                        Err(reason) => println!("{}", reason),
+                    }
+                }
+            }
+        }
+        if let Some(tour) = eclipse_tour.as_ref() {
+            tour.pace(&mut clock);
+        }
+        // Igual que el día en Terra: termina con el eclipse o si se deja la cámara de superficie
+        let eclipse_over = eclipse_tour.as_ref().is_some_and(|tour| {
+            tour.finished(clock.time) || surface_camera.as_ref().is_none_or(|surface| surface.body != tour.eclipse.observer)
+        });
+        if eclipse_over {
+            if let Some(tour) = eclipse_tour.take() {
+                println!("Fin del eclipse");
+                tour.finish(&mut clock);
+            }
+        }
+
+        if keys.pressed(Key::N, minifb::KeyRepeat::No) {
+            facts_visible = !facts_visible;
+        }
+        // Al cambiar de cuerpo la ficha vuelve al primer dato
+        if fact_page.0 != selected_body {
+            fact_page = (selected_body, 0);
+        }
+        // Con el inspector abierto, RePág/AvPág son suyas
+        if let Some(index) = selected_body.filter(|_| facts_visible && !inspector.open) {
+            let count = planets[index].facts.len().max(1);
+            if keys.pressed(Key::PageDown, minifb::KeyRepeat::No) {
+                fact_page.1 = (fact_page.1 + 1) % count;
+            }
+            if keys.pressed(Key::PageUp, minifb::KeyRepeat::No) {
+                fact_page.1 = (fact_page.1 + count - 1) % count;
+            }
+        }
+
+        if keys.pressed(Key::F, minifb::KeyRepeat::No) {
+            let index = (selected_body.unwrap_or(0) + 1) % planets.len();
+            let distance = (planets[index].position - camera.position).norm() as f32;
+            camera.warp_to(planets[index].position, WARP_ARRIVAL_DISTANCE);
+            selected_body = Some(index);
+            status.emit(clock.days(), StatusEvent::Warp { body: &planets[index].name, distance });
+        }
+
+        let control = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        let jump_days = if control { 365.0 } else { 30.0 };
+        let jump = if keys.pressed(Key::Period, minifb::KeyRepeat::Yes) {
+            jump_days
+        } else if keys.pressed(Key::Comma, minifb::KeyRepeat::Yes) {
+            -jump_days
+        } else {
+            0.0
+        };
+        if jump != 0.0 {
+            clock.jump_days(jump);
+            for planet in &mut planets {
+                let state = planet.ephemeris().state_at(clock.time);
+                planet.apply_orbital_state(&state);
+            }
+            // Descarta las instantáneas en camino, que son de antes del salto
+            simulation.replace_bodies(planets.iter().map(CelestialBody::ephemeris).collect());
+        }
+
+        if keys.pressed(Key::J, minifb::KeyRepeat::No) {
+            if let Some((conjunction, _)) = notification.take() {
+                surface_camera = None;
+                view_conjunction(&mut camera, &planets, &conjunction);
+            }
+        }
+
+        if keys.pressed(Key::I, minifb::KeyRepeat::No) {
+            match selected_body {
+                Some(index) => {
+                    camera.enter_orbit(index, &planets);
+                    println!("En órbita alrededor de {}", planets[index].name);
+                }
+                None => println!("Selecciona un cuerpo (F) antes de entrar en órbita"),
+            }
+        }
+
+        if keys.pressed(Key::H, minifb::KeyRepeat::No) && solo_restore.is_none() {
+            if let Some(index) = selected_body {
+                planets[index].hidden = !planets[index].hidden;
+            }
+        }
+
+        if keys.pressed(Key::K, minifb::KeyRepeat::No) {
+            match (solo_restore.take(), selected_body) {
+                (Some(previous), _) => {
+                    for (planet, hidden) in planets.iter_mut().zip(previous) {
+                        planet.hidden = hidden;
+                    }
+                    camera.orbit = None;
+                }
+                (None, Some(index)) => {
+                    solo_restore = Some(planets.iter().map(|planet| planet.hidden).collect());
+                    for (other, planet) in planets.iter_mut().enumerate() {
+                        planet.hidden = other != index;
+                    }
+                    camera.enter_orbit(index, &planets);
+                }
+                (None, None) => println!("Selecciona un cuerpo (F) para el modo solo"),
+            }
+        }
+
+        if keys.pressed(Key::B, minifb::KeyRepeat::No) {
+            show_barycenter = !show_barycenter;
+        }
+
+        if keys.pressed(Key::M, minifb::KeyRepeat::No) {
+            camera.manual_speed = match camera.manual_speed {
+                Some(_) => None,
+                None => Some(camera.speed),
+            };
+            println!("Velocidad {}", if camera.manual_speed.is_some() { "manual" } else { "automática" });
+        }
+        // [ y ] ajustan la velocidad manual (y la activan si estaba en automático)
+        for (key, factor) in [(Key::LeftBracket, 1.0 / 1.5), (Key::RightBracket, 1.5)] {
+            if keys.pressed(key, minifb::KeyRepeat::Yes) {
+                let speed = camera.manual_speed.unwrap_or(camera.speed) * factor;
+                camera.manual_speed = Some(speed.clamp(MIN_SHIP_SPEED, camera.cruise_speed * MAX_SPEED_FACTOR * 4.0));
+            }
+        }
+
+        if keys.pressed(Key::O, minifb::KeyRepeat::No) {
+            observatory_mode = !observatory_mode;
+        }
+
+        if keys.pressed(Key::Key3, minifb::KeyRepeat::No) || menu_command == Some(MenuCommand::ToggleAnaglyph) {
+            anaglyph_mode = !anaglyph_mode;
+            println!("Anaglifo {}", if anaglyph_mode { "activado" } else { "desactivado" });
+        }
+
+        if keys.pressed(Key::R, minifb::KeyRepeat::No) || menu_command == Some(MenuCommand::ToggleToon) {
+            toon_mode = !toon_mode;
+            reprojection = None;
+            println!("Estilo cómic {}", if toon_mode { "activado" } else { "desactivado" });
+        }
+
+        if keys.pressed(Key::F1, minifb::KeyRepeat::No) {
+            wireframe_mode = !wireframe_mode;
+            reprojection = None;
+            println!("Modo alambre {}", if wireframe_mode { "activado" } else { "desactivado" });
+        }
+
+        if keys.pressed(Key::F2, minifb::KeyRepeat::No) {
+            show_ecliptic_grid = !show_ecliptic_grid;
+        }
+
+        if keys.pressed(Key::G, minifb::KeyRepeat::No) {
+            show_gizmo = !show_gizmo;
+        }
+
+        if keys.pressed(Key::X, minifb::KeyRepeat::No) {
+            show_predictions = !show_predictions;
+            if !show_predictions {
+                predictor.clear();
+            }
+        }
+
+        if keys.pressed(Key::U, minifb::KeyRepeat::No) {
+            measurement = match measurement {
+                Some(_) => None,
+                None => Some(Measurement::new(selected_body)),
+            };
+        }
+
+        if keys.pressed(Key::F3, minifb::KeyRepeat::No) {
+            inspector.open = !inspector.open;
+        }
+        if inspector.open {
+            if keys.pressed(Key::PageDown, minifb::KeyRepeat::Yes) {
+                inspector.step(planets.len(), true);
+            }
+            if keys.pressed(Key::PageUp, minifb::KeyRepeat::Yes) {
+                inspector.step(planets.len(), false);
+            }
+            if let Some(index) = inspector.cursor(planets.len()).filter(|_| keys.pressed(Key::Enter, minifb::KeyRepeat::No)) {
+                let distance = (planets[index].position - camera.position).norm() as f32;
+                surface_camera = None;
+                camera.warp_to(planets[index].position, WARP_ARRIVAL_DISTANCE);
+                camera.enter_orbit(index, &planets);
+                selected_body = Some(index);
+                status.emit(clock.days(), StatusEvent::Warp { body: &planets[index].name, distance });
+            }
+        }
+
+        if keys.pressed(Key::Y, minifb::KeyRepeat::No) {
+            path_recorder = match path_recorder.take() {
+                None => Some(PathRecorder::default()),
+                Some(recorder) => {
+                    let seconds = recorder.elapsed();
+                    match recorder.finish() {
+                        Ok(path) => {
+                            let file = format!("recorrido_{:06}.toml", frame_count);
+                            let header = "# Recorrido grabado en vuelo (Y). Se reproduce con --render-video <salida> --camera-path <este archivo>";
+                            match path.save(&file, header) {
+                                Ok(()) => println!("Recorrido de {:.1} s ({} puntos de paso) guardado en {}", seconds, path.keyframes.len(), file),
+                                Err(err) => println!("{}", err),
+                            }
+                        }
+                        Err(err) => println!("No se guarda el recorrido: {}", err),
+                    }
+                    None
+                }
+            };
+        }
+
+        if keys.pressed(Key::F4, minifb::KeyRepeat::No) {
+            shader_panel.open = !shader_panel.open;
+        }
+        if let Some(index) = selected_body.filter(|_| shader_panel.open) {
+            if keys.pressed(Key::End, minifb::KeyRepeat::Yes) {
+                shader_panel.step(true);
+            }
+            if keys.pressed(Key::Home, minifb::KeyRepeat::Yes) {
+                shader_panel.step(false);
+            }
+            if keys.pressed(Key::Insert, minifb::KeyRepeat::Yes) {
+                shader_panel.adjust(&mut planets[index].shader_params, 1.0);
+            }
+            if keys.pressed(Key::Delete, minifb::KeyRepeat::Yes) {
+                shader_panel.adjust(&mut planets[index].shader_params, -1.0);
+            }
+            if keys.pressed(Key::Backspace, minifb::KeyRepeat::No) {
+                let planet = &planets[index];
+                let block = shader_panel::scene_block(&planet.name, &planet.shader_params);
+                let path = shader_panel::dump_path(&planet.name);
+                println!("{}", block);
+                match std::fs::write(&path, &block) {
+                    Ok(()) => println!("Parámetros de shader guardados en {}", path),
+                    Err(err) => println!("No se pudo guardar {}: {}", path, err),
+                }
+            }
+        }
+
+        if keys.pressed(Key::L, minifb::KeyRepeat::No) || menu_command == Some(MenuCommand::ToggleDeferred) {
+            framebuffer.gbuffer = match framebuffer.gbuffer.take() {
+                Some(_) => None,
+                None => Some(GBuffer::new(framebuffer_width, framebuffer_height)),
+            };
+            println!("Sombreado {}", if framebuffer.gbuffer.is_some() { "diferido" } else { "directo" });
+            reprojection = None;
+        }
+
+        if keys.pressed(Key::Q, minifb::KeyRepeat::No) || menu_command == Some(MenuCommand::ToggleAmbientOcclusion) {
+            ambient_occlusion_mode = !ambient_occlusion_mode;
+            reprojection = None;
+            println!("Oclusión ambiental {}", if ambient_occlusion_mode { "activada" } else { "desactivada" });
+            if ambient_occlusion_mode && framebuffer.gbuffer.is_none() {
+                println!("La oclusión ambiental usa el G-buffer: actívalo con L");
+            }
+        }
+
+        if keys.pressed(Key::Z, minifb::KeyRepeat::No) || menu_command == Some(MenuCommand::ToggleLogDepth) {
+            logarithmic_depth = !logarithmic_depth;
+            reprojection = None;
+            println!("Profundidad {}", if logarithmic_depth { "logarítmica" } else { "en perspectiva" });
+        }
+
+        if keys.pressed(Key::C, minifb::KeyRepeat::No) || menu_command == Some(MenuCommand::ToggleCheckerboard) {
+            checkerboard_mode = !checkerboard_mode;
+            println!("Renderizado en ajedrez {}", if checkerboard_mode { "activado" } else { "desactivado" });
+        }
+
+        if keys.pressed(Key::P, minifb::KeyRepeat::No) {
+            clock.toggle_pause();
+            println!("Simulación {}", if clock.paused { "en pausa" } else { "reanudada" });
+        }
+        if keys.pressed(Key::Equal, minifb::KeyRepeat::No)
+            || keys.pressed(Key::NumPadPlus, minifb::KeyRepeat::No)
+        {
+            clock.speed_up();
+            println!("Escala de tiempo: {}x", clock.time_scale);
+        }
+        if keys.pressed(Key::Minus, minifb::KeyRepeat::No)
+            || keys.pressed(Key::NumPadMinus, minifb::KeyRepeat::No)
+        {
+            clock.slow_down();
+            println!("Escala de tiempo: {}x", clock.time_scale);
+        }
+
+        #[cfg(feature = "remote")]
+        let mut screenshot_requests = Vec::new();
+        #[cfg(feature = "remote")]
+        for request in remote.iter().flat_map(RemoteServer::pending) {
+            if let RemoteCommand::Screenshot { .. } = request.command {
+                screenshot_requests.push(request);
+                continue;
+            }
+            if let RemoteCommand::LoadScene { scene } = &request.command {
+                let loaded = find_scene(&scene_paths, scene)
+                    .and_then(|index| Ok((index, open_scene(&scene_paths[index], options.scale_mode)?)));
+                match loaded {
+                    Ok((index, loaded)) => {
+                        request.respond(Ok(format!("escena {}", scene::scene_name(&scene_paths[index]))));
+                        next_scene = Some((SceneSource::File(index), loaded));
+                    }
+                    Err(err) => request.respond(Err(err)),
+                }
+                continue;
+            }
+            let result = apply_remote_command(&request.command, &mut clock, &mut camera, &planets, &mut selected_body);
+            request.respond(result);
+        }
+
+        // El observador toma el reloj del servidor y lo hace avanzar por su cuenta entre estados
+        let net_state = net_observer.as_mut().and_then(NetObserver::latest);
+        if let Some(state) = &net_state {
+            clock.time = state.time;
+            clock.time_scale = state.time_scale;
+            clock.paused = state.paused;
+            observed_ship = Some(state.ship.clone());
+        }
+
+        let sim_delta = clock.advance(delta_time);
+        // Las órbitas usan `clock.time` en f64; animaciones y shaders se conforman con f32
+        let sim_time = clock.time as f32;
+
+        // El salto cambia de sistema a mitad de la animación, con la pantalla en blanco
+        if let Some(jump) = hyperspace.as_mut() {
+            if jump.update(frame_delta) {
+                let destination = jump.destination;
+                let generated = galaxy.as_ref().and_then(|galaxy| galaxy.scene(destination));
+                let loaded = match generated {
+                    Some(mut generated) => {
+                        generated.apply_scale_mode(options.scale_mode.unwrap_or(generated.scale_mode));
+                        Ok((SceneSource::Galaxy, generated))
+                    }
+                    None => open_scene(&scene_paths[0], options.scale_mode).map(|home| (SceneSource::File(0), home)),
+                };
+                match loaded {
+                    Ok(loaded) => {
+                        galaxy_system = destination;
+                        next_scene = Some(loaded);
+                    }
+                    Err(err) => println!("No se pudo cargar el sistema de origen {}", err),
+                }
+            }
+            if jump.finished() {
+                hyperspace = None;
+            }
+        }
+
+        // Cambio de escena: todo lo que depende de los cuerpos se crea de nuevo, como al arrancar
+        if let Some((source, loaded)) = next_scene {
+            if let Some(tour) = day_tour.take() {
+                tour.finish(&mut clock);
+            }
+            if let Some(tour) = eclipse_tour.take() {
+                tour.finish(&mut clock);
+            }
+            planets = loaded
+                .bodies
+                .iter()
+                .map(|config| {
+                    let mut body = CelestialBody::from_config(config, body_mesh(config, &sphere_obj));
+                    let state = body.ephemeris().state_at(clock.time);
+                    body.apply_orbital_state(&state);
+                    body
+                })
+                .collect();
+            belt = loaded.belt.as_ref().map(AsteroidBelt::new);
+            rock_mesh = IndexedMesh::from_triangles(&mesh::simplify_mesh(&belt_rock_mesh(loaded.belt.as_ref(), &sphere_obj), ROCK_TRIANGLES).0);
+            skybox = StarField::random(framebuffer_width, framebuffer_height, &loaded.stars);
+            simulation.replace_bodies(planets.iter().map(CelestialBody::ephemeris).collect());
+
+            let manual_speed = camera.manual_speed;
+            camera = SpaceshipCamera::new(DVec3::new(0.0, 100.0, 300.0));
+            camera.cruise_speed = ship_speed(&planets);
+            camera.manual_speed = manual_speed;
+            previous_camera_position = camera.position;
+            far = far_plane(&planets);
+            selected_body = None;
+            if scenario.take().is_some() {
+                println!("Escenario abandonado");
+            }
+            surface_camera = None;
+            solo_restore = None;
+            fact_page = (None, 0);
+            last_collision = None;
+            reprojection = None;
+            conjunctions.reset();
+            notification = None;
+            predictor.clear();
+            measurement = None;
+            entry_heating = EntryHeating::new();
+            solar_wind = SolarWind::new();
+
+            let name = match source {
+                SceneSource::File(index) => {
+                    scene_watcher = Some(SceneWatcher::new(&scene_paths[index]));
+                    current_scene = Some(index);
+                    // Un archivo de escena siempre cuenta como el sistema de origen
+                    galaxy_system = 0;
+                    scene::scene_name(&scene_paths[index]).to_string()
+                }
+                SceneSource::Galaxy => {
+                    scene_watcher = None;
+                    current_scene = None;
+                    loaded.bodies[0].name.clone()
+                }
+            };
+            grid_spacing = loaded.grid.spacing;
+            scene = loaded;
+            println!("Escena {}: {} cuerpos", name, planets.len());
+        }
+
+        if let Some(result) = scene_watcher.as_mut().and_then(SceneWatcher::poll) {
+            match result {
+                Ok(scene) if scene.bodies.is_empty() => println!("La escena recargada no tiene cuerpos; se ignora"),
+                Ok(mut scene) => {
+                    scene.apply_scale_mode(options.scale_mode.unwrap_or(scene.scale_mode));
+                    let previous_names: Vec<String> = planets.iter().map(|planet| planet.name.clone()).collect();
+                    if let Some(tour) = day_tour.take() {
+                        tour.finish(&mut clock);
+                    }
+                    if let Some(tour) = eclipse_tour.take() {
+                        tour.finish(&mut clock);
+                    }
+                    reload_bodies(&mut planets, &scene, &sphere_obj, clock.time);
+                    belt = scene.belt.as_ref().map(AsteroidBelt::new);
+                    rock_mesh = IndexedMesh::from_triangles(&mesh::simplify_mesh(&belt_rock_mesh(scene.belt.as_ref(), &sphere_obj), ROCK_TRIANGLES).0);
+                    skybox = StarField::random(framebuffer_width, framebuffer_height, &scene.stars);
+                    grid_spacing = scene.grid.spacing;
+                    camera.cruise_speed = ship_speed(&planets);
+                    far = far_plane(&planets);
+                    simulation.replace_bodies(planets.iter().map(CelestialBody::ephemeris).collect());
+                    reprojection = None;
+                    conjunctions.reset();
+                    notification = None;
+
+                    // Los índices guardados apuntan a la lista anterior: se traducen por nombre
+                    let remap = |index: usize| planets.iter().position(|planet| planet.name == previous_names[index]);
+                    selected_body = selected_body.and_then(remap);
+                    measurement = measurement.and_then(|measurement| match measurement.from {
+                        Endpoint::Body(index) => Some(Measurement { from: Endpoint::Body(remap(index)?) }),
+                        Endpoint::Ship => Some(measurement),
+                    });
+                    surface_camera = surface_camera.take().and_then(|mut surface| {
+                        surface.body = remap(surface.body)?;
+                        surface.target = surface.target.and_then(remap);
+                        Some(surface)
+                    });
+                    camera.orbit = camera.orbit.take().and_then(|mut orbit| {
+                        orbit.parent = remap(orbit.parent)?;
+                        Some(orbit)
+                    });
+                    // El modo solo guarda la visibilidad de la lista anterior: se abandona
+                    if solo_restore.take().is_some() {
+                        planets.iter_mut().for_each(|planet| planet.hidden = false);
+                    }
+                    println!("Escena recargada: {} cuerpos", planets.len());
+                }
+                Err(err) => println!("No se pudo recargar la escena: {}", err),
+            }
+        }
+
+        if let Some(snapshot) = simulation.latest() {
+            for (planet, state) in planets.iter_mut().zip(&snapshot.bodies) {
+                planet.apply_orbital_state(state);
+            }
+            camera.position += snapshot.ship_correction;
+        }
+        for body in net_state.iter().flat_map(|state| &state.bodies) {
+            if let Some(planet) = planets.iter_mut().find(|planet| planet.name == body.name) {
+                planet.position = DVec3::from(body.position);
+                planet.rotation = Vec3::from(body.rotation);
+            }
+        }
+        obstacles = scene_bvh(&planets, belt.as_ref(), sim_time);
+        simulation.submit(SimulationStep { time: clock.time, ship_position: camera.position });
+
+        camera.follow_orbit(&planets, sim_delta);
+
+        if show_predictions {
+            predictor.submit(PredictionRequest {
+                time: clock.time,
+                time_scale: if clock.paused { 0.0 } else { clock.time_scale as f64 },
+                horizon: PREDICTION_SECONDS,
+                bodies: planets.iter().map(|planet| (planet.ephemeris(), planet.mass)).collect(),
+                gravity: GRAVITATIONAL_CONSTANT,
+                ship_position: camera.position,
+                ship_velocity: camera.velocity,
+            });
+        }
+
+        if selected_body != reported_selection {
+            let body = selected_body.map(|index| planets[index].name.as_str());
+            status.emit(clock.days(), StatusEvent::Selected { body });
+            reported_selection = selected_body;
+        }
+        let orbit = camera.orbit.as_ref().map(|orbit| orbit.parent);
+        if orbit != reported_orbit {
+            let body = orbit.map(|index| planets[index].name.as_str());
+            status.emit(clock.days(), StatusEvent::Orbit { body });
+            reported_orbit = orbit;
+        }
+
+        for conjunction in conjunctions.update(&planets, camera.position) {
+            println!("{}", conjunction_text(&conjunction, &planets));
+            status.emit(
+                clock.days(),
+                StatusEvent::Conjunction {
+                    first: &planets[conjunction.first].name,
+                    second: &planets[conjunction.second].name,
+                    vantage: match conjunction.vantage {
+                        Vantage::Sun => "sol",
+                        Vantage::Ship => "nave",
+                    },
+                    degrees: conjunction.separation.to_degrees(),
+                },
+            );
+            notification = Some((conjunction, NOTIFICATION_SECONDS));
+        }
+        notification = notification
+            .map(|(conjunction, remaining)| (conjunction, remaining - delta_time))
+            .filter(|(_, remaining)| *remaining > 0.0);
+        for planet in &mut planets {
+            planet.update_eruptions(sim_time, sim_delta);
+        }
+
+        entry_heating.update(
+            Vec3::zeros(),
+            camera.velocity,
+            camera.speed,
+            planets.iter().map(|planet| (narrow(planet.position - camera.position), planet.scale)),
+            delta_time,
+        );
+        if let Some(roar) = entry_roar.as_mut() {
+            roar.volume = volume;
+            roar.update(entry_heating.intensity);
+        }
+
+        let altitude = planets
+            .iter()
+            .map(|planet| (planet.position - camera.position).norm() as f32 - planet.scale)
+            .fold(f32::INFINITY, f32::min);
+        let away_from_sun = narrow(camera.position - planets[0].position).try_normalize(1e-6).unwrap_or(Vec3::zeros());
+        solar_wind.update(narrow(camera.position - previous_camera_position), away_from_sun, altitude, delta_time);
+        previous_camera_position = camera.position;
+
+        let sun_position = narrow(planets[0].position);
+        light.position = Vector3::new(sun_position.x, sun_position.y, sun_position.z);
+
+        let render_start = Instant::now();
+        // El historial solo es válido para la vista única; las vistas múltiples sombrean todo
+        let checkerboard = checkerboard_mode && !observatory_mode && !anaglyph_mode;
+        framebuffer.set_checkerboard(checkerboard.then_some((frame_count % 2) as usize));
+        if let Some(gbuffer) = framebuffer.gbuffer.as_mut() {
+            gbuffer.ambient_occlusion = ambient_occlusion_mode;
+        }
+        framebuffer.reset_reprojection();
+        framebuffer.clear();
+        skybox.render(&mut framebuffer);
+
+        let (camera_eye, camera_forward, camera_up, camera_right, shake) = match surface_camera.as_ref() {
+            Some(surface) => {
+                let (eye, forward, up) = surface.view(&planets);
+                (eye, forward, up, forward.cross(&up).normalize(), Vec3::zeros())
+            }
+            None => {
+                let shake = entry_heating.shake_offset(camera.get_right(), camera.get_up());
+                (camera.position + widen(shake), camera.get_forward(), camera.get_up(), camera.get_right(), shake)
+            }
+        };
+        let camera_target = camera_eye + widen(camera_forward * 10.0 + shake);
+        if let Some(recorder) = &mut path_recorder {
+            // Sin la sacudida de la reentrada, que en el vídeo solo sería ruido
+            recorder.record(delta_time, clock.time, camera_eye - widen(shake), widen(camera_forward));
+        }
+        // Cuerpo visto de cerca (desde la superficie, en órbita o aterrizando): malla adaptativa y
+        // atmósfera. Al aterrizar es además el suelo con el que choca la nave
+        let close_body = surface_camera
+            .as_ref()
+            .map(|surface| surface.body)
+            .or(camera.orbit.as_ref().map(|orbit| orbit.parent))
+            .or(camera.landing.as_ref().map(|landing| landing.body));
+        for (index, planet) in planets.iter_mut().enumerate() {
+            planet.update_close_up((close_body == Some(index)).then_some(camera_eye));
+        }
+        let light_position = planets[0].position;
+
+        let ship_offset = camera.get_forward() * 15.0 + camera.get_right() * -3.0 + camera.get_up() * -2.0;
+        let ship = match &observed_ship {
+            Some(ship) => ship.clone(),
+            None => ShipState {
+                position: (camera.position + widen(ship_offset)).into(),
+                rotation: [-camera.pitch, camera.yaw + PI, 0.0],
+            },
+        };
+        if let Some(server) = &net_server {
+            server.broadcast(&NetState {
+                time: clock.time,
+                time_scale: clock.time_scale,
+                paused: clock.paused,
+                bodies: planets
+                    .iter()
+                    .map(|planet| BodyState { name: planet.name.clone(), position: planet.position.into(), rotation: planet.rotation.into() })
+                    .collect(),
+                ship: ship.clone(),
+            });
+        }
+        let models = SceneModels {
+            ship: ShipModel {
+                mesh: &ywing_mesh,
+                radius: ywing_radius,
+                position: DVec3::from(ship.position),
+                scale: 2.5,
+                rotation: Vec3::from(ship.rotation),
+                shader_params: &ship_shader_params,
+                maps: SurfaceMaps { albedo: ship_texture.as_deref(), normal: ship_normal_map.as_deref() },
+                culling: ywing_culling,
+            },
+            belt: belt.as_ref().map(|belt| BeltModel {
+                belt,
+                mesh: &rock_mesh,
+                shader_params: &rock_shader_params,
+                culling: sphere_culling,
+            }),
+            body_culling: sphere_culling,
+            culling: options.culling,
+        };
+
+        let ecliptic_grid = show_ecliptic_grid.then_some(grid_spacing);
+
+        // Panorama equirectangular de 360° desde la cámara (F11): seis caras de cubo a 90°
+        if keys.pressed(Key::F11, minifb::KeyRepeat::No) {
+            let panorama = panorama::render(|face, index, forward, up| {
+                // Las estrellas son de pantalla: cada cara lleva las suyas
+                let stars = StarsConfig { seed: scene.stars.seed.wrapping_add(index as u64 + 1), ..scene.stars };
+                StarField::random(face.width, face.height, &stars).render(face);
+                let rect = (0.0, 0.0, face.width as f32, face.height as f32);
+                let view = RenderView::new(camera_eye, camera_eye + widen(forward), up, far, rect, sim_time, light_position)
+                    .with_field_of_view(PI / 2.0)
+                    .with_logarithmic_depth(logarithmic_depth)
+                    .with_toon(toon_mode)
+                    .with_wireframe(wireframe_mode)
+                    .with_ecliptic_grid(ecliptic_grid);
+                render_scene(face, &jobs, &view, &planets, &light, &models, show_barycenter);
+            });
+            let path = format!("panorama_{:06}.png", frame_count);
+            match panorama.save_png(&path) {
+                Ok(()) => println!("Panorama guardado en {}", path),
+                Err(err) => println!("No se pudo guardar {}: {}", path, err),
+            }
+        }
+
+        let mut render_stats = RenderStats::default();
+        if observatory_mode {
+            let views = observatory_views(
+                (camera_eye, camera_target, camera_up),
+                &planets,
+                framebuffer_width as f32,
+                framebuffer_height as f32,
+                far,
+                sim_time,
+                light_position,
+            );
+            let (half_w, half_h) = (framebuffer_width / 2, framebuffer_height / 2);
+            for ((col, row), view) in views {
+                let view = view.with_logarithmic_depth(logarithmic_depth).with_toon(toon_mode).with_wireframe(wireframe_mode).with_ecliptic_grid(ecliptic_grid);
+                // El recorte es el del cuadrante de cada vista: si falta alguna, las demás no se corren
+                let (x, y) = (col * half_w, row * half_h);
+                framebuffer.set_scissor(Some((x, y, x + half_w, y + half_h)));
+                render_stats.merge(render_scene(&mut framebuffer, &jobs, &view, &planets, &light, &models, show_barycenter));
+            }
+            framebuffer.set_scissor(None);
+            framebuffer.draw_grid_lines(half_w, half_h, 0x555555);
+            reprojection = None;
+        } else {
+            let full_screen = (0.0, 0.0, framebuffer_width as f32, framebuffer_height as f32);
+            let view = RenderView::new(
+                camera_eye,
+                camera_target,
+                camera_up,
+                far,
+                full_screen,
+                sim_time,
+                light_position,
+            )
+            .with_logarithmic_depth(logarithmic_depth)
+            .with_toon(toon_mode)
+            .with_wireframe(wireframe_mode)
+            .with_ecliptic_grid(ecliptic_grid);
+            if anaglyph_mode {
+                let (left, right) = stereo_views(
+                    camera_eye,
+                    camera_forward,
+                    camera_right,
+                    full_screen,
+                    far,
+                    sim_time,
+                    light_position,
+                );
+                let (left, right) = (
+                    left.with_logarithmic_depth(logarithmic_depth).with_toon(toon_mode).with_wireframe(wireframe_mode).with_ecliptic_grid(ecliptic_grid),
+                    right.with_logarithmic_depth(logarithmic_depth).with_toon(toon_mode).with_wireframe(wireframe_mode).with_ecliptic_grid(ecliptic_grid),
+                );
+                render_stats.merge(render_scene(&mut framebuffer, &jobs, &left, &planets, &light, &models, show_barycenter));
+                render_solar_wind(&mut framebuffer, &left, &solar_wind, camera.position);
+                let left_buffer = framebuffer.buffer.clone();
+                framebuffer.clear();
+                skybox.render(&mut framebuffer);
+                render_stats.merge(render_scene(&mut framebuffer, &jobs, &right, &planets, &light, &models, show_barycenter));
+                render_solar_wind(&mut framebuffer, &right, &solar_wind, camera.position);
+                framebuffer.compose_anaglyph(&left_buffer);
+                reprojection = None;
+            } else {
+                let previous = reprojection.take().filter(|previous| !checkerboard && previous.matches(&view, &planets));
+                let age = match (&previous, (view.viewport_matrix * view.projection_matrix * view.view_matrix).try_inverse()) {
+                    (Some(previous), Some(inverse)) => {
+                        framebuffer.reproject(|x, y| previous.source(&inverse, x, y, framebuffer_width, framebuffer_height));
+                        previous.age + 1
+                    }
+                    _ => 0,
+                };
+                render_stats.merge(render_scene(&mut framebuffer, &jobs, &view, &planets, &light, &models, show_barycenter));
+                render_solar_wind(&mut framebuffer, &view, &solar_wind, camera.position);
+                reprojection = Some(ReprojectionState::new(&view, &planets, age));
+            }
+            // Antes de los overlays, para que no se acumulen en los píxeles reutilizados
+            framebuffer.store_history();
+            if !anaglyph_mode {
+                if let Some(atmosphere) = close_body.and_then(|index| close_up_atmosphere(&view, &planets[index])) {
+                    if let Some(inverse) = (view.viewport_matrix * view.projection_matrix * view.relative_view_matrix).try_inverse() {
+                        atmosphere.render(&mut framebuffer, &inverse, view.relative(view.light_position));
+                    }
+                }
+            }
+            entry_heating.render(&mut framebuffer);
+            if let Some(index) = selected_body.filter(|&index| !planets[index].hidden) {
+                render_selection(&mut framebuffer, &view.uniforms(Mat4::identity()), &planets[index]);
+            }
+
+            let cursor = window
+                .get_mouse_pos(minifb::MouseMode::Discard)
+                .and_then(|(x, y)| upscaler.to_source(x, y, framebuffer_width, framebuffer_height));
+            if let Some(cursor) = cursor {
+                let hovered = view
+                    .picking_ray(cursor.0, cursor.1)
+                    .and_then(|(origin, direction)| pick_object(&obstacles, &planets, origin, direction));
+                if let Some((object, distance)) = hovered {
+                    let name = match object {
+                        SceneObject::Body(index) => planets[index].name.as_str(),
+                        SceneObject::Asteroid(_) => "Asteroide",
+                    };
+                    let text = format!("{}\n{:.0} u", name, distance.max(0.0));
+                    render_tooltip(&mut framebuffer, &theme, cursor, &text);
+                }
+            }
+
+            let speed_text = if camera.physics {
+                format!("Velocidad: {:.0} u/s (balística)", camera.velocity.norm())
+            } else {
+                format!(
+                    "Velocidad: {:.0} u/s ({})",
+                    camera.speed,
+                    if camera.manual_speed.is_some() { "manual" } else { "auto" }
+                )
+            };
+            let speed_x = framebuffer_width as i32 - theme.text_width(&speed_text) as i32 - theme.px(8);
+            theme.text(&mut framebuffer, speed_x, theme.px(8), &speed_text, 0xCCCCCC);
+            if let Some(avoidance) = camera.avoidance.as_ref().filter(|avoidance| avoidance.body < planets.len()) {
+                let text = format!("Esquivando {}", planets[avoidance.body].name);
+                let x = speed_x - theme.text_width(&text) as i32 - theme.px(12);
+                theme.text(&mut framebuffer, x, theme.px(8), &text, 0xFFB040);
+            }
+            if let Some((body, altitude)) = camera.landing_altitude(&planets) {
+                let text = format!("Aterrizando en {}: {:.1} u sobre el suelo", planets[body].name, altitude);
+                let x = speed_x - theme.text_width(&text) as i32 - theme.px(12);
+                theme.text(&mut framebuffer, x, theme.px(8), &text, 0x80E0A0);
+            }
+
+            // El ajuste de shader va en el mismo panel que el inspector, encima, para no taparse
+            let mut summary = Vec::new();
+            if shader_panel.open {
+                let body = selected_body.map(|index| (planets[index].name.as_str(), &planets[index].shader_params));
+                summary.push(shader_panel.text(body));
+            }
+            if inspector.open {
+                let rows = entity_rows(&planets, belt.as_ref(), camera.position, &render_stats);
+                summary.extend([inspector.text(&rows, planets.len()), render_stats.culling_summary()]);
+                summary.extend(mesh_infos.iter().map(MeshInfo::summary));
+                summary.push(format!(
+                    "Texturas: {:.1} / {} MB",
+                    texture_cache.memory_used() as f64 / (1024.0 * 1024.0),
+                    texture_cache.budget() / (1024 * 1024)
+                ));
+                summary.push(memory_report.summary(memory_budget.bytes));
+            }
+            if !summary.is_empty() {
+                hud::render_debug_panel(&mut framebuffer, &theme, &summary.join("\n"), (framebuffer_width as i32 - theme.px(8), theme.px(20)));
+            }
+
+            if let Some(recorder) = &path_recorder {
+                let text = format!("Grabando recorrido: {:.1} s (Y para terminar)", recorder.elapsed());
+                let x = (framebuffer_width as i32 - theme.text_width(&text) as i32) / 2;
+                theme.text(&mut framebuffer, x, theme.px(8), &text, 0xFF5050);
+            }
+            if let Some(status) = visibility_status(&planets, solo_restore.is_some()) {
+                theme.text(&mut framebuffer, theme.px(8), theme.px(8), &status, 0xCCCCCC);
+            }
+            if let Some(prompt) = scenario.as_ref().and_then(ScenarioRun::prompt) {
+                render_scenario_prompt(&mut framebuffer, &theme, &prompt);
+            }
+            if let Some((conjunction, _)) = &notification {
+                render_notification(&mut framebuffer, &theme, &conjunction_text(conjunction, &planets));
+            }
+
+            if let Some(prediction) = predictor.latest().filter(|_| show_predictions) {
+                let world_uniforms = view.uniforms(Mat4::identity());
+                for (planet, path) in planets.iter().zip(&prediction.bodies) {
+                    if !planet.hidden && planet.orbit_radius > 0.0 {
+                        render_predicted_path(&mut framebuffer, &world_uniforms, path, 0x88AACC);
+                    }
+                }
+                render_predicted_path(&mut framebuffer, &world_uniforms, &prediction.ship, 0x66FF99);
+            }
+
+            // Vista previa de la ruta al cuerpo seleccionado mientras la nave vuela libre
+            let free_flight = camera.orbit.is_none() && surface_camera.is_none();
+            if let Some(target) = selected_body.filter(|_| free_flight).map(|index| &planets[index]) {
+                let distance = (target.position - camera.position).norm() as f32;
+                if distance > WARP_ARRIVAL_DISTANCE * 1.5 {
+                    let route = route_preview(narrow(camera.position), narrow(target.position), 48);
+                    let world_uniforms = view.uniforms(Mat4::identity());
+                    for pair in route.windows(2) {
+                        render_line_3d(&mut framebuffer, &world_uniforms, pair[0], pair[1], 0x3399DD);
+                    }
+                    let length = route_length(&route);
+                    let info = format!("Ruta a {}: {:.0} u, {:.1} s", target.name, length, length / camera.speed);
+                    theme.text(&mut framebuffer, theme.px(8), theme.px(20), &info, 0x66BBEE);
+                }
+            }
+
+            // Lecturas orbitales mientras la nave está en órbita (I) o en vuelo balístico, respecto
+            // al cuerpo que domina la gravedad
+            let orbital_body = (surface_camera.is_none() && (camera.orbit.is_some() || camera.physics))
+                .then(|| dominant_body(&planets, camera.position).or(camera.orbit.as_ref().map(|orbit| orbit.parent)))
+                .flatten();
+            if let Some(index) = orbital_body {
+                let time_scale = if clock.paused { 0.0 } else { clock.time_scale };
+                if let Some(elements) = ship_orbital_elements(&camera, &planets, index, time_scale) {
+                    hud::render_orbital_readout(&mut framebuffer, &theme, &planets[index].name, &elements, (theme.px(8), theme.px(20)));
+                }
+            }
+
+            if let Some(measurement) = &measurement {
+                let time_scale = if clock.paused { 0.0 } else { clock.time_scale };
+                let state = |endpoint: Endpoint| match endpoint {
+                    Endpoint::Ship => EndpointState { name: "Nave".to_string(), position: camera.position, velocity: camera.velocity },
+                    Endpoint::Body(index) => EndpointState {
+                        name: planets[index].name.clone(),
+                        position: planets[index].position,
+                        velocity: planets[index].velocity() * time_scale,
+                    },
+                };
+                let text = match measurement.to(selected_body) {
+                    Some(to) => measure::readout(&state(measurement.from), &state(to), astronomical_unit(&planets)),
+                    None => "Medida: selecciona un cuerpo (U para salir)".to_string(),
+                };
+                let (width, height) = theme.text_panel_size(&text, theme.px(4));
+                let origin = (framebuffer_width as i32 - width as i32 - theme.px(8), framebuffer_height as i32 - height as i32 - theme.px(40));
+                theme.text_panel(&mut framebuffer, origin, &text, (0x99DDFF, 0x101820, Some(0x336688)), 4);
+            }
+
+            if let Some(index) = selected_body {
+                let planet = &planets[index];
+                let tour = day_tour.as_ref().filter(|tour| tour.body == index).map(|tour| tour.progress(clock.time));
+                let origin = (theme.px(8), theme.px(96));
+                hud::render_body_info(&mut framebuffer, &theme, &planet.name, planet.rotation_period(), planet.orbital_period(), tour, origin);
+                if facts_visible {
+                    hud::render_fact_card(&mut framebuffer, &theme, &planet.name, &planet.facts, fact_page.1, (theme.px(8), theme.px(136)));
+                }
+            }
+
+            let timeline_width = framebuffer_width as i32 / 2;
+            let timeline_y = framebuffer_height as i32 - theme.px(12);
+            hud::render_timeline(&mut framebuffer, &theme, clock.days(), (timeline_width / 2, timeline_y), timeline_width);
+
+            if show_gizmo {
+                let (width, height) = (framebuffer_width as i32, framebuffer_height as i32);
+                let gizmo = theme.px(40);
+                hud::render_axes_gizmo(&mut framebuffer, &theme, &view.view_matrix, (gizmo, height - gizmo), theme.px(25) as f32);
+                // La escala se mide a la distancia del cuerpo seleccionado, o a 100 unidades
+                let depth = selected_body.map_or(100.0, |index| (planets[index].position - camera_eye).norm() as f32);
+                let origin = (width - theme.px(170), height - theme.px(20));
+                hud::render_scale_bar(&mut framebuffer, &theme, &view.uniforms(Mat4::identity()), depth, origin);
+            }
+        }
+
+        if let Some(jump) = &hyperspace {
+            jump.render(&mut framebuffer);
+        }
+        if let Some(galaxy) = galaxy.as_ref().filter(|_| galaxy_map.open) {
+            galaxy_map.render(&mut framebuffer, &theme, galaxy, galaxy_system);
+        }
+        if pause_menu.open {
+            pause_menu.render(&mut framebuffer, &theme, &menu_values);
+        }
+
+        let render_ms = render_start.elapsed().as_secs_f32() * 1000.0;
+
+        if let Some(writer) = telemetry.as_mut() {
+            let frame = TelemetryFrame {
+                frame: frame_count,
+                sim_time,
+                time_scale: clock.time_scale,
+                frame_ms: frame_delta * 1000.0,
+                render_ms,
+                ship_position: narrow(camera.position).into(),
+                ship_velocity: camera.velocity.into(),
+                bodies: planets
+                    .iter()
+                    .map(|planet| BodySample {
+                        name: &planet.name,
+                        position: narrow(planet.position).into(),
+                        orbit_angle: planet.orbit_angle,
+                    })
+                    .collect(),
+            };
+            if let Err(err) = writer.write_frame(&frame) {
+                println!("Telemetría detenida: {}", err);
+                telemetry = None;
+            }
+        }
+
+        if keys.pressed(Key::E, minifb::KeyRepeat::No) {
+            let bodies: Vec<OrreryBody> = planets
+                .iter()
+                .filter(|planet| !planet.hidden)
+                .map(|planet| orrery_body(&planet.name, planet.orbit_radius, planet.orbit_angle, planet.scale, &planet.shader_params))
+                .collect();
+            export_orrery(&format!("esquema_{:06}.svg", frame_count), &bodies, options.to_scale);
+        }
+
+        if window.is_key_pressed(Key::F12, minifb::KeyRepeat::No) {
+            let path = format!("captura_{:06}.png", frame_count);
+            match framebuffer.save_png(&path) {
+                Ok(()) => println!("Captura guardada en {}", path),
+                Err(err) => println!("No se pudo guardar {}: {}", path, err),
+            }
+        }
+
+        #[cfg(feature = "remote")]
+        for request in screenshot_requests {
+            let requested = match &request.command {
+                RemoteCommand::Screenshot { path } => path.as_deref(),
+                _ => None,
+            };
+            let result = capture_path(requested, frame_count).and_then(|path| {
+                framebuffer
+                    .save_png(&path)
+                    .map(|_| format!("captura guardada en {}", path))
+                    .map_err(|err| format!("no se pudo guardar {}: {}", path, err))
+            });
+            request.respond(result);
+        }
+
+        let output = upscaler.present(&framebuffer.buffer, framebuffer_width, framebuffer_height);
+        window.update_with_buffer(output, window_width, window_height).ok();
+
+        pacer.end_frame();
+        frame_count += 1;
     }
     
-    println!("\n=== Programa terminado - {} frames ===", frame_count);
+    let save = Autosave {
+        time: clock.time,
+        time_scale: clock.time_scale,
+        paused: clock.paused,
+        ship_position: camera.position.into(),
+        ship_yaw: camera.yaw,
+        ship_pitch: camera.pitch,
+        selected_body: selected_body.map(|index| planets[index].name.clone()),
+        hidden_bodies: planets.iter().filter(|planet| planet.hidden).map(|planet| planet.name.clone()).collect(),
+    };
+    match save.save(AUTOSAVE_PATH) {
+        Ok(()) => println!("\nEstado guardado en {} (--resume para continuar)", AUTOSAVE_PATH),
+        Err(err) => println!("\nNo se pudo guardar el estado: {}", err),
+    }
+
+    println!("\n=== Resumen de la sesión ===\n{}", session.summary(clock.time));
 }
\ No newline at end of file
@@ -0,0 +1,76 @@
+use nalgebra_glm::{DVec3, Vec3};
+
+/// Segundos que tarda la luz en recorrer una unidad astronómica
+const LIGHT_SECONDS_PER_AU: f64 = 499.0;
+
+/// Extremo de una medida
+#[derive(Clone, Copy, PartialEq)]
+pub enum Endpoint {
+    Ship,
+    Body(usize),
+}
+
+/// Posición y velocidad (en unidades por segundo real) de un extremo en este frame
+pub struct EndpointState {
+    pub name: String,
+    pub position: DVec3,
+    pub velocity: Vec3,
+}
+
+/// Herramienta de medida (U): fija un extremo (el cuerpo seleccionado, o la nave si no hay
+/// ninguno) y mide hasta el cuerpo que se seleccione después, o hasta la nave si la selección es
+/// el propio extremo fijo.
+pub struct Measurement {
+    pub from: Endpoint,
+}
+
+impl Measurement {
+    pub fn new(selected_body: Option<usize>) -> Self {
+        Measurement { from: selected_body.map_or(Endpoint::Ship, Endpoint::Body) }
+    }
+
+    /// Extremo contrario al fijo; `None` si todavía falta elegirlo
+    pub fn to(&self, selected_body: Option<usize>) -> Option<Endpoint> {
+        match (self.from, selected_body) {
+            (Endpoint::Body(from), Some(index)) if from == index => Some(Endpoint::Ship),
+            (_, Some(index)) => Some(Endpoint::Body(index)),
+            (Endpoint::Body(_), None) => Some(Endpoint::Ship),
+            (Endpoint::Ship, None) => None,
+        }
+    }
+}
+
+/// Distancia, velocidad de acercamiento (positiva si se acercan) y tiempo que tarda la luz de un
+/// extremo a otro. `au` es la unidad astronómica de la escena en unidades de mundo (el radio de la
+/// órbita de Terra); sin ella no se da el tiempo de luz.
+pub fn readout(from: &EndpointState, to: &EndpointState, au: Option<f32>) -> String {
+    let offset = to.position - from.position;
+    let distance = offset.norm();
+    let closing = offset
+        .try_normalize(1e-9)
+        .map_or(0.0, |direction| -(to.velocity - from.velocity).cast::<f64>().dot(&direction));
+    let mut text = format!(
+        "Medida: {} - {}\nDistancia: {:.0} u\n{}: {:.1} u/s",
+        from.name,
+        to.name,
+        distance,
+        if closing >= 0.0 { "Acercamiento" } else { "Alejamiento" },
+        closing.abs()
+    );
+    if let Some(au) = au.filter(|&au| au > 0.0) {
+        let light = distance / au as f64 * LIGHT_SECONDS_PER_AU;
+        text.push_str(&format!("\nLuz: {} ({:.2} UA)", light_time(light), distance / au as f64));
+    }
+    text
+}
+
+/// Tiempo legible: segundos, minutos u horas
+fn light_time(seconds: f64) -> String {
+    if seconds < 60.0 {
+        format!("{:.1} s", seconds)
+    } else if seconds < 3600.0 {
+        format!("{:.0} min {:.0} s", (seconds / 60.0).floor(), (seconds % 60.0).floor())
+    } else {
+        format!("{:.0} h {:.0} min", (seconds / 3600.0).floor(), (seconds % 3600.0 / 60.0).floor())
+    }
+}
@@ -0,0 +1,60 @@
+use std::mem::size_of;
+
+/// Bytes reservados por un `Vec` (su capacidad, no solo lo usado)
+pub fn vec_bytes<T>(items: &Vec<T>) -> usize {
+    items.capacity() * size_of::<T>()
+}
+
+/// Memoria retenida por cada parte del programa, para el panel de depuración (F3). Solo cuenta
+/// los búferes grandes; las estructuras pequeñas y la pila no se incluyen.
+pub struct MemoryReport {
+    categories: Vec<(&'static str, usize)>,
+}
+
+impl MemoryReport {
+    pub fn new() -> Self {
+        MemoryReport { categories: Vec::new() }
+    }
+
+    pub fn add(&mut self, category: &'static str, bytes: usize) {
+        self.categories.push((category, bytes));
+    }
+
+    pub fn total(&self) -> usize {
+        self.categories.iter().map(|&(_, bytes)| bytes).sum()
+    }
+
+    /// Una línea por categoría y el total respecto al presupuesto
+    pub fn summary(&self, budget: usize) -> String {
+        let mut lines: Vec<String> =
+            self.categories.iter().map(|&(category, bytes)| format!("  {}: {:.1} MB", category, megabytes(bytes))).collect();
+        let over = if self.total() > budget { "  (excedido)" } else { "" };
+        lines.insert(0, format!("Memoria: {:.1} / {:.0} MB{}", megabytes(self.total()), megabytes(budget), over));
+        lines.join("\n")
+    }
+}
+
+fn megabytes(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Presupuesto de memoria: avisa por consola al pasarlo y otra vez si se vuelve a pasar después
+/// de haber bajado
+pub struct MemoryBudget {
+    pub bytes: usize,
+    exceeded: bool,
+}
+
+impl MemoryBudget {
+    pub fn new(bytes: usize) -> Self {
+        MemoryBudget { bytes, exceeded: false }
+    }
+
+    pub fn check(&mut self, report: &MemoryReport) {
+        let exceeded = report.total() > self.bytes;
+        if exceeded && !self.exceeded {
+            println!("Aviso: memoria por encima del presupuesto\n{}", report.summary(self.bytes));
+        }
+        self.exceeded = exceeded;
+    }
+}
@@ -0,0 +1,274 @@
+use minifb::{Key, KeyRepeat, Window};
+use crate::framebuffer::Framebuffer;
+use crate::hud::Theme;
+
+/// Escalas de la resolución interna que ofrece el menú, respecto a `--resolution`
+pub const RESOLUTION_SCALES: [f32; 4] = [0.5, 0.75, 1.0, 1.25];
+const VOLUME_STEP: f32 = 0.1;
+
+/// Resumen de las teclas para la entrada "Teclas" del menú
+const KEY_HINTS: &str = "\
+WASD Espacio/Shift Flechas  volar
+F  viajar al siguiente cuerpo   I  orbitar
+M  velocidad auto/manual (manual para aterrizar)   [ ]  velocidad
+V  superficie   T  un día en Terra   F5  eclipse de sol
+B  baricentro   G  ejes   H  ocultar   K  solo
+X  trayectorias previstas   U  medir distancia
+Y  grabar recorrido para --render-video
+O  observatorio   N  datos curiosos
+P  pausa   , .  línea de tiempo   + -  tiempo
+3  anaglifo   R  cómic   C  ajedrez
+L  diferido   Q  oclusión   Z  profundidad log.
+E  esquema   J  ver conjunción
+Tab  mapa de la galaxia (con --galaxy)
+F1  alambre   F2  cuadrícula   F3  inspector
+F4  ajustar shader   F11  panorama 360°
+F12  captura   Esc  menú";
+
+/// Entradas del menú, en orden
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Resume,
+    Scene,
+    Resolution,
+    Deferred,
+    AmbientOcclusion,
+    Checkerboard,
+    LogDepth,
+    Anaglyph,
+    Toon,
+    Volume,
+    KeyHints,
+    Quit,
+}
+
+const ENTRIES: [Entry; 12] = [
+    Entry::Resume,
+    Entry::Scene,
+    Entry::Resolution,
+    Entry::Deferred,
+    Entry::AmbientOcclusion,
+    Entry::Checkerboard,
+    Entry::LogDepth,
+    Entry::Anaglyph,
+    Entry::Toon,
+    Entry::Volume,
+    Entry::KeyHints,
+    Entry::Quit,
+];
+
+/// Lo que el programa tiene que hacer tras la entrada del menú de este frame. Los interruptores
+/// hacen lo mismo que su tecla.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuCommand {
+    /// Índice de la escena que hay que cargar
+    LoadScene(usize),
+    /// Índice nuevo en `RESOLUTION_SCALES`
+    SetResolutionScale(usize),
+    ToggleDeferred,
+    ToggleAmbientOcclusion,
+    ToggleCheckerboard,
+    ToggleLogDepth,
+    ToggleAnaglyph,
+    ToggleToon,
+    SetVolume(f32),
+    Quit,
+}
+
+/// Estado actual de las opciones, para mostrar su valor junto a cada entrada
+pub struct MenuValues {
+    /// Escena cargada; ninguna si es un sistema generado (modo galaxia)
+    pub scene: Option<usize>,
+    pub resolution_scale: usize,
+    pub resolution: (usize, usize),
+    pub deferred: bool,
+    pub ambient_occlusion: bool,
+    pub checkerboard: bool,
+    pub log_depth: bool,
+    pub anaglyph: bool,
+    pub toon: bool,
+    pub volume: f32,
+}
+
+/// Menú de pausa (Esc): mientras está abierto la simulación y la nave se detienen y las teclas
+/// de acceso directo no responden. Se navega con las flechas y Enter; Esc vuelve atrás.
+/// Salir pide confirmación.
+pub struct PauseMenu {
+    pub open: bool,
+    selected: usize,
+    /// Nombres de las escenas y la que se muestra en su entrada (se carga con Enter)
+    scenes: Vec<String>,
+    scene_choice: usize,
+    confirm_quit: bool,
+    show_hints: bool,
+}
+
+impl PauseMenu {
+    pub fn new(scenes: Vec<String>) -> Self {
+        PauseMenu { open: false, selected: 0, scenes, scene_choice: 0, confirm_quit: false, show_hints: false }
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.confirm_quit = false;
+        self.show_hints = false;
+    }
+
+    /// Lee el teclado; debe llamarse una vez por frame, esté o no abierto
+    pub fn handle_input(&mut self, window: &Window, values: &MenuValues) -> Option<MenuCommand> {
+        let pressed = |key| window.is_key_pressed(key, KeyRepeat::No);
+        if !self.open {
+            if pressed(Key::Escape) {
+                self.open = true;
+                self.selected = 0;
+                self.scene_choice = values.scene.unwrap_or(0);
+            }
+            return None;
+        }
+
+        if self.confirm_quit {
+            if pressed(Key::Enter) {
+                return Some(MenuCommand::Quit);
+            }
+            if pressed(Key::Escape) {
+                self.confirm_quit = false;
+            }
+            return None;
+        }
+        if self.show_hints {
+            if pressed(Key::Escape) || pressed(Key::Enter) {
+                self.show_hints = false;
+            }
+            return None;
+        }
+        if pressed(Key::Escape) {
+            self.close();
+            return None;
+        }
+
+        if window.is_key_pressed(Key::Down, KeyRepeat::Yes) {
+            self.selected = (self.selected + 1) % ENTRIES.len();
+        }
+        if window.is_key_pressed(Key::Up, KeyRepeat::Yes) {
+            self.selected = (self.selected + ENTRIES.len() - 1) % ENTRIES.len();
+        }
+        let step = if window.is_key_pressed(Key::Right, KeyRepeat::Yes) {
+            1
+        } else if window.is_key_pressed(Key::Left, KeyRepeat::Yes) {
+            -1
+        } else {
+            0
+        };
+        let enter = pressed(Key::Enter);
+
+        let entry = ENTRIES[self.selected];
+        match entry {
+            Entry::Resolution if step != 0 => {
+                let index = values.resolution_scale as i32 + step;
+                (0..RESOLUTION_SCALES.len() as i32)
+                    .contains(&index)
+                    .then_some(MenuCommand::SetResolutionScale(index as usize))
+            }
+            Entry::Scene if step != 0 => {
+                let count = self.scenes.len();
+                self.scene_choice = (self.scene_choice as i32 + step).rem_euclid(count as i32) as usize;
+                None
+            }
+            Entry::Volume if step != 0 => {
+                let volume = (values.volume + step as f32 * VOLUME_STEP).clamp(0.0, 1.0);
+                Some(MenuCommand::SetVolume((volume * 10.0).round() / 10.0))
+            }
+            _ if !enter && step == 0 => None,
+            Entry::Resume => {
+                self.close();
+                None
+            }
+            Entry::Scene => {
+                self.close();
+                Some(MenuCommand::LoadScene(self.scene_choice))
+            }
+            Entry::Deferred => Some(MenuCommand::ToggleDeferred),
+            Entry::AmbientOcclusion => Some(MenuCommand::ToggleAmbientOcclusion),
+            Entry::Checkerboard => Some(MenuCommand::ToggleCheckerboard),
+            Entry::LogDepth => Some(MenuCommand::ToggleLogDepth),
+            Entry::Anaglyph => Some(MenuCommand::ToggleAnaglyph),
+            Entry::Toon => Some(MenuCommand::ToggleToon),
+            Entry::KeyHints => {
+                self.show_hints = enter;
+                None
+            }
+            Entry::Quit => {
+                self.confirm_quit = enter;
+                None
+            }
+            Entry::Resolution | Entry::Volume => None,
+        }
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, theme: &Theme, values: &MenuValues) {
+        let text = if self.confirm_quit {
+            "Confirmar salida\n\nEnter = salir   Esc = volver".to_string()
+        } else if self.show_hints {
+            format!("Teclas\n\n{}\n\nEnter o Esc para volver", KEY_HINTS)
+        } else {
+            self.menu_text(values)
+        };
+
+        // Oscurece la escena para que el menú destaque
+        for pixel in framebuffer.buffer.iter_mut() {
+            *pixel = (*pixel >> 2) & 0x3F3F3F;
+        }
+        let padding = theme.px(8);
+        let (width, height) = theme.text_panel_size(&text, padding);
+        let x = (framebuffer.width as i32 - width as i32) / 2;
+        let y = (framebuffer.height as i32 - height as i32) / 2;
+        theme.text_panel(framebuffer, (x, y), &text, (0xDDE6EE, 0x0A1018, Some(0x445566)), 8);
+    }
+
+    fn menu_text(&self, values: &MenuValues) -> String {
+        let on_off = |value: bool| if value { "sí" } else { "no" };
+        let mut lines = vec!["PAUSA".to_string(), String::new()];
+        for (index, entry) in ENTRIES.iter().enumerate() {
+            let label = match entry {
+                Entry::Resume => "Continuar".to_string(),
+                Entry::Scene => format!(
+                    "Escena: < {} >{}",
+                    self.scenes[self.scene_choice],
+                    if Some(self.scene_choice) == values.scene { " (actual)" } else { "" }
+                ),
+                Entry::Resolution => format!(
+                    "Resolución: < {:.0}% ({}x{}) >",
+                    RESOLUTION_SCALES[values.resolution_scale] * 100.0,
+                    values.resolution.0,
+                    values.resolution.1
+                ),
+                Entry::Deferred => format!("Sombreado diferido (L): {}", on_off(values.deferred)),
+                Entry::AmbientOcclusion => format!("Oclusión ambiental (Q): {}", on_off(values.ambient_occlusion)),
+                Entry::Checkerboard => format!("Renderizado en ajedrez (C): {}", on_off(values.checkerboard)),
+                Entry::LogDepth => format!("Profundidad logarítmica (Z): {}", on_off(values.log_depth)),
+                Entry::Anaglyph => format!("Anaglifo (3): {}", on_off(values.anaglyph)),
+                Entry::Toon => format!("Estilo cómic (R): {}", on_off(values.toon)),
+                Entry::Volume => format!("Volumen: < {:.0}% >", values.volume * 100.0),
+                Entry::KeyHints => "Teclas".to_string(),
+                Entry::Quit => "Salir".to_string(),
+            };
+            let marker = if index == self.selected { ">" } else { " " };
+            lines.push(format!("{} {}", marker, label));
+        }
+        lines.push(String::new());
+        lines.push("Flechas para elegir, Enter para cambiar".to_string());
+        lines.join("\n")
+    }
+}
+
+/// Teclas de acceso directo: no responden mientras el menú de pausa está abierto
+pub struct Hotkeys<'a> {
+    pub window: &'a Window,
+    pub enabled: bool,
+}
+
+impl Hotkeys<'_> {
+    pub fn pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        self.enabled && self.window.is_key_pressed(key, repeat)
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use nalgebra_glm::Vec3;
+use raylib::math::{Vector3, Vector4};
+use crate::noise;
+use crate::obj::Obj;
+use crate::pipeline::Culling;
+use crate::scene::DisplacementConfig;
+use crate::vertex::Vertex;
+use crate::{ply, stl};
+
+/// Triángulos con menos área que esta se consideran degenerados y se descartan al simplificar
+const MIN_TRIANGLE_AREA: f32 = 0.0001;
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io(std::io::Error),
+    Obj(tobj::LoadError),
+    Format(String),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshError::Io(err) => write!(f, "no se pudo leer el modelo: {}", err),
+            MeshError::Obj(err) => write!(f, "OBJ inválido: {}", err),
+            MeshError::Format(message) => write!(f, "modelo inválido: {}", message),
+        }
+    }
+}
+
+impl From<std::io::Error> for MeshError {
+    fn from(err: std::io::Error) -> Self {
+        MeshError::Io(err)
+    }
+}
+
+impl From<tobj::LoadError> for MeshError {
+    fn from(err: tobj::LoadError) -> Self {
+        MeshError::Obj(err)
+    }
+}
+
+/// Carga un modelo OBJ, STL o PLY según la extensión. Todos pasan por el mismo
+/// posprocesado: se generan las normales que falten y las tangentes.
+pub fn load_model(path: &str) -> Result<Obj, MeshError> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let mut model = match extension.as_deref() {
+        Some("stl") => stl::load(path)?,
+        Some("ply") => ply::load(path)?,
+        _ => Obj::load(path)?,
+    };
+    generate_missing_normals(&mut model);
+    generate_tangents(&mut model);
+    Ok(model)
+}
+
+/// Normales suavizadas (suma de las normales de las caras, ponderadas por área) para los
+/// vértices que no tienen. Los vértices compartidos entre caras quedan suaves; los de mallas
+/// sin índices compartidos, como STL, quedan planos.
+pub fn generate_missing_normals(model: &mut Obj) {
+    if model.vertices_without_normals == 0 {
+        return;
+    }
+
+    let mut sums = vec![Vec3::zeros(); model.vertices.len()];
+    for triangle in model.indices.chunks_exact(3) {
+        let normal = model.face_normal(triangle);
+        for &index in triangle {
+            sums[index as usize] += normal;
+        }
+    }
+
+    for (vertex, sum) in model.vertices.iter_mut().zip(sums) {
+        let missing = vertex.normal.x == 0.0 && vertex.normal.y == 0.0 && vertex.normal.z == 0.0;
+        if let Some(normal) = sum.try_normalize(1e-12).filter(|_| missing) {
+            vertex.normal = Vector3::new(normal.x, normal.y, normal.z);
+        }
+    }
+}
+
+/// Desplaza cada vértice a lo largo de su normal según un ruido fBm evaluado en su posición y
+/// recalcula normales y tangentes. Los vértices duplicados en las costuras de UV reciben el mismo
+/// desplazamiento (el ruido depende solo de la posición) y se sueldan al sumar las normales, así
+/// la superficie no se abre ni muestra aristas de sombreado.
+pub fn displace(model: &mut Obj, config: &DisplacementConfig, seed: u32) {
+    let keys: Vec<[u32; 3]> = model
+        .vertices
+        .iter()
+        .map(|vertex| [vertex.position.x.to_bits(), vertex.position.y.to_bits(), vertex.position.z.to_bits()])
+        .collect();
+
+    for vertex in &mut model.vertices {
+        let p = vertex.position;
+        let height = noise::fbm(Vec3::new(p.x, p.y, p.z) * config.frequency, config.octaves, seed);
+        // Normal del archivo -> espacio de posiciones (y invertida)
+        let n = vertex.normal;
+        let direction = Vector3::new(n.x, -n.y, n.z).normalized();
+        vertex.position = p + direction * (height * config.amplitude * p.length());
+    }
+
+    let mut sums: HashMap<[u32; 3], Vec3> = HashMap::new();
+    for triangle in model.indices.chunks_exact(3) {
+        let normal = model.face_normal(triangle);
+        for &index in triangle {
+            *sums.entry(keys[index as usize]).or_insert_with(Vec3::zeros) += normal;
+        }
+    }
+    for (vertex, key) in model.vertices.iter_mut().zip(&keys) {
+        if let Some(normal) = sums.get(key).and_then(|sum| sum.try_normalize(1e-12)) {
+            vertex.normal = Vector3::new(normal.x, normal.y, normal.z);
+        }
+    }
+    generate_tangents(model);
+}
+
+/// Tangentes para el normal mapping, a partir de cómo cambian las UV en cada triángulo (suma por
+/// vértice y ortogonalización contra la normal). Los vértices sin UV útiles quedan a cero y se
+/// sombrean sin mapa de normales.
+pub fn generate_tangents(model: &mut Obj) {
+    let mut tangents = vec![Vec3::zeros(); model.vertices.len()];
+    let mut bitangents = vec![Vec3::zeros(); model.vertices.len()];
+    for triangle in model.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| &model.vertices[index as usize]);
+        // Igual que con las normales: las aristas se llevan al espacio del archivo (y sin invertir)
+        let edge = |from: &Vertex, to: &Vertex| {
+            let d = to.position - from.position;
+            Vec3::new(d.x, -d.y, d.z)
+        };
+        let (e1, e2) = (edge(a, b), edge(a, c));
+        let (du1, dv1) = (b.tex_coords.x - a.tex_coords.x, b.tex_coords.y - a.tex_coords.y);
+        let (du2, dv2) = (c.tex_coords.x - a.tex_coords.x, c.tex_coords.y - a.tex_coords.y);
+        let determinant = du1 * dv2 - du2 * dv1;
+        if determinant.abs() < 1e-12 {
+            continue;
+        }
+
+        let tangent = (e1 * dv2 - e2 * dv1) / determinant;
+        let bitangent = (e2 * du1 - e1 * du2) / determinant;
+        for &index in triangle {
+            tangents[index as usize] += tangent;
+            bitangents[index as usize] += bitangent;
+        }
+    }
+
+    for ((vertex, tangent), bitangent) in model.vertices.iter_mut().zip(tangents).zip(bitangents) {
+        let normal = Vec3::new(vertex.normal.x, vertex.normal.y, vertex.normal.z);
+        let Some(tangent) = (tangent - normal * normal.dot(&tangent)).try_normalize(1e-12) else {
+            continue;
+        };
+        let handedness = if normal.cross(&tangent).dot(&bitangent) < 0.0 { -1.0 } else { 1.0 };
+        vertex.tangent = Vector4::new(tangent.x, tangent.y, tangent.z, handedness);
+    }
+}
+
+/// Un nivel de detalle de la malla
+#[derive(Debug, Clone)]
+pub struct LodInfo {
+    pub triangles: usize,
+    /// Triángulos de área casi nula descartados al generar este nivel
+    pub degenerate_removed: usize,
+}
+
+/// Estadísticas y diagnósticos de una malla cargada, para la consola y el panel de depuración
+#[derive(Debug, Clone)]
+pub struct MeshInfo {
+    pub name: String,
+    /// El nivel 0 es la malla tal como se cargó
+    pub lods: Vec<LodInfo>,
+    /// Distancia máxima de un vértice al origen del modelo
+    pub bounding_radius: f32,
+    pub vertices_without_normals: usize,
+    pub vertices_without_uvs: usize,
+    /// Caras cuyo sentido de giro no coincide con sus normales: con el culling habitual se
+    /// verían por detrás
+    pub reversed_faces: usize,
+}
+
+impl MeshInfo {
+    pub fn from_obj(name: &str, obj: &Obj) -> Self {
+        let vertices = obj.get_vertex_array();
+        let bounding_radius = obj
+            .vertices
+            .iter()
+            .map(|vertex| vertex.position.length())
+            .fold(0.0, f32::max);
+
+        MeshInfo {
+            name: name.to_string(),
+            lods: vec![LodInfo { triangles: vertices.len() / 3, degenerate_removed: 0 }],
+            bounding_radius,
+            vertices_without_normals: obj.vertices_without_normals,
+            vertices_without_uvs: obj.vertices_without_uvs,
+            reversed_faces: obj.reversed_faces(),
+        }
+    }
+
+    /// Culling con el que la malla se ve por delante (ver `Culling::for_reversed_faces`)
+    pub fn culling(&self) -> Culling {
+        Culling::for_reversed_faces(self.reversed_faces, self.lods[0].triangles)
+    }
+
+    /// Simplifica la malla a `target_triangles` como mucho y registra el resultado como un nivel más
+    pub fn add_lod(&mut self, vertices: &[Vertex], target_triangles: usize) -> Vec<Vertex> {
+        let (simplified, degenerate_removed) = simplify_mesh(vertices, target_triangles);
+        self.lods.push(LodInfo { triangles: simplified.len() / 3, degenerate_removed });
+        simplified
+    }
+
+    /// Resumen en varias líneas, una por nivel de detalle
+    pub fn summary(&self) -> String {
+        let mut text = format!("{}: radio {:.2}", self.name, self.bounding_radius);
+        for (level, lod) in self.lods.iter().enumerate() {
+            text.push_str(&format!("\n  LOD {}: {} triángulos", level, lod.triangles));
+            if lod.degenerate_removed > 0 {
+                text.push_str(&format!(" ({} degenerados quitados)", lod.degenerate_removed));
+            }
+        }
+        if self.vertices_without_normals > 0 {
+            text.push_str(&format!("\n  {} vértices sin normal", self.vertices_without_normals));
+        }
+        if self.vertices_without_uvs > 0 {
+            text.push_str(&format!("\n  {} vértices sin UV", self.vertices_without_uvs));
+        }
+        if self.reversed_faces > 0 {
+            let action = match self.culling() {
+                Culling::Off => "se dibuja por las dos caras",
+                culling if culling == Culling::default() => "se ignoran",
+                _ => "se invierte el culling",
+            };
+            text.push_str(&format!("\n  {} caras con el sentido de giro al revés: {}", self.reversed_faces, action));
+        }
+        text
+    }
+}
+
+/// Reduce la malla saltando triángulos de forma uniforme hasta `target_triangles` y descarta los
+/// degenerados. Devuelve la malla y cuántos triángulos degenerados se quitaron.
+pub fn simplify_mesh(vertices: &[Vertex], target_triangles: usize) -> (Vec<Vertex>, usize) {
+    if vertices.len() < 3 {
+        return (vertices.to_vec(), 0);
+    }
+
+    let current_triangles = vertices.len() / 3;
+    if current_triangles <= target_triangles {
+        return (vertices.to_vec(), 0);
+    }
+
+    let mut simplified = Vec::new();
+    let mut degenerate = 0;
+    let skip_factor = (current_triangles / target_triangles).max(1);
+
+    for i in (0..current_triangles).step_by(skip_factor) {
+        let idx = i * 3;
+        if idx + 2 < vertices.len() {
+            let v0 = &vertices[idx];
+            let v1 = &vertices[idx + 1];
+            let v2 = &vertices[idx + 2];
+
+            let edge1 = Vec3::new(
+                v1.position.x - v0.position.x,
+                v1.position.y - v0.position.y,
+                v1.position.z - v0.position.z,
+            );
+            let edge2 = Vec3::new(
+                v2.position.x - v0.position.x,
+                v2.position.y - v0.position.y,
+                v2.position.z - v0.position.z,
+            );
+
+            let cross = edge1.cross(&edge2);
+            let area = cross.norm();
+
+            if area > MIN_TRIANGLE_AREA {
+                simplified.push(v0.clone());
+                simplified.push(v1.clone());
+                simplified.push(v2.clone());
+            } else {
+                degenerate += 1;
+            }
+        }
+    }
+
+    (simplified, degenerate)
+}
@@ -0,0 +1,149 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use serde::{Deserialize, Serialize};
+
+// Modo observador en red: una instancia (`--serve`) lleva la simulación y manda su estado en cada
+// frame, una línea JSON por frame, a las que se conectan con `--observe`. Estas siguen el reloj,
+// los cuerpos y la nave del servidor, pero con su propia cámara libre. Las dos deben cargar la
+// misma escena: los cuerpos se emparejan por nombre y los que no están en las dos se ignoran.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BodyState {
+    pub name: String,
+    pub position: [f64; 3],
+    pub rotation: [f32; 3],
+}
+
+/// Posición y orientación del modelo de la nave, tal como la dibuja el servidor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShipState {
+    pub position: [f64; 3],
+    pub rotation: [f32; 3],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetState {
+    pub time: f64,
+    pub time_scale: f32,
+    pub paused: bool,
+    pub bodies: Vec<BodyState>,
+    pub ship: ShipState,
+}
+
+/// Frames que se guardan para un observador lento antes de empezar a saltárselos
+const CLIENT_BACKLOG: usize = 4;
+
+/// Lado de la simulación: acepta observadores y les reparte el estado de cada frame
+pub struct NetServer {
+    clients: Arc<Mutex<Vec<SyncSender<Arc<str>>>>>,
+}
+
+impl NetServer {
+    pub fn start(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&clients);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let peer = stream.peer_addr().map_or_else(|_| "?".to_string(), |peer| peer.to_string());
+                let (sender, frames) = mpsc::sync_channel(CLIENT_BACKLOG);
+                accepted.lock().expect("lista de observadores").push(sender);
+                println!("Observador conectado: {}", peer);
+                // Un hilo por observador: uno lento o colgado no frena la simulación
+                thread::spawn(move || {
+                    if let Err(err) = send_frames(stream, frames) {
+                        println!("Observador {} desconectado ({})", peer, err);
+                    }
+                });
+            }
+        });
+
+        Ok(NetServer { clients })
+    }
+
+    /// Manda el estado a todos los observadores; a los que van atrasados se les salta este
+    /// frame y los que se han ido se quitan de la lista
+    pub fn broadcast(&self, state: &NetState) {
+        let mut clients = self.clients.lock().expect("lista de observadores");
+        if clients.is_empty() {
+            return;
+        }
+        let line: Arc<str> = match serde_json::to_string(state) {
+            Ok(line) => line.into(),
+            Err(err) => {
+                println!("No se pudo serializar el estado de red: {}", err);
+                return;
+            }
+        };
+        clients.retain(|client| !matches!(client.try_send(Arc::clone(&line)), Err(TrySendError::Disconnected(_))));
+    }
+}
+
+fn send_frames(stream: TcpStream, frames: Receiver<Arc<str>>) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut writer = io::BufWriter::new(stream);
+    for line in frames {
+        writeln!(writer, "{}", line)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Lado del observador: lee el estado del servidor en segundo plano
+pub struct NetObserver {
+    states: Receiver<NetState>,
+    connected: bool,
+}
+
+impl NetObserver {
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        let (sender, states) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(err) => {
+                        println!("Observador: error de conexión ({})", err);
+                        return;
+                    }
+                };
+                match serde_json::from_str::<NetState>(&line) {
+                    Ok(state) => {
+                        if sender.send(state).is_err() {
+                            return;
+                        }
+                    }
+                    Err(err) => println!("Observador: estado inválido ({})", err),
+                }
+            }
+        });
+
+        Ok(NetObserver { states, connected: true })
+    }
+
+    /// Estado más reciente llegado desde el último frame; los intermedios se descartan. Avisa
+    /// una vez cuando el servidor cierra la conexión (a partir de ahí la simulación es local).
+    pub fn latest(&mut self) -> Option<NetState> {
+        let mut latest = None;
+        loop {
+            match self.states.try_recv() {
+                Ok(state) => latest = Some(state),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    if self.connected {
+                        println!("Observador: el servidor cerró la conexión; la simulación sigue en local");
+                        self.connected = false;
+                    }
+                    break;
+                }
+            }
+        }
+        latest
+    }
+}
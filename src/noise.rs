@@ -0,0 +1,42 @@
+use nalgebra_glm::Vec3;
+
+/// Valor pseudoaleatorio en [-1, 1] para un punto de la rejilla entera
+fn lattice(x: i32, y: i32, z: i32, seed: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8DA6_B343)
+        ^ (y as u32).wrapping_mul(0xD816_3841)
+        ^ (z as u32).wrapping_mul(0xCB1A_B31F)
+        ^ seed.wrapping_mul(0x9E37_79B9);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5BD1_E995);
+    h ^= h >> 15;
+    (h & 0xFFFF) as f32 / 32_767.5 - 1.0
+}
+
+/// Ruido de valor 3D en [-1, 1], interpolado con smoothstep entre los vértices de la celda
+pub fn value_noise(p: Vec3, seed: u32) -> f32 {
+    let cell = p.map(f32::floor);
+    let f = p - cell;
+    let f = f.component_mul(&f).component_mul(&(Vec3::repeat(3.0) - f * 2.0));
+    let (x, y, z) = (cell.x as i32, cell.y as i32, cell.z as i32);
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let corner = |dx: i32, dy: i32, dz: i32| lattice(x + dx, y + dy, z + dz, seed);
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), f.x);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), f.x);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), f.x);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), f.x);
+    lerp(lerp(x00, x10, f.y), lerp(x01, x11, f.y), f.z)
+}
+
+/// Suma de `octaves` capas de ruido, cada una al doble de frecuencia y la mitad de amplitud;
+/// normalizada a [-1, 1]
+pub fn fbm(p: Vec3, octaves: u32, seed: u32) -> f32 {
+    let (mut sum, mut amplitude, mut total, mut frequency) = (0.0, 1.0, 0.0, 1.0);
+    for octave in 0..octaves.max(1) {
+        sum += value_noise(p * frequency, seed.wrapping_add(octave)) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
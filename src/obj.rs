@@ -1,22 +1,106 @@
-use crate::vertex::Vertex;
+use std::fmt;
+use std::fs;
+use std::io::{BufReader, Cursor};
+use std::path::{Path, PathBuf};
+use crate::vertex::{IndexedMesh, Vertex};
+use nalgebra_glm::Vec3;
 use raylib::math::{Vector2, Vector3};
 use tobj;
 
+#[derive(Clone)]
 pub struct Obj {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    /// Textura difusa del primer material que la declare, relativa a la carpeta del OBJ
+    pub diffuse_texture: Option<PathBuf>,
+    /// Mapa de normales en espacio tangente (`map_Bump`/`bump` del MTL)
+    pub normal_texture: Option<PathBuf>,
+    /// Color difuso (Kd) y exponente especular (Ns) del primer material
+    pub diffuse_color: Option<[f32; 3]>,
+    pub shininess: Option<f32>,
+    /// Vértices de modelos que no traían normales o coordenadas de textura
+    pub vertices_without_normals: usize,
+    pub vertices_without_uvs: usize,
 }
 
+/// Registro del OBJ que se descartó al cargar
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjWarning {
+    /// Línea del archivo, empezando en 1 (0 si el aviso no es de una línea concreta)
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ObjWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "línea {}: {}", self.line, self.message)
+        }
+    }
+}
+
+/// Avisos que se muestran al cargar; del resto solo se da el total
+const MAX_REPORTED_WARNINGS: usize = 10;
+
 impl Obj {
     pub fn load(path: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)?;
+        let source = fs::read(path).map_err(|_| tobj::LoadError::OpenFileFailed)?;
+        let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+        let (model, warnings) = Obj::parse(&source, directory)?;
+
+        for warning in warnings.iter().take(MAX_REPORTED_WARNINGS) {
+            println!("{}: {}", path, warning);
+        }
+        if warnings.len() > MAX_REPORTED_WARNINGS {
+            println!("{}: {} avisos más", path, warnings.len() - MAX_REPORTED_WARNINGS);
+        }
+        Ok(model)
+    }
+
+    /// Carga un OBJ desde memoria. Los registros mal formados (líneas cortadas, valores no
+    /// finitos, índices fuera de rango) se descartan con un aviso en lugar de abortar la carga,
+    /// así un modelo del usuario nunca tumba el programa. `directory` es donde se buscan los MTL.
+    pub fn parse(source: &[u8], directory: &Path) -> Result<(Self, Vec<ObjWarning>), tobj::LoadError> {
+        let (source, mut warnings) = sanitize(&String::from_utf8_lossy(source));
+        let load_material = |file: &Path| tobj::load_mtl(directory.join(file));
+        let (models, materials) =
+            tobj::load_obj_buf(&mut BufReader::new(Cursor::new(source)), &tobj::GPU_LOAD_OPTIONS, load_material)?;
+
+        // Un MTL ausente o inválido no impide cargar la geometría
+        let materials = materials.unwrap_or_else(|err| {
+            warnings.push(ObjWarning { line: 0, message: format!("materiales no disponibles: {}", err) });
+            Vec::new()
+        });
+        let diffuse_texture = materials
+            .iter()
+            .find_map(|material| material.diffuse_texture.as_ref())
+            .map(|texture| directory.join(texture));
+        let normal_texture = materials
+            .iter()
+            .find_map(|material| material.normal_texture.as_ref())
+            .map(|texture| directory.join(texture));
+        let diffuse_color = materials.first().and_then(|material| material.diffuse);
+        let shininess = materials.first().and_then(|material| material.shininess);
 
         let mut vertices = Vec::new();
         let mut indices = Vec::new();
+        let mut vertices_without_normals = 0;
+        let mut vertices_without_uvs = 0;
 
         for model in models {
             let mesh = &model.mesh;
             let num_vertices = mesh.positions.len() / 3;
+            // Si solo algunas caras traen normales o UV, las del modelo entero se ignoran
+            let has_normals = mesh.normals.len() == num_vertices * 3;
+            let has_uvs = mesh.texcoords.len() == num_vertices * 2;
+            if !has_normals {
+                vertices_without_normals += num_vertices;
+            }
+            if !has_uvs {
+                vertices_without_uvs += num_vertices;
+            }
 
             for i in 0..num_vertices {
                 let x = mesh.positions[i * 3];
@@ -24,7 +108,7 @@ impl Obj {
                 let z = mesh.positions[i * 3 + 2];
                 let position = Vector3::new(x, -y, z);
 
-                let normal = if !mesh.normals.is_empty() {
+                let normal = if has_normals {
                     let nx = mesh.normals[i * 3];
                     let ny = mesh.normals[i * 3 + 1];
                     let nz = mesh.normals[i * 3 + 2];
@@ -33,7 +117,7 @@ impl Obj {
                     Vector3::zero()
                 };
 
-                let tex_coords = if !mesh.texcoords.is_empty() {
+                let tex_coords = if has_uvs {
                     let u = mesh.texcoords[i * 2];
                     let v = mesh.texcoords[i * 2 + 1];
                     Vector2::new(u, v)
@@ -43,17 +127,175 @@ impl Obj {
 
                 vertices.push(Vertex::new(position, normal, tex_coords));
             }
-            indices.extend_from_slice(&mesh.indices);
+            let base = (vertices.len() - num_vertices) as u32;
+            indices.extend(mesh.indices.iter().map(|&index| base + index));
         }
 
-        Ok(Obj { vertices, indices })
+        Ok((Obj {
+            vertices,
+            indices,
+            diffuse_texture,
+            normal_texture,
+            diffuse_color,
+            shininess,
+            vertices_without_normals,
+            vertices_without_uvs,
+        }, warnings))
+    }
+
+    /// Bytes de los vértices e índices cargados
+    pub fn memory_bytes(&self) -> usize {
+        self.vertices.capacity() * std::mem::size_of::<Vertex>() + self.indices.capacity() * std::mem::size_of::<u32>()
     }
 
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
         let mut vertex_array = Vec::new();
         for &index in &self.indices {
-            vertex_array.push(self.vertices[index as usize].clone());
+            if let Some(vertex) = self.vertices.get(index as usize) {
+                vertex_array.push(vertex.clone());
+            }
         }
         vertex_array
     }
-}
\ No newline at end of file
+
+    /// Normal de un triángulo, con longitud proporcional a su área, en el espacio de las normales
+    pub fn face_normal(&self, triangle: &[u32]) -> Vec3 {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| {
+            let p = self.vertices[index as usize].position;
+            Vec3::new(p.x, p.y, p.z)
+        });
+        let n = (b - a).cross(&(c - a));
+        // Las posiciones se guardan con la y invertida (ver `load`), lo que también invierte el
+        // sentido de giro; las normales se guardan en el espacio del archivo, como las del OBJ
+        Vec3::new(-n.x, n.y, -n.z)
+    }
+
+    /// Caras cuya normal geométrica (según el orden de sus vértices) apunta al lado contrario que
+    /// las normales de sus vértices. Las degeneradas y las que no tienen normales no cuentan.
+    pub fn reversed_faces(&self) -> usize {
+        self.indices
+            .chunks_exact(3)
+            .filter(|triangle| {
+                let normals: Vec3 = triangle
+                    .iter()
+                    .map(|&index| {
+                        let n = self.vertices[index as usize].normal;
+                        Vec3::new(n.x, n.y, n.z)
+                    })
+                    .sum();
+                self.face_normal(triangle).dot(&normals) < 0.0
+            })
+            .count()
+    }
+
+    /// Vértices e índices tal como se cargaron, sin los triángulos con índices fuera de rango
+    pub fn get_indexed_mesh(&self) -> IndexedMesh {
+        let in_range = |triangle: &&[u32]| triangle.iter().all(|&index| (index as usize) < self.vertices.len());
+        let indices = self.indices.chunks_exact(3).filter(in_range).flatten().copied().collect();
+        IndexedMesh { vertices: self.vertices.clone(), indices }
+    }
+}
+
+/// Reescribe el OBJ dejando solo registros que tobj acepta sin errores: vértices con el número
+/// justo de valores finitos y caras de al menos tres vértices con índices absolutos y en rango.
+/// Las líneas y puntos (`l`, `p`) se descartan sin aviso: el render solo usa triángulos.
+pub fn sanitize(source: &str) -> (String, Vec<ObjWarning>) {
+    let mut output = String::with_capacity(source.len());
+    let mut warnings = Vec::new();
+    // Posiciones, coordenadas de textura y normales aceptadas hasta ahora
+    let mut counts = [0usize; 3];
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let values: Vec<&str> = words.collect();
+        let accepted = match keyword {
+            "v" => floats(&values, 3).map(|record| {
+                counts[0] += 1;
+                format!("v {}", record)
+            }),
+            "vt" => floats(&values, 2).map(|record| {
+                counts[1] += 1;
+                format!("vt {}", record)
+            }),
+            "vn" => floats(&values, 3).map(|record| {
+                counts[2] += 1;
+                format!("vn {}", record)
+            }),
+            "f" => face(&values, counts).map(|record| format!("f {}", record)),
+            "l" | "p" => Ok(String::new()),
+            "usemtl" | "mtllib" if values.is_empty() => Err("falta el nombre".to_string()),
+            _ => Ok(line.to_string()),
+        };
+        match accepted {
+            Ok(record) => {
+                output.push_str(&record);
+                output.push('\n');
+            }
+            Err(message) => warnings.push(ObjWarning { line: index + 1, message: format!("'{}' descartado: {}", keyword, message) }),
+        }
+    }
+    (output, warnings)
+}
+
+/// Los `count` primeros valores, que deben ser números finitos; el resto (colores por vértice,
+/// la w de las coordenadas homogéneas) se descarta
+fn floats(values: &[&str], count: usize) -> Result<String, String> {
+    if values.len() < count {
+        return Err(format!("se esperaban {} valores y hay {}", count, values.len()));
+    }
+    let mut record = Vec::with_capacity(count);
+    for value in &values[..count] {
+        match value.parse::<f32>() {
+            Ok(number) if number.is_finite() => record.push(number.to_string()),
+            _ => return Err(format!("valor no válido '{}'", value)),
+        }
+    }
+    Ok(record.join(" "))
+}
+
+/// Cara con índices absolutos. Si no todos sus vértices traen UV o normal, se quitan de todos.
+fn face(values: &[&str], counts: [usize; 3]) -> Result<String, String> {
+    if values.len() < 3 {
+        return Err(format!("una cara necesita 3 vértices y tiene {}", values.len()));
+    }
+    let mut corners = Vec::with_capacity(values.len());
+    for value in values {
+        let mut corner = [None; 3];
+        for (slot, part) in value.split('/').enumerate() {
+            if slot >= 3 {
+                return Err(format!("vértice mal formado '{}'", value));
+            }
+            if part.is_empty() && slot > 0 {
+                continue;
+            }
+            corner[slot] = Some(resolve_index(part, counts[slot]).ok_or_else(|| format!("índice no válido '{}'", value))?);
+        }
+        corners.push(corner);
+    }
+
+    let shared = [1, 2].map(|slot| corners.iter().all(|corner| corner[slot].is_some()));
+    let record: Vec<String> = corners
+        .iter()
+        .map(|corner| {
+            let position = corner[0].unwrap_or(0);
+            match (shared[0], shared[1]) {
+                (true, true) => format!("{}/{}/{}", position, corner[1].unwrap_or(0), corner[2].unwrap_or(0)),
+                (true, false) => format!("{}/{}", position, corner[1].unwrap_or(0)),
+                (false, true) => format!("{}//{}", position, corner[2].unwrap_or(0)),
+                (false, false) => position.to_string(),
+            }
+        })
+        .collect();
+    Ok(record.join(" "))
+}
+
+/// Índice de OBJ (desde 1, o negativo contando desde el último) a índice absoluto desde 1
+fn resolve_index(text: &str, count: usize) -> Option<usize> {
+    let index: i64 = text.parse().ok()?;
+    let absolute = if index < 0 { count as i64 + 1 + index } else { index };
+    (1..=count as i64).contains(&absolute).then_some(absolute as usize)
+}
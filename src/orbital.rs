@@ -0,0 +1,43 @@
+use nalgebra_glm::Vec3;
+
+/// Elementos de la órbita de la nave respecto a un cuerpo, a partir de su posición y velocidad
+/// relativas. Periapsis y apoapsis se dan como altitudes sobre la superficie.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalElements {
+    pub altitude: f32,
+    pub speed: f32,
+    pub periapsis: f32,
+    /// `None` si la trayectoria es de escape (parabólica o hiperbólica)
+    pub apoapsis: Option<f32>,
+}
+
+impl OrbitalElements {
+    /// `mu` es G·M del cuerpo y `body_radius` su radio
+    pub fn from_state(position: Vec3, velocity: Vec3, mu: f32, body_radius: f32) -> Option<Self> {
+        let distance = position.norm();
+        if distance <= 0.0 || mu <= 0.0 {
+            return None;
+        }
+        let speed = velocity.norm();
+
+        // Energía específica y momento angular: a = -mu / 2E, e = sqrt(1 + 2E·h² / mu²)
+        let energy = speed * speed / 2.0 - mu / distance;
+        let momentum = position.cross(&velocity).norm();
+        let eccentricity = (1.0 + 2.0 * energy * momentum * momentum / (mu * mu)).max(0.0).sqrt();
+
+        let (periapsis, apoapsis) = if energy < 0.0 {
+            let semi_major_axis = -mu / (2.0 * energy);
+            (semi_major_axis * (1.0 - eccentricity), Some(semi_major_axis * (1.0 + eccentricity)))
+        } else {
+            // Escape: el periapsis sale de la ecuación de la cónica, r = h² / (mu · (1 + e))
+            (momentum * momentum / (mu * (1.0 + eccentricity)), None)
+        };
+
+        Some(OrbitalElements {
+            altitude: distance - body_radius,
+            speed,
+            periapsis: periapsis - body_radius,
+            apoapsis: apoapsis.map(|apoapsis| apoapsis - body_radius),
+        })
+    }
+}
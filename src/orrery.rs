@@ -0,0 +1,164 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::framebuffer::Framebuffer;
+use crate::text;
+
+/// Lado de la imagen exportada en píxeles
+const SIZE: usize = 1000;
+const MARGIN: f32 = 70.0;
+const BACKGROUND: u32 = 0x000011;
+const ORBIT_COLOR: u32 = 0x334466;
+const LABEL_COLOR: u32 = 0xDDEEFF;
+
+/// Lo que el esquema necesita de cada cuerpo
+pub struct OrreryBody {
+    pub name: String,
+    pub orbit_radius: f32,
+    pub orbit_angle: f32,
+    pub scale: f32,
+    pub color: u32,
+}
+
+/// Cuerpo ya colocado en la imagen: centro, radio de su órbita y de su disco, en píxeles
+struct Placed<'a> {
+    body: &'a OrreryBody,
+    x: f32,
+    y: f32,
+    orbit: f32,
+    radius: f32,
+}
+
+/// Vista cenital con el sol en el centro. A escala, distancias y tamaños usan el mismo factor
+/// (los cuerpos suelen quedar de un píxel). En modo esquemático las órbitas se reparten a
+/// intervalos iguales por orden de distancia y los discos crecen con la raíz del tamaño.
+fn layout(bodies: &[OrreryBody], to_scale: bool) -> (Vec<Placed<'_>>, Option<f32>) {
+    let center = SIZE as f32 / 2.0;
+    let usable = center - MARGIN;
+
+    let extent = bodies.iter().map(|body| body.orbit_radius + body.scale).fold(1.0, f32::max);
+    let pixels_per_unit = usable / extent;
+
+    let mut radii: Vec<f32> = bodies.iter().map(|body| body.orbit_radius).filter(|&radius| radius > 0.0).collect();
+    radii.sort_by(f32::total_cmp);
+    radii.dedup();
+    let max_scale = bodies.iter().map(|body| body.scale).fold(1e-6, f32::max);
+
+    let placed = bodies
+        .iter()
+        .map(|body| {
+            let (orbit, radius) = if to_scale {
+                (body.orbit_radius * pixels_per_unit, (body.scale * pixels_per_unit).max(1.5))
+            } else {
+                let rank = radii.iter().position(|&radius| radius == body.orbit_radius);
+                let orbit = rank.map_or(0.0, |rank| (rank + 1) as f32 / radii.len() as f32 * usable);
+                (orbit, 4.0 + 12.0 * (body.scale / max_scale).sqrt())
+            };
+            Placed {
+                body,
+                x: center + orbit * body.orbit_angle.cos(),
+                y: center + orbit * body.orbit_angle.sin(),
+                orbit,
+                radius,
+            }
+        })
+        .collect();
+
+    (placed, to_scale.then_some(pixels_per_unit))
+}
+
+fn caption(pixels_per_unit: Option<f32>) -> String {
+    match pixels_per_unit {
+        Some(pixels_per_unit) => format!("Vista cenital a escala: 1 px = {:.2} unidades", 1.0 / pixels_per_unit),
+        None => "Vista cenital esquemática (distancias y tamaños no a escala)".to_string(),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn to_svg(bodies: &[OrreryBody], to_scale: bool) -> String {
+    let (placed, pixels_per_unit) = layout(bodies, to_scale);
+    let center = SIZE as f32 / 2.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#, SIZE);
+    let _ = writeln!(svg, r##"<rect width="100%" height="100%" fill="#{:06X}"/>"##, BACKGROUND);
+    for body in placed.iter().filter(|placed| placed.orbit > 0.0) {
+        let _ = writeln!(
+            svg,
+            r##"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="none" stroke="#{:06X}"/>"##,
+            center, center, body.orbit, ORBIT_COLOR
+        );
+    }
+    for body in &placed {
+        let _ = writeln!(
+            svg,
+            r##"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="#{:06X}"/>"##,
+            body.x, body.y, body.radius, body.body.color
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" fill="#{:06X}" font-family="monospace" font-size="14">{}</text>"##,
+            body.x + body.radius + 4.0,
+            body.y + 4.0,
+            LABEL_COLOR,
+            escape_xml(&body.body.name)
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r##"<text x="20" y="{}" fill="#{:06X}" font-family="monospace" font-size="14">{}</text>"##,
+        SIZE - 20,
+        LABEL_COLOR,
+        escape_xml(&caption(pixels_per_unit))
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn render_png(bodies: &[OrreryBody], to_scale: bool, path: &Path) -> io::Result<()> {
+    let (placed, pixels_per_unit) = layout(bodies, to_scale);
+    let center = SIZE as f32 / 2.0;
+
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.set_background_color(BACKGROUND);
+    framebuffer.clear();
+
+    for body in placed.iter().filter(|placed| placed.orbit > 0.0) {
+        let steps = (body.orbit * std::f32::consts::TAU).ceil() as usize;
+        for step in 0..steps {
+            let angle = step as f32 / steps as f32 * std::f32::consts::TAU;
+            let x = center + body.orbit * angle.cos();
+            let y = center + body.orbit * angle.sin();
+            framebuffer.overlay_pixel(x as i32, y as i32, ORBIT_COLOR);
+        }
+    }
+    for body in &placed {
+        let reach = body.radius.ceil() as i32;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                if ((dx * dx + dy * dy) as f32) <= body.radius * body.radius {
+                    framebuffer.overlay_pixel(body.x as i32 + dx, body.y as i32 + dy, body.body.color);
+                }
+            }
+        }
+        let label_x = (body.x + body.radius + 4.0) as i32;
+        text::draw_text(&mut framebuffer, label_x, body.y as i32 - 7, &body.body.name, LABEL_COLOR, 2);
+    }
+    text::draw_text(&mut framebuffer, 20, SIZE as i32 - 30, &caption(pixels_per_unit), LABEL_COLOR, 2);
+
+    framebuffer.save_png(&path.to_string_lossy())
+}
+
+/// Exporta el esquema a SVG o PNG según la extensión de `path`
+pub fn export(path: &str, bodies: &[OrreryBody], to_scale: bool) -> io::Result<()> {
+    let path = Path::new(path);
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("svg") => fs::write(path, to_svg(bodies, to_scale)),
+        Some(extension) if extension.eq_ignore_ascii_case("png") => render_png(bodies, to_scale, path),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "la extensión debe ser .svg o .png")),
+    }
+}
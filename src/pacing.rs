@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+/// Límite de frames por segundo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameCap {
+    Fps(u32),
+    Uncapped,
+}
+
+impl FrameCap {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "30" => Ok(FrameCap::Fps(30)),
+            "60" => Ok(FrameCap::Fps(60)),
+            "120" => Ok(FrameCap::Fps(120)),
+            "uncapped" => Ok(FrameCap::Uncapped),
+            other => Err(format!("límite de FPS desconocido: {} (30, 60, 120 o uncapped)", other)),
+        }
+    }
+
+    fn frame_time(self) -> Option<Duration> {
+        match self {
+            FrameCap::Fps(fps) => Some(Duration::from_secs_f64(1.0 / fps.max(1) as f64)),
+            FrameCap::Uncapped => None,
+        }
+    }
+}
+
+/// Delta máximo de un frame: tras una parada larga (ventana arrastrada, depurador) el mundo
+/// avanza como mucho esto en lugar de dar un salto
+const MAX_DELTA: f32 = 0.25;
+/// Con espera activa, se duerme hasta este margen antes del plazo y el resto se gira
+const SPIN_MARGIN: Duration = Duration::from_micros(1500);
+
+/// Ritmo de frames: mide el delta real de cada frame y espera al final hasta el plazo del
+/// siguiente. Los plazos se encadenan (el siguiente es el anterior más un frame), así el ritmo
+/// medio es el del límite aunque `sleep` se pase unos microsegundos; si un frame llega tarde se
+/// cuenta como perdido y se vuelve a empezar desde ahora, sin ráfagas para recuperar.
+///
+/// minifb no permite elegir la sincronización vertical: el límite sustituye a
+/// `Window::limit_update_rate`, que se desactiva.
+pub struct FramePacer {
+    frame_time: Option<Duration>,
+    busy_wait: bool,
+    last_frame: Instant,
+    deadline: Instant,
+    dropped: u64,
+}
+
+impl FramePacer {
+    /// `busy_wait` apura el plazo girando en vez de durmiendo: más preciso, a costa de un núcleo
+    pub fn new(cap: FrameCap, busy_wait: bool) -> Self {
+        let now = Instant::now();
+        FramePacer { frame_time: cap.frame_time(), busy_wait, last_frame: now, deadline: now, dropped: 0 }
+    }
+
+    /// Al empezar cada frame: segundos reales desde el anterior (hasta `MAX_DELTA`)
+    pub fn begin_frame(&mut self) -> f32 {
+        let now = Instant::now();
+        let delta = (now - self.last_frame).as_secs_f32();
+        self.last_frame = now;
+        delta.min(MAX_DELTA)
+    }
+
+    /// Al terminar cada frame: espera hasta el plazo del siguiente
+    pub fn end_frame(&mut self) {
+        let Some(frame_time) = self.frame_time else {
+            return;
+        };
+        self.deadline += frame_time;
+        let now = Instant::now();
+        if now > self.deadline {
+            self.dropped += ((now - self.deadline).as_secs_f64() / frame_time.as_secs_f64()) as u64 + 1;
+            self.deadline = now;
+            return;
+        }
+
+        if self.busy_wait {
+            if let Some(sleep) = (self.deadline - now).checked_sub(SPIN_MARGIN) {
+                std::thread::sleep(sleep);
+            }
+            while Instant::now() < self.deadline {
+                std::hint::spin_loop();
+            }
+        } else {
+            std::thread::sleep(self.deadline - now);
+        }
+    }
+
+    /// Frames que no llegaron a su plazo desde la última llamada
+    pub fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+}
@@ -0,0 +1,78 @@
+use std::f32::consts::PI;
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+
+/// Lado de cada cara del cubo. Cuatro caras cubren el ecuador del panorama con un píxel de cara
+/// por píxel de panorama.
+pub const FACE_SIZE: usize = 512;
+pub const PANORAMA_WIDTH: usize = FACE_SIZE * 4;
+pub const PANORAMA_HEIGHT: usize = FACE_SIZE * 2;
+
+/// Caras del cubo como (dirección de la vista, arriba), alineadas con los ejes del mundo
+const CUBE_FACES: [(Vec3, Vec3); 6] = [
+    (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+    (Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+    (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+    (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+];
+
+/// Panorama equirectangular de 360° × 180° alrededor de un punto. `render_face` dibuja en el
+/// framebuffer cuadrado que recibe la vista con 90° de campo hacia `forward` (con `up` arriba);
+/// el índice de la cara sirve para variar lo que va en espacio de pantalla, como las estrellas.
+/// El centro de la imagen mira hacia -Z y el norte (+Y) queda arriba.
+pub fn render<F: FnMut(&mut Framebuffer, usize, Vec3, Vec3)>(mut render_face: F) -> Framebuffer {
+    let faces: Vec<Framebuffer> = CUBE_FACES
+        .iter()
+        .enumerate()
+        .map(|(index, &(forward, up))| {
+            let mut face = Framebuffer::new(FACE_SIZE, FACE_SIZE);
+            face.clear();
+            render_face(&mut face, index, forward, up);
+            face
+        })
+        .collect();
+
+    let mut panorama = Framebuffer::new(PANORAMA_WIDTH, PANORAMA_HEIGHT);
+    for y in 0..PANORAMA_HEIGHT {
+        let latitude = PI / 2.0 - (y as f32 + 0.5) / PANORAMA_HEIGHT as f32 * PI;
+        for x in 0..PANORAMA_WIDTH {
+            let longitude = (x as f32 + 0.5) / PANORAMA_WIDTH as f32 * 2.0 * PI - PI;
+            let direction = Vec3::new(latitude.cos() * longitude.sin(), latitude.sin(), -latitude.cos() * longitude.cos());
+            panorama.buffer[y * PANORAMA_WIDTH + x] = sample_cube(&faces, direction);
+        }
+    }
+    panorama
+}
+
+/// Color del cubo en `direction`, con filtro bilineal dentro de la cara
+fn sample_cube(faces: &[Framebuffer], direction: Vec3) -> u32 {
+    // La cara es la del eje en el que la dirección avanza más
+    let (index, &(forward, up)) = CUBE_FACES
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| direction.dot(&a.0).total_cmp(&direction.dot(&b.0)))
+        .expect("el cubo tiene seis caras");
+    let right = forward.cross(&up);
+    let along = direction.dot(&forward);
+    // Coordenadas en la cara de -1 a 1, con la y de pantalla hacia abajo
+    let u = direction.dot(&right) / along;
+    let v = -direction.dot(&up) / along;
+    let face = &faces[index];
+    let to_pixel = |t: f32| ((t + 1.0) / 2.0 * FACE_SIZE as f32 - 0.5).clamp(0.0, (FACE_SIZE - 1) as f32);
+    let (px, py) = (to_pixel(u), to_pixel(v));
+    let (x0, y0) = (px.floor() as usize, py.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(FACE_SIZE - 1), (y0 + 1).min(FACE_SIZE - 1));
+    let (fx, fy) = (px - x0 as f32, py - y0 as f32);
+
+    let texel = |x: usize, y: usize| face.buffer[y * FACE_SIZE + x];
+    let mut color = 0;
+    for shift in [16, 8, 0] {
+        let channel = |pixel: u32| ((pixel >> shift) & 0xFF) as f32;
+        let top = channel(texel(x0, y0)) * (1.0 - fx) + channel(texel(x1, y0)) * fx;
+        let bottom = channel(texel(x0, y1)) * (1.0 - fx) + channel(texel(x1, y1)) * fx;
+        color |= ((top * (1.0 - fy) + bottom * fy).round() as u32) << shift;
+    }
+    color
+}
@@ -0,0 +1,115 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::memory::vec_bytes;
+use crate::{project_to_screen, Uniforms};
+
+/// Partícula simple; la posición es relativa al origen que se pasa al renderizar
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub age: f32,
+    pub lifetime: f32,
+    pub color_start: Vec3,
+    pub color_end: Vec3,
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    max_particles: usize,
+    /// Aceleración hacia el origen del sistema (gravedad del cuerpo que las emite)
+    pub attraction: f32,
+    /// Fracción de velocidad que se pierde por segundo
+    pub drag: f32,
+}
+
+impl ParticleSystem {
+    pub fn new(max_particles: usize) -> Self {
+        ParticleSystem {
+            particles: Vec::with_capacity(max_particles),
+            max_particles,
+            attraction: 0.0,
+            drag: 0.0,
+        }
+    }
+
+    /// Bytes reservados para las partículas (la capacidad máxima se reserva al crear el sistema)
+    pub fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.particles)
+    }
+
+    pub fn emit(&mut self, particle: Particle) {
+        if self.particles.len() < self.max_particles {
+            self.particles.push(particle);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter()
+    }
+
+    /// Desplaza todas las partículas (cuando el origen del sistema se mueve)
+    pub fn shift(&mut self, offset: Vec3) {
+        for particle in &mut self.particles {
+            particle.position += offset;
+        }
+    }
+
+    /// Elimina las partículas que se alejaron más de `radius` del origen
+    pub fn retain_within(&mut self, radius: f32) {
+        self.particles.retain(|particle| particle.position.norm() <= radius);
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        let damping = (1.0 - self.drag * delta_time).max(0.0);
+        let attraction = self.attraction;
+
+        for particle in &mut self.particles {
+            if attraction != 0.0 && particle.position.norm() > 0.0 {
+                particle.velocity -= particle.position.normalize() * attraction * delta_time;
+            }
+            particle.velocity *= damping;
+            particle.position += particle.velocity * delta_time;
+            particle.age += delta_time;
+        }
+
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, origin: Vec3) {
+        for particle in &self.particles {
+            let Some(screen) = project_to_screen(uniforms, origin + particle.position) else {
+                continue;
+            };
+            if screen.x < 0.0 || screen.y < 0.0 {
+                continue;
+            }
+
+            let life = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+            let color = particle.color_start.lerp(&particle.color_end, life) * (1.0 - life * 0.7);
+            let r = (color.x.clamp(0.0, 1.0) * 255.0) as u32;
+            let g = (color.y.clamp(0.0, 1.0) * 255.0) as u32;
+            let b = (color.z.clamp(0.0, 1.0) * 255.0) as u32;
+            framebuffer.set_current_color((r << 16) | (g << 8) | b);
+
+            let x = screen.x as usize;
+            let y = screen.y as usize;
+            framebuffer.point(x, y, screen.z);
+
+            // Las partículas jóvenes se dibujan más grandes
+            if life < 0.4 {
+                framebuffer.point(x + 1, y, screen.z);
+                framebuffer.point(x, y + 1, screen.z);
+                framebuffer.point(x + 1, y + 1, screen.z);
+            }
+        }
+    }
+}
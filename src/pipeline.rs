@@ -0,0 +1,321 @@
+use std::ops::ControlFlow;
+use nalgebra_glm::{self as glm, look_at, perspective, Mat3, Mat4, Vec3, Vec4};
+use raylib::math::Vector3;
+use crate::clip::{clip_corners, clip_position, clip_triangle, Clipped};
+use crate::depth::DepthMode;
+use crate::fragment::Fragment;
+use crate::light::Light;
+use crate::line::clip_to_screen;
+use crate::shaders::vertex_shader;
+use crate::triangle::triangle_each;
+use crate::vertex::{IndexedMesh, Vertex};
+
+// Etapas del pipeline que no tocan el framebuffer: transformación de vértices, recorte y
+// rasterización a fragmentos. Son funciones puras (mismas entradas, mismos fragmentos), lo que
+// permite comprobarlas sin ventana en las pruebas de referencia de `tests/golden.rs`.
+
+/// Escala, rotación (Z · Y · X) y traslación de un modelo
+pub fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    let rotation_matrix_x = Mat4::new(
+        1.0,  0.0,    0.0,   0.0,
+        0.0,  cos_x, -sin_x, 0.0,
+        0.0,  sin_x,  cos_x, 0.0,
+        0.0,  0.0,    0.0,   1.0,
+    );
+
+    let rotation_matrix_y = Mat4::new(
+        cos_y,  0.0,  sin_y, 0.0,
+        0.0,    1.0,  0.0,   0.0,
+        -sin_y, 0.0,  cos_y, 0.0,
+        0.0,    0.0,  0.0,   1.0,
+    );
+
+    let rotation_matrix_z = Mat4::new(
+        cos_z, -sin_z, 0.0, 0.0,
+        sin_z,  cos_z, 0.0, 0.0,
+        0.0,    0.0,  1.0, 0.0,
+        0.0,    0.0,  0.0, 1.0,
+    );
+
+    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+
+    let transform_matrix = Mat4::new(
+        scale, 0.0,   0.0,   translation.x,
+        0.0,   scale, 0.0,   translation.y,
+        0.0,   0.0,   scale, translation.z,
+        0.0,   0.0,   0.0,   1.0,
+    );
+
+    transform_matrix * rotation_matrix
+}
+
+/// Inversa traspuesta de la parte lineal del modelo, la que lleva las normales al mundo: con
+/// escala no uniforme la propia matriz las inclinaría y dejarían de ser perpendiculares a la
+/// superficie. Si el modelo no tiene inversa (escala 0) se usa tal cual.
+pub fn create_normal_matrix(model_matrix: &Mat4) -> Mat3 {
+    let linear = glm::mat4_to_mat3(model_matrix);
+    linear.try_inverse().map_or(linear, |inverse| inverse.transpose())
+}
+
+pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
+    look_at(&eye, &center, &up)
+}
+
+pub fn create_projection_matrix(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+    perspective(fov_y, aspect, near, far)
+}
+
+/// NDC a píxeles del rectángulo (x, y, width, height); la y de pantalla crece hacia abajo
+pub fn create_viewport_matrix(x: f32, y: f32, width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0, 0.0, 0.0, x + width / 2.0,
+        0.0, -height / 2.0, 0.0, y + height / 2.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+/// Matrices y parámetros comunes a todos los vértices y fragmentos de un dibujo
+#[derive(Clone, Copy)]
+pub struct Uniforms {
+    pub model_matrix: Mat4,
+    /// Para las normales (ver `create_normal_matrix`); se calcula una vez por dibujo
+    pub normal_matrix: Mat3,
+    /// Lleva direcciones del mundo al espacio del modelo (`None` si no tiene inversa); también
+    /// se calcula una vez por dibujo para no invertir la matriz en cada fragmento
+    pub inverse_model_matrix: Option<Mat4>,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    pub time: f32,
+    pub camera_position: Vec3,
+    pub light_position: Vec3,
+    pub depth_mode: DepthMode,
+    /// Qué caras se dibujan; las demás se descartan antes de rasterizar
+    pub culling: Culling,
+}
+
+impl Uniforms {
+    /// Normal del modelo en el mundo, sin normalizar
+    pub fn world_normal(&self, normal: Vec3) -> Vec3 {
+        self.normal_matrix * normal
+    }
+}
+
+/// Fracción de caras al revés que se tolera sin cambiar el culling de la malla (ver
+/// `Culling::for_reversed_faces`): unas pocas caras mal orientadas no justifican dibujar todas
+/// dos veces
+const TOLERATED_REVERSED_FACES: f32 = 0.02;
+
+/// Qué triángulos se dibujan según su sentido de giro en pantalla (con la y hacia abajo, como
+/// el framebuffer). Una malla cerrada solo necesita los que miran a la cámara; con el sentido
+/// equivocado se verían solo las caras de atrás, y una malla con las caras mezcladas necesita
+/// las dos (ver `Culling::for_reversed_faces`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Culling {
+    /// Se dibujan las dos caras
+    Off,
+    /// Se dibujan los que giran en sentido horario y se descartan los demás. Es el de las
+    /// mallas tal como las carga `Obj::load` (con la y invertida), y el de la icosfera.
+    #[default]
+    Clockwise,
+    /// Los que giran en sentido antihorario, para las mallas con las caras al revés
+    CounterClockwise,
+}
+
+impl Culling {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "off" => Ok(Culling::Off),
+            "cw" => Ok(Culling::Clockwise),
+            "ccw" => Ok(Culling::CounterClockwise),
+            other => Err(format!("sentido de giro desconocido: {} (cw, ccw u off)", other)),
+        }
+    }
+
+    /// Culling con el que se ve por delante una malla de `triangles` caras de las que `reversed`
+    /// giran al revés (ver `Obj::reversed_faces`): el habitual si giran como las de `Obj::load`,
+    /// el contrario si casi todas van al revés y ninguno si las hay de los dos sentidos
+    pub fn for_reversed_faces(reversed: usize, triangles: usize) -> Self {
+        let reversed = reversed as f32 / triangles.max(1) as f32;
+        if reversed <= TOLERATED_REVERSED_FACES {
+            Culling::default()
+        } else if reversed >= 1.0 - TOLERATED_REVERSED_FACES {
+            Culling::default().reversed()
+        } else {
+            Culling::Off
+        }
+    }
+
+    /// El sentido contrario, para las mallas con todas las caras al revés
+    pub fn reversed(self) -> Self {
+        match self {
+            Culling::Off => Culling::Off,
+            Culling::Clockwise => Culling::CounterClockwise,
+            Culling::CounterClockwise => Culling::Clockwise,
+        }
+    }
+
+    /// Si el triángulo (ya en pantalla) se dibuja. Los degenerados se dejan al rasterizador, que
+    /// no produce nada con ellos.
+    pub fn keeps(self, a: &Vertex, b: &Vertex, c: &Vertex) -> bool {
+        let (a, b, c) = (a.transformed_position, b.transformed_position, c.transformed_position);
+        let doubled_area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+        match self {
+            Culling::Off => true,
+            Culling::Clockwise => doubled_area >= 0.0,
+            Culling::CounterClockwise => doubled_area <= 0.0,
+        }
+    }
+}
+
+/// Punto de mundo a pantalla (x, y en píxeles y la profundidad codificada), o `None` si queda
+/// detrás de la cámara
+pub fn project_to_screen(uniforms: &Uniforms, point: Vec3) -> Option<Vector3> {
+    let clip = uniforms.projection_matrix
+        * uniforms.view_matrix
+        * nalgebra_glm::vec4(point.x, point.y, point.z, 1.0);
+
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    let ndc = nalgebra_glm::vec4(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    let screen = uniforms.viewport_matrix * ndc;
+    Some(Vector3::new(screen.x, screen.y, uniforms.depth_mode.encode(clip.z, clip.w)))
+}
+
+/// Proyecta un segmento de mundo a pantalla, recortándolo contra el plano cercano y los bordes
+pub fn project_segment(uniforms: &Uniforms, width: f32, height: f32, from: Vec3, to: Vec3) -> Option<(Vector3, Vector3)> {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let mut a = view_projection * nalgebra_glm::vec4(from.x, from.y, from.z, 1.0);
+    let mut b = view_projection * nalgebra_glm::vec4(to.x, to.y, to.z, 1.0);
+
+    // Plano cercano en espacio de recorte: z + w >= 0
+    let da = a.z + a.w;
+    let db = b.z + b.w;
+    if da < 0.0 && db < 0.0 {
+        return None;
+    }
+    if da < 0.0 {
+        a += (b - a) * (da / (da - db));
+    } else if db < 0.0 {
+        b += (a - b) * (db / (db - da));
+    }
+
+    let to_screen = |clip: nalgebra_glm::Vec4| {
+        let w = clip.w.max(1e-6);
+        let screen = uniforms.viewport_matrix * nalgebra_glm::vec4(clip.x / w, clip.y / w, clip.z / w, 1.0);
+        Vector3::new(screen.x, screen.y, uniforms.depth_mode.encode(clip.z, w))
+    };
+
+    clip_to_screen(to_screen(a), to_screen(b), width, height)
+}
+
+/// Color en [0, 1] a 0xRRGGBB
+pub fn pack_color(color: Vector3) -> u32 {
+    let r = (color.x.clamp(0.0, 1.0) * 255.0) as u32;
+    let g = (color.y.clamp(0.0, 1.0) * 255.0) as u32;
+    let b = (color.z.clamp(0.0, 1.0) * 255.0) as u32;
+    (r << 16) | (g << 8) | b
+}
+
+/// Etapa de geometría: transforma los vértices y rasteriza los triángulos visibles en fragmentos
+pub fn rasterize(uniforms: &Uniforms, vertex_array: &[Vertex], light: &Light) -> Vec<Fragment> {
+    let mut fragments = Vec::new();
+    rasterize_each(uniforms, vertex_array, light, |fragment| {
+        fragments.push(fragment);
+        ControlFlow::Continue(())
+    });
+    fragments
+}
+
+/// Como `rasterize`, pero entrega cada fragmento a `emit` en cuanto sale del rasterizador, sin
+/// guardarlos: quien sombrea en el momento solo se queda con el color. Si `emit` corta, se
+/// dejan de rasterizar el resto de triángulos. Devuelve cuántos triángulos se han rasterizado.
+pub fn rasterize_each<F: FnMut(Fragment) -> ControlFlow<()>>(uniforms: &Uniforms, vertex_array: &[Vertex], light: &Light, mut emit: F) -> usize {
+    let transformed_vertices = transform_triangles(uniforms, vertex_array);
+    let mut rasterized = 0;
+
+    for tri in transformed_vertices.chunks_exact(3) {
+        rasterized += 1;
+        let flow = triangle_each(&tri[0], &tri[1], &tri[2], light, |mut fragment| {
+            if uniforms.depth_mode != DepthMode::Perspective {
+                fragment.depth = uniforms.depth_mode.resolve(fragment.depth);
+            }
+            emit(fragment)
+        });
+        if flow.is_break() {
+            break;
+        }
+    }
+    rasterized
+}
+
+/// Vértices de los triángulos visibles ya en pantalla, de 3 en 3 y listos para rasterizar. Las
+/// caras que descarta `uniforms.culling` no salen, así el rasterizado directo y el de tiles
+/// dibujan los mismos triángulos.
+pub fn transform_triangles(uniforms: &Uniforms, vertex_array: &[Vertex]) -> Vec<Vertex> {
+    // Recorte contra el frustum antes de proyectar: sin él, un triángulo con un vértice detrás de
+    // la cámara (w <= 0) da la vuelta a la pantalla al dividir por w. Los que quedan fuera de
+    // algún plano ni se transforman.
+    let clip_matrix = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
+    for triangle in vertex_array.chunks_exact(3) {
+        match clip_triangle(triangle, &clip_matrix) {
+            Clipped::Inside => transformed_vertices.extend(triangle.iter().map(|vertex| vertex_shader(vertex, uniforms))),
+            Clipped::Outside => {}
+            Clipped::Split(vertices) => transformed_vertices.extend(vertices.iter().map(|vertex| vertex_shader(vertex, uniforms))),
+        }
+    }
+    cull_back_faces(uniforms.culling, transformed_vertices)
+}
+
+/// Como `transform_triangles`, para una malla indexada: cada vértice pasa por el vertex shader
+/// una sola vez aunque lo compartan varios triángulos. Solo los vértices nuevos de los recortes
+/// se transforman aparte. La salida es la misma, de 3 en 3 y en el mismo orden.
+pub fn transform_indexed(uniforms: &Uniforms, mesh: &IndexedMesh) -> Vec<Vertex> {
+    let clip_matrix = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+    let positions: Vec<Vec4> = mesh.vertices.iter().map(|vertex| clip_position(vertex, &clip_matrix)).collect();
+    let mut shaded: Vec<Option<Vertex>> = vec![None; mesh.vertices.len()];
+    let mut transformed_vertices = Vec::with_capacity(mesh.indices.len());
+    for triangle in mesh.indices.chunks_exact(3) {
+        let corners = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+        match clip_corners(corners.map(|index| &mesh.vertices[index]), corners.map(|index| positions[index])) {
+            Clipped::Inside => {
+                for index in corners {
+                    let vertex = shaded[index].get_or_insert_with(|| vertex_shader(&mesh.vertices[index], uniforms));
+                    transformed_vertices.push(vertex.clone());
+                }
+            }
+            Clipped::Outside => {}
+            Clipped::Split(vertices) => transformed_vertices.extend(vertices.iter().map(|vertex| vertex_shader(vertex, uniforms))),
+        }
+    }
+    cull_back_faces(uniforms.culling, transformed_vertices)
+}
+
+/// Quita de `vertices` (de 3 en 3, en pantalla) los triángulos que `culling` no dibuja. El
+/// recorte conserva el sentido de giro, así que los trozos de un triángulo cortado se quedan o
+/// se van juntos.
+fn cull_back_faces(culling: Culling, mut vertices: Vec<Vertex>) -> Vec<Vertex> {
+    if culling == Culling::Off {
+        return vertices;
+    }
+    let mut kept = 0;
+    for triangle in 0..vertices.len() / 3 {
+        let [a, b, c] = [0, 1, 2].map(|corner| triangle * 3 + corner);
+        if culling.keeps(&vertices[a], &vertices[b], &vertices[c]) {
+            for corner in 0..3 {
+                vertices.swap(kept * 3 + corner, triangle * 3 + corner);
+            }
+            kept += 1;
+        }
+    }
+    vertices.truncate(kept * 3);
+    vertices
+}
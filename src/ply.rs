@@ -0,0 +1,243 @@
+use std::fs;
+use raylib::math::{Vector2, Vector3};
+use crate::mesh::MeshError;
+use crate::obj::Obj;
+use crate::vertex::Vertex;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+/// Tipo escalar de una propiedad, con su tamaño en bytes en los formatos binarios
+#[derive(Debug, Clone, Copy)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self, MeshError> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            other => return Err(MeshError::Format(format!("tipo PLY desconocido: {}", other))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Property {
+    Scalar { name: String, scalar: Scalar },
+    /// Lista con su contador y el tipo de cada elemento, p. ej. `vertex_indices`
+    List { name: String, count: Scalar, item: Scalar },
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Lee los valores del cuerpo del archivo, ya sea texto o binario
+struct Reader<'a> {
+    format: Format,
+    data: &'a [u8],
+    offset: usize,
+    words: std::str::SplitWhitespace<'a>,
+}
+
+impl Reader<'_> {
+    fn value(&mut self, scalar: Scalar) -> Result<f64, MeshError> {
+        if self.format == Format::Ascii {
+            let word = self.words.next().ok_or_else(|| MeshError::Format("PLY truncado".to_string()))?;
+            return word.parse().map_err(|_| MeshError::Format(format!("valor PLY inválido: {}", word)));
+        }
+
+        let size = scalar.size();
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + size)
+            .ok_or_else(|| MeshError::Format("PLY truncado".to_string()))?;
+        self.offset += size;
+        let mut buffer = [0u8; 8];
+        buffer[..size].copy_from_slice(bytes);
+        if self.format == Format::BigEndian {
+            buffer[..size].reverse();
+        }
+        let [b0, b1, b2, b3, ..] = buffer;
+        Ok(match scalar {
+            Scalar::I8 => b0 as i8 as f64,
+            Scalar::U8 => b0 as f64,
+            Scalar::I16 => i16::from_le_bytes([b0, b1]) as f64,
+            Scalar::U16 => u16::from_le_bytes([b0, b1]) as f64,
+            Scalar::I32 => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            Scalar::U32 => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            Scalar::F32 => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            Scalar::F64 => f64::from_le_bytes(buffer),
+        })
+    }
+}
+
+/// Carga un PLY ASCII o binario (little o big endian). Del elemento `vertex` se usan x, y, z,
+/// nx, ny, nz y las coordenadas de textura (u/v, s/t o texture_u/texture_v); las caras con más
+/// de tres vértices se triangulan en abanico. Los demás elementos se leen y se descartan.
+pub fn load(path: &str) -> Result<Obj, MeshError> {
+    let data = fs::read(path)?;
+    let header_end = find_header_end(&data).ok_or_else(|| MeshError::Format("PLY sin end_header".to_string()))?;
+    let header = String::from_utf8_lossy(&data[..header_end]);
+    let (format, elements) = parse_header(&header)?;
+
+    let body_text = if format == Format::Ascii {
+        String::from_utf8_lossy(&data[header_end..]).into_owned()
+    } else {
+        String::new()
+    };
+    let mut reader = Reader { format, data: &data, offset: header_end, words: body_text.split_whitespace() };
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let (mut has_normals, mut has_uvs) = (false, false);
+
+    for element in &elements {
+        for _ in 0..element.count {
+            let mut position = [0.0f32; 3];
+            let mut normal = [0.0f32; 3];
+            let mut uv = [0.0f32; 2];
+            let mut face = Vec::new();
+
+            for property in &element.properties {
+                match property {
+                    Property::Scalar { name, scalar } => {
+                        let value = reader.value(*scalar)? as f32;
+                        match name.as_str() {
+                            "x" => position[0] = value,
+                            "y" => position[1] = value,
+                            "z" => position[2] = value,
+                            "nx" => normal[0] = value,
+                            "ny" => normal[1] = value,
+                            "nz" => normal[2] = value,
+                            "u" | "s" | "texture_u" => uv[0] = value,
+                            "v" | "t" | "texture_v" => uv[1] = value,
+                            _ => {}
+                        }
+                        has_normals |= element.name == "vertex" && name == "nx";
+                        has_uvs |= element.name == "vertex" && matches!(name.as_str(), "u" | "s" | "texture_u");
+                    }
+                    Property::List { name, count, item } => {
+                        let length = reader.value(*count)? as usize;
+                        for _ in 0..length {
+                            let value = reader.value(*item)?;
+                            if name == "vertex_indices" || name == "vertex_index" {
+                                face.push(value as u32);
+                            }
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let [x, y, z] = position;
+                    let [nx, ny, nz] = normal;
+                    vertices.push(Vertex::new(Vector3::new(x, -y, z), Vector3::new(nx, ny, nz), Vector2::new(uv[0], uv[1])));
+                }
+                "face" => {
+                    for corner in 1..face.len().saturating_sub(1) {
+                        indices.extend_from_slice(&[face[0], face[corner], face[corner + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(&index) = indices.iter().find(|&&index| index as usize >= vertices.len()) {
+        return Err(MeshError::Format(format!("índice de vértice fuera de rango: {}", index)));
+    }
+
+    let count = vertices.len();
+    Ok(Obj {
+        vertices,
+        indices,
+        diffuse_texture: None,
+        normal_texture: None,
+        diffuse_color: None,
+        shininess: None,
+        vertices_without_normals: if has_normals { 0 } else { count },
+        vertices_without_uvs: if has_uvs { 0 } else { count },
+    })
+}
+
+/// Posición del primer byte tras la línea `end_header`
+fn find_header_end(data: &[u8]) -> Option<usize> {
+    let marker = b"end_header";
+    let start = data.windows(marker.len()).position(|window| window == marker)?;
+    let newline = data[start..].iter().position(|&byte| byte == b'\n')?;
+    Some(start + newline + 1)
+}
+
+fn parse_header(header: &str) -> Result<(Format, Vec<Element>), MeshError> {
+    let invalid = |line: &str| MeshError::Format(format!("cabecera PLY inválida: {}", line.trim()));
+    let mut lines = header.lines();
+    if lines.next().map(str::trim) != Some("ply") {
+        return Err(MeshError::Format("no es un archivo PLY".to_string()));
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["format", "ascii", _] => format = Some(Format::Ascii),
+            ["format", "binary_little_endian", _] => format = Some(Format::LittleEndian),
+            ["format", "binary_big_endian", _] => format = Some(Format::BigEndian),
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| invalid(line))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let element = elements.last_mut().ok_or_else(|| invalid(line))?;
+                element.properties.push(Property::List {
+                    name: name.to_string(),
+                    count: Scalar::parse(count)?,
+                    item: Scalar::parse(item)?,
+                });
+            }
+            ["property", scalar, name] => {
+                let element = elements.last_mut().ok_or_else(|| invalid(line))?;
+                element.properties.push(Property::Scalar { name: name.to_string(), scalar: Scalar::parse(scalar)? });
+            }
+            ["comment", ..] | ["obj_info", ..] | ["end_header"] | [] => {}
+            _ => return Err(invalid(line)),
+        }
+    }
+
+    let format = format.ok_or_else(|| MeshError::Format("PLY sin línea format".to_string()))?;
+    Ok((format, elements))
+}
@@ -0,0 +1,124 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use nalgebra_glm::{DVec3, Vec3};
+use crate::simulation::{Ephemeris, SHIP_CLEARANCE};
+
+/// Puntos de cada trayectoria de los cuerpos
+const BODY_SAMPLES: usize = 64;
+/// Pasos de integración de la nave y cada cuántos se guarda un punto
+const SHIP_STEPS: usize = 480;
+const SHIP_SAMPLE_EVERY: usize = 6;
+
+/// Estado del que parte una predicción
+pub struct PredictionRequest {
+    /// Instante de simulación y escala de tiempo actuales
+    pub time: f64,
+    pub time_scale: f64,
+    /// Segundos de tiempo real que se predicen
+    pub horizon: f32,
+    /// Cada cuerpo con su masa
+    pub bodies: Vec<(Ephemeris, f32)>,
+    /// Constante gravitacional de la escena
+    pub gravity: f32,
+    pub ship_position: DVec3,
+    /// Velocidad de la nave en unidades por segundo real
+    pub ship_velocity: Vec3,
+}
+
+/// Trayectorias previstas, del presente al final del horizonte
+pub struct Prediction {
+    /// Una por cuerpo, en el orden de la petición
+    pub bodies: Vec<Vec<DVec3>>,
+    /// Vuelo balístico de la nave (motores parados) bajo la gravedad de los cuerpos; se corta si
+    /// choca con uno
+    pub ship: Vec<DVec3>,
+}
+
+/// Hilo que calcula las trayectorias previstas. Igual que `SimulationWorker`, el render envía
+/// una petición por frame, el hilo atiende solo la más reciente y el render dibuja la última
+/// predicción que haya llegado, sin esperar.
+pub struct TrajectoryPredictor {
+    requests: Sender<PredictionRequest>,
+    results: Receiver<Prediction>,
+    latest: Option<Prediction>,
+}
+
+impl TrajectoryPredictor {
+    pub fn spawn() -> Self {
+        let (requests, request_receiver) = mpsc::channel::<PredictionRequest>();
+        let (result_sender, results) = mpsc::channel();
+
+        thread::spawn(move || {
+            while let Ok(first) = request_receiver.recv() {
+                let request = request_receiver.try_iter().last().unwrap_or(first);
+                if result_sender.send(predict(&request)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        TrajectoryPredictor { requests, results, latest: None }
+    }
+
+    pub fn submit(&self, request: PredictionRequest) {
+        let _ = self.requests.send(request);
+    }
+
+    /// Última predicción recibida
+    pub fn latest(&mut self) -> Option<&Prediction> {
+        if let Some(prediction) = self.results.try_iter().last() {
+            self.latest = Some(prediction);
+        }
+        self.latest.as_ref()
+    }
+
+    /// Olvida la predicción guardada (p. ej. al cambiar de escena)
+    pub fn clear(&mut self) {
+        self.results.try_iter().for_each(drop);
+        self.latest = None;
+    }
+}
+
+fn predict(request: &PredictionRequest) -> Prediction {
+    let sim_horizon = request.horizon as f64 * request.time_scale;
+    let bodies = request
+        .bodies
+        .iter()
+        .map(|(ephemeris, _)| {
+            (0..=BODY_SAMPLES)
+                .map(|sample| ephemeris.state_at(request.time + sim_horizon * sample as f64 / BODY_SAMPLES as f64).position)
+                .collect()
+        })
+        .collect();
+
+    // Euler semiimplícito en tiempo real; los cuerpos avanzan `time_scale` veces más rápido
+    let step = request.horizon as f64 / SHIP_STEPS as f64;
+    let mut position = request.ship_position;
+    let mut velocity = request.ship_velocity.cast::<f64>();
+    let mut ship = vec![position];
+    for index in 1..=SHIP_STEPS {
+        let time = request.time + index as f64 * step * request.time_scale;
+        let mut acceleration = DVec3::zeros();
+        let mut crashed = false;
+        for (ephemeris, mass) in &request.bodies {
+            let offset = ephemeris.state_at(time).position - position;
+            let distance = offset.norm();
+            if distance < (ephemeris.scale + SHIP_CLEARANCE) as f64 {
+                crashed = true;
+                break;
+            }
+            acceleration += offset * (request.gravity * mass) as f64 / distance.powi(3);
+        }
+        if crashed {
+            ship.push(position);
+            break;
+        }
+        velocity += acceleration * step;
+        position += velocity * step;
+        if index % SHIP_SAMPLE_EVERY == 0 {
+            ship.push(position);
+        }
+    }
+
+    Prediction { bodies, ship }
+}
@@ -0,0 +1,111 @@
+use nalgebra_glm::{self as glm, Vec3};
+use crate::framebuffer::Framebuffer;
+use crate::shaders::ShaderParams;
+use crate::Uniforms;
+
+// Protuberancias solares: arcos de plasma anclados en dos puntos de la superficie que
+// sobresalen del limbo. Se dibujan como billboards aditivos con prueba de profundidad,
+// así la esfera de la estrella tapa los que quedan detrás.
+
+const PROMINENCE_SLOTS: u32 = 4;
+const ARC_SAMPLES: usize = 28;
+const PROMINENCE_LIFETIME: f32 = 7.0;
+
+struct Prominence {
+    foot_a: Vec3,
+    foot_b: Vec3,
+    height: f32,
+    intensity: f32,
+}
+
+/// Protuberancia activa en el espacio `slot` (en espacio del modelo, esfera unitaria)
+fn prominence(slot: u32, time: f32, params: &ShaderParams) -> Option<Prominence> {
+    let period = 11.0 + params.hash01(500 + slot) * 8.0;
+    let phase = params.hash01(510 + slot) * period;
+    let t = (time + phase).rem_euclid(period);
+    if t >= PROMINENCE_LIFETIME {
+        return None;
+    }
+    let progress = t / PROMINENCE_LIFETIME;
+
+    // Cada ciclo elige nuevos puntos de anclaje
+    let cycle = ((time + phase) / period).floor() as u32;
+    let key = 600 + slot * 16 + (cycle % 64) * 256;
+    let theta = params.hash01(key) * std::f32::consts::TAU;
+    let y = params.hash01(key + 1) * 1.4 - 0.7;
+    let r = (1.0 - y * y).sqrt();
+    let foot_a = Vec3::new(r * theta.cos(), y, r * theta.sin());
+
+    let tangent = foot_a.cross(&Vec3::new(0.0, 1.0, 0.0)).normalize();
+    let bitangent = foot_a.cross(&tangent);
+    let spin = params.hash01(key + 2) * std::f32::consts::TAU;
+    let span = 0.25 + params.hash01(key + 3) * 0.25;
+    let offset = (tangent * spin.cos() + bitangent * spin.sin()) * span;
+    let foot_b = (foot_a + offset).normalize();
+
+    Some(Prominence {
+        foot_a,
+        foot_b,
+        height: (0.2 + params.hash01(key + 4) * 0.25) * (0.6 + 0.4 * progress),
+        intensity: (progress * std::f32::consts::PI).sin(),
+    })
+}
+
+pub fn render_prominences(framebuffer: &mut Framebuffer, uniforms: &Uniforms, params: &ShaderParams) {
+    let mvp = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+    let pixel_scale = uniforms.viewport_matrix[(1, 1)].abs() * uniforms.projection_matrix[(1, 1)];
+    let model_scale = (uniforms.model_matrix * glm::vec4(1.0, 0.0, 0.0, 0.0)).xyz().norm();
+
+    for slot in 0..PROMINENCE_SLOTS {
+        let Some(arc) = prominence(slot, uniforms.time, params) else {
+            continue;
+        };
+
+        for i in 0..=ARC_SAMPLES {
+            let t = i as f32 / ARC_SAMPLES as f32;
+            let bulge = (t * std::f32::consts::PI).sin();
+            let dir = (arc.foot_a * (1.0 - t) + arc.foot_b * t).normalize();
+            // Ondulación lenta para que el arco no sea rígido
+            let wobble = (uniforms.time * 1.3 + t * 9.0).sin() * 0.02 * bulge;
+            let local = dir * (1.0 + arc.height * bulge + wobble);
+
+            let clip = mvp * glm::vec4(local.x, local.y, local.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let screen = uniforms.viewport_matrix
+                * glm::vec4(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+            let depth = uniforms.depth_mode.encode(clip.z, clip.w);
+
+            // Grosor del arco en píxeles: más grueso en la base, más fino en la cima
+            let thickness = model_scale * (0.05 - 0.02 * bulge);
+            let radius = (thickness * pixel_scale / clip.w).clamp(1.0, 8.0);
+            let brightness = arc.intensity * (0.6 + 0.4 * (1.0 - bulge));
+
+            draw_glow(framebuffer, screen.x, screen.y, depth, radius, brightness);
+        }
+    }
+}
+
+/// Mancha circular con caída lineal, sumada al framebuffer
+fn draw_glow(framebuffer: &mut Framebuffer, cx: f32, cy: f32, depth: f32, radius: f32, brightness: f32) {
+    let r = radius.ceil() as i32;
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let x = cx as i32 + dx;
+            let y = cy as i32 + dy;
+            if x < 0 || y < 0 {
+                continue;
+            }
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            let weight = (1.0 - distance / radius).max(0.0) * brightness * 0.35;
+            if weight <= 0.0 {
+                continue;
+            }
+            let red = (255.0 * weight) as u32;
+            let green = (90.0 * weight) as u32;
+            let blue = (30.0 * weight) as u32;
+            framebuffer.add_point(x as usize, y as usize, (red << 16) | (green << 8) | blue, depth);
+        }
+    }
+}
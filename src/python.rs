@@ -0,0 +1,137 @@
+//! Módulo de Python (característica `python`): la escena de `ffi` con clases de Python y el
+//! render a arrays de NumPy, para generar imágenes de planetas y animaciones de órbitas por lotes
+//! desde scripts. Se compila con maturin (ver `pyproject.toml`) y se importa como
+//! `computer_graphics_v3`; `ffi/planets.py` es un ejemplo.
+
+use nalgebra_glm::Vec3;
+use numpy::ndarray::Array3;
+use numpy::{IntoPyArray, PyArray3};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::ffi;
+use crate::shaders::PlanetShaderType;
+
+/// Shader por nombre, como en los archivos de escena (sin `Mesh`, que necesita un modelo)
+fn shader_from_name(name: &str) -> PyResult<PlanetShaderType> {
+    match name {
+        "Terra" => Ok(PlanetShaderType::Terra),
+        "Vulcan" => Ok(PlanetShaderType::Vulcan),
+        "Solarius" => Ok(PlanetShaderType::Solarius),
+        "Nepturion" => Ok(PlanetShaderType::Nepturion),
+        "Mossar" => Ok(PlanetShaderType::Mossar),
+        other => Err(PyValueError::new_err(format!(
+            "shader desconocido: {} (usa Terra, Vulcan, Solarius, Nepturion o Mossar)",
+            other
+        ))),
+    }
+}
+
+/// Cuerpo que se añade a una `Scene`; los campos se pueden cambiar antes de añadirlo
+#[pyclass]
+#[derive(Clone)]
+pub struct CelestialBody {
+    #[pyo3(get, set)]
+    pub shader: String,
+    #[pyo3(get, set)]
+    pub seed: u32,
+    #[pyo3(get, set)]
+    pub orbit_radius: f32,
+    #[pyo3(get, set)]
+    pub orbit_speed: f32,
+    #[pyo3(get, set)]
+    pub scale: f32,
+}
+
+#[pymethods]
+impl CelestialBody {
+    #[new]
+    #[pyo3(signature = (shader, seed = 0, orbit_radius = 0.0, orbit_speed = 0.0, scale = 10.0))]
+    fn new(shader: String, seed: u32, orbit_radius: f32, orbit_speed: f32, scale: f32) -> PyResult<Self> {
+        shader_from_name(&shader)?;
+        Ok(CelestialBody { shader, seed, orbit_radius, orbit_speed, scale })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CelestialBody('{}', seed={}, orbit_radius={}, orbit_speed={}, scale={})",
+            self.shader, self.seed, self.orbit_radius, self.orbit_speed, self.scale
+        )
+    }
+}
+
+/// Escena sin ventana: cuerpos en órbita alrededor del origen, una cámara y un reloj
+#[pyclass(name = "Scene")]
+pub struct PyScene {
+    scene: ffi::Scene,
+}
+
+#[pymethods]
+impl PyScene {
+    #[new]
+    fn new(width: usize, height: usize) -> PyResult<Self> {
+        if width == 0 || height == 0 {
+            return Err(PyValueError::new_err("el ancho y el alto deben ser mayores que 0"));
+        }
+        Ok(PyScene { scene: ffi::Scene::new(width, height) })
+    }
+
+    /// Añade el cuerpo y devuelve su índice
+    fn add_body(&mut self, body: &CelestialBody) -> PyResult<usize> {
+        let shader = shader_from_name(&body.shader)?;
+        Ok(self.scene.add_body(shader, body.seed, body.orbit_radius, body.orbit_speed, body.scale))
+    }
+
+    #[pyo3(signature = (eye, target = (0.0, 0.0, 0.0)))]
+    fn set_camera(&mut self, eye: (f32, f32, f32), target: (f32, f32, f32)) {
+        self.scene.set_camera(Vec3::new(eye.0, eye.1, eye.2), Vec3::new(target.0, target.1, target.2));
+    }
+
+    /// Avanza el reloj `delta_time` segundos
+    fn step(&mut self, delta_time: f32) {
+        self.scene.set_time(self.scene.time() + delta_time);
+    }
+
+    #[getter]
+    fn time(&self) -> f32 {
+        self.scene.time()
+    }
+
+    #[setter]
+    fn set_time(&mut self, time: f32) {
+        self.scene.set_time(time);
+    }
+
+    /// Dibuja el instante actual: array `uint8` de forma (alto, ancho, 4), RGBA
+    fn render<'py>(&mut self, py: Python<'py>) -> Bound<'py, PyArray3<u8>> {
+        let (width, height) = self.scene.size();
+        let mut pixels = vec![0; width * height * 4];
+        self.scene.render(&mut pixels);
+        Array3::from_shape_vec((height, width, 4), pixels).expect("el búfer mide alto × ancho × 4").into_pyarray(py)
+    }
+}
+
+/// Distancia a la luz del planeta de `render_planet`
+const PLANET_DISTANCE: f32 = 100.0;
+
+/// Imagen de un solo planeta centrado que llena casi todo el cuadro, para generar miniaturas
+#[pyfunction]
+#[pyo3(signature = (shader, seed = 0, size = 256, time = 0.0))]
+fn render_planet<'py>(py: Python<'py>, shader: &str, seed: u32, size: usize, time: f32) -> PyResult<Bound<'py, PyArray3<u8>>> {
+    let mut scene = PyScene::new(size, size)?;
+    // La luz está en el origen: el planeta se aparta y la cámara lo mira en diagonal, con tres
+    // cuartas partes del disco iluminadas. A 22 unidades la esfera de radio 10 ocupa unos 9/10
+    // del cuadro con 60° de campo.
+    let center = Vec3::new(PLANET_DISTANCE, 0.0, 0.0);
+    scene.scene.add_body(shader_from_name(shader)?, seed, PLANET_DISTANCE, 0.0, 10.0);
+    scene.scene.set_camera(center + Vec3::new(-1.0, 0.0, 1.0).normalize() * 22.0, center);
+    scene.scene.set_time(time);
+    Ok(scene.render(py))
+}
+
+#[pymodule]
+fn computer_graphics_v3(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<CelestialBody>()?;
+    module.add_class::<PyScene>()?;
+    module.add_function(wrap_pyfunction!(render_planet, module)?)?;
+    Ok(())
+}
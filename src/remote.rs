@@ -0,0 +1,128 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::json;
+
+// Servidor de control remoto: acepta conexiones TCP y recibe un comando JSON por línea, p. ej.
+//   {"cmd": "set_timescale", "value": 8}
+//   {"cmd": "warp", "body": "Terra"}
+//   {"cmd": "select", "body": "Vulcan"}
+//   {"cmd": "screenshot", "path": "captura.png"}
+//   {"cmd": "load_scene", "scene": "enana_roja"}
+// Cada comando recibe una línea JSON de respuesta: {"ok": true, "message": ...} o {"ok": false, "error": ...}.
+// Los comandos se ejecutan en el hilo principal al inicio del siguiente frame.
+// Las capturas van siempre a CAPTURE_DIR: el cliente solo elige el nombre del archivo.
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum RemoteCommand {
+    SetTimescale { value: f32 },
+    Pause { paused: Option<bool> },
+    Warp { body: String },
+    Select { body: String },
+    Screenshot { path: Option<String> },
+    /// Cambia a otra de las escenas disponibles (por nombre de archivo, sin extensión)
+    LoadScene { scene: String },
+}
+
+/// Carpeta de las capturas pedidas por control remoto
+const CAPTURE_DIR: &str = "capturas_remotas";
+
+/// Ruta de la captura de `screenshot`. El nombre que manda el cliente tiene que ser un archivo
+/// `.png` suelto: nada de carpetas, `..` ni rutas absolutas, que le dejarían escribir donde quiera.
+pub fn capture_path(requested: Option<&str>, frame: u64) -> Result<String, String> {
+    let name = match requested {
+        Some(name) => {
+            // ':' también fuera: en Windows "C:x.png" es relativo a otra unidad
+            let bare = !name.contains(['/', '\\', ':']) && !name.contains("..") && !Path::new(name).is_absolute();
+            if !bare || !name.ends_with(".png") || name.len() <= ".png".len() {
+                return Err(format!("nombre de captura no válido: {} (solo un nombre de archivo .png)", name));
+            }
+            name.to_string()
+        }
+        None => format!("captura_{:06}.png", frame),
+    };
+    fs::create_dir_all(CAPTURE_DIR).map_err(|err| format!("no se pudo crear {}: {}", CAPTURE_DIR, err))?;
+    Ok(format!("{}/{}", CAPTURE_DIR, name))
+}
+
+pub struct RemoteRequest {
+    pub command: RemoteCommand,
+    reply: Sender<Result<String, String>>,
+}
+
+impl RemoteRequest {
+    pub fn respond(self, result: Result<String, String>) {
+        // El cliente pudo haberse desconectado; no es un error para la simulación
+        let _ = self.reply.send(result);
+    }
+}
+
+pub struct RemoteServer {
+    requests: Receiver<RemoteRequest>,
+}
+
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl RemoteServer {
+    pub fn start(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let (sender, requests) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    if let Err(err) = handle_client(stream, sender) {
+                        println!("Control remoto: conexión cerrada ({})", err);
+                    }
+                });
+            }
+        });
+
+        Ok(RemoteServer { requests })
+    }
+
+    /// Comandos recibidos desde el último frame
+    pub fn pending(&self) -> Vec<RemoteRequest> {
+        self.requests.try_iter().collect()
+    }
+}
+
+fn handle_client(stream: TcpStream, sender: Sender<RemoteRequest>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = match serde_json::from_str::<RemoteCommand>(&line) {
+            Ok(command) => {
+                let (reply, response) = mpsc::channel();
+                if sender.send(RemoteRequest { command, reply }).is_err() {
+                    return Ok(());
+                }
+                response
+                    .recv_timeout(REPLY_TIMEOUT)
+                    .unwrap_or_else(|_| Err("sin respuesta de la simulación".to_string()))
+            }
+            Err(err) => Err(format!("comando inválido: {}", err)),
+        };
+
+        let response = match result {
+            Ok(message) => json!({ "ok": true, "message": message }),
+            Err(error) => json!({ "ok": false, "error": error }),
+        };
+        writeln!(writer, "{}", response)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use nalgebra_glm::{self as glm, Vec3};
+use raylib::math::{Vector2, Vector3};
+use crate::framebuffer::Framebuffer;
+use crate::light::Light;
+use crate::shaders::{ring_color, ShaderParams, RING_INNER, RING_OUTER};
+use crate::vertex::Vertex;
+use crate::{pack_color, rasterize, Culling, Uniforms};
+
+// Anillos de los gigantes gaseosos. De lejos son una corona plana translúcida; al acercarse al
+// plano de los anillos se sustituyen por un campo de trozos de hielo alrededor de la cámara,
+// dibujados como impostores (discos sombreados como esferas), para poder atravesarlos.
+
+const ANNULUS_SEGMENTS: usize = 64;
+
+/// Lado de cada celda del campo de trozos, en radios del planeta
+const CHUNK_CELL: f32 = 0.04;
+const CHUNKS_PER_CELL: u32 = 2;
+/// Distancia (en radios del planeta) hasta la que se generan trozos alrededor de la cámara
+const FIELD_RADIUS: f32 = 0.35;
+/// Semiespesor de los anillos
+const RING_THICKNESS: f32 = 0.015;
+const CHUNK_SIZE: f32 = 0.004;
+const ICE_COLOR: Vector3 = Vector3 { x: 0.78, y: 0.85, z: 0.92 };
+
+/// Corona plana entre los radios de los anillos; se ve por las dos caras porque se dibuja sin
+/// culling
+fn annulus_vertices() -> Vec<Vertex> {
+    let normal = Vector3::new(0.0, 1.0, 0.0);
+    let point = |index: usize, radius: f32| {
+        let angle = index as f32 / ANNULUS_SEGMENTS as f32 * std::f32::consts::TAU;
+        let position = Vector3::new(angle.cos() * radius, 0.0, angle.sin() * radius);
+        Vertex::new(position, normal, Vector2::zero())
+    };
+
+    let mut vertices = Vec::with_capacity(ANNULUS_SEGMENTS * 6);
+    for i in 0..ANNULUS_SEGMENTS {
+        let (a, b) = (point(i, RING_INNER), point(i, RING_OUTER));
+        let (c, d) = (point(i + 1, RING_INNER), point(i + 1, RING_OUTER));
+        for [p, q, r] in [[&a, &b, &c], [&c, &b, &d]] {
+            vertices.extend([p.clone(), q.clone(), r.clone()]);
+        }
+    }
+    vertices
+}
+
+/// 0 lejos de los anillos, 1 con la cámara dentro de ellos: cuánto se ve el campo de trozos en
+/// lugar de la corona
+fn flyby_blend(camera: Vec3) -> f32 {
+    let radius = camera.x.hypot(camera.z);
+    let outside = (RING_INNER - radius).max(radius - RING_OUTER).max(0.0);
+    let distance = outside.hypot(camera.y);
+    1.0 - ((distance - FIELD_RADIUS * 0.3) / (FIELD_RADIUS * 0.5)).clamp(0.0, 1.0)
+}
+
+pub fn render_rings(framebuffer: &mut Framebuffer, uniforms: &Uniforms, light: &Light, params: &ShaderParams) {
+    let Some(inverse_model) = uniforms.inverse_model_matrix else {
+        return;
+    };
+    // Cámara en el espacio del modelo (radio del planeta = 1)
+    let camera = (inverse_model * glm::vec4(uniforms.camera_position.x, uniforms.camera_position.y, uniforms.camera_position.z, 1.0)).xyz();
+    let blend = flyby_blend(camera);
+
+    if blend < 1.0 {
+        let uniforms = &Uniforms { culling: Culling::Off, ..*uniforms };
+        for fragment in rasterize(uniforms, &annulus_vertices(), light) {
+            let color = ring_color(&fragment, uniforms, fragment.world_position) * (1.0 - blend);
+            framebuffer.add_point(fragment.position.x as usize, fragment.position.y as usize, pack_color(color), fragment.depth);
+        }
+    }
+    if blend > 0.0 {
+        render_chunks(framebuffer, uniforms, camera, blend, params);
+    }
+}
+
+/// Trozos de hielo en las celdas cercanas a la cámara. Cada celda genera siempre los mismos
+/// (dependen de la semilla del planeta y de sus coordenadas), así el campo no cambia al moverse.
+fn render_chunks(framebuffer: &mut Framebuffer, uniforms: &Uniforms, camera: Vec3, blend: f32, params: &ShaderParams) {
+    let mvp = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+    let pixel_scale = uniforms.viewport_matrix[(1, 1)].abs() * uniforms.projection_matrix[(1, 1)];
+    let model_scale = (uniforms.model_matrix * glm::vec4(1.0, 0.0, 0.0, 0.0)).xyz().norm();
+
+    let cells = (FIELD_RADIUS / CHUNK_CELL).ceil() as i32;
+    let (center_x, center_z) = ((camera.x / CHUNK_CELL).floor() as i32, (camera.z / CHUNK_CELL).floor() as i32);
+    for cell_x in center_x - cells..=center_x + cells {
+        for cell_z in center_z - cells..=center_z + cells {
+            for index in 0..CHUNKS_PER_CELL {
+                let key = (cell_x as u32).wrapping_mul(0x8DA6_B343) ^ (cell_z as u32).wrapping_mul(0xD816_3841) ^ index.wrapping_mul(0x1B87_3593);
+                let local = Vec3::new(
+                    (cell_x as f32 + params.hash01(key)) * CHUNK_CELL,
+                    (params.hash01(key ^ 0x5F) * 2.0 - 1.0) * RING_THICKNESS,
+                    (cell_z as f32 + params.hash01(key ^ 0xA3)) * CHUNK_CELL,
+                );
+                let radius = local.x.hypot(local.z);
+                if !(RING_INNER..RING_OUTER).contains(&radius) {
+                    continue;
+                }
+                // Los trozos encogen hacia el borde del campo para que no aparezcan de golpe
+                let fade = 1.0 - ((local - camera).norm() / FIELD_RADIUS).min(1.0);
+                let size = CHUNK_SIZE * (0.5 + params.hash01(key ^ 0x3C)) * fade * blend;
+                if size <= 0.0 {
+                    continue;
+                }
+
+                let clip = mvp * glm::vec4(local.x, local.y, local.z, 1.0);
+                if clip.w <= 0.0 {
+                    continue;
+                }
+                let screen = uniforms.viewport_matrix * glm::vec4(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+                let pixels = size * model_scale * pixel_scale / clip.w;
+                if pixels < 0.5 {
+                    continue;
+                }
+
+                let world = (uniforms.model_matrix * glm::vec4(local.x, local.y, local.z, 1.0)).xyz();
+                let to_light = uniforms.view_matrix * (uniforms.light_position - world).normalize().push(0.0);
+                let chunk = Chunk {
+                    x: screen.x,
+                    y: screen.y,
+                    depth: uniforms.depth_mode.encode(clip.z, clip.w),
+                    radius: pixels.min(64.0),
+                    light: to_light.xyz(),
+                    phase: params.hash01(key ^ 0x71) * std::f32::consts::TAU,
+                };
+                chunk.draw(framebuffer);
+            }
+        }
+    }
+}
+
+/// Impostor de un trozo de hielo en pantalla
+struct Chunk {
+    x: f32,
+    y: f32,
+    depth: f32,
+    radius: f32,
+    /// Dirección hacia la luz en espacio de vista
+    light: Vec3,
+    /// Fase del contorno irregular
+    phase: f32,
+}
+
+impl Chunk {
+    /// Disco con el contorno algo irregular, sombreado como si fuera una esfera
+    fn draw(&self, framebuffer: &mut Framebuffer) {
+        let r = self.radius.ceil() as i32;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let (x, y) = (self.x as i32 + dx, self.y as i32 + dy);
+                if x < 0 || y < 0 || x as usize >= framebuffer.width || y as usize >= framebuffer.height {
+                    continue;
+                }
+                let (nx, ny) = (dx as f32 / self.radius, dy as f32 / self.radius);
+                let outline = 1.0 + (ny.atan2(nx) * 3.0 + self.phase).sin() * 0.15;
+                let d2 = (nx * nx + ny * ny) / (outline * outline);
+                if d2 > 1.0 {
+                    continue;
+                }
+                // En pantalla la y crece hacia abajo; en espacio de vista la cámara mira hacia -z
+                let normal = Vec3::new(nx, -ny, (1.0 - d2).sqrt());
+                let diffuse = normal.dot(&self.light).max(0.0);
+                framebuffer.point_with_depth(x as usize, y as usize, pack_color(ICE_COLOR * (0.08 + 0.92 * diffuse)), self.depth);
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use serde::Deserialize;
+
+fn default_time_scale() -> f32 {
+    1.0
+}
+
+/// Escenario guiado (`--scenario`, en TOML): una escena, la nave colocada en vuelo balístico
+/// respecto a un cuerpo y una lista de pasos con un texto para el jugador y la condición que
+/// lo da por cumplido. Si la nave choca, el escenario vuelve a empezar.
+#[derive(Deserialize)]
+pub struct Scenario {
+    pub title: String,
+    /// Escena en la que transcurre: nombre de archivo de `assets/scenes` sin extensión, o ruta
+    pub scene: String,
+    /// Instante de simulación de partida
+    #[serde(default)]
+    pub time: f64,
+    #[serde(default = "default_time_scale")]
+    pub time_scale: f32,
+    pub ship: ShipStart,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+pub struct ShipStart {
+    /// Cuerpo desde el que se cuenta `offset` y al que mira la nave al empezar
+    pub body: String,
+    pub offset: [f64; 3],
+    /// Velocidad inicial en unidades por segundo real, respecto a la escena (no al cuerpo)
+    pub velocity: [f32; 3],
+}
+
+#[derive(Deserialize)]
+pub struct Step {
+    pub text: String,
+    pub until: Goal,
+}
+
+/// Condición que cierra un paso
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Goal {
+    /// Pasan estos segundos reales desde que empezó el paso
+    Seconds { seconds: f32 },
+    /// La nave se acerca al centro del cuerpo a menos de `distance`
+    Closer { body: String, distance: f64 },
+    /// La nave se aleja del centro del cuerpo a más de `distance`
+    Farther { body: String, distance: f64 },
+    /// La velocidad de la nave supera la de escape del cuerpo a su distancia. Se mide respecto
+    /// a la escena, así que solo tiene sentido con la estrella, que no se mueve.
+    Escape { body: String },
+}
+
+impl Scenario {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|err| format!("no se pudo leer {}: {}", path, err))?;
+        let scenario: Scenario = toml::from_str(&contents).map_err(|err| format!("escenario inválido: {}", err))?;
+        if scenario.steps.is_empty() {
+            return Err("el escenario no tiene pasos".to_string());
+        }
+        Ok(scenario)
+    }
+
+    /// Nombres de los cuerpos a los que se refiere, para comprobar que están en la escena
+    pub fn bodies(&self) -> impl Iterator<Item = &str> {
+        let goals = self.steps.iter().filter_map(|step| match &step.until {
+            Goal::Seconds { .. } => None,
+            Goal::Closer { body, .. } | Goal::Farther { body, .. } | Goal::Escape { body } => Some(body.as_str()),
+        });
+        std::iter::once(self.ship.body.as_str()).chain(goals)
+    }
+}
+
+/// Lo que el escenario necesita saber de un cuerpo en este frame
+pub struct BodyReading {
+    /// Distancia de la nave al centro del cuerpo
+    pub distance: f64,
+    /// Velocidad de escape del cuerpo a esa distancia
+    pub escape_speed: f32,
+}
+
+/// Avance por los pasos de un escenario
+pub struct ScenarioRun {
+    pub scenario: Scenario,
+    step: usize,
+    elapsed: f32,
+}
+
+impl ScenarioRun {
+    pub fn new(scenario: Scenario) -> Self {
+        ScenarioRun { scenario, step: 0, elapsed: 0.0 }
+    }
+
+    /// Vuelve al primer paso (la nave y el reloj los recoloca quien llama)
+    pub fn restart(&mut self) {
+        self.step = 0;
+        self.elapsed = 0.0;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.step >= self.scenario.steps.len()
+    }
+
+    /// Comprueba la condición del paso actual y pasa al siguiente si se cumple. Devuelve el
+    /// paso que empieza, si cambia; `body` da los datos de un cuerpo por nombre.
+    pub fn update<F: Fn(&str) -> Option<BodyReading>>(&mut self, delta_time: f32, ship_speed: f32, body: F) -> Option<usize> {
+        let step = self.scenario.steps.get(self.step)?;
+        self.elapsed += delta_time;
+        let done = match &step.until {
+            Goal::Seconds { seconds } => self.elapsed >= *seconds,
+            Goal::Closer { body: name, distance } => body(name).is_some_and(|reading| reading.distance < *distance),
+            Goal::Farther { body: name, distance } => body(name).is_some_and(|reading| reading.distance > *distance),
+            Goal::Escape { body: name } => body(name).is_some_and(|reading| ship_speed > reading.escape_speed),
+        };
+        if !done {
+            return None;
+        }
+        self.step += 1;
+        self.elapsed = 0.0;
+        Some(self.step)
+    }
+
+    /// Texto del paso actual para el HUD, con el título y el número de paso
+    pub fn prompt(&self) -> Option<String> {
+        let step = self.scenario.steps.get(self.step)?;
+        Some(format!("{} ({}/{})\n{}", self.scenario.title, self.step + 1, self.scenario.steps.len(), step.text))
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+use serde::Deserialize;
+use raylib::math::Vector3;
+use crate::shaders::{PlanetShaderType, ShaderParams};
+
+/// Archivo de escena: lista de cuerpos celestes con sus parámetros orbitales y de shader
+#[derive(Debug, Deserialize)]
+pub struct SceneFile {
+    pub bodies: Vec<BodyConfig>,
+    pub belt: Option<BeltConfig>,
+    #[serde(default)]
+    pub stars: StarsConfig,
+    #[serde(default)]
+    pub scale_mode: ScaleMode,
+    #[serde(default)]
+    pub grid: GridConfig,
+}
+
+/// Escala del sistema. Los valores del archivo describen la disposición estilizada; el modo
+/// semirrealista los transforma al cargar (ver `SceneFile::apply_scale_mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScaleMode {
+    /// Sistema compacto, tal como está en el archivo
+    #[default]
+    Stylized,
+    /// Cuerpos más pequeños y órbitas más separadas, cada vez más hacia fuera como en un sistema
+    /// real, pero con la distancia comprimida por un logaritmo para que se pueda recorrer
+    SemiRealistic,
+}
+
+/// Radio a partir del cual el modo semirrealista empieza a separar las órbitas
+const SEMI_REALISTIC_KNEE: f32 = 150.0;
+const SEMI_REALISTIC_SPREAD: f32 = 2.0;
+const SEMI_REALISTIC_PLANET_SCALE: f32 = 0.4;
+const SEMI_REALISTIC_STAR_SCALE: f32 = 0.6;
+
+impl ScaleMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "stylized" => Ok(ScaleMode::Stylized),
+            "semi-realistic" => Ok(ScaleMode::SemiRealistic),
+            other => Err(format!("escala desconocida: {} (usa stylized o semi-realistic)", other)),
+        }
+    }
+
+    /// Radio orbital en este modo a partir del radio estilizado: r · (1 + k · ln(1 + r / r0))
+    pub fn orbit_radius(self, radius: f32) -> f32 {
+        match self {
+            ScaleMode::Stylized => radius,
+            ScaleMode::SemiRealistic => {
+                radius * (1.0 + SEMI_REALISTIC_SPREAD * (1.0 + radius / SEMI_REALISTIC_KNEE).ln())
+            }
+        }
+    }
+
+    /// Lado de las celdas de la cuadrícula de la eclíptica: en el modo semirrealista se estira
+    /// tanto como la órbita que está en el codo, para que siga abarcando lo mismo entre órbitas
+    pub fn grid_spacing(self, spacing: f32) -> f32 {
+        spacing * self.orbit_radius(SEMI_REALISTIC_KNEE) / SEMI_REALISTIC_KNEE
+    }
+
+    /// Tamaño de un cuerpo; las estrellas (radio orbital 0) se reducen menos que los planetas
+    pub fn body_scale(self, scale: f32, orbit_radius: f32) -> f32 {
+        match self {
+            ScaleMode::Stylized => scale,
+            ScaleMode::SemiRealistic if orbit_radius == 0.0 => scale * SEMI_REALISTIC_STAR_SCALE,
+            ScaleMode::SemiRealistic => scale * SEMI_REALISTIC_PLANET_SCALE,
+        }
+    }
+}
+
+/// Campo de estrellas del fondo
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct StarsConfig {
+    /// Estrellas por cada 10 000 píxeles
+    pub density: f32,
+    /// Magnitud de las estrellas más débiles que se generan
+    pub faintest_magnitude: f32,
+    pub seed: u64,
+}
+
+impl Default for StarsConfig {
+    fn default() -> Self {
+        StarsConfig {
+            density: 4.0,
+            faintest_magnitude: 6.0,
+            seed: 7,
+        }
+    }
+}
+
+/// Cuadrícula de referencia sobre el plano de la eclíptica (F2)
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct GridConfig {
+    /// Lado de cada celda, en las unidades del archivo (las de la disposición estilizada)
+    pub spacing: f32,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        GridConfig { spacing: 50.0 }
+    }
+}
+
+/// Cinturón de asteroides opcional: rocas en órbitas circulares entre dos radios
+#[derive(Debug, Deserialize)]
+pub struct BeltConfig {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub count: usize,
+    #[serde(default)]
+    pub thickness: f32,
+    #[serde(default)]
+    pub seed: u64,
+    /// Relieve de las rocas cercanas
+    pub displacement: Option<DisplacementConfig>,
+}
+
+/// Relieve por ruido que se aplica a la malla al construirla: cada vértice se desplaza a lo
+/// largo de su normal `amplitude` (fracción del radio) por el valor del ruido en su posición
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct DisplacementConfig {
+    pub amplitude: f32,
+    #[serde(default = "default_displacement_frequency")]
+    pub frequency: f32,
+    #[serde(default = "default_displacement_octaves")]
+    pub octaves: u32,
+}
+
+fn default_displacement_frequency() -> f32 {
+    2.0
+}
+
+fn default_displacement_octaves() -> u32 {
+    4
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BodyConfig {
+    pub name: String,
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+    pub scale: f32,
+    pub mass: f32,
+    pub rotation_speed: [f32; 3],
+    pub shader: PlanetShaderType,
+    #[serde(default)]
+    pub params: ShaderParamsConfig,
+    pub displacement: Option<DisplacementConfig>,
+    /// Datos curiosos que se muestran, uno por página, al seleccionar el cuerpo (tecla N)
+    #[serde(default)]
+    pub facts: Vec<String>,
+}
+
+/// Valores opcionales que sobreescriben los `ShaderParams` por defecto del tipo de shader
+#[derive(Debug, Default, Deserialize)]
+pub struct ShaderParamsConfig {
+    pub seed: Option<u32>,
+    pub palette: Option<[[f32; 3]; 3]>,
+    pub noise_scale: Option<f32>,
+    pub cloud_coverage: Option<f32>,
+    pub ocean_level: Option<f32>,
+    pub ice_cap_size: Option<f32>,
+}
+
+impl ShaderParamsConfig {
+    pub fn apply(&self, base: ShaderParams) -> ShaderParams {
+        let mut params = base;
+        if let Some(seed) = self.seed {
+            params.seed = seed;
+        }
+        if let Some(palette) = self.palette {
+            params.palette = palette.map(|[r, g, b]| Vector3::new(r, g, b));
+        }
+        if let Some(noise_scale) = self.noise_scale {
+            params.noise_scale = noise_scale;
+        }
+        if let Some(cloud_coverage) = self.cloud_coverage {
+            params.cloud_coverage = cloud_coverage.clamp(0.0, 1.0);
+        }
+        if let Some(ocean_level) = self.ocean_level {
+            params.ocean_level = ocean_level.clamp(0.0, 1.0);
+        }
+        if let Some(ice_cap_size) = self.ice_cap_size {
+            params.ice_cap_size = ice_cap_size.clamp(0.0, 1.0);
+        }
+        params
+    }
+}
+
+impl BodyConfig {
+    pub fn shader_params(&self) -> ShaderParams {
+        self.params.apply(ShaderParams::for_type(self.shader))
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    /// El archivo se lee bien pero algún valor no tiene sentido
+    Invalid(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "no se pudo leer la escena: {}", err),
+            SceneError::Parse(err) => write!(f, "escena inválida: {}", err),
+            SceneError::Invalid(reason) => write!(f, "escena inválida: {}", reason),
+        }
+    }
+}
+
+impl From<std::io::Error> for SceneError {
+    fn from(err: std::io::Error) -> Self {
+        SceneError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for SceneError {
+    fn from(err: toml::de::Error) -> Self {
+        SceneError::Parse(err)
+    }
+}
+
+impl SceneFile {
+    pub fn load(path: &str) -> Result<Self, SceneError> {
+        let contents = fs::read_to_string(path)?;
+        let scene: SceneFile = toml::from_str(&contents)?;
+        let spacing = scene.grid.spacing;
+        if !spacing.is_finite() || spacing <= 0.0 {
+            return Err(SceneError::Invalid(format!("[grid] spacing debe ser positivo (es {})", spacing)));
+        }
+        Ok(scene)
+    }
+
+    /// Reescala cuerpos y cinturón desde la disposición estilizada del archivo. Las velocidades
+    /// angulares no cambian: los años duran lo mismo en los dos modos.
+    pub fn apply_scale_mode(&mut self, mode: ScaleMode) {
+        for body in &mut self.bodies {
+            body.scale = mode.body_scale(body.scale, body.orbit_radius);
+            body.orbit_radius = mode.orbit_radius(body.orbit_radius);
+        }
+        if let Some(belt) = self.belt.as_mut() {
+            belt.inner_radius = mode.orbit_radius(belt.inner_radius);
+            belt.outer_radius = mode.orbit_radius(belt.outer_radius);
+        }
+        self.grid.spacing = mode.grid_spacing(self.grid.spacing);
+        self.scale_mode = mode;
+    }
+}
+
+/// Archivos de escena de `directory` (los `.toml`, por nombre), con `first` delante
+pub fn list_scenes(directory: &str, first: &str) -> Vec<String> {
+    let mut paths: Vec<String> = fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+                .map(|path| path.to_string_lossy().into_owned())
+                .filter(|path| Path::new(path) != Path::new(first))
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths.insert(0, first.to_string());
+    paths
+}
+
+/// Nombre corto de una escena: el del archivo sin extensión
+pub fn scene_name(path: &str) -> &str {
+    Path::new(path).file_stem().and_then(|stem| stem.to_str()).unwrap_or(path)
+}
+
+/// Vigila el archivo de escena y lo vuelve a cargar cuando cambia su fecha de modificación
+pub struct SceneWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+impl SceneWatcher {
+    pub fn new(path: &str) -> Self {
+        SceneWatcher {
+            path: path.to_string(),
+            modified: Self::modified_time(path),
+            last_check: Instant::now(),
+        }
+    }
+
+    fn modified_time(path: &str) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    /// Devuelve la escena recargada si el archivo cambió desde la última comprobación
+    pub fn poll(&mut self) -> Option<Result<SceneFile, SceneError>> {
+        if self.last_check.elapsed() < WATCH_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+
+        let modified = Self::modified_time(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(SceneFile::load(&self.path))
+    }
+}
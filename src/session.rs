@@ -0,0 +1,108 @@
+use std::fmt;
+use std::fs;
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use crate::clock::SECONDS_PER_DAY;
+
+/// Archivo donde se guarda el estado al salir (y del que lee `--resume`)
+pub const AUTOSAVE_PATH: &str = "autosave.json";
+
+/// Estado de la simulación que se guarda al salir. Los cuerpos no se guardan: sus posiciones
+/// salen de la efeméride en `time`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Autosave {
+    pub time: f64,
+    pub time_scale: f32,
+    pub paused: bool,
+    pub ship_position: [f64; 3],
+    pub ship_yaw: f32,
+    pub ship_pitch: f32,
+    /// Por nombre, para que siga valiendo si la escena cambia de orden
+    pub selected_body: Option<String>,
+    pub hidden_bodies: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum AutosaveError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for AutosaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AutosaveError::Io(err) => write!(f, "no se pudo acceder al autoguardado: {}", err),
+            AutosaveError::Json(err) => write!(f, "autoguardado inválido: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for AutosaveError {
+    fn from(err: std::io::Error) -> Self {
+        AutosaveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for AutosaveError {
+    fn from(err: serde_json::Error) -> Self {
+        AutosaveError::Json(err)
+    }
+}
+
+impl Autosave {
+    pub fn load(path: &str) -> Result<Self, AutosaveError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), AutosaveError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Un cuerpo cuenta como visitado cuando la nave se acerca a menos de este número de radios
+pub const VISIT_RADII: f32 = 4.0;
+
+/// Estadísticas de la sesión para el resumen al salir
+pub struct SessionStats {
+    start: Instant,
+    start_time: f64,
+    frames: u64,
+    /// En el orden de la primera visita
+    visited: Vec<String>,
+}
+
+impl SessionStats {
+    /// `sim_time` es el tiempo simulado al empezar (distinto de 0 si se reanuda un autoguardado)
+    pub fn new(sim_time: f64) -> Self {
+        SessionStats { start: Instant::now(), start_time: sim_time, frames: 0, visited: Vec::new() }
+    }
+
+    pub fn record_frame(&mut self) {
+        self.frames += 1;
+    }
+
+    pub fn record_visit(&mut self, body: &str) {
+        if !self.visited.iter().any(|name| name == body) {
+            println!("Primera visita a {}", body);
+            self.visited.push(body.to_string());
+        }
+    }
+
+    /// Resumen en varias líneas; `sim_time` es el tiempo simulado al salir
+    pub fn summary(&self, sim_time: f64) -> String {
+        let seconds = self.start.elapsed().as_secs_f64();
+        let average_fps = if seconds > 0.0 { self.frames as f64 / seconds } else { 0.0 };
+        let days = (sim_time - self.start_time) / SECONDS_PER_DAY as f64;
+        let visited = if self.visited.is_empty() { "ninguno".to_string() } else { self.visited.join(", ") };
+        format!(
+            "Frames: {}\nDuración: {:.0} s ({:.1} FPS de media)\nTiempo simulado: {:.1} días ({:.2} años)\nCuerpos visitados: {}",
+            self.frames,
+            seconds,
+            average_fps,
+            days,
+            days / 365.0,
+            visited
+        )
+    }
+}
@@ -0,0 +1,144 @@
+use crate::shaders::ShaderParams;
+
+// Panel de ajuste de shaders (F4): los `ShaderParams` del cuerpo seleccionado, uno por fila, que
+// se cambian en vivo para probar el aspecto sin recompilar ni recargar la escena. Lo que se
+// consigue se guarda como un bloque `[bodies.params]` listo para pegar en el archivo de escena.
+
+/// Una fila del panel: nombre (el del archivo de escena), paso de cada pulsación y límites (la
+/// semilla va aparte, ver `ShaderPanel::adjust`)
+struct Row {
+    name: &'static str,
+    step: f32,
+    range: (f32, f32),
+}
+
+const ROWS: [Row; 15] = [
+    Row { name: "seed", step: 1.0, range: (0.0, 0.0) },
+    Row { name: "palette[0].r", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "palette[0].g", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "palette[0].b", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "palette[1].r", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "palette[1].g", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "palette[1].b", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "palette[2].r", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "palette[2].g", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "palette[2].b", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "noise_scale", step: 0.1, range: (0.1, 20.0) },
+    Row { name: "cloud_coverage", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "ocean_level", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "ice_cap_size", step: 0.05, range: (0.0, 1.0) },
+    Row { name: "shininess", step: 4.0, range: (1.0, 256.0) },
+];
+
+/// Valor de la fila `row`
+fn value(params: &ShaderParams, row: usize) -> f32 {
+    match row {
+        0 => params.seed as f32,
+        1..=9 => {
+            let color = params.palette[(row - 1) / 3];
+            [color.x, color.y, color.z][(row - 1) % 3]
+        }
+        10 => params.noise_scale,
+        11 => params.cloud_coverage,
+        12 => params.ocean_level,
+        13 => params.ice_cap_size,
+        _ => params.shininess,
+    }
+}
+
+fn set_value(params: &mut ShaderParams, row: usize, value: f32) {
+    match row {
+        0 => params.seed = value as u32,
+        1..=9 => {
+            let color = &mut params.palette[(row - 1) / 3];
+            match (row - 1) % 3 {
+                0 => color.x = value,
+                1 => color.y = value,
+                _ => color.z = value,
+            }
+        }
+        10 => params.noise_scale = value,
+        11 => params.cloud_coverage = value,
+        12 => params.ocean_level = value,
+        13 => params.ice_cap_size = value,
+        _ => params.shininess = value,
+    }
+}
+
+pub struct ShaderPanel {
+    pub open: bool,
+    cursor: usize,
+}
+
+impl ShaderPanel {
+    pub fn new() -> Self {
+        ShaderPanel { open: false, cursor: 0 }
+    }
+
+    /// Mueve la marca una fila, dando la vuelta en los extremos
+    pub fn step(&mut self, forward: bool) {
+        self.cursor = if forward { (self.cursor + 1) % ROWS.len() } else { (self.cursor + ROWS.len() - 1) % ROWS.len() };
+    }
+
+    /// Sube (`direction` = 1) o baja (-1) un paso el valor marcado
+    pub fn adjust(&self, params: &mut ShaderParams, direction: f32) {
+        // La semilla no cabe entera en un f32
+        if self.cursor == 0 {
+            params.seed = params.seed.wrapping_add_signed(direction as i32);
+            return;
+        }
+        let row = &ROWS[self.cursor];
+        // Redondeado al paso para no acumular el error de sumar decimales: 0.35 y no 0.35000002
+        let changed = ((value(params, self.cursor) / row.step).round() + direction) * row.step;
+        set_value(params, self.cursor, changed.clamp(row.range.0, row.range.1));
+    }
+
+    pub fn text(&self, body: Option<(&str, &ShaderParams)>) -> String {
+        let Some((name, params)) = body else {
+            return "Shader: selecciona un cuerpo (F)".to_string();
+        };
+        let mut lines = vec![
+            format!("Shader de {}", name),
+            "Inicio/Fin elegir, Insert/Supr subir/bajar, Retroceso guardar".to_string(),
+        ];
+        for (index, row) in ROWS.iter().enumerate() {
+            let marker = if index == self.cursor { ">" } else { " " };
+            let value = if index == 0 { params.seed.to_string() } else { format!("{:.2}", value(params, index)) };
+            lines.push(format!("{} {:<15}{}", marker, row.name, value));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Bloque de escena con los parámetros de `params`, para sustituir el del cuerpo `name`. Los
+/// valores van completos, en la forma más corta que se vuelve a leer igual y siempre con
+/// decimales (un `1` sería un entero en TOML). `shininess` solo lo usa el shader de mallas y la
+/// escena no lo lee, así que va comentado.
+pub fn scene_block(name: &str, params: &ShaderParams) -> String {
+    let palette: Vec<String> = params.palette.iter().map(|color| format!("[{:?}, {:?}, {:?}]", color.x, color.y, color.z)).collect();
+    format!(
+        "# Parámetros de shader de {}: sustituyen los de su [bodies.params]\n\
+         [bodies.params]\n\
+         seed = {}\n\
+         palette = [{}]\n\
+         noise_scale = {:?}\n\
+         cloud_coverage = {:?}\n\
+         ocean_level = {:?}\n\
+         ice_cap_size = {:?}\n\
+         # shininess = {:?}\n",
+        name,
+        params.seed,
+        palette.join(", "),
+        params.noise_scale,
+        params.cloud_coverage,
+        params.ocean_level,
+        params.ice_cap_size,
+        params.shininess,
+    )
+}
+
+/// Archivo en el que se guarda el bloque de `name`, solo con letras y cifras ASCII
+pub fn dump_path(name: &str) -> String {
+    let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect();
+    format!("shader_{}.toml", name)
+}
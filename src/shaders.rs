@@ -1,21 +1,129 @@
 use raylib::prelude::*;
 use crate::vertex::Vertex;
 use crate::fragment::Fragment;
-use crate::Uniforms;
+use crate::pipeline::Uniforms;
+use crate::texture::Texture;
 use nalgebra_glm::{self as glm, length};
+use serde::Deserialize;
 
 // =============================================================
 // === CONVERSIÓN ENTRE nalgebra_glm Y raylib ==================
 // =============================================================
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 pub enum PlanetShaderType {
     Terra,       // Planeta tipo Tierra (océanos, nubes, vegetación)
     Vulcan,      // Planeta volcánico / rocoso
     Solarius,    // Estrella (plasma, fuego, manchas solares)
     Nepturion,   // Planeta gaseoso tipo Neptuno
     Mossar,      // Planeta orgánico o musgoso
- 
+    Mesh,        // Modelos que no son planetas: color de material o textura, Blinn-Phong
+}
+
+/// Parámetros procedurales por planeta, para que dos planetas del mismo tipo se vean distintos.
+/// `palette` guarda los tres colores principales de cada shader (ver `ShaderParams::for_type`).
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderParams {
+    pub seed: u32,
+    pub palette: [Vector3; 3],
+    pub noise_scale: f32,
+    pub cloud_coverage: f32, // 0..1, solo Terra
+    pub ocean_level: f32,    // 0..1, solo Terra
+    pub ice_cap_size: f32,   // 0..1, fracción del hemisferio cubierta por hielo polar
+    pub shininess: f32,      // exponente especular, solo Mesh
+}
+
+impl ShaderParams {
+    /// Valores por defecto que reproducen el aspecto original de cada shader
+    pub fn for_type(shader_type: PlanetShaderType) -> Self {
+        let palette = match shader_type {
+            // océano, tierra, nubes
+            PlanetShaderType::Terra => [
+                Vector3::new(0.0, 0.25, 0.8),
+                Vector3::new(0.1, 0.6, 0.2),
+                Vector3::new(1.0, 1.0, 1.0),
+            ],
+            // roca, lava, erupción
+            PlanetShaderType::Vulcan => [
+                Vector3::new(0.3, 0.2, 0.15),
+                Vector3::new(1.0, 0.4, 0.05),
+                Vector3::new(1.0, 0.8, 0.3),
+            ],
+            // núcleo, llama, borde
+            PlanetShaderType::Solarius => [
+                Vector3::new(1.0, 0.9, 0.3),
+                Vector3::new(1.0, 0.5, 0.0),
+                Vector3::new(1.0, 0.15, 0.0),
+            ],
+            // banda oscura, banda clara, brillo
+            PlanetShaderType::Nepturion => [
+                Vector3::new(0.05, 0.2, 0.7),
+                Vector3::new(0.2, 0.4, 0.9),
+                Vector3::new(0.5, 0.8, 1.0),
+            ],
+            // musgo, sombra, bioluminiscencia
+            PlanetShaderType::Mossar => [
+                Vector3::new(0.1, 0.6, 0.2),
+                Vector3::new(0.05, 0.25, 0.05),
+                Vector3::new(0.4, 1.0, 0.6),
+            ],
+            // difuso (sin textura), especular, ambiente
+            PlanetShaderType::Mesh => [
+                Vector3::new(0.7, 0.7, 0.72),
+                Vector3::new(0.6, 0.6, 0.6),
+                Vector3::new(0.08, 0.08, 0.1),
+            ],
+        };
+
+        let ice_cap_size = match shader_type {
+            PlanetShaderType::Terra => 0.25,
+            PlanetShaderType::Mossar => 0.15,
+            _ => 0.0,
+        };
+
+        ShaderParams {
+            seed: 0,
+            palette,
+            noise_scale: 1.0,
+            cloud_coverage: 0.5,
+            ocean_level: 0.5,
+            ice_cap_size,
+            shininess: 32.0,
+        }
+    }
+
+    /// Valor pseudoaleatorio estable en [0, 1) derivado de la semilla y una clave
+    pub fn hash01(&self, key: u32) -> f32 {
+        let mut h = self.seed.wrapping_mul(0x9E37_79B9) ^ key.wrapping_mul(0x85EB_CA6B);
+        h ^= h >> 15;
+        h = h.wrapping_mul(0xC2B2_AE35);
+        h ^= h >> 13;
+        (h % 10_000) as f32 / 10_000.0
+    }
+
+    /// Desplazamiento pseudoaleatorio del patrón derivado de la semilla (cero para seed = 0)
+    fn seed_offset(&self) -> Vector3 {
+        if self.seed == 0 {
+            return Vector3::zero();
+        }
+        Vector3::new(
+            self.hash01(1) * 100.0 - 50.0,
+            self.hash01(2) * 100.0 - 50.0,
+            self.hash01(3) * 100.0 - 50.0,
+        )
+    }
+
+    /// Posición usada para evaluar los patrones procedurales
+    fn pattern_position(&self, p: Vector3) -> Vector3 {
+        (p + self.seed_offset()) * self.noise_scale
+    }
+}
+
+/// Texturas de una malla: albedo y mapa de normales en espacio tangente
+#[derive(Clone, Copy, Default)]
+pub struct SurfaceMaps<'a> {
+    pub albedo: Option<&'a Texture>,
+    pub normal: Option<&'a Texture>,
 }
 
 /// Convierte una `glm::Mat4` a una `raylib::Matrix`
@@ -81,20 +189,380 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     let ndc_vec4 = Vector4::new(ndc.x, ndc.y, ndc.z, 1.0);
     let screen_position = multiply_matrix_vector4(&viewport_mat, &ndc_vec4);
 
+    // El valor de profundidad que se interpola depende del modo del z-buffer (ver `DepthMode`)
+    let depth = if clip_position.w != 0.0 {
+        uniforms.depth_mode.interpolant(clip_position.z, clip_position.w)
+    } else {
+        screen_position.z
+    };
     let transformed_position = Vector3::new(
         screen_position.x,
         screen_position.y,
-        screen_position.z,
+        depth,
     );
+    let world_normal = uniforms
+        .world_normal(glm::vec3(vertex.normal.x, vertex.normal.y, vertex.normal.z))
+        .try_normalize(1e-12)
+        .unwrap_or_else(glm::Vec3::zeros);
+    let transformed_normal = Vector3::new(world_normal.x, world_normal.y, world_normal.z);
 
     // Retornamos el vértice transformado
     Vertex {
         position: vertex.position,
         normal: vertex.normal,
         tex_coords: vertex.tex_coords,
+        tangent: vertex.tangent,
         color: vertex.color,
         transformed_position,
-        transformed_normal: vertex.normal, // TODO: normal matrix
+        transformed_normal,
+    }
+}
+
+// =============================================================
+// === UTILIDADES COMPARTIDAS ==================================
+// =============================================================
+
+/// Posición y normal del fragmento en espacio de mundo
+fn world_surface(fragment: &Fragment, uniforms: &Uniforms) -> Option<(glm::Vec3, glm::Vec3)> {
+    let p = fragment.world_position;
+    let n = fragment.normal;
+    let model = &uniforms.model_matrix;
+
+    let world_pos = (model * glm::vec4(p.x, p.y, p.z, 1.0)).xyz();
+    let world_normal = uniforms.world_normal(glm::vec3(n.x, n.y, n.z));
+    if world_normal.norm() == 0.0 {
+        return None;
+    }
+    Some((world_pos, world_normal.normalize()))
+}
+
+/// Dirección hacia la luz en el espacio de las normales del modelo
+fn light_in_object_space(fragment: &Fragment, uniforms: &Uniforms) -> Option<Vector3> {
+    let (world_pos, _) = world_surface(fragment, uniforms)?;
+    let to_light = (uniforms.light_position - world_pos).normalize();
+    let inverse_model = uniforms.inverse_model_matrix?;
+    let light = (inverse_model * glm::vec4(to_light.x, to_light.y, to_light.z, 0.0)).xyz().try_normalize(1e-6)?;
+    Some(Vector3::new(light.x, light.y, light.z))
+}
+
+/// Oscurecimiento del limbo: 1 en el centro del disco visto desde la cámara,
+/// cae hacia el borde según `strength` (ley lineal en mu = cos del ángulo de visión)
+fn limb_darkening(fragment: &Fragment, uniforms: &Uniforms, strength: f32) -> f32 {
+    let Some((world_pos, world_normal)) = world_surface(fragment, uniforms) else {
+        return 1.0;
+    };
+    let to_camera = (uniforms.camera_position - world_pos).normalize();
+    let mu = world_normal.dot(&to_camera).clamp(0.0, 1.0);
+    1.0 - strength * (1.0 - mu.sqrt())
+}
+
+// =============================================================
+// === NORMAL MAPPING ==========================================
+// =============================================================
+
+/// Paso, en unidades del modelo, con el que se derivan las alturas procedurales
+const HEIGHT_STEP: f32 = 0.01;
+
+/// Base tangente (T, B, N) del fragmento en el espacio de sus normales, o `None` si la malla no
+/// tiene tangentes (sin UV) o la normal es nula
+fn tangent_frame(fragment: &Fragment) -> Option<(Vector3, Vector3, Vector3)> {
+    let normal = fragment.normal;
+    if normal.length() == 0.0 {
+        return None;
+    }
+    let t = fragment.tangent;
+    let tangent = Vector3::new(t.x, t.y, t.z);
+    // La tangente interpolada deja de ser perpendicular a la normal: se reortogonaliza
+    let tangent = tangent - normal * normal.dot(tangent);
+    if tangent.length() < 1e-6 {
+        return None;
+    }
+    let tangent = tangent.normalized();
+    let bitangent = normal.cross(tangent) * if t.w < 0.0 { -1.0 } else { 1.0 };
+    Some((tangent, bitangent, normal))
+}
+
+/// Lleva una normal en espacio tangente (z = fuera de la superficie) al espacio de las normales
+/// del modelo. Sin base tangente devuelve la normal geométrica.
+fn apply_normal_map(fragment: &Fragment, tangent_normal: Vector3) -> Vector3 {
+    match tangent_frame(fragment) {
+        Some((t, b, n)) => (t * tangent_normal.x + b * tangent_normal.y + n * tangent_normal.z).normalized(),
+        None => fragment.normal,
+    }
+}
+
+/// Normal en espacio tangente leída de un mapa de normales (RGB en 0..1 -> -1..1)
+fn sample_normal_map(fragment: &Fragment, texture: &Texture) -> Vector3 {
+    let texel = texture.sample_grad(
+        fragment.tex_coords.x,
+        fragment.tex_coords.y,
+        fragment.tex_coords_ddx,
+        fragment.tex_coords_ddy,
+    );
+    (texel * 2.0 - Vector3::one()).normalized()
+}
+
+/// Normal en espacio tangente derivada de un campo de alturas del modelo, por diferencias finitas
+/// a lo largo de la tangente y la bitangente. `strength` escala la pendiente.
+fn height_normal<F: Fn(Vector3) -> f32>(fragment: &Fragment, strength: f32, height: F) -> Vector3 {
+    let flat = Vector3::new(0.0, 0.0, 1.0);
+    let Some((tangent, bitangent, _)) = tangent_frame(fragment) else {
+        return flat;
+    };
+    if strength <= 0.0 {
+        return flat;
+    }
+    // Las posiciones tienen la y invertida respecto a las normales (ver `Obj::load`)
+    let to_position = |d: Vector3| Vector3::new(d.x, -d.y, d.z) * HEIGHT_STEP;
+    let p = fragment.world_position;
+    let center = height(p);
+    let slope_t = (height(p + to_position(tangent)) - center) / HEIGHT_STEP;
+    let slope_b = (height(p + to_position(bitangent)) - center) / HEIGHT_STEP;
+    Vector3::new(-slope_t * strength, -slope_b * strength, 1.0).normalized()
+}
+
+/// Cuánto cambia la iluminación difusa al usar la normal perturbada en lugar de la geométrica.
+/// Multiplica el color ya iluminado por `triangle`, así el relieve solo añade detalle.
+fn relief_shading(fragment: &Fragment, uniforms: &Uniforms, perturbed: Vector3) -> f32 {
+    let Some(light) = light_in_object_space(fragment, uniforms) else {
+        return 1.0;
+    };
+    let flat = fragment.normal.normalized().dot(light);
+    if flat <= 0.05 {
+        return 1.0;
+    }
+    (perturbed.dot(light).max(0.0) / flat).clamp(0.0, 2.0)
+}
+
+// =============================================================
+// === ILUMINACIÓN DESPUÉS DEL SOMBREADO =======================
+// =============================================================
+
+/// Lo que devuelve el shader de un planeta antes de iluminarlo: el albedo, la normal con la que
+/// se ilumina (en el espacio de las normales del modelo, con el relieve procedural) y la luz que
+/// se suma sin pasar por la difusa, como la emisión o los reflejos especulares
+pub struct ShadedSurface {
+    pub albedo: Vector3,
+    pub normal: Vector3,
+    pub emission: Vector3,
+}
+
+impl ShadedSurface {
+    /// Superficie con la normal geométrica del fragmento y sin luz propia
+    pub fn new(fragment: &Fragment, albedo: Vector3) -> Self {
+        ShadedSurface { albedo, normal: fragment.normal, emission: Vector3::zero() }
+    }
+
+    pub fn with_normal(self, normal: Vector3) -> Self {
+        ShadedSurface { normal, ..self }
+    }
+
+    pub fn with_emission(self, emission: Vector3) -> Self {
+        ShadedSurface { emission, ..self }
+    }
+}
+
+/// Pasada de iluminación: la difusa que trae el fragmento del rasterizador, corregida con la
+/// normal de la superficie, sobre el albedo, más la emisión
+pub fn light_surface(fragment: &Fragment, uniforms: &Uniforms, surface: &ShadedSurface) -> Vector3 {
+    let diffuse = if surface.normal == fragment.normal {
+        fragment.color
+    } else {
+        fragment.color * relief_shading(fragment, uniforms, surface.normal)
+    };
+    surface.albedo * diffuse + surface.emission
+}
+
+// =============================================================
+// === MAPEOS DE SUPERFICIE ====================================
+// =============================================================
+
+/// Coordenadas esféricas de una dirección (no hace falta normalizarla), ambas en 0..1:
+/// `x` es la longitud (0 en -X, creciendo hacia +Z) y `y` la latitud (0 en el polo sur, 1 en el norte).
+/// Las franjas en `y` quedan igual de anchas en el ecuador y cerca de los polos.
+pub fn spherical_uv(direction: Vector3) -> Vector2 {
+    let length = direction.length();
+    if length == 0.0 {
+        return Vector2::new(0.5, 0.5);
+    }
+    let d = direction / length;
+    let u = 0.5 + d.z.atan2(-d.x) / std::f32::consts::TAU;
+    let v = 0.5 + d.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI;
+    Vector2::new(u, v)
+}
+
+/// Aplica un patrón 2D por proyección en los planos YZ, XZ y XY, mezclados según cuánto mira la
+/// normal a cada eje. `sharpness` alto estrecha la transición entre proyecciones.
+/// A diferencia de un mapeo UV, no se estira en los polos ni tiene costura.
+pub fn triplanar<F: Fn(f32, f32) -> f32>(position: Vector3, normal: Vector3, sharpness: f32, pattern: F) -> f32 {
+    let weights = Vector3::new(
+        normal.x.abs().powf(sharpness),
+        normal.y.abs().powf(sharpness),
+        normal.z.abs().powf(sharpness),
+    );
+    let total = weights.x + weights.y + weights.z;
+    if total == 0.0 {
+        return pattern(position.x, position.z);
+    }
+    (pattern(position.y, position.z) * weights.x
+        + pattern(position.x, position.z) * weights.y
+        + pattern(position.x, position.y) * weights.z)
+        / total
+}
+
+/// Ancho que abarca un píxel de una función escalar del punto, por diferencias finitas con las
+/// posiciones de los fragmentos vecinos (`position_ddx`/`position_ddy`)
+fn footprint<F: Fn(Vector3) -> f32>(fragment: &Fragment, f: F) -> f32 {
+    let p = fragment.world_position;
+    let center = f(p);
+    let dx = (f(p + fragment.position_ddx) - center).abs();
+    let dy = (f(p + fragment.position_ddy) - center).abs();
+    dx.max(dy)
+}
+
+/// Cuánto cambia `dot(weights, pattern_position(p))` al avanzar un píxel en pantalla (el mayor
+/// entre x e y). Es el ancho, en unidades del argumento, que abarca un píxel de ese patrón.
+fn pattern_width(fragment: &Fragment, params: &ShaderParams, weights: Vector3) -> f32 {
+    let dx = weights.dot(fragment.position_ddx).abs();
+    let dy = weights.dot(fragment.position_ddy).abs();
+    dx.max(dy) * params.noise_scale
+}
+
+/// Contraste que conserva una onda sinusoidal al promediarla sobre un píxel que abarca `width`
+/// radianes: 1 si la onda es mucho más ancha que el píxel, 0 desde medio periodo por píxel.
+/// Los patrones se mezclan con su valor medio según este factor para no parpadear de lejos.
+fn band_limit(width: f32) -> f32 {
+    let half = width * 0.5;
+    if half < 1e-3 {
+        1.0
+    } else if half >= std::f32::consts::PI {
+        0.0
+    } else {
+        half.sin() / half
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Hash entero 3D -> vector pseudoaleatorio en [0, 1)^3 (para el ruido celular)
+fn hash_cell(x: i32, y: i32, z: i32) -> Vector3 {
+    let mut h = (x as u32).wrapping_mul(0x8DA6_B343)
+        ^ (y as u32).wrapping_mul(0xD816_3841)
+        ^ (z as u32).wrapping_mul(0xCB1A_B31F);
+    let mut next = || {
+        h ^= h >> 13;
+        h = h.wrapping_mul(0x5BD1_E995);
+        h ^= h >> 15;
+        (h & 0xFFFF) as f32 / 65_536.0
+    };
+    Vector3::new(next(), next(), next())
+}
+
+/// Ruido de Worley: distancias al punto característico más cercano (f1) y al segundo (f2)
+fn worley(p: Vector3) -> (f32, f32) {
+    let cell = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+    let mut f1 = f32::MAX;
+    let mut f2 = f32::MAX;
+
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                let (cx, cy, cz) = (cell.0 + dx, cell.1 + dy, cell.2 + dz);
+                let feature = Vector3::new(cx as f32, cy as f32, cz as f32) + hash_cell(cx, cy, cz);
+                let d = (feature - p).length();
+                if d < f1 {
+                    f2 = f1;
+                    f1 = d;
+                } else if d < f2 {
+                    f2 = d;
+                }
+            }
+        }
+    }
+
+    (f1, f2)
+}
+
+/// Cobertura de tundra y de hielo (0..1) del casquete polar en el punto `p` del modelo
+fn polar_cover(p: Vector3, params: &ShaderParams) -> (f32, f32) {
+    let radius = p.length();
+    if params.ice_cap_size <= 0.0 || radius == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    // 0 en el ecuador, 1 en los polos
+    let latitude = (p.y / radius).abs();
+    let cap_start = 1.0 - params.ice_cap_size;
+
+    // Borde irregular para que el casquete no sea un círculo perfecto
+    let q = params.pattern_position(p);
+    let edge_noise = (q.x * 7.0 + q.z * 5.0).sin() * (q.z * 4.0 - q.x * 3.0).cos() * 0.05;
+    let lat = latitude + edge_noise;
+
+    let tundra = smoothstep(cap_start - 0.25, cap_start, lat);
+    let ice = smoothstep(cap_start - 0.03, cap_start + 0.05, lat);
+    (tundra, ice)
+}
+
+/// Gradiente de clima polo-ecuador para planetas terrestres:
+/// el bioma base se enfría (tundra) al acercarse al casquete y termina en hielo en los polos.
+fn apply_climate_gradient(color: Vector3, p: Vector3, params: &ShaderParams) -> Vector3 {
+    let (tundra, ice) = polar_cover(p, params);
+    if ice <= 0.0 && tundra <= 0.0 {
+        return color;
+    }
+
+    let tundra_color = Vector3::new(0.45, 0.5, 0.45);
+    let ice_color = Vector3::new(0.9, 0.95, 1.0);
+
+    color.lerp(tundra_color, tundra * 0.5).lerp(ice_color, ice)
+}
+
+/// Frecuencia de las crestas de presión del hielo polar, en crestas por unidad de patrón
+const ICE_RIDGE_FREQUENCY: f32 = 14.0;
+
+/// Altura de las crestas del hielo polar: líneas afiladas y onduladas, solo dentro del casquete
+fn ice_ridge_height(p: Vector3, params: &ShaderParams) -> f32 {
+    let (_, ice) = polar_cover(p, params);
+    if ice <= 0.0 {
+        return 0.0;
+    }
+    let q = params.pattern_position(p) * ICE_RIDGE_FREQUENCY;
+    let ridge = 1.0 - (q.x + (q.z * 0.6).sin() * 2.0).sin().abs();
+    ridge * ridge * ice * 0.01
+}
+
+// =============================================================
+// === ERUPCIONES VOLCÁNICAS ===================================
+// =============================================================
+// El calendario de erupciones es determinista (depende solo del tiempo y la semilla),
+// así el shader y el emisor de partículas coinciden sin compartir estado.
+
+pub const ERUPTION_HOTSPOTS: u32 = 5;
+const ERUPTION_DURATION: f32 = 2.5;
+
+/// Dirección (en espacio del modelo, esfera unitaria) del punto caliente `index`
+pub fn hotspot_direction(index: u32, params: &ShaderParams) -> Vector3 {
+    let u = params.hash01(100 + index * 2);
+    let v = params.hash01(101 + index * 2);
+    let theta = u * std::f32::consts::TAU;
+    let y = v * 1.6 - 0.8; // evita los polos
+    let r = (1.0 - y * y).sqrt();
+    Vector3::new(r * theta.cos(), y, r * theta.sin())
+}
+
+/// Intensidad 0..1 de la erupción del punto caliente `index` en el instante `time`
+pub fn eruption_intensity(index: u32, time: f32, params: &ShaderParams) -> f32 {
+    let period = 7.0 + params.hash01(200 + index) * 6.0;
+    let phase = params.hash01(300 + index) * period;
+    let t = (time + phase).rem_euclid(period);
+    if t < ERUPTION_DURATION {
+        (t / ERUPTION_DURATION * std::f32::consts::PI).sin()
+    } else {
+        0.0
     }
 }
 
@@ -102,56 +570,168 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
 // === FRAGMENT SHADERS DE EJEMPLO =============================
 // =============================================================
 #[allow(dead_code)]
-fn shader_terra(fragment: &Fragment, time: f32) -> Vector3 {
-    let p = fragment.world_position;
-    let base_color = fragment.color;
+fn shader_terra(fragment: &Fragment, uniforms: &Uniforms, params: &ShaderParams) -> ShadedSurface {
+    let time = uniforms.time;
+    let p = params.pattern_position(fragment.world_position);
 
     // Simula océanos con sinusoides lentas
     let ocean = ((p.x * 0.8 + p.y * 1.2 + time * 0.5).sin() * 0.5 + 0.5).powf(1.8);
 
-    // Continentes verdes usando patrones de interferencia
-    let land = ((p.x * 2.1 + p.z * 1.4 - time * 0.2).cos() * (p.y * 1.5).sin()).abs();
+    // Continentes verdes usando patrones de interferencia (ocean_level sube o baja el mar)
+    let land_raw = ((p.x * 2.1 + p.z * 1.4 - time * 0.2).cos() * (p.y * 1.5).sin()).abs();
+    let land = (land_raw - (params.ocean_level - 0.5)).clamp(0.0, 1.0);
 
-    // Nubes dinámicas
-    let clouds = ((p.x * 5.0 + p.y * 5.0 + time * 2.0).sin() * 0.5 + 0.5).powf(6.0);
+    let clouds = cloud_density(p, time, params);
 
-    let color_ocean = Vector3::new(0.0, 0.25, 0.8);
-    let color_land = Vector3::new(0.1, 0.6, 0.2);
-    let color_clouds = Vector3::new(1.0, 1.0, 1.0);
+    let [color_ocean, color_land, color_clouds] = params.palette;
 
     let mix_earth = color_ocean * (1.0 - land) + color_land * land;
+    let mix_earth = apply_climate_gradient(mix_earth, fragment.world_position, params);
+    let mix_earth = mix_earth * (1.0 - cloud_shadow(fragment, uniforms, params));
     let final_color = mix_earth * (1.0 - clouds * 0.3) + color_clouds * clouds * 0.5;
 
-    Vector3::new(
-        base_color.x * final_color.x,
-        base_color.y * final_color.y,
-        base_color.z * final_color.z,
-    )
+    // Crestas del hielo polar, que se apagan bajo las nubes y cuando no caben en un píxel
+    let pixel = fragment.position_ddx.length().max(fragment.position_ddy.length()) * params.noise_scale;
+    let ridges = band_limit(pixel * ICE_RIDGE_FREQUENCY * std::f32::consts::TAU) * (1.0 - clouds);
+    let normal = apply_normal_map(fragment, height_normal(fragment, ridges, |q| ice_ridge_height(q, params)));
+    let surface = ShadedSurface::new(fragment, final_color).with_normal(normal);
+
+    // Reflejo especular solo sobre el océano y donde no hay nubes; la tierra queda mate
+    let water = (1.0 - land) * (1.0 - clouds).clamp(0.0, 1.0);
+    if water <= 0.01 {
+        return surface;
+    }
+    surface.with_emission(Vector3::new(1.0, 0.95, 0.85) * ocean_specular(fragment, uniforms, params) * water)
 }
 
-#[allow(dead_code)]
-fn shader_vulcan(fragment: &Fragment, time: f32) -> Vector3 {
+/// Altura de la capa de nubes sobre la superficie (radio del cuerpo = 1)
+const CLOUD_ALTITUDE: f32 = 0.04;
+const CLOUD_SHADOW_STRENGTH: f32 = 0.45;
+
+/// Densidad 0..1 de la capa de nubes en el punto `p` (ya en espacio de patrón).
+/// Más cobertura => exponente menor => más nubes.
+fn cloud_density(p: Vector3, time: f32, params: &ShaderParams) -> f32 {
+    let cloud_exponent = ((1.0 - params.cloud_coverage) * 12.0).max(0.5);
+    ((p.x * 5.0 + p.y * 5.0 + time * 2.0).sin() * 0.5 + 0.5).powf(cloud_exponent)
+}
+
+/// Sombra que la capa de nubes proyecta sobre la superficie: se busca la nube que atraviesa el
+/// rayo hacia el sol a `CLOUD_ALTITUDE` de altura, así que la sombra aparece desplazada respecto
+/// a la nube y se alarga cerca del terminador
+fn cloud_shadow(fragment: &Fragment, uniforms: &Uniforms, params: &ShaderParams) -> f32 {
+    if params.cloud_coverage <= 0.0 {
+        return 0.0;
+    }
+    let Some((world_pos, world_normal)) = world_surface(fragment, uniforms) else {
+        return 0.0;
+    };
+    let to_light = (uniforms.light_position - world_pos).normalize();
+    let facing = world_normal.dot(&to_light);
+    if facing <= 0.0 {
+        return 0.0;
+    }
+    let Some(inverse_model) = uniforms.inverse_model_matrix else {
+        return 0.0;
+    };
+
+    // Dirección al sol en espacio de objeto, donde viven los patrones
+    let light = (inverse_model * glm::vec4(to_light.x, to_light.y, to_light.z, 0.0)).xyz().normalize();
+    let distance = CLOUD_ALTITUDE / facing.max(0.25);
     let p = fragment.world_position;
-    let base_color = fragment.color;
+    let above = Vector3::new(p.x + light.x * distance, p.y + light.y * distance, p.z + light.z * distance);
+    cloud_density(params.pattern_position(above), uniforms.time, params) * CLOUD_SHADOW_STRENGTH
+}
+
+/// Término especular Blinn-Phong con la normal perturbada por olas animadas.
+/// La perturbación solo afecta al brillo, no a la iluminación difusa.
+fn ocean_specular(fragment: &Fragment, uniforms: &Uniforms, params: &ShaderParams) -> f32 {
+    let p = fragment.world_position;
+    let Some((world_pos, world_normal)) = world_surface(fragment, uniforms) else {
+        return 0.0;
+    };
+
+    let to_light = (uniforms.light_position - world_pos).normalize();
+    let to_camera = (uniforms.camera_position - world_pos).normalize();
+    if world_normal.dot(&to_light) <= 0.0 {
+        return 0.0;
+    }
 
-    let crack_pattern = ((p.x * 8.0).sin() * (p.y * 8.0).cos() * (p.z * 6.0).sin()).abs();
+    // Olas: dos trenes de ondas cruzados que se desplazan con el tiempo
+    let q = params.pattern_position(p) * 12.0;
+    let time = uniforms.time;
+    let wave = glm::vec3(
+        (q.x + q.y * 0.5 + time * 1.7).sin(),
+        (q.y - q.z * 0.7 + time * 1.3).sin(),
+        (q.z + q.x * 0.6 - time * 1.9).cos(),
+    );
+    let wavy_normal = (world_normal + wave * 0.12).normalize();
+
+    let half_vector = (to_light + to_camera).normalize();
+    let shimmer = (time * 6.0 + q.x * 3.0).sin() * 0.15 + 0.85;
+    wavy_normal.dot(&half_vector).max(0.0).powf(80.0) * 1.4 * shimmer
+}
+
+/// Grietas de lava en 0..1 (1 en el centro de la grieta), en espacio de patrón
+fn lava_cracks(p: Vector3) -> f32 {
+    ((p.x * 8.0).sin() * (p.y * 8.0).cos() * (p.z * 6.0).sin()).abs()
+}
+
+/// Frecuencia de los cráteres de los planetas volcánicos, en celdas por unidad de patrón
+const CRATER_FREQUENCY: f32 = 3.0;
+
+/// Altura del relieve volcánico: cuencos de cráter con borde elevado y las grietas hundidas
+fn vulcan_height(p: Vector3, params: &ShaderParams) -> f32 {
+    let q = params.pattern_position(p);
+    let (f1, _) = worley(q * CRATER_FREQUENCY);
+    let bowl = 1.0 - smoothstep(0.0, 0.3, f1);
+    let rim = smoothstep(0.2, 0.3, f1) * (1.0 - smoothstep(0.3, 0.42, f1));
+    (rim * 0.4 - bowl) * 0.03 - lava_cracks(q).powf(3.0) * 0.02
+}
+
+#[allow(dead_code)]
+fn shader_vulcan(fragment: &Fragment, uniforms: &Uniforms, params: &ShaderParams) -> ShadedSurface {
+    let time = uniforms.time;
+    let p = params.pattern_position(fragment.world_position);
+
+    // Relieve de cráteres y grietas; se apaga cuando un cráter ocupa menos de un píxel
+    let pixel = fragment.position_ddx.length().max(fragment.position_ddy.length()) * params.noise_scale;
+    let relief = band_limit(pixel * CRATER_FREQUENCY * std::f32::consts::TAU);
+    let normal = apply_normal_map(fragment, height_normal(fragment, relief, |q| vulcan_height(q, params)));
+
+    let crack_pattern = lava_cracks(p);
     let heat_wave = ((p.x * 3.0 + p.y * 2.0 + time * 5.0).sin() * 0.5 + 0.5).powf(8.0);
 
-    let rock_color = Vector3::new(0.3, 0.2, 0.15);
-    let lava_color = Vector3::new(1.0, 0.4, 0.05);
+    // De lejos las grietas se funden con su valor medio (~0.076) en lugar de parpadear
+    let crack_contrast = band_limit(pattern_width(fragment, params, Vector3::new(8.0, 0.0, 0.0)))
+        * band_limit(pattern_width(fragment, params, Vector3::new(0.0, 8.0, 0.0)))
+        * band_limit(pattern_width(fragment, params, Vector3::new(0.0, 0.0, 6.0)));
+    let cracks = crack_pattern.powf(3.0) * crack_contrast + 0.076 * (1.0 - crack_contrast);
+
+    let [rock_color, lava_color, _] = params.palette;
 
-    let lava_mix = crack_pattern.powf(3.0) * heat_wave;
+    let lava_mix = cracks * heat_wave;
     let color = rock_color * (1.0 - lava_mix) + lava_color * lava_mix;
 
     // Brillo dinámico (simula calor)
     let glow = (time * 10.0).sin() * 0.1 + 0.9;
-    color * glow * base_color
+
+    // Puntos calientes: brillan siempre un poco y se encienden al entrar en erupción (emisivo)
+    let dir = fragment.world_position.normalized();
+    let mut flare = 0.0;
+    for index in 0..ERUPTION_HOTSPOTS {
+        let closeness = dir.dot(hotspot_direction(index, params));
+        let heat = 0.15 + eruption_intensity(index, time, params) * 1.5;
+        flare += smoothstep(0.93, 0.995, closeness) * heat;
+    }
+    ShadedSurface::new(fragment, color * glow)
+        .with_normal(normal)
+        .with_emission(params.palette[2] * flare)
 }
 
 #[allow(dead_code)]
-pub fn shader_solarius(fragment: &Fragment, time: f32) -> Vector3 {
-    let p = fragment.world_position;
-    let base_color = fragment.color;
+pub fn shader_solarius(fragment: &Fragment, uniforms: &Uniforms, params: &ShaderParams) -> ShadedSurface {
+    let time = uniforms.time;
+    let p = params.pattern_position(fragment.world_position);
 
     // Movimiento tipo flujo solar
     let plasma = ((p.x * 4.0 + time * 3.0).sin() + (p.y * 5.0 - time * 2.0).cos()).abs();
@@ -162,14 +742,19 @@ pub fn shader_solarius(fragment: &Fragment, time: f32) -> Vector3 {
     let spot_factor = (1.0 - sunspots * 0.5).max(0.0);
 
     // Paleta de colores (desde el núcleo al borde)
-    let color_core = Vector3::new(1.0, 0.9, 0.3);   // centro brillante
-    let color_flame = Vector3::new(1.0, 0.5, 0.0);  // medio ardiente
-    let color_outer = Vector3::new(1.0, 0.15, 0.0); // borde rojo oscuro
+    let [color_core, color_flame, color_outer] = params.palette; // centro, medio, borde
 
     // Mezcla entre colores
     let mix1 = color_core * plasma + color_flame * (1.0 - plasma);
     let mix2 = mix1 * spot_factor + color_outer * (1.0 - spot_factor);
 
+    // Granulación: celdas de convección brillantes separadas por carriles oscuros
+    let drift = Vector3::new(time * 0.15, -time * 0.1, time * 0.12);
+    let (f1, f2) = worley(p * 6.0 + drift);
+    let lanes = smoothstep(0.0, 0.25, f2 - f1);
+    let cell_glow = 1.0 - f1 * 0.35;
+    let mix2 = mix2 * (0.7 + 0.3 * lanes) * cell_glow;
+
     // Pulso radiante (animación de brillo)
     let pulse = (time * 3.0).sin() * 0.25 + 0.9;
 
@@ -177,44 +762,74 @@ pub fn shader_solarius(fragment: &Fragment, time: f32) -> Vector3 {
     let emission_intensity = 2.5; // controla cuánta “luz” emite
     let color_emission = mix2 * emission_intensity * pulse;
 
-    // Combinar con color base del fragmento (si tu modelo tiene color)
-    (base_color * 0.3) + color_emission
+    // Oscurecimiento del limbo: el borde del disco es más tenue y más rojizo
+    let limb = limb_darkening(fragment, uniforms, 0.75);
+    let color_emission = color_emission.lerp(color_outer * emission_intensity, (1.0 - limb) * 0.6) * limb;
+
+    // Un poco de la luz del fragmento (si tu modelo tiene color) bajo la emisión
+    ShadedSurface::new(fragment, Vector3::one() * 0.3).with_emission(color_emission)
 }
 
 
 #[allow(dead_code)]
-pub fn shader_nepturion(fragment: &Fragment, time: f32) -> Vector3 {
+pub fn shader_nepturion(fragment: &Fragment, uniforms: &Uniforms, params: &ShaderParams) -> ShadedSurface {
+    let time = uniforms.time;
     let p = fragment.world_position;
-    let base_color = fragment.color;
+    let q = params.pattern_position(p);
 
     // --- Superficie gaseosa animada ---
-    let band = ((p.y * 4.0 + time * 0.8).sin() * 0.5 + 0.5).powf(2.0);
-    let turbulence = ((p.x * 6.0 + p.z * 4.0 + time * 2.0).cos() * 0.5 + 0.5).powf(3.0);
-
-    let band_color1 = Vector3::new(0.05, 0.2, 0.7);
-    let band_color2 = Vector3::new(0.2, 0.4, 0.9);
-    let highlight = Vector3::new(0.5, 0.8, 1.0);
+    // Franjas por latitud: igual de anchas hasta los polos
+    let band_frequency = 4.0 * std::f32::consts::PI * params.noise_scale;
+    let band_phase = params.hash01(4) * std::f32::consts::TAU;
+    let latitude = spherical_uv(p).y;
+    let band_contrast = band_limit(footprint(fragment, |point| spherical_uv(point).y) * band_frequency);
+    let band = ((latitude * band_frequency + band_phase + time * 0.8).sin() * 0.5 + 0.5).powf(2.0) * band_contrast
+        + 0.375 * (1.0 - band_contrast);
+    let swirl = |q: Vector3| ((q.x * 6.0 + q.z * 4.0 + time * 2.0).cos() * 0.5 + 0.5).powf(3.0);
+    let turbulence = swirl(q);
+
+    let [band_color1, band_color2, highlight] = params.palette;
 
     let gas_mix = band_color1 * band + band_color2 * (1.0 - band);
     let final_color = gas_mix * (1.0 - turbulence * 0.3) + highlight * turbulence * 0.4;
 
     // --- Brillo atmosférico leve ---
-    let glow = ((p.y + time * 0.2).sin() * 0.5 + 0.5) * 0.2 + 0.8;
-    let mut color = final_color * glow * base_color;
+    let glow = ((q.y + time * 0.2).sin() * 0.5 + 0.5) * 0.2 + 0.8;
+    let limb = limb_darkening(fragment, uniforms, 0.6);
+
+    // Los remolinos abomban un poco las nubes: relieve suave que se apaga de lejos
+    let pixel = fragment.position_ddx.length().max(fragment.position_ddy.length()) * params.noise_scale;
+    let swirls = band_limit(pixel * 6.0 * std::f32::consts::TAU) * 0.004;
+    let normal = apply_normal_map(fragment, height_normal(fragment, swirls, |point| swirl(params.pattern_position(point))));
 
     // --- 🌌 Anillos orbitales ---
+    ShadedSurface::new(fragment, final_color * glow * limb)
+        .with_normal(normal)
+        .with_emission(ring_color(fragment, uniforms, p))
+}
+
+
+/// Radios interior y exterior de los anillos de los gigantes gaseosos, en radios del planeta
+pub const RING_INNER: f32 = 1.2;
+pub const RING_OUTER: f32 = 2.5;
+
+/// Color (aditivo) de los anillos en el punto `p` del modelo; negro fuera de ellos
+pub fn ring_color(fragment: &Fragment, uniforms: &Uniforms, p: Vector3) -> Vector3 {
+    let time = uniforms.time;
     // Calculamos distancia desde el eje Y (plano de los anillos)
     let r = length(&glm::vec3(p.x, 0.0, p.z));
-
-    // Definimos región donde hay anillos
-    let ring_inner = 1.2;
-    let ring_outer = 2.5;
+    let (ring_inner, ring_outer) = (RING_INNER, RING_OUTER);
 
     if r > ring_inner && r < ring_outer {
         // Ondulación sutil y rotación del patrón
         let rotation = (time * 0.5).sin() * 0.3;
         let ring_pattern = (((r * 30.0) + rotation).sin() * 0.5 + 0.5).powf(6.0);
 
+        // Las 30 franjas por unidad de radio son lo primero que parpadea: d(r) = (x dx + z dz) / r
+        let radial = |d: Vector3| ((p.x * d.x + p.z * d.z) / r * 30.0).abs();
+        let ring_contrast = band_limit(radial(fragment.position_ddx).max(radial(fragment.position_ddy)));
+        let ring_pattern = ring_pattern * ring_contrast + 0.226 * (1.0 - ring_contrast);
+
         // Color de los anillos
         let ring_color = Vector3::new(0.7, 0.9, 1.0) * 1.5;
 
@@ -225,44 +840,102 @@ pub fn shader_nepturion(fragment: &Fragment, time: f32) -> Vector3 {
         let tilt = (p.y * 2.0).abs().max(0.1);
         let transparency = (1.0 - tilt).clamp(0.0, 1.0) * 0.6;
 
-        // Color combinado
-        let ring_contrib = ring_color * ring_pattern * fade * transparency;
-        color += ring_contrib;
+        // Color combinado; el polvo fino brilla mucho más a contraluz
+        return ring_color * ring_pattern * fade * transparency * (1.0 + forward_scattering(fragment, uniforms));
     }
-
-    color
+    Vector3::zero()
 }
 
+/// Asimetría del polvo de los anillos: cerca de 1 dispersa casi todo hacia delante
+const RING_DUST_ASYMMETRY: f32 = 0.75;
+const RING_FORWARD_STRENGTH: f32 = 0.35;
+
+/// Brillo extra del polvo cuando el sol queda detrás de los anillos (fase de Henyey-Greenstein
+/// respecto a la dispersión isótropa, menos 1): 0 con el sol de frente o de lado, fuerte a contraluz
+fn forward_scattering(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let Some((world_pos, _)) = world_surface(fragment, uniforms) else {
+        return 0.0;
+    };
+    let view = (world_pos - uniforms.camera_position).normalize();
+    let to_light = (uniforms.light_position - world_pos).normalize();
+    let cos_theta = view.dot(&to_light);
+
+    let g = RING_DUST_ASYMMETRY;
+    let phase = (1.0 - g * g) / (1.0 + g * g - 2.0 * g * cos_theta).powf(1.5);
+    ((phase - 1.0) * RING_FORWARD_STRENGTH).clamp(0.0, 4.0)
+}
 
 #[allow(dead_code)]
-fn shader_mossar(fragment: &Fragment, time: f32) -> Vector3 {
-    let p = fragment.world_position;
-    let base_color = fragment.color;
+fn shader_mossar(fragment: &Fragment, time: f32, params: &ShaderParams) -> ShadedSurface {
+    let p = params.pattern_position(fragment.world_position);
 
-    let moss = ((p.x * 3.0 + p.y * 2.5).cos() * (p.z * 3.5).sin() * 0.5 + 0.5).powf(2.5);
+    let moss = triplanar(p, fragment.normal, 4.0, |a, b| ((a * 3.0 + b * 2.5).cos() * (b * 3.5).sin() * 0.5 + 0.5).powf(2.5));
     let bio_glow = ((p.x + p.y + time * 1.5).sin() * 0.5 + 0.5).powf(10.0);
 
-    let color_moss = Vector3::new(0.1, 0.6, 0.2);
-    let color_dark = Vector3::new(0.05, 0.25, 0.05);
-    let color_glow = Vector3::new(0.4, 1.0, 0.6);
+    let [color_moss, color_dark, color_glow] = params.palette;
 
     let blend = color_moss * moss + color_dark * (1.0 - moss);
+    let blend = apply_climate_gradient(blend, fragment.world_position, params);
     let final_color = blend * (1.0 - bio_glow * 0.3) + color_glow * bio_glow * 0.5;
 
-    final_color * base_color
+    ShadedSurface::new(fragment, final_color)
 }
 
+/// Modelos que no son planetas: albedo de la textura (o el color difuso de la paleta) con
+/// iluminación Blinn-Phong desde la posición de la luz y, si lo hay, mapa de normales
+fn shader_mesh(fragment: &Fragment, uniforms: &Uniforms, params: &ShaderParams, maps: SurfaceMaps) -> Vector3 {
+    let [diffuse_color, specular_color, ambient] = params.palette;
+    let albedo = match maps.albedo {
+        Some(texture) => texture.sample_grad(
+            fragment.tex_coords.x,
+            fragment.tex_coords.y,
+            fragment.tex_coords_ddx,
+            fragment.tex_coords_ddy,
+        ),
+        None => diffuse_color,
+    };
 
+    let Some((world_pos, world_normal)) = world_surface(fragment, uniforms) else {
+        return albedo;
+    };
+    let world_normal = match maps.normal {
+        Some(normal_map) => {
+            let n = apply_normal_map(fragment, sample_normal_map(fragment, normal_map));
+            uniforms.world_normal(glm::vec3(n.x, n.y, n.z)).try_normalize(1e-6).unwrap_or(world_normal)
+        }
+        None => world_normal,
+    };
+    let to_light = (uniforms.light_position - world_pos).normalize();
+    let to_camera = (uniforms.camera_position - world_pos).normalize();
+    let diffuse = world_normal.dot(&to_light).max(0.0);
 
+    let specular = if diffuse > 0.0 {
+        let half = (to_light + to_camera).normalize();
+        world_normal.dot(&half).max(0.0).powf(params.shininess)
+    } else {
+        0.0
+    };
+
+    ambient + albedo * diffuse + specular_color * specular
+}
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, planet_type: PlanetShaderType) -> Vector3 {
+/// Color final del fragmento. Los planetas se sombrean primero (`ShadedSurface`) y se iluminan
+/// después con la normal que devuelve su shader; las mallas hacen su propio Blinn-Phong.
+pub fn fragment_shader(
+    fragment: &Fragment,
+    uniforms: &Uniforms,
+    planet_type: PlanetShaderType,
+    params: &ShaderParams,
+    maps: SurfaceMaps,
+) -> Vector3 {
     let time = uniforms.time;
-    match planet_type {
-        PlanetShaderType::Terra => shader_terra(fragment, time),
-        PlanetShaderType::Vulcan => shader_vulcan(fragment, time),
-        PlanetShaderType::Solarius => shader_solarius(fragment, time),
-        PlanetShaderType::Nepturion => shader_nepturion(fragment, time),
-        PlanetShaderType::Mossar => shader_mossar(fragment, time),
-    
-    }
+    let surface = match planet_type {
+        PlanetShaderType::Terra => shader_terra(fragment, uniforms, params),
+        PlanetShaderType::Vulcan => shader_vulcan(fragment, uniforms, params),
+        PlanetShaderType::Solarius => shader_solarius(fragment, uniforms, params),
+        PlanetShaderType::Nepturion => shader_nepturion(fragment, uniforms, params),
+        PlanetShaderType::Mossar => shader_mossar(fragment, time, params),
+        PlanetShaderType::Mesh => return shader_mesh(fragment, uniforms, params, maps),
+    };
+    light_surface(fragment, uniforms, &surface)
 }
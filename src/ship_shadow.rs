@@ -0,0 +1,103 @@
+use nalgebra_glm::{self as glm, Vec3};
+use crate::framebuffer::Framebuffer;
+use crate::Uniforms;
+
+// Sombra de contacto de la nave: una mancha oscura en la superficie justo debajo de ella (hacia
+// el centro del cuerpo, no según el Sol), más grande y más tenue cuanto más alto vuela. Es un
+// disco en el plano tangente al punto de debajo que solo oscurece los píxeles cuya profundidad
+// cae sobre la superficie, así no mancha el cielo tras el horizonte ni la propia nave.
+
+/// Altitud a partir de la que ya no hay sombra, en radios del cuerpo
+const MAX_ALTITUDE: f32 = 0.5;
+/// Cuánto oscurece el centro de la mancha con la nave a ras de suelo
+const DARKNESS: f32 = 0.65;
+/// Lo que crece el radio de la mancha por unidad de altitud
+const SPREAD: f32 = 0.25;
+/// Radio de la mancha a ras de suelo respecto al de la nave, que es alargada y no llena su círculo
+const FOOTPRINT: f32 = 0.6;
+
+/// Dibuja la sombra de una nave de radio `ship_radius` situada en `ship`, relativa al centro de
+/// un cuerpo de radio `radius` cuyo relieve sube y baja hasta `relief`. `uniforms` lleva el
+/// modelo del cuerpo sin escala ni giro.
+pub fn render_ship_shadow(framebuffer: &mut Framebuffer, uniforms: &Uniforms, ship: Vec3, radius: f32, relief: f32, ship_radius: f32) {
+    let distance = ship.norm();
+    let altitude = distance - radius;
+    let max_altitude = radius * MAX_ALTITUDE;
+    if distance <= 0.0 || !(0.0..max_altitude).contains(&altitude) {
+        return;
+    }
+    let fade = (1.0 - altitude / max_altitude).powi(2);
+    let up = ship / distance;
+    let blob_radius = ship_radius * FOOTPRINT + altitude * SPREAD;
+    let helper = if up.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+    let tangent = up.cross(&helper).normalize() * blob_radius;
+    let bitangent = up.cross(&tangent);
+
+    // El disco es plano y la superficie se curva por debajo: la ventana de profundidad va de
+    // algo por encima del relieve a la flecha de la esfera (más el relieve) por debajo
+    let center = up * radius;
+    let sagitta = radius - (radius * radius - blob_radius * blob_radius).max(0.0).sqrt();
+    let near = center + up * (relief + blob_radius * 0.02);
+    let far = center - up * (sagitta + relief + blob_radius * 0.1);
+
+    let mvp = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+    // (x, y) en píxeles y el valor de profundidad que se interpola en pantalla
+    let project = |point: Vec3| {
+        let clip = mvp * glm::vec4(point.x, point.y, point.z, 1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let screen = uniforms.viewport_matrix * glm::vec4(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+        Some(glm::vec3(screen.x, screen.y, uniforms.depth_mode.interpolant(clip.z, clip.w)))
+    };
+    let corners = [near, near + tangent, near + bitangent, far, far + tangent, far + bitangent];
+    let [Some(c), Some(a), Some(b), Some(far_c), Some(far_a), Some(far_b)] = corners.map(project) else {
+        return;
+    };
+
+    // Ejes de la elipse en pantalla; casi de canto no se ve
+    let (axis_a, axis_b) = (a - c, b - c);
+    let det = axis_a.x * axis_b.y - axis_a.y * axis_b.x;
+    if det.abs() < 1e-3 {
+        return;
+    }
+    let (Some(near_depth), Some(far_depth)) = (screen_plane(c, a, b), screen_plane(far_c, far_a, far_b)) else {
+        return;
+    };
+    let half_width = (axis_a.x * axis_a.x + axis_b.x * axis_b.x).sqrt();
+    let half_height = (axis_a.y * axis_a.y + axis_b.y * axis_b.y).sqrt();
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    if c.x + half_width < 0.0 || c.x - half_width >= width || c.y + half_height < 0.0 || c.y - half_height >= height {
+        return;
+    }
+    let (x0, x1) = ((c.x - half_width).max(0.0) as usize, (c.x + half_width).min(width - 1.0) as usize);
+    let (y0, y1) = ((c.y - half_height).max(0.0) as usize, (c.y + half_height).min(height - 1.0) as usize);
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let (dx, dy) = (px - c.x, py - c.y);
+            let u = (dx * axis_b.y - dy * axis_b.x) / det;
+            let v = (axis_a.x * dy - axis_a.y * dx) / det;
+            let r2 = u * u + v * v;
+            if r2 >= 1.0 {
+                continue;
+            }
+            let amount = DARKNESS * fade * (1.0 - r2).powi(2);
+            let (near, far) = (near_depth(px, py), far_depth(px, py));
+            framebuffer.darken_point(x, y, amount, uniforms.depth_mode.resolve(near), uniforms.depth_mode.resolve(far));
+        }
+    }
+}
+
+/// Valor de profundidad de un plano en cada píxel: es afín en pantalla, así que lo fijan tres
+/// de sus puntos proyectados (x, y, valor)
+fn screen_plane(p0: Vec3, p1: Vec3, p2: Vec3) -> Option<impl Fn(f32, f32) -> f32> {
+    let (e1, e2) = (p1 - p0, p2 - p0);
+    let det = e1.x * e2.y - e1.y * e2.x;
+    if det.abs() < 1e-6 {
+        return None;
+    }
+    let gradient_x = (e1.z * e2.y - e2.z * e1.y) / det;
+    let gradient_y = (e1.x * e2.z - e2.x * e1.z) / det;
+    Some(move |x: f32, y: f32| p0.z + gradient_x * (x - p0.x) + gradient_y * (y - p0.y))
+}
@@ -0,0 +1,181 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use nalgebra_glm::{DVec3, Vec3};
+
+/// Distancia mínima entre la nave y la superficie de un cuerpo
+pub const SHIP_CLEARANCE: f32 = 15.0;
+
+/// Avance máximo de un cuerpo por sub-paso: ángulo orbital (rad) y fracción de su radio
+const MAX_ANGULAR_STEP: f32 = 0.02;
+const MAX_LINEAR_STEP: f32 = 0.5;
+const MAX_SUBSTEPS: usize = 512;
+
+/// Elementos fijos de la órbita y la rotación de un cuerpo. Las órbitas son circulares y
+/// uniformes, así que el estado en cualquier instante se calcula directamente a partir del
+/// tiempo de simulación, sin acumular pasos: sirve igual para avanzar, saltar o rebobinar.
+#[derive(Debug, Clone)]
+pub struct Ephemeris {
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+    /// Ángulo orbital en t = 0
+    pub orbit_phase: f32,
+    pub rotation_speed: Vec3,
+    /// Rotación en t = 0
+    pub rotation_phase: Vec3,
+    pub scale: f32,
+}
+
+impl Ephemeris {
+    /// Estado en el instante `time`. El ángulo y la posición se calculan en doble precisión:
+    /// con radios y tiempos grandes, f32 haría temblar los cuerpos al acercarse a ellos.
+    pub fn state_at(&self, time: f64) -> OrbitalState {
+        let orbit_angle = self.orbit_phase as f64 + self.orbit_speed as f64 * time;
+        let radius = self.orbit_radius as f64;
+        let rotation = self.rotation_phase.cast::<f64>() + self.rotation_speed.cast::<f64>() * time;
+        OrbitalState {
+            position: DVec3::new(radius * orbit_angle.cos(), 0.0, radius * orbit_angle.sin()),
+            rotation: rotation.map(|angle| angle.rem_euclid(std::f64::consts::TAU)).cast(),
+            orbit_angle: orbit_angle.rem_euclid(std::f64::consts::TAU) as f32,
+            scale: self.scale,
+        }
+    }
+}
+
+/// Estado de un cuerpo en un instante
+#[derive(Debug, Clone)]
+pub struct OrbitalState {
+    pub position: DVec3,
+    pub rotation: Vec3,
+    pub orbit_angle: f32,
+    pub scale: f32,
+}
+
+/// Sub-pasos necesarios para que ningún cuerpo avance más de lo permitido en `delta_time`.
+/// Con escalas de tiempo altas un solo paso haría saltar los planetas a través de la nave.
+pub fn substeps(bodies: &[Ephemeris], delta_time: f64) -> usize {
+    let steps = bodies
+        .iter()
+        .map(|body| {
+            let angle = (body.orbit_speed * delta_time as f32).abs();
+            let distance = angle * body.orbit_radius;
+            (angle / MAX_ANGULAR_STEP).max(distance / (body.scale * MAX_LINEAR_STEP))
+        })
+        .fold(1.0, f32::max);
+    (steps.ceil() as usize).clamp(1, MAX_SUBSTEPS)
+}
+
+/// Empuja `position` fuera de cualquier cuerpo que la haya alcanzado
+fn push_out(position: DVec3, bodies: &[OrbitalState]) -> DVec3 {
+    let mut position = position;
+    for body in bodies {
+        let min_distance = (body.scale + SHIP_CLEARANCE) as f64;
+        let offset = position - body.position;
+        if offset.norm() < min_distance {
+            let direction = offset.try_normalize(1e-6).unwrap_or(DVec3::new(0.0, 1.0, 0.0));
+            position = body.position + direction * min_distance;
+        }
+    }
+    position
+}
+
+/// Petición de avance: instante de simulación a alcanzar y posición actual de la nave
+pub struct SimulationStep {
+    pub time: f64,
+    pub ship_position: DVec3,
+}
+
+enum SimulationInput {
+    Step(SimulationStep),
+    /// Sustituye la lista de cuerpos (recarga de la escena)
+    Replace(Vec<Ephemeris>),
+}
+
+/// Estado completo en el instante del último paso recibido
+pub struct SimulationSnapshot {
+    /// Número de reemplazos de cuerpos aplicados; permite descartar instantáneas anteriores a una recarga
+    pub generation: u32,
+    pub bodies: Vec<OrbitalState>,
+    /// Desplazamiento a aplicar a la nave si algún cuerpo la alcanzó
+    pub ship_correction: DVec3,
+}
+
+/// Hilo que calcula las órbitas. El render envía un paso por frame y consume siempre
+/// la instantánea más reciente, sin esperar a que el hilo termine.
+pub struct SimulationWorker {
+    inputs: Sender<SimulationInput>,
+    generation: u32,
+    snapshots: Receiver<SimulationSnapshot>,
+}
+
+impl SimulationWorker {
+    pub fn spawn(bodies: Vec<Ephemeris>) -> Self {
+        let (inputs, input_receiver) = mpsc::channel::<SimulationInput>();
+        let (snapshot_sender, snapshots) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut bodies = bodies;
+            let mut generation = 0;
+            let mut last_time = None;
+            while let Ok(first) = input_receiver.recv() {
+                // Si el render va por delante, solo cuenta el último paso pendiente
+                let mut step = None;
+                for input in std::iter::once(first).chain(input_receiver.try_iter()) {
+                    match input {
+                        SimulationInput::Step(pending) => step = Some(pending),
+                        SimulationInput::Replace(replacement) => {
+                            bodies = replacement;
+                            generation += 1;
+                            // Tras un salto en el tiempo o un cambio de escena no hay recorrido
+                            // que sub-dividir: la nave no pasó por las posiciones intermedias
+                            last_time = None;
+                        }
+                    }
+                }
+                let Some(SimulationStep { time, ship_position }) = step else {
+                    continue;
+                };
+
+                // Los sub-pasos solo sirven para que la nave no quede atravesada por un cuerpo
+                let from = last_time.unwrap_or(time);
+                last_time = Some(time);
+                let count = substeps(&bodies, time - from);
+                let mut ship = ship_position;
+                let mut states = Vec::new();
+                for index in 1..=count {
+                    let instant = from + (time - from) * index as f64 / count as f64;
+                    states = bodies.iter().map(|body| body.state_at(instant)).collect();
+                    ship = push_out(ship, &states);
+                }
+
+                let snapshot = SimulationSnapshot {
+                    generation,
+                    bodies: states,
+                    ship_correction: ship - ship_position,
+                };
+                if snapshot_sender.send(snapshot).is_err() {
+                    break;
+                }
+            }
+        });
+
+        SimulationWorker { inputs, generation: 0, snapshots }
+    }
+
+    pub fn submit(&self, step: SimulationStep) {
+        // Si el hilo terminó, el render sigue mostrando el último estado
+        let _ = self.inputs.send(SimulationInput::Step(step));
+    }
+
+    pub fn replace_bodies(&mut self, bodies: Vec<Ephemeris>) {
+        self.generation += 1;
+        let _ = self.inputs.send(SimulationInput::Replace(bodies));
+    }
+
+    /// Instantánea más reciente, descartando las intermedias
+    pub fn latest(&self) -> Option<SimulationSnapshot> {
+        self.snapshots
+            .try_iter()
+            .filter(|snapshot| snapshot.generation == self.generation)
+            .last()
+    }
+}
@@ -0,0 +1,100 @@
+use nalgebra_glm::{Mat4, Vec3};
+use crate::depth::DepthMode;
+use crate::framebuffer::Framebuffer;
+use crate::gbuffer::{GBuffer, GSample};
+
+/// Radio de la semiesfera de muestreo en unidades de mundo (la nave mide unas 2.5)
+const RADIUS: f32 = 1.5;
+const MIN_PIXELS: f32 = 2.0;
+const MAX_PIXELS: f32 = 24.0;
+/// Evita que una superficie se ocluya a sí misma por la precisión del z-buffer
+const BIAS: f32 = 0.1;
+const STRENGTH: f32 = 1.2;
+const DIRECTIONS: usize = 8;
+
+/// Reconstruye la posición en espacio de vista del píxel (x, y) con valor de z-buffer `z`
+fn view_position(projection: &Mat4, viewport: &Mat4, depth_mode: DepthMode, x: f32, y: f32, z: f32) -> Vec3 {
+    let depth = depth_mode.linear(projection, z);
+    let ndc_x = (x - viewport[(0, 3)]) / viewport[(0, 0)];
+    let ndc_y = (y - viewport[(1, 3)]) / viewport[(1, 1)];
+    Vec3::new(
+        ndc_x * depth / projection[(0, 0)],
+        ndc_y * depth / projection[(1, 1)],
+        -depth,
+    )
+}
+
+/// Oclusión ambiental en espacio de pantalla sobre los píxeles del G-buffer.
+/// Por píxel se toman 16 muestras del z-buffer en 8 direcciones; cada vecino que queda por
+/// delante de la superficie (según su normal) y cerca de ella oscurece el color ya sombreado.
+/// `view_normal` da la normal del fragmento en espacio de vista.
+pub fn apply<F: Fn(&GSample) -> Vec3>(
+    framebuffer: &mut Framebuffer,
+    gbuffer: &GBuffer,
+    projection: &Mat4,
+    viewport: &Mat4,
+    depth_mode: DepthMode,
+    view_normal: F,
+) {
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+    let directions: Vec<(f32, f32)> = (0..DIRECTIONS)
+        .map(|i| {
+            let angle = i as f32 / DIRECTIONS as f32 * std::f32::consts::TAU;
+            (angle.cos(), angle.sin())
+        })
+        .collect();
+
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            let Some(sample) = gbuffer.sample(index) else {
+                continue;
+            };
+            let ndc_z = framebuffer.zbuffer[index];
+            if ndc_z != sample.fragment.depth {
+                continue;
+            }
+
+            let center = view_position(projection, viewport, depth_mode, x as f32, y as f32, ndc_z);
+            let normal = view_normal(sample);
+            let pixels = (viewport[(0, 0)].abs() * projection[(0, 0)] * RADIUS / -center.z).clamp(MIN_PIXELS, MAX_PIXELS);
+
+            // Rotación distinta por píxel (patrón 4x4) para cambiar bandas por ruido fino
+            let jitter = ((x % 4) * 4 + (y % 4)) as f32 / 16.0 * (std::f32::consts::TAU / DIRECTIONS as f32);
+            let (sin_j, cos_j) = jitter.sin_cos();
+
+            let mut occlusion = 0.0;
+            let mut count = 0;
+            for &(dx, dy) in &directions {
+                let (dx, dy) = (dx * cos_j - dy * sin_j, dx * sin_j + dy * cos_j);
+                for step in [0.5, 1.0] {
+                    let sx = x as f32 + dx * pixels * step;
+                    let sy = y as f32 + dy * pixels * step;
+                    if sx < 0.0 || sy < 0.0 || sx >= width as f32 || sy >= height as f32 {
+                        continue;
+                    }
+                    count += 1;
+                    let neighbor_z = framebuffer.zbuffer[sy as usize * width + sx as usize];
+                    if neighbor_z == f32::INFINITY {
+                        continue;
+                    }
+                    let offset = view_position(projection, viewport, depth_mode, sx, sy, neighbor_z) - center;
+                    let distance = offset.norm();
+                    if distance < 1e-4 {
+                        continue;
+                    }
+                    let facing = (normal.dot(&offset) / distance - BIAS).max(0.0);
+                    let falloff = 1.0 / (1.0 + (distance / RADIUS).powi(2));
+                    occlusion += facing * falloff;
+                }
+            }
+            if count > 0 {
+                let factor = (1.0 - STRENGTH * occlusion / count as f32).clamp(0.0, 1.0);
+                let color = framebuffer.buffer[index];
+                let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * factor) as u32) << shift;
+                framebuffer.buffer[index] = channel(16) | channel(8) | channel(0);
+            }
+        }
+    }
+}
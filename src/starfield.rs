@@ -0,0 +1,137 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::framebuffer::Framebuffer;
+use crate::memory::vec_bytes;
+use crate::scene::StarsConfig;
+
+/// Clase espectral de Harvard, de la más caliente (azul) a la más fría (roja)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpectralClass {
+    O,
+    B,
+    A,
+    F,
+    G,
+    K,
+    M,
+}
+
+impl SpectralClass {
+    /// Clases con su frecuencia relativa entre las estrellas visibles a simple vista
+    const TABLE: [(SpectralClass, u32); 7] = [
+        (SpectralClass::O, 1),
+        (SpectralClass::B, 10),
+        (SpectralClass::A, 20),
+        (SpectralClass::F, 17),
+        (SpectralClass::G, 15),
+        (SpectralClass::K, 27),
+        (SpectralClass::M, 10),
+    ];
+
+    /// Color aparente de la clase a brillo máximo
+    pub fn color(self) -> (u32, u32, u32) {
+        match self {
+            SpectralClass::O => (155, 176, 255),
+            SpectralClass::B => (170, 191, 255),
+            SpectralClass::A => (202, 215, 255),
+            SpectralClass::F => (248, 247, 255),
+            SpectralClass::G => (255, 244, 234),
+            SpectralClass::K => (255, 210, 161),
+            SpectralClass::M => (255, 204, 111),
+        }
+    }
+
+    fn random<R: Rng>(rng: &mut R) -> Self {
+        let total: u32 = Self::TABLE.iter().map(|(_, weight)| weight).sum();
+        let mut pick = rng.random_range(0..total);
+        for (class, weight) in Self::TABLE {
+            if pick < weight {
+                return class;
+            }
+            pick -= weight;
+        }
+        SpectralClass::G
+    }
+}
+
+/// Estrella del fondo en coordenadas de pantalla
+#[derive(Debug, Clone)]
+pub struct Star {
+    pub x: usize,
+    pub y: usize,
+    pub class: SpectralClass,
+    /// Magnitud aparente: menor es más brillante
+    pub magnitude: f32,
+}
+
+impl Star {
+    /// Brillo 0..1: cada magnitud es un factor 2.512; las de magnitud 1 o menos saturan
+    fn brightness(&self) -> f32 {
+        10f32.powf(-0.4 * (self.magnitude - 1.0)).clamp(0.25, 1.0)
+    }
+
+    /// Radio en píxeles del destello en cruz
+    fn size(&self) -> i32 {
+        if self.magnitude < 1.0 {
+            2
+        } else if self.magnitude < 2.5 {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn color(&self, scale: f32) -> u32 {
+        let (r, g, b) = self.class.color();
+        let brightness = self.brightness() * scale;
+        let channel = |value: u32| (value as f32 * brightness) as u32;
+        (channel(r) << 16) | (channel(g) << 8) | channel(b)
+    }
+}
+
+/// Campo de estrellas fijo en pantalla. Cualquier fuente de estrellas (la generación al azar
+/// o un catálogo) produce una lista de `Star` y se dibuja igual.
+pub struct StarField {
+    stars: Vec<Star>,
+}
+
+impl StarField {
+    /// `config.density` estrellas por cada 10 000 píxeles. Las magnitudes siguen la ley de
+    /// conteo N(< m) ∝ 10^(m / 2): hay muchas más estrellas débiles que brillantes.
+    pub fn random(width: usize, height: usize, config: &StarsConfig) -> Self {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let count = (width * height) as f32 / 10_000.0 * config.density.max(0.0);
+        let stars = (0..count as usize)
+            .map(|_| {
+                let u: f32 = rng.random_range(1e-4..1.0);
+                Star {
+                    x: rng.random_range(0..width),
+                    y: rng.random_range(0..height),
+                    class: SpectralClass::random(&mut rng),
+                    magnitude: (config.faintest_magnitude + 2.0 * u.log10()).max(-1.0),
+                }
+            })
+            .collect();
+        StarField { stars }
+    }
+
+    /// Dibuja el fondo sin tocar el z-buffer: debe llamarse justo después de `clear`
+    pub fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.stars)
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer) {
+        for star in &self.stars {
+            let (x, y) = (star.x as i32, star.y as i32);
+            framebuffer.overlay_pixel(x, y, star.color(1.0));
+
+            let glow = star.color(0.5);
+            for arm in 1..=star.size() {
+                framebuffer.overlay_pixel(x - arm, y, glow);
+                framebuffer.overlay_pixel(x + arm, y, glow);
+                framebuffer.overlay_pixel(x, y - arm, glow);
+                framebuffer.overlay_pixel(x, y + arm, glow);
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use serde::Serialize;
+
+/// Prefijo de las líneas de estado, para separarlas del resto de la salida
+const PREFIX: &str = "@estado ";
+
+/// Cambio de estado importante
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StatusEvent<'a> {
+    /// `None` al quitar la selección
+    Selected { body: Option<&'a str> },
+    Warp { body: &'a str, distance: f32 },
+    /// La nave ha chocado con el cuerpo (se queda frenada en su superficie)
+    Collision { body: &'a str },
+    Orbit { body: Option<&'a str> },
+    Conjunction { first: &'a str, second: &'a str, vantage: &'a str, degrees: f32 },
+}
+
+#[derive(Serialize)]
+struct StatusLine<'a> {
+    /// Tiempo simulado en días
+    day: f32,
+    #[serde(flatten)]
+    event: StatusEvent<'a>,
+}
+
+/// Salida de estado para lectores de pantalla y overlays (`--status-output`): cada cambio
+/// importante se escribe en stdout como una línea `@estado {json}`, p. ej.
+/// `@estado {"day":12.5,"event":"selected","body":"Terra"}`
+pub struct StatusOutput {
+    enabled: bool,
+}
+
+impl StatusOutput {
+    pub fn new(enabled: bool) -> Self {
+        StatusOutput { enabled }
+    }
+
+    pub fn emit(&self, day: f32, event: StatusEvent) {
+        if !self.enabled {
+            return;
+        }
+        match serde_json::to_string(&StatusLine { day, event }) {
+            Ok(json) => println!("{}{}", PREFIX, json),
+            Err(err) => eprintln!("No se pudo escribir el estado: {}", err),
+        }
+    }
+}
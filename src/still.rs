@@ -0,0 +1,41 @@
+use crate::framebuffer::Framebuffer;
+
+/// Reduce `samples`, dibujado a `factor` veces la resolución final en cada eje, a la media de
+/// cada bloque de `factor × factor`. Las estrellas van aparte en `stars`, a la resolución final:
+/// promediadas con el supersampling se quedarían en un punto apagado. Solo se ponen donde el
+/// bloque entero es fondo (sin profundidad), con el máximo por canal para no tapar los brillos
+/// aditivos como las protuberancias.
+pub fn resolve(samples: &Framebuffer, factor: usize, stars: &Framebuffer) -> Framebuffer {
+    let (width, height) = (samples.width / factor, samples.height / factor);
+    let mut output = Framebuffer::new(width, height);
+    let count = (factor * factor) as u32;
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sums = [0u32; 3];
+            let mut background = true;
+            for sy in y * factor..(y + 1) * factor {
+                for sx in x * factor..(x + 1) * factor {
+                    let index = sy * samples.width + sx;
+                    let pixel = samples.buffer[index];
+                    sums[0] += (pixel >> 16) & 0xFF;
+                    sums[1] += (pixel >> 8) & 0xFF;
+                    sums[2] += pixel & 0xFF;
+                    background &= samples.zbuffer[index] == f32::INFINITY;
+                }
+            }
+
+            let mut color = 0;
+            for (channel, sum) in sums.iter().enumerate() {
+                let shift = 16 - channel * 8;
+                let mut value = (sum + count / 2) / count;
+                if background {
+                    value = value.max((stars.buffer[y * width + x] >> shift) & 0xFF);
+                }
+                color |= value << shift;
+            }
+            output.buffer[y * width + x] = color;
+        }
+    }
+    output
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use raylib::math::{Vector2, Vector3};
+use crate::mesh::MeshError;
+use crate::obj::Obj;
+use crate::vertex::Vertex;
+
+/// Cabecera (80 bytes) y número de triángulos (u32) de un STL binario
+const BINARY_HEADER: usize = 84;
+/// Normal, tres vértices (12 f32) y atributo (u16) por triángulo
+const BINARY_TRIANGLE: usize = 50;
+
+/// Carga un STL binario o ASCII. Las normales del archivo se ignoran (a menudo vienen a cero o
+/// mal orientadas) y se generan después a partir de la geometría, como con cualquier malla sin
+/// normales. STL no tiene coordenadas de textura.
+pub fn load(path: &str) -> Result<Obj, MeshError> {
+    let data = fs::read(path)?;
+    // Un STL binario también puede empezar por "solid": se decide por el tamaño declarado
+    let positions = if is_binary(&data) {
+        parse_binary(&data)
+    } else {
+        parse_ascii(&String::from_utf8_lossy(&data))?
+    };
+
+    let vertices: Vec<Vertex> = positions
+        .iter()
+        .map(|&[x, y, z]| Vertex::new(Vector3::new(x, -y, z), Vector3::zero(), Vector2::zero()))
+        .collect();
+    let count = vertices.len();
+    Ok(Obj {
+        indices: (0..count as u32).collect(),
+        vertices,
+        diffuse_texture: None,
+        normal_texture: None,
+        diffuse_color: None,
+        shininess: None,
+        vertices_without_normals: count,
+        vertices_without_uvs: count,
+    })
+}
+
+fn is_binary(data: &[u8]) -> bool {
+    if data.len() < BINARY_HEADER {
+        return false;
+    }
+    let triangles = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+    data.len() == BINARY_HEADER + triangles * BINARY_TRIANGLE
+}
+
+fn parse_binary(data: &[u8]) -> Vec<[f32; 3]> {
+    let mut positions = Vec::new();
+    for triangle in data[BINARY_HEADER..].chunks_exact(BINARY_TRIANGLE) {
+        let read = |offset: usize| f32::from_le_bytes(triangle[offset..offset + 4].try_into().unwrap());
+        // Los primeros 12 bytes son la normal
+        for corner in 0..3 {
+            let offset = 12 + corner * 12;
+            positions.push([read(offset), read(offset + 4), read(offset + 8)]);
+        }
+    }
+    positions
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<[f32; 3]>, MeshError> {
+    let mut positions = Vec::new();
+    for line in text.lines() {
+        let mut words = line.split_whitespace();
+        if words.next() != Some("vertex") {
+            continue;
+        }
+        let mut coordinate = || -> Result<f32, MeshError> {
+            let word = words.next().ok_or_else(|| MeshError::Format(format!("vértice incompleto: {}", line.trim())))?;
+            word.parse().map_err(|_| MeshError::Format(format!("coordenada inválida: {}", word)))
+        };
+        positions.push([coordinate()?, coordinate()?, coordinate()?]);
+    }
+    if positions.is_empty() || positions.len() % 3 != 0 {
+        return Err(MeshError::Format(format!("STL sin triángulos completos ({} vértices)", positions.len())));
+    }
+    Ok(positions)
+}
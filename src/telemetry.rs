@@ -0,0 +1,146 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryFormat {
+    JsonLines,
+    Csv,
+}
+
+impl TelemetryFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "jsonl" | "json" => Ok(TelemetryFormat::JsonLines),
+            "csv" => Ok(TelemetryFormat::Csv),
+            other => Err(format!("formato de telemetría desconocido: {}", other)),
+        }
+    }
+
+    /// Formato por defecto según el destino (extensión .csv => CSV)
+    pub fn for_destination(destination: &str) -> Self {
+        if destination.to_ascii_lowercase().ends_with(".csv") {
+            TelemetryFormat::Csv
+        } else {
+            TelemetryFormat::JsonLines
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BodySample<'a> {
+    pub name: &'a str,
+    pub position: [f32; 3],
+    pub orbit_angle: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TelemetryFrame<'a> {
+    pub frame: u64,
+    pub sim_time: f32,
+    pub time_scale: f32,
+    pub frame_ms: f32,
+    pub render_ms: f32,
+    pub ship_position: [f32; 3],
+    pub ship_velocity: [f32; 3],
+    pub bodies: Vec<BodySample<'a>>,
+}
+
+/// Cada cuánto se vacía el búfer: a cada frame serían demasiadas escrituras pequeñas. Lo que
+/// quede se escribe al cerrar (el `BufWriter` se vacía al soltarlo).
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Escribe un registro por frame a un archivo o a un socket TCP (`tcp://host:puerto`)
+pub struct TelemetryWriter {
+    out: BufWriter<Box<dyn Write>>,
+    format: TelemetryFormat,
+    /// Cuerpos de la última cabecera CSV: si la escena cambia, las columnas también
+    header_bodies: Option<Vec<String>>,
+    last_flush: Instant,
+}
+
+impl TelemetryWriter {
+    pub fn open(destination: &str, format: TelemetryFormat) -> io::Result<Self> {
+        let sink: Box<dyn Write> = match destination.strip_prefix("tcp://") {
+            Some(address) => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_nodelay(true)?;
+                Box::new(stream)
+            }
+            None => Box::new(File::create(destination)?),
+        };
+
+        Ok(TelemetryWriter {
+            out: BufWriter::new(sink),
+            format,
+            header_bodies: None,
+            last_flush: Instant::now(),
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &TelemetryFrame) -> io::Result<()> {
+        match self.format {
+            TelemetryFormat::JsonLines => {
+                serde_json::to_writer(&mut self.out, frame)?;
+                writeln!(self.out)?;
+            }
+            TelemetryFormat::Csv => {
+                // Tras recargar o cambiar de escena las columnas de los cuerpos ya no son las de
+                // la cabecera: se escribe otra antes de la primera fila con los nuevos
+                let same_bodies = self.header_bodies.as_ref().is_some_and(|names| {
+                    names.len() == frame.bodies.len() && names.iter().zip(&frame.bodies).all(|(name, body)| name == body.name)
+                });
+                if !same_bodies {
+                    self.write_csv_header(frame)?;
+                    self.header_bodies = Some(frame.bodies.iter().map(|body| body.name.to_string()).collect());
+                }
+                self.write_csv_row(frame)?;
+            }
+        }
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.last_flush = Instant::now();
+            self.out.flush()?;
+        }
+        Ok(())
+    }
+
+    fn write_csv_header(&mut self, frame: &TelemetryFrame) -> io::Result<()> {
+        write!(
+            self.out,
+            "frame,sim_time,time_scale,frame_ms,render_ms,ship_x,ship_y,ship_z,ship_vx,ship_vy,ship_vz"
+        )?;
+        for body in &frame.bodies {
+            for suffix in ["_x", "_y", "_z", "_angle"] {
+                write!(self.out, ",{}", csv_field(&format!("{}{}", body.name, suffix)))?;
+            }
+        }
+        writeln!(self.out)
+    }
+
+    fn write_csv_row(&mut self, frame: &TelemetryFrame) -> io::Result<()> {
+        let [px, py, pz] = frame.ship_position;
+        let [vx, vy, vz] = frame.ship_velocity;
+        write!(
+            self.out,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            frame.frame, frame.sim_time, frame.time_scale, frame.frame_ms, frame.render_ms,
+            px, py, pz, vx, vy, vz
+        )?;
+        for body in &frame.bodies {
+            let [x, y, z] = body.position;
+            write!(self.out, ",{},{},{},{}", x, y, z, body.orbit_angle)?;
+        }
+        writeln!(self.out)
+    }
+}
+
+/// Campo de CSV: entre comillas (y con las comillas dobladas) si lleva comas, comillas o saltos
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
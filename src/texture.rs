@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use raylib::math::{Vector2, Vector3};
+
+/// Un nivel de la cadena de mipmaps
+struct MipLevel {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vector3>,
+}
+
+impl MipLevel {
+    /// Texel más cercano; las coordenadas se repiten fuera de [0, 1] y `v` crece hacia arriba
+    fn sample(&self, u: f32, v: f32) -> Vector3 {
+        let u = u.rem_euclid(1.0);
+        let v = 1.0 - v.rem_euclid(1.0);
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+        self.pixels[y * self.width + x]
+    }
+
+    /// Mitad de tamaño (redondeando hacia arriba), promediando bloques de 2×2
+    fn downsample(&self) -> MipLevel {
+        let width = self.width.div_ceil(2);
+        let height = self.height.div_ceil(2);
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = Vector3::zero();
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (2 * x + dx).min(self.width - 1);
+                    let sy = (2 * y + dy).min(self.height - 1);
+                    sum += self.pixels[sy * self.width + sx];
+                }
+                pixels.push(sum / 4.0);
+            }
+        }
+        MipLevel { width, height, pixels }
+    }
+}
+
+/// Textura RGB en memoria, con colores en 0..1 y su cadena de mipmaps hasta 1×1
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    /// El nivel 0 es la imagen original
+    levels: Vec<MipLevel>,
+}
+
+impl Texture {
+    /// Carga un PNG de 8 bits (gris, gris + alfa, RGB, RGBA o paleta); el alfa se descarta
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data)?;
+
+        let channels = info.color_type.samples();
+        let pixels = data[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let (r, g, b) = if channels >= 3 {
+                    (pixel[0], pixel[1], pixel[2])
+                } else {
+                    (pixel[0], pixel[0], pixel[0])
+                };
+                Vector3::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+            })
+            .collect();
+
+        Ok(Texture::from_pixels(info.width as usize, info.height as usize, pixels))
+    }
+
+    /// Textura a partir de texeles ya calculados; genera los mipmaps
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Vector3>) -> Self {
+        assert_eq!(pixels.len(), width * height, "la textura necesita ancho × alto texeles");
+        let mut levels = vec![MipLevel { width, height, pixels }];
+        while let Some(last) = levels.last().filter(|level| level.width > 1 || level.height > 1) {
+            let next = last.downsample();
+            levels.push(next);
+        }
+        Texture { width, height, levels }
+    }
+
+    /// Memoria que ocupan los texeles, mipmaps incluidos
+    pub fn byte_size(&self) -> usize {
+        self.levels.iter().map(|level| level.pixels.len()).sum::<usize>() * std::mem::size_of::<Vector3>()
+    }
+
+    /// Muestra el texel más cercano del nivel 0; las coordenadas se repiten fuera de [0, 1] y
+    /// `v` crece hacia arriba como en OBJ
+    pub fn sample(&self, u: f32, v: f32) -> Vector3 {
+        self.levels[0].sample(u, v)
+    }
+
+    /// Muestra con el nivel de mipmap que corresponde a la huella del fragmento en la textura:
+    /// `ddx` y `ddy` son cuánto cambian las coordenadas (u, v) de un píxel al siguiente.
+    /// Mezcla los dos niveles más cercanos para que no se note el salto entre ellos.
+    pub fn sample_grad(&self, u: f32, v: f32, ddx: Vector2, ddy: Vector2) -> Vector3 {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let footprint_x = (ddx.x * width).hypot(ddx.y * height);
+        let footprint_y = (ddy.x * width).hypot(ddy.y * height);
+        let lod = footprint_x.max(footprint_y).max(1e-6).log2();
+        let max_level = (self.levels.len() - 1) as f32;
+        if lod <= 0.0 {
+            return self.sample(u, v);
+        }
+        if lod >= max_level {
+            return self.levels[self.levels.len() - 1].sample(u, v);
+        }
+
+        let level = lod.floor() as usize;
+        let blend = lod - level as f32;
+        let fine = self.levels[level].sample(u, v);
+        let coarse = self.levels[level + 1].sample(u, v);
+        fine + (coarse - fine) * blend
+    }
+}
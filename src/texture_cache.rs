@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use crate::texture::Texture;
+
+/// Gestor de texturas: evita cargar dos veces el mismo archivo y lleva la cuenta de la memoria
+/// que ocupan, para compararla con el presupuesto en el inspector
+pub struct TextureCache {
+    budget: usize,
+    used: usize,
+    files: HashMap<PathBuf, Rc<Texture>>,
+}
+
+impl TextureCache {
+    /// `budget` en bytes
+    pub fn new(budget: usize) -> Self {
+        TextureCache { budget, used: 0, files: HashMap::new() }
+    }
+
+    pub fn memory_used(&self) -> usize {
+        self.used
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Textura de `path`, cargándola solo la primera vez
+    pub fn load(&mut self, path: &Path) -> std::io::Result<Rc<Texture>> {
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if let Some(texture) = self.files.get(&key) {
+            return Ok(Rc::clone(texture));
+        }
+
+        let texture = Rc::new(Texture::load(path)?);
+        self.used += texture.byte_size();
+        self.files.insert(key, Rc::clone(&texture));
+        Ok(texture)
+    }
+}
@@ -0,0 +1,125 @@
+use std::ops::ControlFlow;
+use crate::depth::DepthMode;
+use crate::fragment::Fragment;
+use crate::light::Light;
+use crate::triangle::{triangle_depths_in, triangle_each_in, PixelRect};
+use crate::vertex::Vertex;
+
+// Rasterización por tiles: la pantalla se parte en cuadrados de `TILE_SIZE` píxeles, cada
+// triángulo se apunta en los tiles que toca su caja y cada tile se rasteriza por separado (en
+// paralelo, sin compartir nada más que la lectura del z-buffer). Dentro del tile hay primero una
+// pasada solo de profundidad y después otra que entrega únicamente el fragmento visible de cada
+// píxel, así que se sombrea una vez por píxel como mucho: el coste del frame depende de la
+// resolución y no de cuánta geometría se solape.
+
+/// Lado de cada tile en píxeles
+pub const TILE_SIZE: usize = 32;
+
+/// Un triángulo apuntado en un tile: el dibujo al que pertenece y sus tres vértices en pantalla
+#[derive(Clone, Copy)]
+pub struct TileTriangle<'a> {
+    pub draw: usize,
+    pub vertices: &'a [Vertex],
+    pub depth_mode: DepthMode,
+}
+
+/// Rejilla de tiles que cubre un framebuffer de `width × height`; los de la última fila y
+/// columna pueden quedar más pequeños
+pub struct TileGrid {
+    width: usize,
+    height: usize,
+    columns: usize,
+    rows: usize,
+}
+
+impl TileGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        TileGrid { width, height, columns: width.div_ceil(TILE_SIZE), rows: height.div_ceil(TILE_SIZE) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Píxeles del tile, recortados al framebuffer
+    pub fn rect(&self, tile: usize) -> PixelRect {
+        let (x, y) = ((tile % self.columns) * TILE_SIZE, (tile / self.columns) * TILE_SIZE);
+        (x as i32, y as i32, (x + TILE_SIZE).min(self.width) as i32, (y + TILE_SIZE).min(self.height) as i32)
+    }
+
+    /// Apunta cada triángulo de cada dibujo (vértices en pantalla de 3 en 3, como los da
+    /// `transform_triangles`) en los tiles que toca su caja. En cada tile quedan en el orden de
+    /// los dibujos, que es el que decide los empates de profundidad.
+    pub fn bin<'a, I: IntoIterator<Item = (&'a [Vertex], DepthMode)>>(&self, draws: I) -> Vec<Vec<TileTriangle<'a>>> {
+        let mut bins = vec![Vec::new(); self.len()];
+        if self.is_empty() {
+            return bins;
+        }
+        for (draw, (vertices, depth_mode)) in draws.into_iter().enumerate() {
+            for triangle in vertices.chunks_exact(3) {
+                let xs = triangle.iter().map(|vertex| vertex.transformed_position.x);
+                let ys = triangle.iter().map(|vertex| vertex.transformed_position.y);
+                let (min_x, max_x) = xs.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| (min.min(x), max.max(x)));
+                let (min_y, max_y) = ys.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), y| (min.min(y), max.max(y)));
+                if !(max_x >= 0.0 && max_y >= 0.0 && min_x < self.width as f32 && min_y < self.height as f32) {
+                    continue;
+                }
+                let column = |x: f32| (x.max(0.0) as usize / TILE_SIZE).min(self.columns - 1);
+                let row = |y: f32| (y.max(0.0) as usize / TILE_SIZE).min(self.rows - 1);
+                for tile_row in row(min_y)..=row(max_y) {
+                    for tile_column in column(min_x)..=column(max_x) {
+                        bins[tile_row * self.columns + tile_column].push(TileTriangle { draw, vertices: triangle, depth_mode });
+                    }
+                }
+            }
+        }
+        bins
+    }
+}
+
+/// Rasteriza los triángulos de un tile. `depth` trae la profundidad que ya hay en los píxeles de
+/// `rect` (por filas) y sale con la final; `emit` recibe, para cada píxel en el que algún triángulo
+/// queda por delante, el dibujo y el fragmento visible: el más cercano y, si empatan, el del
+/// primer dibujo, igual que con la prueba de profundidad estricta del framebuffer.
+pub fn rasterize_tile<F: FnMut(usize, Fragment)>(rect: PixelRect, triangles: &[TileTriangle], depth: &mut [f32], light: &Light, mut emit: F) {
+    let (x0, y0, x1, _) = rect;
+    let stride = (x1 - x0) as usize;
+    let index = |x: i32, y: i32| (y - y0) as usize * stride + (x - x0) as usize;
+    let resolve = |depth_mode: DepthMode, depth: f32| {
+        if depth_mode != DepthMode::Perspective {
+            depth_mode.resolve(depth)
+        } else {
+            depth
+        }
+    };
+
+    let initial = depth.to_vec();
+    for triangle in triangles {
+        let [a, b, c] = [&triangle.vertices[0], &triangle.vertices[1], &triangle.vertices[2]];
+        triangle_depths_in(a, b, c, rect, |x, y, fragment_depth| {
+            let fragment_depth = resolve(triangle.depth_mode, fragment_depth);
+            let pixel = &mut depth[index(x, y)];
+            if fragment_depth < *pixel {
+                *pixel = fragment_depth;
+            }
+        });
+    }
+
+    let mut done = vec![false; depth.len()];
+    for triangle in triangles {
+        let [a, b, c] = [&triangle.vertices[0], &triangle.vertices[1], &triangle.vertices[2]];
+        let _ = triangle_each_in(a, b, c, light, rect, |mut fragment| {
+            fragment.depth = resolve(triangle.depth_mode, fragment.depth);
+            let pixel = index(fragment.position.x as i32, fragment.position.y as i32);
+            if !done[pixel] && fragment.depth == depth[pixel] && fragment.depth < initial[pixel] {
+                done[pixel] = true;
+                emit(triangle.draw, fragment);
+            }
+            ControlFlow::Continue(())
+        });
+    }
+}
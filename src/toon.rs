@@ -0,0 +1,74 @@
+use nalgebra_glm::Mat4;
+use raylib::prelude::Vector3;
+use crate::depth::DepthMode;
+use crate::framebuffer::Framebuffer;
+use crate::pack_color;
+
+/// Niveles de luz del estilo cómic
+const BANDS: f32 = 3.0;
+/// Luz máxima que trae un fragmento del rasterizador (el gris base de `triangle`)
+const FULL_LIGHT: f32 = 0.5;
+const OUTLINE_COLOR: u32 = 0x05050A;
+/// Salto relativo de la profundidad lineal hacia un vecino a partir del que hay silueta
+const SILHOUETTE_JUMP: f32 = 0.04;
+/// Cambio relativo de la pendiente de la profundidad a partir del que hay arista
+const CREASE_BEND: f32 = 0.015;
+
+/// Luz difusa del fragmento (`Fragment::color`) redondeada hacia arriba a `BANDS` niveles; lo
+/// que no recibe luz sigue a oscuras
+pub fn quantize_light(color: Vector3) -> Vector3 {
+    let intensity = color.x.max(color.y).max(color.z);
+    if intensity <= 0.0 {
+        return color;
+    }
+    let level = (intensity / FULL_LIGHT * BANDS).ceil().min(BANDS) / BANDS * FULL_LIGHT;
+    color * (level / intensity)
+}
+
+/// Color plano y vivo para la órbita de un cuerpo, a partir del color principal de su paleta
+pub fn orbit_color(color: Vector3) -> u32 {
+    let brightest = color.x.max(color.y).max(color.z).max(1e-3);
+    pack_color(color * (0.85 / brightest))
+}
+
+/// Contornos oscuros sobre la geometría recién dibujada (los píxeles cuya profundidad ya no es
+/// la de `depth_before`). Hay silueta donde la profundidad lineal salta hacia un vecino más lejano
+/// o hacia el fondo, y arista donde su pendiente cambia de golpe, que es donde gira la normal.
+/// Los puntos y líneas dibujados antes no se tocan: siendo tan finos, serían todo contorno.
+pub fn outline(framebuffer: &mut Framebuffer, depth_before: &[f32], projection: &Mat4, depth_mode: DepthMode) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let linear: Vec<f32> = framebuffer
+        .zbuffer
+        .iter()
+        .map(|&z| if z.is_finite() { depth_mode.linear(projection, z) } else { f32::INFINITY })
+        .collect();
+
+    let mut edges = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            let depth = linear[index];
+            if framebuffer.zbuffer[index] == depth_before[index] || !depth.is_finite() || depth <= 0.0 {
+                continue;
+            }
+            let neighbours = [
+                (x > 0).then(|| index - 1),
+                (x + 1 < width).then(|| index + 1),
+                (y > 0).then(|| index - width),
+                (y + 1 < height).then(|| index + width),
+            ];
+            let silhouette = neighbours.iter().flatten().any(|&neighbour| linear[neighbour] > depth * (1.0 + SILHOUETTE_JUMP));
+            let bend = |before: Option<usize>, after: Option<usize>| match (before, after) {
+                (Some(before), Some(after)) => (linear[before] + linear[after] - 2.0 * depth).abs() / depth,
+                _ => 0.0,
+            };
+            let crease = bend(neighbours[0], neighbours[1]).max(bend(neighbours[2], neighbours[3])) > CREASE_BEND;
+            if silhouette || crease {
+                edges.push(index);
+            }
+        }
+    }
+    for index in edges {
+        framebuffer.buffer[index] = OUTLINE_COLOR;
+    }
+}
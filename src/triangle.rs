@@ -1,11 +1,12 @@
+use std::ops::{Add, ControlFlow, Div, Mul, Sub};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::light::Light;
-use raylib::prelude::Vector3;
+use raylib::prelude::{Vector2, Vector3, Vector4};
 
 /// Optimized barycentric coordinates with early exit
 #[inline(always)]
-fn barycentric_coordinates(p_x: f32, p_y: f32, a: &Vertex, b: &Vertex, c: &Vertex) -> Option<(f32, f32, f32)> {
+pub fn barycentric_coordinates(p_x: f32, p_y: f32, a: &Vertex, b: &Vertex, c: &Vertex) -> Option<(f32, f32, f32)> {
     let a_x = a.transformed_position.x;
     let a_y = a.transformed_position.y;
     let b_x = b.transformed_position.x;
@@ -41,39 +42,230 @@ fn barycentric_coordinates(p_x: f32, p_y: f32, a: &Vertex, b: &Vertex, c: &Verte
     Some((w1, w2, w3))
 }
 
+/// Screen-space derivatives of the barycentric weights along x and y. The weights are affine in
+/// screen space, so they are constant across the whole triangle.
+fn weight_derivatives(a: &Vertex, b: &Vertex, c: &Vertex) -> Option<([f32; 3], [f32; 3])> {
+    let (a_x, a_y) = (a.transformed_position.x, a.transformed_position.y);
+    let (b_x, b_y) = (b.transformed_position.x, b.transformed_position.y);
+    let (c_x, c_y) = (c.transformed_position.x, c.transformed_position.y);
+
+    let denom = (b_y - c_y) * (a_x - c_x) + (c_x - b_x) * (a_y - c_y);
+    if denom.abs() < 1e-10 {
+        return None;
+    }
+
+    let (w1_dx, w1_dy) = ((b_y - c_y) / denom, (c_x - b_x) / denom);
+    let (w2_dx, w2_dy) = ((c_y - a_y) / denom, (a_x - c_x) / denom);
+    let (w3_dx, w3_dy) = (-w1_dx - w2_dx, -w1_dy - w2_dy);
+    Some(([w1_dx, w2_dx, w3_dx], [w1_dy, w2_dy, w3_dy]))
+}
+
+/// Screen-space derivatives (ddx, ddy) of the interpolated position
+fn position_derivatives(a: &Vertex, b: &Vertex, c: &Vertex) -> (Vector3, Vector3) {
+    let Some((dx, dy)) = weight_derivatives(a, b, c) else {
+        return (Vector3::zero(), Vector3::zero());
+    };
+    let derivative = |[w1, w2, w3]: [f32; 3]| {
+        Vector3::new(
+            w1 * a.position.x + w2 * b.position.x + w3 * c.position.x,
+            w1 * a.position.y + w2 * b.position.y + w3 * c.position.y,
+            w1 * a.position.z + w2 * b.position.z + w3 * c.position.z,
+        )
+    };
+    (derivative(dx), derivative(dy))
+}
+
+/// Screen-space derivatives (ddx, ddy) of the texture coordinates, used to pick the mip level
+fn tex_coords_derivatives(a: &Vertex, b: &Vertex, c: &Vertex) -> (Vector2, Vector2) {
+    let Some((dx, dy)) = weight_derivatives(a, b, c) else {
+        return (Vector2::zero(), Vector2::zero());
+    };
+    let derivative = |[w1, w2, w3]: [f32; 3]| {
+        Vector2::new(
+            w1 * a.tex_coords.x + w2 * b.tex_coords.x + w3 * c.tex_coords.x,
+            w1 * a.tex_coords.y + w2 * b.tex_coords.y + w3 * c.tex_coords.y,
+        )
+    };
+    (derivative(dx), derivative(dy))
+}
+
 /// Scanline rasterization - MUCH faster than pixel-by-pixel
 pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, light: &Light) -> Vec<Fragment> {
     let mut fragments = Vec::with_capacity(100); // Pre-allocate
+    let _ = triangle_each(v1, v2, v3, light, |fragment| {
+        fragments.push(fragment);
+        ControlFlow::Continue(())
+    });
+    fragments
+}
+
+/// Same as `triangle`, but hands each fragment to `emit` as soon as it is produced instead of
+/// collecting them, so callers can shade in place. Stops as soon as `emit` breaks.
+pub fn triangle_each<F: FnMut(Fragment) -> ControlFlow<()>>(
+    v1: &Vertex,
+    v2: &Vertex,
+    v3: &Vertex,
+    light: &Light,
+    emit: F,
+) -> ControlFlow<()> {
+    triangle_each_in(v1, v2, v3, light, FULL_RECT, emit)
+}
+
+/// Pixel rectangle (x0, y0, x1, y1), x1 and y1 excluded, that rasterization is limited to
+pub type PixelRect = (i32, i32, i32, i32);
+
+/// No limit other than the triangle itself
+pub const FULL_RECT: PixelRect = (i32::MIN, i32::MIN, i32::MAX, i32::MAX);
+
+/// A run of adjacent pixels of one scanline that are evaluated together: 4 SIMD lanes with the
+/// `simd` feature, a single pixel without it. Both paths run the same operations in the same
+/// order, so a pixel gets the same bits either way.
+#[cfg(feature = "simd")]
+type Pixels = wide::f32x4;
+#[cfg(not(feature = "simd"))]
+type Pixels = f32;
+
+const LANES: usize = std::mem::size_of::<Pixels>() / std::mem::size_of::<f32>();
+
+/// What the inner loop needs from `Pixels` beyond plain arithmetic (`sqrt` and `max` are
+/// inherent on both types, and `From<f32>` broadcasts)
+trait Lanes: Copy + From<f32> + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> {
+    /// 0, 1, 2... one per lane
+    fn offsets() -> Self;
+    /// `1 / self` where `self` is positive, 1 elsewhere (so scaling by it leaves zero vectors alone)
+    fn recip_positive(self) -> Self;
+    /// Bit mask of the lanes whose weights are all inside the triangle
+    fn coverage(w1: Self, w2: Self, w3: Self) -> u32;
+    fn to_lanes(self) -> [f32; LANES];
+}
+
+#[cfg(not(feature = "simd"))]
+impl Lanes for f32 {
+    #[inline(always)]
+    fn offsets() -> Self {
+        0.0
+    }
+
+    #[inline(always)]
+    fn recip_positive(self) -> Self {
+        if self > 0.0 { 1.0 / self } else { 1.0 }
+    }
+
+    #[inline(always)]
+    fn coverage(w1: Self, w2: Self, w3: Self) -> u32 {
+        ((0.0..=1.0).contains(&w1) && (0.0..=1.0).contains(&w2) && w3 >= 0.0) as u32
+    }
+
+    #[inline(always)]
+    fn to_lanes(self) -> [f32; LANES] {
+        [self]
+    }
+}
+
+#[cfg(feature = "simd")]
+impl Lanes for wide::f32x4 {
+    #[inline(always)]
+    fn offsets() -> Self {
+        wide::f32x4::new([0.0, 1.0, 2.0, 3.0])
+    }
+
+    #[inline(always)]
+    fn recip_positive(self) -> Self {
+        use wide::CmpGt;
+        self.cmp_gt(Self::ZERO).blend(Self::ONE / self, Self::ONE)
+    }
+
+    #[inline(always)]
+    fn coverage(w1: Self, w2: Self, w3: Self) -> u32 {
+        use wide::{CmpGe, CmpLe};
+        let inside = w1.cmp_ge(Self::ZERO) & w1.cmp_le(Self::ONE) & w2.cmp_ge(Self::ZERO) & w2.cmp_le(Self::ONE) & w3.cmp_ge(Self::ZERO);
+        inside.move_mask() as u32
+    }
+
+    #[inline(always)]
+    fn to_lanes(self) -> [f32; LANES] {
+        self.to_array()
+    }
+}
+
+/// Indices of the set lanes of `mask`, lowest first
+fn covered_lanes(mut mask: u32) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let lane = mask.trailing_zeros() as usize;
+            mask &= mask - 1;
+            lane
+        })
+    })
+}
 
+/// The barycentric weights as edge functions of the pixel center. They are affine in screen
+/// space, so the y term is computed once per scanline and stepping along x only adds the x term.
+struct EdgeFunctions {
+    dx: [f32; 2],
+    dy: [f32; 2],
+    origin: (f32, f32),
+}
+
+impl EdgeFunctions {
+    fn new(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Option<Self> {
+        let (dx, dy) = weight_derivatives(v1, v2, v3)?;
+        Some(EdgeFunctions {
+            dx: [dx[0], dx[1]],
+            dy: [dy[0], dy[1]],
+            origin: (v3.transformed_position.x, v3.transformed_position.y),
+        })
+    }
+
+    /// y terms of (w1, w2) on the scanline through pixel centers at `y`
+    fn row(&self, y: f32) -> [f32; 2] {
+        let offset = y - self.origin.1;
+        [self.dy[0] * offset, self.dy[1] * offset]
+    }
+
+    /// Weights at pixel centers `x` of the scanline whose y terms are `row`
+    #[inline(always)]
+    fn weights(&self, row: [f32; 2], x: Pixels) -> (Pixels, Pixels, Pixels) {
+        let offset = x - Pixels::from(self.origin.0);
+        let w1 = Pixels::from(self.dx[0]) * offset + Pixels::from(row[0]);
+        let w2 = Pixels::from(self.dx[1]) * offset + Pixels::from(row[1]);
+        (w1, w2, Pixels::from(1.0) - w1 - w2)
+    }
+}
+
+/// Calls `visit` with the first pixel (x, y) of every horizontal span the triangle covers inside
+/// `rect`, `LANES` pixels at a time: the mask of the covered ones and their barycentric weights.
+/// Both windings are covered: back faces are dropped before rasterizing, when the triangles are
+/// transformed (see `Culling` in `pipeline`).
+fn scan_covered<F: FnMut(i32, i32, u32, (Pixels, Pixels, Pixels)) -> ControlFlow<()>>(
+    v1: &Vertex,
+    v2: &Vertex,
+    v3: &Vertex,
+    rect: PixelRect,
+    mut visit: F,
+) -> ControlFlow<()> {
     // Sort vertices by Y coordinate
     let mut verts = [v1, v2, v3];
     verts.sort_by(|a, b| a.transformed_position.y.partial_cmp(&b.transformed_position.y).unwrap());
     
-    let (top, mid, bottom) = (verts[0], verts[1], verts[2]);
+    let (top, bottom) = (verts[0], verts[2]);
 
-    // Quick backface culling check
-    let edge1_x = mid.transformed_position.x - top.transformed_position.x;
-    let edge1_y = mid.transformed_position.y - top.transformed_position.y;
-    let edge2_x = bottom.transformed_position.x - top.transformed_position.x;
-    let edge2_y = bottom.transformed_position.y - top.transformed_position.y;
-    
-    let cross = edge1_x * edge2_y - edge1_y * edge2_x;
-    if cross <= 0.0 {
-        return fragments; // Backface culled
-    }
-
-    let base_color = Vector3::new(0.5, 0.5, 0.5);
+    let Some(edges) = EdgeFunctions::new(v1, v2, v3) else {
+        return ControlFlow::Continue(()); // Degenerate
+    };
 
     // Get bounds
-    let min_y = top.transformed_position.y.floor() as i32;
-    let max_y = bottom.transformed_position.y.ceil() as i32;
+    let (rect_x0, rect_y0, rect_x1, rect_y1) = rect;
+    let min_y = (top.transformed_position.y.floor() as i32).max(rect_y0);
+    let max_y = (bottom.transformed_position.y.ceil() as i32).min(rect_y1.saturating_sub(1));
 
     // Scanline algorithm
     for y in min_y..=max_y {
         let y_f = y as f32 + 0.5;
 
         // Find X intersections for this scanline
-        let mut x_intersections = Vec::with_capacity(2);
+        let mut x_intersections = [0.0; 2];
+        let mut intersections = 0;
 
         // Check each edge
         for i in 0..3 {
@@ -89,84 +281,167 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, light: &Light) -> Vec<Fra
             }
 
             // Check if scanline intersects this edge
-            if (y_f >= y1 && y_f < y2) || (y_f >= y2 && y_f < y1) {
+            if ((y_f >= y1 && y_f < y2) || (y_f >= y2 && y_f < y1)) && intersections < 2 {
                 let t = (y_f - y1) / (y2 - y1);
-                let x = v_a.transformed_position.x + t * (v_b.transformed_position.x - v_a.transformed_position.x);
-                x_intersections.push(x);
+                x_intersections[intersections] = v_a.transformed_position.x + t * (v_b.transformed_position.x - v_a.transformed_position.x);
+                intersections += 1;
             }
         }
 
-        if x_intersections.len() < 2 {
+        if intersections < 2 {
             continue;
         }
 
-        let x_min = x_intersections[0].min(x_intersections[1]).floor() as i32;
-        let x_max = x_intersections[0].max(x_intersections[1]).ceil() as i32;
-
-        // Rasterize this scanline
-        for x in x_min..=x_max {
-            let p_x = x as f32 + 0.5;
-
-            if let Some((w1, w2, w3)) = barycentric_coordinates(p_x, y_f, v1, v2, v3) {
-                // Interpolate normal
-                let interpolated_normal = Vector3::new(
-                    w1 * v1.normal.x + w2 * v2.normal.x + w3 * v3.normal.x,
-                    w1 * v1.normal.y + w2 * v2.normal.y + w3 * v3.normal.y,
-                    w1 * v1.normal.z + w2 * v2.normal.z + w3 * v3.normal.z,
-                );
-
-                let normal_length = (interpolated_normal.x * interpolated_normal.x
-                                   + interpolated_normal.y * interpolated_normal.y
-                                   + interpolated_normal.z * interpolated_normal.z).sqrt();
-                
-                let normalized_normal = if normal_length > 0.0 {
-                    Vector3::new(
-                        interpolated_normal.x / normal_length,
-                        interpolated_normal.y / normal_length,
-                        interpolated_normal.z / normal_length,
-                    )
-                } else {
-                    interpolated_normal
-                };
-
-                // Interpolate world position
-                let world_pos = Vector3::new(
-                    w1 * v1.position.x + w2 * v2.position.x + w3 * v3.position.x,
-                    w1 * v1.position.y + w2 * v2.position.y + w3 * v3.position.y,
-                    w1 * v1.position.z + w2 * v2.position.z + w3 * v3.position.z,
-                );
-
-                // Light calculation
-                let light_dir_x = light.position.x - world_pos.x;
-                let light_dir_y = light.position.y - world_pos.y;
-                let light_dir_z = light.position.z - world_pos.z;
-                
-                let light_length = (light_dir_x * light_dir_x + light_dir_y * light_dir_y + light_dir_z * light_dir_z).sqrt();
-                
-                let (light_dir_norm_x, light_dir_norm_y, light_dir_norm_z) = if light_length > 0.0 {
-                    (light_dir_x / light_length, light_dir_y / light_length, light_dir_z / light_length)
-                } else {
-                    (0.0, 0.0, 0.0)
-                };
-
-                let intensity = (normalized_normal.x * light_dir_norm_x
-                               + normalized_normal.y * light_dir_norm_y
-                               + normalized_normal.z * light_dir_norm_z).max(0.0);
-
-                let shaded_color = Vector3::new(
-                    base_color.x * intensity,
-                    base_color.y * intensity,
-                    base_color.z * intensity,
-                );
-
-                let depth = w1 * v1.transformed_position.z
-                          + w2 * v2.transformed_position.z
-                          + w3 * v3.transformed_position.z;
-
-                fragments.push(Fragment::new_with_world_pos(p_x, y_f, shaded_color, depth, world_pos));
+        let x_min = (x_intersections[0].min(x_intersections[1]).floor() as i32).max(rect_x0);
+        let x_max = (x_intersections[0].max(x_intersections[1]).ceil() as i32).min(rect_x1.saturating_sub(1));
+
+        // Rasterize this scanline, LANES pixels per step; the last step masks off the lanes
+        // past the end of the span
+        let row = edges.row(y_f);
+        for x0 in (x_min..=x_max).step_by(LANES) {
+            let remaining = (x_max - x0 + 1).min(LANES as i32) as u32;
+            let x = Pixels::from(x0 as f32 + 0.5) + Pixels::offsets();
+            let (w1, w2, w3) = edges.weights(row, x);
+            let mask = Pixels::coverage(w1, w2, w3) & (u32::MAX >> (32 - remaining));
+            if mask != 0 {
+                visit(x0, y, mask, (w1, w2, w3))?;
             }
         }
     }
 
-    fragments
-}
\ No newline at end of file
+    ControlFlow::Continue(())
+}
+
+/// Depth of every covered pixel in `rect`, without building fragments. It is the same depth that
+/// `triangle_each_in` gives the fragment of that pixel, bit for bit.
+pub fn triangle_depths_in<F: FnMut(i32, i32, f32)>(v1: &Vertex, v2: &Vertex, v3: &Vertex, rect: PixelRect, mut emit: F) {
+    let _ = scan_covered(v1, v2, v3, rect, |x0, y, mask, (w1, w2, w3)| {
+        let depths = interpolated_depth(v1, v2, v3, w1, w2, w3).to_lanes();
+        for lane in covered_lanes(mask) {
+            emit(x0 + lane as i32, y, depths[lane]);
+        }
+        ControlFlow::Continue(())
+    });
+}
+
+#[inline(always)]
+fn interpolated_depth(v1: &Vertex, v2: &Vertex, v3: &Vertex, w1: Pixels, w2: Pixels, w3: Pixels) -> Pixels {
+    w1 * Pixels::from(v1.transformed_position.z)
+        + w2 * Pixels::from(v2.transformed_position.z)
+        + w3 * Pixels::from(v3.transformed_position.z)
+}
+
+/// Interpolated vertex attributes and lighting of a run of pixels (`Attributes<Pixels>`), or
+/// the same split per lane (`Attributes<[f32; LANES]>`)
+struct Attributes<T> {
+    depth: T,
+    normal: [T; 3],
+    world_pos: [T; 3],
+    intensity: T,
+    tex_coords: [T; 2],
+    tangent: [T; 3],
+}
+
+impl Attributes<Pixels> {
+    #[inline(always)]
+    fn interpolate(v1: &Vertex, v2: &Vertex, v3: &Vertex, light: &Light, (w1, w2, w3): (Pixels, Pixels, Pixels)) -> Self {
+        let mix = |a: f32, b: f32, c: f32| w1 * Pixels::from(a) + w2 * Pixels::from(b) + w3 * Pixels::from(c);
+
+        // Interpolate normal
+        let normal = [
+            mix(v1.normal.x, v2.normal.x, v3.normal.x),
+            mix(v1.normal.y, v2.normal.y, v3.normal.y),
+            mix(v1.normal.z, v2.normal.z, v3.normal.z),
+        ];
+        let normal_scale = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt().recip_positive();
+        let normal = normal.map(|component| component * normal_scale);
+
+        // Interpolate world position
+        let world_pos = [
+            mix(v1.position.x, v2.position.x, v3.position.x),
+            mix(v1.position.y, v2.position.y, v3.position.y),
+            mix(v1.position.z, v2.position.z, v3.position.z),
+        ];
+
+        // Light calculation
+        let light_dir = [
+            Pixels::from(light.position.x) - world_pos[0],
+            Pixels::from(light.position.y) - world_pos[1],
+            Pixels::from(light.position.z) - world_pos[2],
+        ];
+        let light_scale = (light_dir[0] * light_dir[0] + light_dir[1] * light_dir[1] + light_dir[2] * light_dir[2]).sqrt().recip_positive();
+        let light_dir = light_dir.map(|component| component * light_scale);
+        let intensity = (normal[0] * light_dir[0] + normal[1] * light_dir[1] + normal[2] * light_dir[2]).max(Pixels::from(0.0));
+
+        Attributes {
+            depth: interpolated_depth(v1, v2, v3, w1, w2, w3),
+            normal,
+            world_pos,
+            intensity,
+            tex_coords: [
+                mix(v1.tex_coords.x, v2.tex_coords.x, v3.tex_coords.x),
+                mix(v1.tex_coords.y, v2.tex_coords.y, v3.tex_coords.y),
+            ],
+            tangent: [
+                mix(v1.tangent.x, v2.tangent.x, v3.tangent.x),
+                mix(v1.tangent.y, v2.tangent.y, v3.tangent.y),
+                mix(v1.tangent.z, v2.tangent.z, v3.tangent.z),
+            ],
+        }
+    }
+
+    fn to_lanes(&self) -> Attributes<[f32; LANES]> {
+        Attributes {
+            depth: self.depth.to_lanes(),
+            normal: self.normal.map(Lanes::to_lanes),
+            world_pos: self.world_pos.map(Lanes::to_lanes),
+            intensity: self.intensity.to_lanes(),
+            tex_coords: self.tex_coords.map(Lanes::to_lanes),
+            tangent: self.tangent.map(Lanes::to_lanes),
+        }
+    }
+}
+
+/// `triangle_each` limited to the pixels of `rect`
+pub fn triangle_each_in<F: FnMut(Fragment) -> ControlFlow<()>>(
+    v1: &Vertex,
+    v2: &Vertex,
+    v3: &Vertex,
+    light: &Light,
+    rect: PixelRect,
+    mut emit: F,
+) -> ControlFlow<()> {
+    let base_color = Vector3::new(0.5, 0.5, 0.5);
+    let (position_ddx, position_ddy) = position_derivatives(v1, v2, v3);
+    let (tex_coords_ddx, tex_coords_ddy) = tex_coords_derivatives(v1, v2, v3);
+
+    scan_covered(v1, v2, v3, rect, |x0, y, mask, weights| {
+        let attributes = Attributes::interpolate(v1, v2, v3, light, weights).to_lanes();
+        let y_f = y as f32 + 0.5;
+        for lane in covered_lanes(mask) {
+            let p_x = (x0 + lane as i32) as f32 + 0.5;
+            let intensity = attributes.intensity[lane];
+            let shaded_color = Vector3::new(
+                base_color.x * intensity,
+                base_color.y * intensity,
+                base_color.z * intensity,
+            );
+            let [x, y, z] = attributes.world_pos.map(|component| component[lane]);
+            let world_pos = Vector3::new(x, y, z);
+
+            let mut fragment = Fragment::new_with_world_pos(p_x, y_f, shaded_color, attributes.depth[lane], world_pos);
+            let [x, y, z] = attributes.normal.map(|component| component[lane]);
+            fragment.normal = Vector3::new(x, y, z);
+            let [u, v] = attributes.tex_coords.map(|component| component[lane]);
+            fragment.tex_coords = Vector2::new(u, v);
+            let [x, y, z] = attributes.tangent.map(|component| component[lane]);
+            fragment.tangent = Vector4::new(x, y, z, v1.tangent.w);
+            fragment.position_ddx = position_ddx;
+            fragment.position_ddy = position_ddy;
+            fragment.tex_coords_ddx = tex_coords_ddx;
+            fragment.tex_coords_ddy = tex_coords_ddy;
+            emit(fragment)?;
+        }
+        ControlFlow::Continue(())
+    })
+}
@@ -0,0 +1,120 @@
+use crate::memory::vec_bytes;
+
+/// Filtro usado al escalar el framebuffer interno a la ventana
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleFilter {
+    Nearest,
+    Bilinear,
+}
+
+impl ScaleFilter {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "nearest" => Ok(ScaleFilter::Nearest),
+            "bilinear" => Ok(ScaleFilter::Bilinear),
+            other => Err(format!("filtro desconocido: {} (nearest o bilinear)", other)),
+        }
+    }
+}
+
+/// Escala la imagen interna al tamaño de la ventana conservando la proporción;
+/// el espacio sobrante queda en negro. Con `integer` solo se usan factores enteros.
+pub struct Upscaler {
+    pub width: usize,
+    pub height: usize,
+    filter: ScaleFilter,
+    integer: bool,
+    buffer: Vec<u32>,
+}
+
+impl Upscaler {
+    pub fn new(width: usize, height: usize, filter: ScaleFilter, integer: bool) -> Self {
+        Upscaler {
+            width,
+            height,
+            filter,
+            integer,
+            buffer: vec![0; width * height],
+        }
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        vec_bytes(&self.buffer)
+    }
+
+    /// Rectángulo (x, y, ancho, alto) que ocupa la imagen dentro de la ventana
+    fn target_rect(&self, source_width: usize, source_height: usize) -> (usize, usize, usize, usize) {
+        let scale_x = self.width as f32 / source_width as f32;
+        let scale_y = self.height as f32 / source_height as f32;
+        let mut scale = scale_x.min(scale_y);
+        if self.integer && scale >= 1.0 {
+            scale = scale.floor();
+        }
+
+        let width = ((source_width as f32 * scale) as usize).clamp(1, self.width);
+        let height = ((source_height as f32 * scale) as usize).clamp(1, self.height);
+        ((self.width - width) / 2, (self.height - height) / 2, width, height)
+    }
+
+    /// Convierte una posición de la ventana a coordenadas de la imagen interna
+    pub fn to_source(&self, x: f32, y: f32, source_width: usize, source_height: usize) -> Option<(f32, f32)> {
+        let (x0, y0, width, height) = self.target_rect(source_width, source_height);
+        let u = (x - x0 as f32) / width as f32;
+        let v = (y - y0 as f32) / height as f32;
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return None;
+        }
+        Some((u * source_width as f32, v * source_height as f32))
+    }
+
+    pub fn present(&mut self, source: &[u32], source_width: usize, source_height: usize) -> &[u32] {
+        let (x0, y0, width, height) = self.target_rect(source_width, source_height);
+        self.buffer.fill(0);
+
+        let step_x = source_width as f32 / width as f32;
+        let step_y = source_height as f32 / height as f32;
+
+        for y in 0..height {
+            let row = (y0 + y) * self.width;
+            // Centro del píxel de destino en coordenadas de la imagen interna
+            let sy = (y as f32 + 0.5) * step_y - 0.5;
+            for x in 0..width {
+                let sx = (x as f32 + 0.5) * step_x - 0.5;
+                self.buffer[row + x0 + x] = match self.filter {
+                    ScaleFilter::Nearest => {
+                        let ix = (sx + 0.5).clamp(0.0, (source_width - 1) as f32) as usize;
+                        let iy = (sy + 0.5).clamp(0.0, (source_height - 1) as f32) as usize;
+                        source[iy * source_width + ix]
+                    }
+                    ScaleFilter::Bilinear => sample_bilinear(source, source_width, source_height, sx, sy),
+                };
+            }
+        }
+
+        &self.buffer
+    }
+}
+
+fn sample_bilinear(source: &[u32], width: usize, height: usize, x: f32, y: f32) -> u32 {
+    let x = x.clamp(0.0, (width - 1) as f32);
+    let y = y.clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+    let corners = [
+        (source[y0 * width + x0], (1.0 - fx) * (1.0 - fy)),
+        (source[y0 * width + x1], fx * (1.0 - fy)),
+        (source[y1 * width + x0], (1.0 - fx) * fy),
+        (source[y1 * width + x1], fx * fy),
+    ];
+
+    let channel = |shift: u32| {
+        let value: f32 = corners
+            .iter()
+            .map(|(pixel, weight)| ((pixel >> shift) & 0xFF) as f32 * weight)
+            .sum();
+        (value.round() as u32).min(0xFF) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
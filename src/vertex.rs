@@ -1,12 +1,16 @@
 #![allow(dead_code)]
 
-use raylib::math::{Vector2, Vector3};
+use std::collections::HashMap;
+use raylib::math::{Vector2, Vector3, Vector4};
 
 #[derive(Clone, Debug)]
 pub struct Vertex {
   pub position: Vector3,
   pub normal: Vector3,
   pub tex_coords: Vector2,
+  /// Tangente en el espacio de las normales (dirección de u creciente); w = ±1 es el sentido de
+  /// la bitangente, B = w · (N × T). Cero si la malla no tiene UV (ver `mesh::generate_tangents`)
+  pub tangent: Vector4,
   pub color: Vector3,
   pub transformed_position: Vector3,
   pub transformed_normal: Vector3,
@@ -18,6 +22,7 @@ impl Vertex {
       position,
       normal,
       tex_coords,
+      tangent: Vector4::new(0.0, 0.0, 0.0, 0.0),
       color: Vector3::new(0.0, 0.0, 0.0), // Black
       transformed_position: position,
       transformed_normal: normal,
@@ -29,6 +34,7 @@ impl Vertex {
       position,
       normal: Vector3::new(0.0, 0.0, 0.0),
       tex_coords: Vector2::new(0.0, 0.0),
+      tangent: Vector4::new(0.0, 0.0, 0.0, 0.0),
       color,
       transformed_position: Vector3::new(0.0, 0.0, 0.0),
       transformed_normal: Vector3::new(0.0, 0.0, 0.0),
@@ -47,9 +53,58 @@ impl Default for Vertex {
       position: Vector3::new(0.0, 0.0, 0.0),
       normal: Vector3::new(0.0, 1.0, 0.0),
       tex_coords: Vector2::new(0.0, 0.0),
+      tangent: Vector4::new(0.0, 0.0, 0.0, 0.0),
       color: Vector3::new(0.0, 0.0, 0.0), // Black
       transformed_position: Vector3::new(0.0, 0.0, 0.0),
       transformed_normal: Vector3::new(0.0, 1.0, 0.0),
     }
   }
-}
\ No newline at end of file
+}
+
+/// Malla indexada: cada vértice distinto una sola vez y los triángulos como índices de 3 en 3.
+/// El render transforma cada vértice una vez aunque lo compartan varios triángulos.
+#[derive(Clone, Debug, Default)]
+pub struct IndexedMesh {
+  pub vertices: Vec<Vertex>,
+  pub indices: Vec<u32>,
+}
+
+impl IndexedMesh {
+  /// Junta los vértices idénticos (en todos sus atributos) de una lista de triángulos
+  pub fn from_triangles(triangles: &[Vertex]) -> Self {
+    let mut mesh = IndexedMesh::default();
+    let mut seen: HashMap<[u32; 15], u32> = HashMap::new();
+    for vertex in triangles.iter().take(triangles.len() / 3 * 3) {
+      let index = *seen.entry(vertex_key(vertex)).or_insert_with(|| {
+        mesh.vertices.push(vertex.clone());
+        (mesh.vertices.len() - 1) as u32
+      });
+      mesh.indices.push(index);
+    }
+    mesh
+  }
+
+  pub fn triangle_count(&self) -> usize {
+    self.indices.len() / 3
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.indices.len() < 3
+  }
+
+  /// La misma malla como lista de triángulos, con los vértices compartidos repetidos
+  pub fn to_triangles(&self) -> Vec<Vertex> {
+    self.indices.iter().map(|&index| self.vertices[index as usize].clone()).collect()
+  }
+
+  /// Bytes de los vértices e índices
+  pub fn memory_bytes(&self) -> usize {
+    self.vertices.capacity() * std::mem::size_of::<Vertex>() + self.indices.capacity() * std::mem::size_of::<u32>()
+  }
+}
+
+/// Bits de los atributos de entrada del vértice (no los transformados)
+fn vertex_key(vertex: &Vertex) -> [u32; 15] {
+  let (p, n, uv, t, c) = (vertex.position, vertex.normal, vertex.tex_coords, vertex.tangent, vertex.color);
+  [p.x, p.y, p.z, n.x, n.y, n.z, uv.x, uv.y, t.x, t.y, t.z, t.w, c.x, c.y, c.z].map(f32::to_bits)
+}
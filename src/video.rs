@@ -0,0 +1,83 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use crate::framebuffer::Framebuffer;
+
+/// Destino de los fotogramas de `--render-video`
+pub enum VideoSink {
+    /// YUV4MPEG2 sin comprimir (`.y4m`), que leen ffmpeg y la mayoría de reproductores y editores
+    Y4m(BufWriter<File>),
+    /// Fotogramas RGB por la entrada estándar de ffmpeg, que los codifica en H.264
+    Ffmpeg(Child, ChildStdin),
+}
+
+impl VideoSink {
+    /// Con extensión `.y4m` escribe el archivo directamente; con cualquier otra lanza ffmpeg
+    pub fn create(path: &str, width: usize, height: usize, fps: u32) -> io::Result<Self> {
+        if path.ends_with(".y4m") {
+            let mut file = BufWriter::new(File::create(path)?);
+            writeln!(file, "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444", width, height, fps)?;
+            return Ok(VideoSink::Y4m(file));
+        }
+
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgb24"])
+            .args(["-s", &format!("{}x{}", width, height), "-r", &fps.to_string(), "-i", "-"])
+            // yuv420p (lo que reproduce todo el mundo) necesita ancho y alto pares
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2", "-c:v", "libx264", "-pix_fmt", "yuv420p", path])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => io::Error::new(err.kind(), "no se encontró ffmpeg (instálalo o usa una salida .y4m)"),
+                _ => err,
+            })?;
+        let stdin = child.stdin.take().expect("stdin de ffmpeg redirigida");
+        Ok(VideoSink::Ffmpeg(child, stdin))
+    }
+
+    pub fn write_frame(&mut self, frame: &Framebuffer) -> io::Result<()> {
+        match self {
+            VideoSink::Y4m(file) => {
+                file.write_all(b"FRAME\n")?;
+                // Planos Y, U y V completos (4:4:4), BT.601 de rango limitado
+                let weights = [(16.0, [0.257, 0.504, 0.098]), (128.0, [-0.148, -0.291, 0.439]), (128.0, [0.439, -0.368, -0.071])];
+                for (offset, [r, g, b]) in weights {
+                    let plane: Vec<u8> = frame
+                        .buffer
+                        .iter()
+                        .map(|&pixel| {
+                            let channel = |shift: u32| ((pixel >> shift) & 0xFF) as f32;
+                            (offset + r * channel(16) + g * channel(8) + b * channel(0)).round().clamp(0.0, 255.0) as u8
+                        })
+                        .collect();
+                    file.write_all(&plane)?;
+                }
+                Ok(())
+            }
+            VideoSink::Ffmpeg(_, stdin) => {
+                let bytes: Vec<u8> = frame
+                    .buffer
+                    .iter()
+                    .flat_map(|&pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+                    .collect();
+                stdin.write_all(&bytes)
+            }
+        }
+    }
+
+    /// Cierra el archivo o espera a que ffmpeg termine de codificar
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            VideoSink::Y4m(mut file) => file.flush(),
+            VideoSink::Ffmpeg(mut child, stdin) => {
+                drop(stdin);
+                let status = child.wait()?;
+                if status.success() {
+                    Ok(())
+                } else {
+                    Err(io::Error::other(format!("ffmpeg terminó con {}", status)))
+                }
+            }
+        }
+    }
+}
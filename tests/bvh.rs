@@ -0,0 +1,69 @@
+//! La jerarquía de esferas debe dar lo mismo que recorrer todos los objetos uno a uno.
+
+use computer_graphics_v3::bvh::Bvh;
+use nalgebra_glm::DVec3;
+use proptest::prelude::*;
+
+fn point() -> impl Strategy<Value = DVec3> {
+    (-500.0f64..500.0, -500.0f64..500.0, -500.0f64..500.0).prop_map(|(x, y, z)| DVec3::new(x, y, z))
+}
+
+fn spheres() -> impl Strategy<Value = Vec<(DVec3, f64)>> {
+    prop::collection::vec((point(), 0.5f64..60.0), 0..200)
+}
+
+/// Lo mismo que `Bvh::ray_cast`, sin jerarquía
+fn brute_ray_cast(spheres: &[(DVec3, f64)], origin: DVec3, direction: DVec3) -> Option<(usize, f64)> {
+    spheres
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &(center, radius))| {
+            let to_center = center - origin;
+            let along = to_center.dot(&direction);
+            let closest_sq = to_center.norm_squared() - along * along;
+            if along < 0.0 || closest_sq > radius * radius {
+                return None;
+            }
+            Some((index, along - (radius * radius - closest_sq).sqrt()))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+proptest! {
+    #[test]
+    fn ray_cast_matches_brute_force(spheres in spheres(), origin in point(), target in point()) {
+        let direction = (target - origin).try_normalize(1e-6).unwrap_or(DVec3::new(1.0, 0.0, 0.0));
+        let bvh = Bvh::build(spheres.iter().enumerate().map(|(index, &(center, radius))| (center, radius, index)));
+        let found = bvh.ray_cast(origin, direction, |_| true);
+        let expected = brute_ray_cast(&spheres, origin, direction);
+        // Con empates de distancia puede salir otro objeto, pero a la misma distancia
+        prop_assert_eq!(found.is_some(), expected.is_some());
+        if let (Some((_, found)), Some((_, expected))) = (found, expected) {
+            prop_assert!((found - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn ray_cast_skips_rejected_objects(spheres in spheres(), origin in point(), target in point()) {
+        let direction = (target - origin).try_normalize(1e-6).unwrap_or(DVec3::new(1.0, 0.0, 0.0));
+        let bvh = Bvh::build(spheres.iter().enumerate().map(|(index, &(center, radius))| (center, radius, index)));
+        let even: Vec<(DVec3, f64)> = spheres.iter().step_by(2).copied().collect();
+        let found = bvh.ray_cast(origin, direction, |index| index % 2 == 0).map(|(_, distance)| distance);
+        let expected = brute_ray_cast(&even, origin, direction).map(|(_, distance)| distance);
+        prop_assert_eq!(found.is_some(), expected.is_some());
+        if let (Some(found), Some(expected)) = (found, expected) {
+            prop_assert!((found - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn overlapping_matches_brute_force(spheres in spheres(), position in point(), margin in 0.0f64..30.0) {
+        let bvh = Bvh::build(spheres.iter().enumerate().map(|(index, &(center, radius))| (center, radius, index)));
+        let mut found = bvh.overlapping(position, margin);
+        found.sort();
+        let expected: Vec<usize> = (0..spheres.len())
+            .filter(|&index| (position - spheres[index].0).norm() < spheres[index].1 + margin)
+            .collect();
+        prop_assert_eq!(found, expected);
+    }
+}
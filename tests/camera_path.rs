@@ -0,0 +1,63 @@
+//! Un recorrido grabado en vuelo debe poder reproducirse: sin los puntos de paso en los que la
+//! simulación no avanzó, con un ritmo positivo y en un archivo que `CameraPath::load` acepta.
+
+use std::env;
+use std::fs;
+use computer_graphics_v3::camera_path::{CameraPath, PathRecorder};
+use nalgebra_glm::DVec3;
+
+/// Graba un frame de 0.3 s por cada instante de simulación de `sim_times`, avanzando en x
+fn record(sim_times: &[f64]) -> PathRecorder {
+    let mut recorder = PathRecorder::default();
+    for (index, &sim_time) in sim_times.iter().enumerate() {
+        recorder.record(0.3, sim_time, DVec3::new(index as f64, 0.0, 0.0), DVec3::new(0.0, 0.0, -1.0));
+    }
+    recorder
+}
+
+#[test]
+fn recorded_path_survives_scrubbing_and_reloads() {
+    // Avanza, vuelve atrás con la línea de tiempo, se queda en pausa y sigue
+    let sim_times = [10.0, 12.0, 14.0, 5.0, 6.0, 14.0, 14.0, 16.0, 18.0];
+    let path = record(&sim_times).finish().unwrap();
+    let kept: Vec<f64> = path.keyframes.iter().map(|keyframe| keyframe.position[0]).collect();
+    assert_eq!(kept, [0.0, 1.0, 2.0, 7.0, 8.0]);
+    assert_eq!(path.start_time, Some(10.0));
+    // 8 s de simulación en los 2.1 s reales entre el primer y el último punto de paso, sin el
+    // frame en pausa
+    assert_eq!(path.keyframes[0].time, 0.0);
+    assert!((path.keyframes[4].time - 2.1).abs() < 1e-5, "{}", path.keyframes[4].time);
+    assert!((path.time_scale - 8.0 / 2.1).abs() < 1e-5, "{}", path.time_scale);
+
+    let file = env::temp_dir().join(format!("recorrido_prueba_{}.toml", std::process::id()));
+    let file = file.to_str().unwrap();
+    path.save(file, "# Prueba").unwrap();
+    let loaded = CameraPath::load(file);
+    fs::remove_file(file).unwrap();
+    let loaded = loaded.unwrap();
+    assert_eq!(loaded.fps, path.fps);
+    assert_eq!(loaded.time_scale, path.time_scale);
+    assert_eq!(loaded.start_time, path.start_time);
+    assert_eq!(loaded.keyframes.len(), path.keyframes.len());
+    for (loaded, recorded) in loaded.keyframes.iter().zip(&path.keyframes) {
+        assert_eq!((loaded.time, loaded.position), (recorded.time, recorded.position));
+    }
+}
+
+#[test]
+fn recording_started_in_pause_begins_when_time_moves() {
+    let path = record(&[3.0, 3.0, 3.0, 3.0, 5.0, 7.0]).finish().unwrap();
+    let times: Vec<f32> = path.keyframes.iter().map(|keyframe| keyframe.time).collect();
+    assert_eq!(path.start_time, Some(3.0));
+    assert!(times.iter().zip([0.0, 0.3, 0.6]).all(|(time, expected)| (time - expected).abs() < 1e-5), "{:?}", times);
+    assert!((path.time_scale - 4.0 / 0.6).abs() < 1e-4, "{}", path.time_scale);
+}
+
+#[test]
+fn empty_or_stalled_recordings_are_rejected() {
+    assert!(PathRecorder::default().finish().is_err());
+    assert!(record(&[3.0]).finish().is_err());
+    // En pausa todo el rato, o solo hacia atrás
+    assert!(record(&[3.0, 3.0, 3.0, 3.0]).finish().is_err());
+    assert!(record(&[9.0, 7.0, 5.0, 3.0]).finish().is_err());
+}
@@ -0,0 +1,89 @@
+//! El culling por defecto debe quedarse con las caras de `sphere1.obj` que miran a la cámara (es
+//! el sentido de giro de `Obj::load`), el contrario con las de detrás y sin culling con todas. Y
+//! la misma esfera con el sentido de giro al revés debe detectarse y pedir el culling contrario.
+
+use std::f32::consts::PI;
+use computer_graphics_v3::depth::DepthMode;
+use computer_graphics_v3::obj::Obj;
+use computer_graphics_v3::pipeline::{
+    create_projection_matrix, create_view_matrix, create_viewport_matrix, transform_indexed, Culling, Uniforms,
+};
+use computer_graphics_v3::vertex::IndexedMesh;
+use nalgebra_glm::{Mat3, Mat4, Vec3};
+
+const WIDTH: f32 = 320.0;
+const HEIGHT: f32 = 240.0;
+
+fn uniforms(eye: Vec3, culling: Culling) -> Uniforms {
+    Uniforms {
+        model_matrix: Mat4::identity(),
+        normal_matrix: Mat3::identity(),
+        inverse_model_matrix: Some(Mat4::identity()),
+        view_matrix: create_view_matrix(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0)),
+        projection_matrix: create_projection_matrix(PI / 3.0, WIDTH / HEIGHT, 0.1, 100.0),
+        viewport_matrix: create_viewport_matrix(0.0, 0.0, WIDTH, HEIGHT),
+        time: 0.0,
+        camera_position: eye,
+        light_position: Vec3::zeros(),
+        depth_mode: DepthMode::Perspective,
+        culling,
+    }
+}
+
+/// Caras que miran a `eye` y que le dan la espalda. La esfera está centrada en el origen, así que
+/// hacia fuera es hacia donde queda el centro del triángulo; las casi de canto no cuentan.
+fn facing(mesh: &IndexedMesh, eye: Vec3) -> (usize, usize) {
+    let (mut front, mut back) = (0, 0);
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| {
+            let p = mesh.vertices[index as usize].position;
+            Vec3::new(p.x, p.y, p.z)
+        });
+        let normal = (b - a).cross(&(c - a)).normalize();
+        let outward = if normal.dot(&(a + b + c)) < 0.0 { -normal } else { normal };
+        let toward_eye = outward.dot(&(eye - a).normalize());
+        if toward_eye > 1e-3 {
+            front += 1;
+        } else if toward_eye < -1e-3 {
+            back += 1;
+        }
+    }
+    (front, back)
+}
+
+#[test]
+fn default_culling_keeps_the_faces_toward_the_camera() {
+    let mesh = Obj::load("assets/models/sphere1.obj").unwrap().get_indexed_mesh();
+    let triangles = mesh.triangle_count();
+    for eye in [Vec3::new(0.0, 0.0, 4.0), Vec3::new(3.0, 2.0, -1.5), Vec3::new(-0.5, -3.5, 1.0)] {
+        let (front, back) = facing(&mesh, eye);
+        let edge_on = triangles - front - back;
+        let kept = |culling| transform_indexed(&uniforms(eye, culling), &mesh).len() / 3;
+
+        let (default, reversed) = (kept(Culling::default()), kept(Culling::default().reversed()));
+        assert!((front..=front + edge_on).contains(&default), "{:?}: {} de {} caras delanteras", eye, default, front);
+        assert!((back..=back + edge_on).contains(&reversed), "{:?}: {} de {} caras traseras", eye, reversed, back);
+        assert_eq!(kept(Culling::Off), triangles);
+    }
+}
+
+#[test]
+fn reversed_winding_gets_the_reversed_culling() {
+    let mut sphere = Obj::load("assets/models/sphere1.obj").unwrap();
+    let triangles = sphere.indices.len() / 3;
+    assert_eq!(sphere.reversed_faces(), 0);
+    assert_eq!(Culling::for_reversed_faces(sphere.reversed_faces(), triangles), Culling::default());
+
+    // Mismas normales, vértices de cada cara en el orden contrario
+    for triangle in sphere.indices.chunks_exact_mut(3) {
+        triangle.swap(1, 2);
+    }
+    assert_eq!(sphere.reversed_faces(), triangles);
+    assert_eq!(Culling::for_reversed_faces(sphere.reversed_faces(), triangles), Culling::default().reversed());
+
+    // Con la mitad de las caras de cada lado no vale ninguno de los dos sentidos
+    for triangle in sphere.indices.chunks_exact_mut(6) {
+        triangle.swap(1, 2);
+    }
+    assert_eq!(Culling::for_reversed_faces(sphere.reversed_faces(), triangles), Culling::Off);
+}
@@ -0,0 +1,42 @@
+//! La interfaz C, llamada igual que lo haría un programa en C.
+
+use computer_graphics_v3::ffi::*;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+#[test]
+fn renders_bodies_into_rgba_buffer() {
+    let mut pixels = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    unsafe {
+        let scene = cg_scene_create(WIDTH, HEIGHT);
+        assert!(!scene.is_null());
+        assert_eq!(cg_scene_add_body(scene, 2, 0, 0.0, 0.0, 40.0), 0);
+        assert_eq!(cg_scene_add_body(scene, 0, 1, 120.0, 0.5, 10.0), 1);
+        cg_scene_set_camera(scene, 0.0, 0.0, 300.0, 0.0, 0.0, 0.0);
+        cg_scene_step(scene, 0.25);
+        assert_eq!(cg_scene_render(scene, pixels.as_mut_ptr(), pixels.len()), 0);
+        cg_scene_destroy(scene);
+    }
+
+    // La estrella ocupa el centro; las esquinas quedan en negro opaco
+    let center = ((HEIGHT / 2 * WIDTH + WIDTH / 2) * 4) as usize;
+    assert!(pixels[center..center + 3].iter().any(|&channel| channel > 0));
+    assert_eq!(&pixels[0..4], &[0, 0, 0, 255]);
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    let mut pixels = vec![0u8; 16];
+    unsafe {
+        assert!(cg_scene_create(0, HEIGHT).is_null());
+        let scene = cg_scene_create(WIDTH, HEIGHT);
+        assert_eq!(cg_scene_add_body(scene, 99, 0, 0.0, 0.0, 1.0), -1);
+        assert_eq!(cg_scene_render(scene, pixels.as_mut_ptr(), pixels.len()), -1);
+        assert_eq!(cg_scene_render(scene, std::ptr::null_mut(), 0), -1);
+        assert_eq!(cg_scene_render(std::ptr::null_mut(), pixels.as_mut_ptr(), pixels.len()), -1);
+        cg_scene_step(std::ptr::null_mut(), 1.0);
+        cg_scene_destroy(scene);
+        cg_scene_destroy(std::ptr::null_mut());
+    }
+}
@@ -0,0 +1,209 @@
+//! Pruebas de referencia del pipeline: entradas fijas y la salida guardada en `tests/golden/`.
+//! No necesitan ventana: usan las etapas puras de la biblioteca. Si un cambio altera la imagen
+//! a propósito, se regeneran con `GOLDEN_UPDATE=1 cargo test --test golden` y se revisa el diff.
+
+use std::f32::consts::PI;
+use std::fs;
+use std::path::PathBuf;
+use computer_graphics_v3::depth::DepthMode;
+use computer_graphics_v3::fragment::Fragment;
+use computer_graphics_v3::light::Light;
+use computer_graphics_v3::pipeline::{
+    create_model_matrix, create_normal_matrix, create_projection_matrix, create_view_matrix, create_viewport_matrix, pack_color,
+    project_segment, rasterize, Culling, Uniforms,
+};
+use computer_graphics_v3::shaders::{fragment_shader, light_surface, vertex_shader, PlanetShaderType, ShadedSurface, ShaderParams, SurfaceMaps};
+use computer_graphics_v3::vertex::Vertex;
+use nalgebra_glm::Vec3;
+use raylib::math::{Vector2, Vector3};
+
+/// Diferencia máxima admitida en cada valor (posiciones en píxeles, colores en 0..1)
+const TOLERANCE: f32 = 1e-3;
+const WIDTH: f32 = 64.0;
+const HEIGHT: f32 = 48.0;
+
+/// Compara `rows` con `tests/golden/<name>.txt`, una fila por línea
+fn check_golden(name: &str, rows: &[Vec<f32>]) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", &format!("{}.txt", name)].iter().collect();
+    if std::env::var_os("GOLDEN_UPDATE").is_some() {
+        let text: String = rows
+            .iter()
+            .map(|row| row.iter().map(|value| format!("{:.5}", value)).collect::<Vec<_>>().join(" ") + "\n")
+            .collect();
+        fs::write(&path, text).unwrap();
+        return;
+    }
+
+    let text = fs::read_to_string(&path).unwrap_or_else(|err| panic!("falta {}: {}", path.display(), err));
+    let expected: Vec<Vec<f32>> = text
+        .lines()
+        .map(|line| line.split_whitespace().map(|value| value.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(expected.len(), rows.len(), "{}: número de filas distinto", name);
+    for (index, (expected, actual)) in expected.iter().zip(rows).enumerate() {
+        assert_eq!(expected.len(), actual.len(), "{}: fila {} de distinto largo", name, index);
+        for (e, a) in expected.iter().zip(actual) {
+            assert!((e - a).abs() <= TOLERANCE, "{}: fila {}: esperado {:?}, obtenido {:?}", name, index, expected, actual);
+        }
+    }
+}
+
+fn uniforms() -> Uniforms {
+    let eye = Vec3::new(0.0, 0.5, 3.0);
+    let model_matrix = create_model_matrix(Vec3::zeros(), 1.0, Vec3::new(0.3, 0.5, 0.0));
+    Uniforms {
+        model_matrix,
+        normal_matrix: create_normal_matrix(&model_matrix),
+        inverse_model_matrix: model_matrix.try_inverse(),
+        view_matrix: create_view_matrix(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0)),
+        projection_matrix: create_projection_matrix(PI / 3.0, WIDTH / HEIGHT, 0.1, 100.0),
+        viewport_matrix: create_viewport_matrix(0.0, 0.0, WIDTH, HEIGHT),
+        time: 1.5,
+        camera_position: eye,
+        light_position: Vec3::new(5.0, 3.0, 4.0),
+        depth_mode: DepthMode::Perspective,
+        culling: Culling::default(),
+    }
+}
+
+fn triangle_vertices() -> Vec<Vertex> {
+    let normal = Vector3::new(0.0, 0.0, 1.0);
+    vec![
+        Vertex::new(Vector3::new(-1.0, -0.8, 0.2), normal, Vector2::new(0.0, 0.0)),
+        Vertex::new(Vector3::new(1.1, -0.6, 0.0), normal, Vector2::new(1.0, 0.0)),
+        Vertex::new(Vector3::new(-0.2, 1.0, -0.1), normal, Vector2::new(0.5, 1.0)),
+    ]
+}
+
+#[test]
+fn vertex_transformation() {
+    let uniforms = uniforms();
+    let rows: Vec<Vec<f32>> = triangle_vertices()
+        .iter()
+        .map(|vertex| {
+            let transformed = vertex_shader(vertex, &uniforms);
+            let (p, n) = (transformed.transformed_position, transformed.transformed_normal);
+            vec![p.x, p.y, p.z, n.x, n.y, n.z]
+        })
+        .collect();
+    check_golden("vertex_transformation", &rows);
+}
+
+#[test]
+fn segment_clipping() {
+    let uniforms = uniforms();
+    let segments = [
+        // Dentro de la pantalla
+        (Vec3::new(-0.5, 0.0, 0.0), Vec3::new(0.5, 0.3, 0.0)),
+        // Sale por un borde
+        (Vec3::new(0.0, 0.0, 0.0), Vec3::new(20.0, 1.0, 0.0)),
+        // Cruza el plano cercano (el ojo está en z = 3)
+        (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.2, 0.5, 10.0)),
+        // Detrás de la cámara
+        (Vec3::new(0.0, 0.0, 5.0), Vec3::new(1.0, 0.0, 6.0)),
+    ];
+    let rows: Vec<Vec<f32>> = segments
+        .iter()
+        .map(|&(from, to)| match project_segment(&uniforms, WIDTH, HEIGHT, from, to) {
+            Some((a, b)) => vec![1.0, a.x, a.y, a.z, b.x, b.y, b.z],
+            None => vec![0.0],
+        })
+        .collect();
+    check_golden("segment_clipping", &rows);
+}
+
+#[test]
+fn triangle_rasterization() {
+    // El triángulo gira al revés que las mallas de `Obj::load`; aquí se comprueba el rasterizado
+    let uniforms = Uniforms { culling: Culling::Off, ..uniforms() };
+    let light = Light::new(Vector3::new(5.0, 3.0, 4.0));
+    let fragments = rasterize(&uniforms, &triangle_vertices(), &light);
+
+    // Primero el número de fragmentos y las sumas (cualquier píxel de más o de menos las cambia);
+    // después una muestra de fragmentos
+    let sum = |value: fn(&Fragment) -> f32| fragments.iter().map(value).sum::<f32>();
+    let mut rows = vec![vec![
+        fragments.len() as f32,
+        sum(|f| f.position.x),
+        sum(|f| f.position.y),
+        sum(|f| f.depth),
+    ]];
+    rows.extend(fragments.iter().step_by(37).map(|f| {
+        vec![
+            f.position.x,
+            f.position.y,
+            f.depth,
+            f.world_position.x,
+            f.world_position.y,
+            f.world_position.z,
+            f.tex_coords.x,
+            f.tex_coords.y,
+            f.color.x,
+        ]
+    }));
+    check_golden("triangle_rasterization", &rows);
+}
+
+/// Fragmentos repartidos por una esfera unidad, con la normal y las UV que tendría la malla
+fn sphere_fragments() -> Vec<Fragment> {
+    let mut fragments = Vec::new();
+    for latitude in 0..5 {
+        for longitude in 0..8 {
+            let theta = (latitude as f32 + 0.5) / 5.0 * PI;
+            let phi = longitude as f32 / 8.0 * 2.0 * PI;
+            let p = Vector3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+            let mut fragment = Fragment::new_with_world_pos(32.0, 24.0, Vector3::new(1.0, 1.0, 1.0), 0.5, p);
+            fragment.normal = p;
+            fragment.tex_coords = Vector2::new(longitude as f32 / 8.0, 1.0 - (latitude as f32 + 0.5) / 5.0);
+            fragments.push(fragment);
+        }
+    }
+    fragments
+}
+
+#[test]
+fn planet_shaders() {
+    let uniforms = uniforms();
+    let shaders = [
+        PlanetShaderType::Terra,
+        PlanetShaderType::Vulcan,
+        PlanetShaderType::Solarius,
+        PlanetShaderType::Nepturion,
+        PlanetShaderType::Mossar,
+        PlanetShaderType::Mesh,
+    ];
+    let mut rows = Vec::new();
+    for shader in shaders {
+        let params = ShaderParams::for_type(shader);
+        for fragment in sphere_fragments() {
+            let color = fragment_shader(&fragment, &uniforms, shader, &params, SurfaceMaps::default());
+            rows.push(vec![color.x, color.y, color.z]);
+        }
+    }
+    check_golden("planet_shaders", &rows);
+}
+
+#[test]
+fn surface_normal_drives_lighting() {
+    let uniforms = uniforms();
+    // El fragmento de la esfera más de cara a la luz
+    let fragment = sphere_fragments()
+        .into_iter()
+        .max_by(|a, b| a.normal.dot(Vector3::new(5.0, 3.0, 4.0)).total_cmp(&b.normal.dot(Vector3::new(5.0, 3.0, 4.0))))
+        .unwrap();
+    let albedo = Vector3::new(0.8, 0.6, 0.4);
+    let emission = Vector3::new(0.1, 0.0, 0.0);
+
+    let flat = light_surface(&fragment, &uniforms, &ShadedSurface::new(&fragment, albedo).with_emission(emission));
+    assert_eq!(flat, albedo * fragment.color + emission);
+
+    let tilted = ShadedSurface::new(&fragment, albedo).with_normal(-fragment.normal);
+    assert_eq!(light_surface(&fragment, &uniforms, &tilted), Vector3::zero());
+}
+
+#[test]
+fn color_packing() {
+    let colors = [Vector3::new(1.0, 0.5, 0.0), Vector3::new(-0.2, 0.25, 1.7), Vector3::new(0.1, 0.2, 0.3)];
+    let packed: Vec<u32> = colors.iter().map(|&color| pack_color(color)).collect();
+    assert_eq!(packed, vec![0xFF7F00, 0x003FFF, 0x19334C]);
+}
@@ -0,0 +1,53 @@
+//! El parche de relieve debe reproducir la superficie que muestrea y no dejar pasar los picos
+//! que quedan bajo la huella de la nave.
+
+use computer_graphics_v3::heightfield::Heightfield;
+use nalgebra_glm::Vec3;
+use proptest::prelude::*;
+
+fn hills(direction: Vec3) -> f32 {
+    1.0 + 0.02 * (direction.x * 9.0).sin() * (direction.z * 7.0).cos() + 0.01 * (direction.y * 5.0).sin()
+}
+
+fn unit() -> impl Strategy<Value = Vec3> {
+    (-1.0f32..1.0, -1.0f32..1.0, -1.0f32..1.0)
+        .prop_filter("no nulo", |(x, y, z)| x * x + y * y + z * z > 0.01)
+        .prop_map(|(x, y, z)| Vec3::new(x, y, z).normalize())
+}
+
+proptest! {
+    /// Dentro del parche la interpolación sigue a la función; fuera no hay dato
+    #[test]
+    fn interpolates_the_sampled_surface(center in unit(), (u, v) in (-1.0f32..1.0, -1.0f32..1.0)) {
+        let field = Heightfield::sample(center, 0.1, 65, hills);
+        let helper = if center.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+        let tangent = center.cross(&helper).normalize();
+        let bitangent = center.cross(&tangent);
+
+        let inside = (center + (tangent * u + bitangent * v) * 0.099).normalize();
+        let radius = field.radius_at(inside);
+        prop_assert!(radius.is_some());
+        prop_assert!((radius.unwrap() - hills(inside)).abs() < 1e-3, "{:?} vs {}", radius, hills(inside));
+        prop_assert!(field.contains(inside, 0.0));
+
+        let outside = (center + (tangent * u.signum() + bitangent * v) * 0.2).normalize();
+        prop_assert!(field.radius_at(outside).is_none());
+        prop_assert!(!field.contains(outside, 0.0));
+        prop_assert!(field.radius_at(-center).is_none());
+    }
+}
+
+#[test]
+fn footprint_finds_nearby_peaks() {
+    let center = Vec3::new(0.0, 1.0, 0.0);
+    let peak = Vec3::new(0.02, 1.0, 0.0).normalize();
+    // Un pico estrecho a 0.02 del centro
+    let spike = |direction: Vec3| 1.0 + 0.1 * (-(direction - peak).norm_squared() / 1e-5).exp();
+    let field = Heightfield::sample(center, 0.1, 101, spike);
+
+    let under = field.radius_at(center).unwrap();
+    assert!(under < 1.001, "{}", under);
+    assert!(field.highest_within(center, 0.03).unwrap() > 1.09);
+    assert!(field.highest_within(center, 0.01).unwrap() < 1.001);
+    assert!(field.highest_within(-center, 0.03).is_none());
+}
@@ -0,0 +1,109 @@
+//! La icosfera adaptativa debe cerrar sin grietas entre niveles, seguir el sentido de
+//! `sphere1.obj` y concentrar los triángulos cerca de la cámara.
+
+use computer_graphics_v3::icosphere::{adaptive_icosphere, Tessellation, BASE_LEVEL};
+use computer_graphics_v3::obj::Obj;
+use computer_graphics_v3::vertex::Vertex;
+use nalgebra_glm::Vec3;
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+type Key = [u32; 3];
+
+fn position(vertex: &Vertex) -> Vec3 {
+    Vec3::new(vertex.position.x, vertex.position.y, vertex.position.z)
+}
+
+fn key(point: &Vec3) -> Key {
+    [point.x.to_bits(), point.y.to_bits(), point.z.to_bits()]
+}
+
+fn triangles(vertices: &[Vertex]) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+    vertices.chunks(3).map(|triangle| [position(&triangle[0]), position(&triangle[1]), position(&triangle[2])])
+}
+
+/// Signo del sentido visto desde fuera: positivo si es antihorario en las posiciones
+fn winding([a, b, c]: [Vec3; 3]) -> f32 {
+    (b - a).cross(&(c - a)).dot(&(a + b + c)).signum()
+}
+
+/// Aristas de `edges` en sentido contrario que cubren (a, b): la misma o sus dos mitades (por
+/// el punto medio de la cuerda) cubiertas a su vez
+fn cover(edges: &HashSet<(Key, Key)>, a: Vec3, b: Vec3, depth: u32, used: &mut Vec<(Key, Key)>) -> bool {
+    if edges.contains(&(key(&b), key(&a))) {
+        used.push((key(&b), key(&a)));
+        return true;
+    }
+    let middle = (a + b) * 0.5;
+    depth > 0 && cover(edges, a, middle, depth - 1, used) && cover(edges, middle, b, depth - 1, used)
+}
+
+fn smooth(tessellation: &Tessellation) -> Vec<Vertex> {
+    adaptive_icosphere(tessellation, |_| 1.0)
+}
+
+fn bumpy(tessellation: &Tessellation) -> Vec<Vertex> {
+    adaptive_icosphere(tessellation, |direction| 1.0 + 0.05 * (direction.x * 7.0).sin() * (direction.z * 5.0).cos())
+}
+
+#[test]
+fn far_camera_gets_the_base_mesh() {
+    let tessellation = Tessellation { eye: Vec3::new(0.0, 0.0, 500.0), detail: 0.1, max_level: 8 };
+    let vertices = smooth(&tessellation);
+    assert_eq!(vertices.len() / 3, 20 * 4usize.pow(BASE_LEVEL));
+}
+
+#[test]
+fn winding_matches_the_bundled_sphere() {
+    let sphere = Obj::load("assets/models/sphere1.obj").unwrap().get_vertex_array();
+    let expected = winding(triangles(&sphere).next().unwrap());
+    assert!(triangles(&sphere).all(|triangle| winding(triangle) == expected));
+
+    let tessellation = Tessellation { eye: Vec3::new(0.3, 0.9, 0.6), detail: 0.1, max_level: 6 };
+    assert!(triangles(&bumpy(&tessellation)).all(|triangle| winding(triangle) == expected));
+}
+
+#[test]
+fn detail_concentrates_near_the_camera() {
+    let eye = Vec3::new(0.0, 0.0, 1.1);
+    let tessellation = Tessellation { eye, detail: 0.1, max_level: 7 };
+    let vertices = smooth(&tessellation);
+    assert!(vertices.len() / 3 > 20 * 4usize.pow(BASE_LEVEL) * 4);
+
+    let longest_edge = |[a, b, c]: [Vec3; 3]| (a - b).norm().max((b - c).norm()).max((c - a).norm());
+    let (near, far): (Vec<_>, Vec<_>) = triangles(&vertices).partition(|[a, b, c]| ((a + b + c) / 3.0).z > 0.9);
+    let near_longest = near.into_iter().map(longest_edge).fold(0.0, f32::max);
+    let far_shortest = far.into_iter().filter(|[a, ..]| a.z < -0.5).map(longest_edge).fold(f32::MAX, f32::min);
+    assert!(near_longest * 4.0 < far_shortest, "{} vs {}", near_longest, far_shortest);
+}
+
+proptest! {
+    #[test]
+    fn levels_join_without_cracks(
+        (x, y, z) in (-1.0f32..1.0, -1.0f32..1.0, -1.0f32..1.0),
+        distance in 1.02f32..4.0,
+        detail in 0.05f32..0.5,
+    ) {
+        let direction = Vec3::new(x, y, z);
+        prop_assume!(direction.norm() > 0.1);
+        let tessellation = Tessellation { eye: direction.normalize() * distance, detail, max_level: 6 };
+        let vertices = bumpy(&tessellation);
+        let edges: HashSet<(Key, Key)> = triangles(&vertices)
+            .flat_map(|[a, b, c]| [(a, b), (b, c), (c, a)])
+            .map(|(a, b)| (key(&a), key(&b)))
+            .collect();
+
+        // Cada arista la cubre el vecino, entera o en mitades; las que no tienen gemela exacta
+        // tienen que ser alguna de esas mitades
+        let unmatched: Vec<(Vec3, Vec3)> = triangles(&vertices)
+            .flat_map(|[a, b, c]| [(a, b), (b, c), (c, a)])
+            .filter(|(from, to)| !edges.contains(&(key(to), key(from))))
+            .collect();
+        let mut used = Vec::new();
+        let covered: Vec<bool> = unmatched.iter().map(|&(from, to)| cover(&edges, from, to, 6, &mut used)).collect();
+        let used: HashSet<(Key, Key)> = used.into_iter().collect();
+        for (&(from, to), covered) in unmatched.iter().zip(covered) {
+            prop_assert!(covered || used.contains(&(key(&from), key(&to))), "grieta entre {:?} y {:?}", from, to);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Recuperación de errores del lector de OBJ: los registros malos se descartan con aviso y el
+//! resto del modelo se carga.
+
+use std::path::Path;
+use computer_graphics_v3::obj::Obj;
+use computer_graphics_v3::vertex::{IndexedMesh, Vertex};
+
+fn parse(source: &str) -> (Obj, Vec<usize>) {
+    let (model, warnings) = Obj::parse(source.as_bytes(), Path::new("/nonexistent")).unwrap();
+    (model, warnings.iter().map(|warning| warning.line).collect())
+}
+
+#[test]
+fn bad_records_are_skipped() {
+    let (model, warning_lines) = parse(
+        "v 0 0 0\n\
+         v 1 0 0\n\
+         v 0 1\n\
+         v nan 1 0\n\
+         v 0 1 0\n\
+         v 1e60 0 0\n\
+         f 1 2 3\n\
+         f 1 2 9\n\
+         f 1 2\n\
+         f 1/x 2 3\n\
+         f -1 -2 -3\n\
+         usemtl\n",
+    );
+    assert_eq!(warning_lines, vec![3, 4, 6, 8, 9, 10, 12]);
+    assert_eq!(model.vertices.len(), 3);
+    assert_eq!(model.get_vertex_array().len(), 6);
+}
+
+#[test]
+fn mixed_attributes_are_dropped_per_face() {
+    let (model, warning_lines) = parse(
+        "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\n\
+         f 1/1/1 2//1 3/1\n",
+    );
+    assert!(warning_lines.is_empty());
+    assert_eq!(model.vertices.len(), 3);
+    assert_eq!(model.vertices_without_uvs, 3);
+    assert_eq!(model.vertices_without_normals, 3);
+}
+
+#[test]
+fn groups_keep_their_own_vertices() {
+    let (model, _) = parse(
+        "o a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n\
+         o b\nv 5 0 0\nv 6 0 0\nv 5 1 0\nf 4 5 6\n",
+    );
+    let xs: Vec<f32> = model.get_vertex_array().iter().map(|vertex| vertex.position.x).collect();
+    assert_eq!(xs, vec![0.0, 1.0, 0.0, 5.0, 6.0, 5.0]);
+}
+
+#[test]
+fn bundled_models_load_cleanly() {
+    for path in ["assets/models/sphere1.obj", "assets/models/Y-wing.obj"] {
+        let source = std::fs::read(path).unwrap();
+        let (model, warnings) = Obj::parse(&source, Path::new("assets/models")).unwrap();
+        // sphere1.obj nombra un MTL que no está en el repositorio: ese aviso no es de una línea
+        assert!(warnings.iter().all(|warning| warning.line == 0), "{}: {:?}", path, warnings);
+        assert!(!model.indices.is_empty());
+    }
+}
+
+#[test]
+fn indexed_mesh_matches_the_vertex_array() {
+    let model = Obj::load("assets/models/Y-wing.obj").unwrap();
+    let mesh = model.get_indexed_mesh();
+    let positions = |vertices: &[Vertex]| -> Vec<[f32; 3]> { vertices.iter().map(|vertex| [vertex.position.x, vertex.position.y, vertex.position.z]).collect() };
+    assert_eq!(positions(&mesh.to_triangles()), positions(&model.get_vertex_array()));
+    // Los vértices compartidos se guardan una sola vez
+    assert!(mesh.vertices.len() < mesh.indices.len());
+
+    let welded = IndexedMesh::from_triangles(&model.get_vertex_array());
+    assert_eq!(positions(&welded.to_triangles()), positions(&model.get_vertex_array()));
+    assert!(welded.vertices.len() <= mesh.vertices.len());
+}
+
+#[test]
+fn arbitrary_bytes_do_not_panic() {
+    // Variaciones deterministas de un OBJ válido; el fuzzer de `fuzz/` cubre el resto
+    let base = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\nf 1/1/1 2/1/1 3/1/1\n".to_vec();
+    let mut state: u32 = 0x9E37_79B9;
+    for _ in 0..2000 {
+        let mut data = base.clone();
+        for _ in 0..4 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let position = state as usize % data.len();
+            data[position] = (state >> 24) as u8;
+        }
+        let _ = Obj::parse(&data, Path::new("/nonexistent"));
+    }
+}
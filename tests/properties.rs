@@ -0,0 +1,289 @@
+//! Propiedades del rasterizador y del recorte comprobadas con entradas aleatorias (proptest).
+//! Cuando una falla, proptest reduce el caso y lo guarda en `tests/properties.proptest-regressions`
+//! para que se repita en las siguientes ejecuciones.
+
+use std::f32::consts::PI;
+use std::ops::ControlFlow;
+use computer_graphics_v3::clip::{clip_triangle, Clipped};
+use computer_graphics_v3::depth::DepthMode;
+use computer_graphics_v3::light::Light;
+use computer_graphics_v3::line::clip_to_screen;
+use computer_graphics_v3::pipeline::{
+    create_model_matrix, create_normal_matrix, create_projection_matrix, create_view_matrix, create_viewport_matrix,
+    project_segment, transform_indexed,
+    transform_triangles, Culling, Uniforms,
+};
+use computer_graphics_v3::shaders::vertex_shader;
+use computer_graphics_v3::tiles::{rasterize_tile, TileGrid};
+use computer_graphics_v3::triangle::{barycentric_coordinates, triangle, triangle_each};
+use computer_graphics_v3::vertex::{IndexedMesh, Vertex};
+use nalgebra_glm::{Mat3, Mat4, Vec3, Vec4};
+use proptest::prelude::*;
+use raylib::math::{Vector2, Vector3};
+
+const WIDTH: f32 = 320.0;
+const HEIGHT: f32 = 240.0;
+
+/// Vértice ya transformado: solo importa su posición en pantalla (y la profundidad en z)
+fn screen_vertex(x: f32, y: f32, z: f32) -> Vertex {
+    let mut vertex = Vertex::new(Vector3::new(x, y, z), Vector3::new(0.0, 0.0, 1.0), Vector2::zero());
+    vertex.set_transformed(Vector3::new(x, y, z), Vector3::new(0.0, 0.0, 1.0));
+    vertex
+}
+
+/// Doble del área con signo del triángulo en pantalla
+fn doubled_area(a: &Vertex, b: &Vertex, c: &Vertex) -> f32 {
+    let (a, b, c) = (a.transformed_position, b.transformed_position, c.transformed_position);
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn screen_point() -> impl Strategy<Value = (f32, f32, f32)> {
+    (-0.5 * WIDTH..1.5 * WIDTH, -0.5 * HEIGHT..1.5 * HEIGHT, -1.0f32..1.0)
+}
+
+fn world_point() -> impl Strategy<Value = Vec3> {
+    (-50.0f32..50.0, -50.0f32..50.0, -50.0f32..50.0).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+}
+
+fn uniforms(eye: Vec3) -> Uniforms {
+    Uniforms {
+        model_matrix: Mat4::identity(),
+        normal_matrix: Mat3::identity(),
+        inverse_model_matrix: Some(Mat4::identity()),
+        view_matrix: create_view_matrix(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0)),
+        projection_matrix: create_projection_matrix(PI / 3.0, WIDTH / HEIGHT, 0.1, 1000.0),
+        viewport_matrix: create_viewport_matrix(0.0, 0.0, WIDTH, HEIGHT),
+        time: 0.0,
+        camera_position: eye,
+        light_position: Vec3::new(0.0, 0.0, 0.0),
+        depth_mode: DepthMode::Perspective,
+        culling: Culling::default(),
+    }
+}
+
+fn world_vertex(point: Vec3) -> Vertex {
+    Vertex::new(Vector3::new(point.x, point.y, point.z), Vector3::new(0.0, 0.0, 1.0), Vector2::zero())
+}
+
+/// Con la tolerancia justa para los errores de redondeo de f32 en coordenadas de pantalla
+fn inside_screen(point: Vector3) -> bool {
+    let epsilon = 1e-2;
+    (-epsilon..=WIDTH - 1.0 + epsilon).contains(&point.x) && (-epsilon..=HEIGHT - 1.0 + epsilon).contains(&point.y)
+}
+
+proptest! {
+    /// Un punto construido con pesos convexos recupera esos pesos, que suman 1 y reproducen el punto
+    #[test]
+    fn barycentric_weights_reproduce_the_point(
+        a in screen_point(),
+        b in screen_point(),
+        c in screen_point(),
+        weights in (0.01f32..1.0, 0.01f32..1.0, 0.01f32..1.0),
+    ) {
+        let (a, b, c) = (screen_vertex(a.0, a.1, a.2), screen_vertex(b.0, b.1, b.2), screen_vertex(c.0, c.1, c.2));
+        prop_assume!(doubled_area(&a, &b, &c).abs() > 50.0);
+
+        let total = weights.0 + weights.1 + weights.2;
+        let (u, v, w) = (weights.0 / total, weights.1 / total, weights.2 / total);
+        let (pa, pb, pc) = (a.transformed_position, b.transformed_position, c.transformed_position);
+        let x = u * pa.x + v * pb.x + w * pc.x;
+        let y = u * pa.y + v * pb.y + w * pc.y;
+
+        let Some((w1, w2, w3)) = barycentric_coordinates(x, y, &a, &b, &c) else {
+            return Err(TestCaseError::fail("punto interior rechazado"));
+        };
+        prop_assert!((w1 + w2 + w3 - 1.0).abs() < 1e-5);
+        prop_assert!((w1 - u).abs() < 1e-3 && (w2 - v).abs() < 1e-3 && (w3 - w).abs() < 1e-3);
+        prop_assert!((w1 * pa.x + w2 * pb.x + w3 * pc.x - x).abs() < 1e-2);
+        prop_assert!((w1 * pa.y + w2 * pb.y + w3 * pc.y - y).abs() < 1e-2);
+    }
+
+    /// Los pesos devueltos son siempre válidos, esté el punto donde esté
+    #[test]
+    fn barycentric_weights_are_convex(
+        a in screen_point(),
+        b in screen_point(),
+        c in screen_point(),
+        p in screen_point(),
+    ) {
+        let (a, b, c) = (screen_vertex(a.0, a.1, a.2), screen_vertex(b.0, b.1, b.2), screen_vertex(c.0, c.1, c.2));
+        if let Some((w1, w2, w3)) = barycentric_coordinates(p.0, p.1, &a, &b, &c) {
+            prop_assert!((w1 + w2 + w3 - 1.0).abs() < 1e-4);
+            for weight in [w1, w2, w3] {
+                prop_assert!((-1e-4..=1.0 + 1e-4).contains(&weight));
+            }
+        }
+    }
+
+    /// Todos los fragmentos caen dentro de la caja del triángulo y su profundidad entre las de
+    /// los vértices
+    #[test]
+    fn fragments_stay_inside_the_triangle_bounds(
+        a in screen_point(),
+        b in screen_point(),
+        c in screen_point(),
+    ) {
+        let (a, b, c) = (screen_vertex(a.0, a.1, a.2), screen_vertex(b.0, b.1, b.2), screen_vertex(c.0, c.1, c.2));
+        let light = Light::new(Vector3::new(0.0, 0.0, 10.0));
+        let positions = [a.transformed_position, b.transformed_position, c.transformed_position];
+        let min = |value: fn(&Vector3) -> f32| positions.iter().map(value).fold(f32::INFINITY, f32::min) - 1e-3;
+        let max = |value: fn(&Vector3) -> f32| positions.iter().map(value).fold(f32::NEG_INFINITY, f32::max) + 1e-3;
+
+        for fragment in triangle(&a, &b, &c, &light) {
+            prop_assert!((min(|p| p.x)..=max(|p| p.x)).contains(&fragment.position.x));
+            prop_assert!((min(|p| p.y)..=max(|p| p.y)).contains(&fragment.position.y));
+            prop_assert!((min(|p| p.z)..=max(|p| p.z)).contains(&fragment.depth));
+        }
+    }
+
+    /// Un segmento recortado queda dentro de la pantalla; si ya estaba dentro, no cambia
+    #[test]
+    fn clipped_segments_stay_on_screen(start in screen_point(), end in screen_point()) {
+        let start = Vector3::new(start.0, start.1, start.2);
+        let end = Vector3::new(end.0, end.1, end.2);
+        if let Some((from, to)) = clip_to_screen(start, end, WIDTH, HEIGHT) {
+            prop_assert!(inside_screen(from) && inside_screen(to));
+            prop_assert!(from.z.min(to.z) >= start.z.min(end.z) - 1e-4);
+            prop_assert!(from.z.max(to.z) <= start.z.max(end.z) + 1e-4);
+        }
+        if inside_screen(start) && inside_screen(end) {
+            let clipped = clip_to_screen(start, end, WIDTH, HEIGHT);
+            prop_assert!(clipped.is_some_and(|(from, to)| (from.x - start.x).abs() < 1e-3 && (to.y - end.y).abs() < 1e-3));
+        }
+    }
+
+    /// Un segmento de mundo proyectado queda en pantalla y por delante del plano cercano
+    #[test]
+    fn projected_segments_stay_inside_the_frustum(eye in world_point(), from in world_point(), to in world_point()) {
+        prop_assume!(eye.norm() > 1.0);
+        let uniforms = uniforms(eye);
+        if let Some((a, b)) = project_segment(&uniforms, WIDTH, HEIGHT, from, to) {
+            prop_assert!(inside_screen(a) && inside_screen(b));
+            prop_assert!(a.z >= -1.0 - 1e-3 && b.z >= -1.0 - 1e-3);
+        }
+    }
+
+    /// Los triángulos recortados quedan dentro de los seis planos del frustum (por delante de la
+    /// cámara), y los que estaban dentro pasan tal cual
+    #[test]
+    fn clipped_triangles_stay_inside_the_frustum(
+        eye in world_point(),
+        a in world_point(),
+        b in world_point(),
+        c in world_point(),
+    ) {
+        prop_assume!(eye.norm() > 1.0);
+        let uniforms = uniforms(eye);
+        let clip_matrix = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+        let triangle = [world_vertex(a), world_vertex(b), world_vertex(c)];
+        let clip_position = |vertex: &Vertex| {
+            let p = vertex.position;
+            clip_matrix * Vec4::new(p.x, p.y, p.z, 1.0)
+        };
+        let inside = |position: &Vec4| {
+            let epsilon = 1e-3 * position.w.abs().max(1.0);
+            position.w > 0.0
+                && position.x.abs() <= position.w + epsilon
+                && position.y.abs() <= position.w + epsilon
+                && position.z.abs() <= position.w + epsilon
+        };
+
+        match clip_triangle(&triangle, &clip_matrix) {
+            Clipped::Inside => prop_assert!(triangle.iter().map(clip_position).all(|position| inside(&position))),
+            Clipped::Outside => {}
+            Clipped::Split(vertices) => {
+                prop_assert!(vertices.len() % 3 == 0 && (3..=21).contains(&vertices.len()));
+                for vertex in &vertices {
+                    prop_assert!(inside(&clip_position(vertex)));
+                }
+            }
+        }
+    }
+
+    /// Una malla indexada sale de la transformación igual que su lista de triángulos, en el
+    /// mismo orden, aunque cada vértice compartido se haya transformado una sola vez
+    #[test]
+    fn indexed_meshes_transform_like_triangle_lists(
+        eye in world_point(),
+        points in prop::collection::vec(world_point(), 3..12),
+        picks in prop::collection::vec(any::<prop::sample::Index>(), 3..60),
+    ) {
+        prop_assume!(eye.norm() > 1.0);
+        let uniforms = uniforms(eye);
+        let indices = picks.iter().take(picks.len() / 3 * 3).map(|pick| pick.index(points.len()) as u32).collect();
+        let mesh = IndexedMesh { vertices: points.into_iter().map(world_vertex).collect(), indices };
+        let screen = |vertices: Vec<Vertex>| -> Vec<(f32, f32, f32)> {
+            vertices.iter().map(|vertex| (vertex.transformed_position.x, vertex.transformed_position.y, vertex.transformed_position.z)).collect()
+        };
+        prop_assert_eq!(screen(transform_indexed(&uniforms, &mesh)), screen(transform_triangles(&uniforms, &mesh.to_triangles())));
+    }
+
+    /// Con la matriz de normales, la normal transformada sigue perpendicular a la superficie en el
+    /// mundo aunque el modelo gire y escale distinto en cada eje
+    #[test]
+    fn normals_stay_perpendicular_under_non_uniform_scale(
+        a in world_point(),
+        b in world_point(),
+        c in world_point(),
+        (sx, sy, sz) in (0.2f32..5.0, 0.2f32..5.0, 0.2f32..5.0),
+        (rx, ry, rz) in (-PI..PI, -PI..PI, -PI..PI),
+    ) {
+        let normal = (b - a).cross(&(c - a));
+        prop_assume!(normal.norm() > 1.0);
+        let model_matrix = create_model_matrix(Vec3::zeros(), 1.0, Vec3::new(rx, ry, rz)) * Mat4::new_nonuniform_scaling(&Vec3::new(sx, sy, sz));
+        let uniforms = Uniforms { model_matrix, normal_matrix: create_normal_matrix(&model_matrix), inverse_model_matrix: model_matrix.try_inverse(), ..uniforms(Vec3::new(0.0, 0.0, 100.0)) };
+
+        let n = normal.normalize();
+        let vertex = Vertex::new(Vector3::new(a.x, a.y, a.z), Vector3::new(n.x, n.y, n.z), Vector2::zero());
+        let t = vertex_shader(&vertex, &uniforms).transformed_normal;
+        let transformed = Vec3::new(t.x, t.y, t.z);
+        prop_assert!((transformed.norm() - 1.0).abs() < 1e-4);
+        let world = |p: Vec3| (model_matrix * Vec4::new(p.x, p.y, p.z, 1.0)).xyz();
+        for edge in [world(b) - world(a), world(c) - world(a)] {
+            prop_assert!(transformed.dot(&edge.normalize()).abs() < 1e-3, "{} · {}", transformed, edge);
+        }
+    }
+
+    /// Rasterizar por tiles deja en cada píxel la misma profundidad y el mismo dibujo que la
+    /// prueba de profundidad estricta de toda la vida, dibujo a dibujo sobre la pantalla entera
+    #[test]
+    fn tiles_match_the_per_pixel_depth_test(points in prop::collection::vec(screen_point(), 3..=12)) {
+        let light = Light::new(Vector3::new(0.0, 0.0, 10.0));
+        let draws: Vec<Vec<Vertex>> = points
+            .chunks_exact(3)
+            .map(|triangle| triangle.iter().map(|&(x, y, z)| screen_vertex(x, y, z)).collect())
+            .collect();
+        let (width, height) = (WIDTH as usize, HEIGHT as usize);
+
+        let mut expected = vec![(f32::INFINITY, usize::MAX); width * height];
+        for (draw, vertices) in draws.iter().enumerate() {
+            let _ = triangle_each(&vertices[0], &vertices[1], &vertices[2], &light, |fragment| {
+                let (x, y) = (fragment.position.x.floor() as i32, fragment.position.y.floor() as i32);
+                if !(0..WIDTH as i32).contains(&x) || !(0..HEIGHT as i32).contains(&y) {
+                    return ControlFlow::Continue(());
+                }
+                let pixel = &mut expected[y as usize * width + x as usize];
+                if pixel.0 > fragment.depth {
+                    *pixel = (fragment.depth, draw);
+                }
+                ControlFlow::Continue(())
+            });
+        }
+
+        let mut tiled = vec![(f32::INFINITY, usize::MAX); width * height];
+        let mut repeated = 0;
+        let grid = TileGrid::new(width, height);
+        let bins = grid.bin(draws.iter().map(|vertices| (vertices.as_slice(), DepthMode::Perspective)));
+        for (tile, bin) in bins.iter().enumerate() {
+            let rect = grid.rect(tile);
+            let mut depth = vec![f32::INFINITY; ((rect.2 - rect.0) * (rect.3 - rect.1)) as usize];
+            rasterize_tile(rect, bin, &mut depth, &light, |draw, fragment| {
+                let pixel = &mut tiled[fragment.position.y as usize * width + fragment.position.x as usize];
+                repeated += usize::from(pixel.1 != usize::MAX);
+                *pixel = (fragment.depth, draw);
+            });
+        }
+        prop_assert_eq!(repeated, 0, "dos fragmentos en el mismo píxel");
+        prop_assert_eq!(tiled, expected);
+    }
+}